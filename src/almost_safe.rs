@@ -0,0 +1,159 @@
+//! "Almost safe" analysis, with MaxSMT.
+//!
+//! Smallest sets of transition constraints to violate to reach a falsification, with `assert-soft`.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+use expr::{Expr, Op};
+
+use crate::{smt, Check};
+
+/// Top-level conjuncts of an expression.
+pub fn conjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::App { op: Op::And, args } => args.iter().flat_map(conjuncts).collect(),
+        expr => vec![expr],
+    }
+}
+
+/// Selector enabling the `idx`-th transition constraint.
+fn selector(idx: usize) -> String {
+    format!("keep_{}", idx)
+}
+
+/// Result of the analysis for a candidate.
+enum Relax {
+    /// Transition constraints to violate, empty if the candidate is falsified.
+    Violate(Vec<usize>),
+    /// Not falsifiable in `k` steps or less even with all constraints violated.
+    Unreachable,
+}
+
+impl<'env> Check<'env> {
+    /// Minimal set of transition constraints to violate to falsify a candidate in `max` steps
+    /// or less.
+    fn relax(&self, idx: usize, candidate: &str, constraints: &[&Expr], max: usize) -> Res<Relax> {
+        let sys = &self.sys;
+        let def = sys.po_s().get(candidate).ok_or_else(|| {
+            format!(
+                "failed to retrieve definition for candidate `{}`",
+                candidate,
+            )
+        })?;
+        let mut solver = self.solver(&format!("almost_safe_{}.smt2", idx))?;
+        solver.intent(&format!(
+            "almost safe: unrolling with relaxable constraints for `{}`",
+            candidate
+        ))?;
+        for c_idx in 0..constraints.len() {
+            solver.command(format!("(declare-fun {} () Bool)", selector(c_idx)))?
+        }
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        let mut bad = format!("(or (not {})", smt::Unroll::new(def, 0));
+        for step in 1..=max {
+            solver.declare_state(sys, step)?;
+            for (c_idx, constraint) in constraints.iter().enumerate() {
+                solver.assert(format!(
+                    "(=> {} {})",
+                    selector(c_idx),
+                    smt::Unroll::new(constraint, step - 1)
+                ))?
+            }
+            bad.push_str(&format!(" (not {})", smt::Unroll::new(def, step)))
+        }
+        bad.push(')');
+        solver.assert(bad)?;
+        for c_idx in 0..constraints.len() {
+            solver.assert_soft(selector(c_idx), "almost_safe")?
+        }
+
+        solver.intent(&format!(
+            "almost safe: fewest constraints to violate to falsify `{}`",
+            candidate
+        ))?;
+        if !solver.check_sat()? {
+            return Ok(Relax::Unreachable);
+        }
+        let mut violated = Vec::new();
+        for c_idx in 0..constraints.len() {
+            if solver.get_value(&selector(c_idx))? == smt::SExpr::Atom("false".into()) {
+                violated.push(c_idx)
+            }
+        }
+        Ok(Relax::Violate(violated))
+    }
+
+    /// Runs the almost safe analysis on the candidates not proved by induction, up to depth
+    /// `max`.
+    pub fn almost_safe(&self, base: &BaseRes, step: &StepRes, max: usize) -> Res<()> {
+        let candidates: Vec<&String> = self
+            .sys
+            .po_s()
+            .keys()
+            .filter(|candidate| {
+                !base.okay.contains(candidate.as_str()) || !step.okay.contains(candidate.as_str())
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Ok(());
+        }
+        let constraints = conjuncts(self.sys.trans());
+
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "looking for the fewest of {} transition constraint(s) to violate to falsify {} \
+                candidate(s), {}...",
+                constraints.len(),
+                candidates.len(),
+                s.bold.paint("MaxSMT")
+            )
+        });
+        let mut results = Vec::with_capacity(candidates.len());
+        for (idx, candidate) in candidates.into_iter().enumerate() {
+            let relax = self
+                .relax(idx, candidate, &constraints, max)
+                .chain_err(|| format!("during almost safe analysis of `{}`", candidate))?;
+            results.push((candidate, relax))
+        }
+
+        println!("|===| {} result", self.bold.paint("Almost safe"));
+        for (idx, (candidate, relax)) in results.iter().enumerate() {
+            if idx > 0 {
+                println!("|")
+            }
+            match relax {
+                Relax::Violate(violated) if violated.is_empty() => println!(
+                    "| - `{}` is {} in {} step(s) or less without violating any constraint",
+                    self.red.paint(candidate.as_str()),
+                    self.red.paint("falsified"),
+                    max
+                ),
+                Relax::Violate(violated) => {
+                    println!(
+                        "| - `{}` is {}, it is falsified in {} step(s) or less if the following \
+                        {} transition constraint(s) are violated",
+                        self.bold.paint(candidate.as_str()),
+                        self.green.paint("almost safe"),
+                        max,
+                        violated.len()
+                    );
+                    for c_idx in violated {
+                        println!("|   `{}`", self.bold.paint(constraints[*c_idx].to_string()))
+                    }
+                }
+                Relax::Unreachable => println!(
+                    "| - `{}` is not falsified in {} step(s) or less even if all transition \
+                    constraints are violated, only the initial predicate matters",
+                    self.green.paint(candidate.as_str()),
+                    max
+                ),
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
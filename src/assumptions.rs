@@ -0,0 +1,92 @@
+//! Consistency of the `--assume` assumptions.
+
+mikino_api::prelude!();
+
+use crate::{Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Checks that the assumptions of the system do not contradict it up to depth `max`.
+    ///
+    /// `original` is the text of the system without the assumptions. Only reports something if
+    /// they do. Yields the depth at which they become contradictory, if any.
+    pub fn assumptions(&self, original: &str, max: usize) -> Res<Option<usize>> {
+        let sys = &self.sys;
+        let original = parse::trans(original).chain_err(|| "while parsing system")?;
+        if self.env.verb > 0 {
+            self.env.progress(|s| {
+                format!(
+                    "checking the {} are consistent up to depth {}...",
+                    s.bold.paint("assumptions"),
+                    max
+                )
+            })
+        }
+        let mut assumed = self.phase_solver(Phase::Bmc, "assumptions.smt2")?;
+        let mut unassumed = self.phase_solver(Phase::Bmc, "assumptions_original.smt2")?;
+        assumed.intent("assumptions: initial state(s), with assumptions")?;
+        assumed.declare_state(sys, 0)?;
+        assumed.assert_init(sys, 0)?;
+        unassumed.intent("assumptions: initial state(s), without assumptions")?;
+        unassumed.declare_state(&original, 0)?;
+        unassumed.assert_init(&original, 0)?;
+
+        let mut contradiction = None;
+        for depth in 0..=max {
+            if depth > 0 {
+                assumed.declare_state(sys, depth)?;
+                assumed.assert_trans(sys, depth - 1)?;
+                unassumed.declare_state(&original, depth)?;
+                unassumed.assert_trans(&original, depth - 1)?;
+            }
+            assumed.intent(&format!(
+                "assumptions: is there an execution of {} step(s)?",
+                depth
+            ))?;
+            if assumed.check_sat()? {
+                continue;
+            }
+            unassumed.intent(&format!(
+                "assumptions: is there an execution of {} step(s) without the assumptions?",
+                depth
+            ))?;
+            // Executions of the system itself may stop too, the sanity and deadlock checks are
+            // about that.
+            if unassumed.check_sat()? {
+                contradiction = Some(depth)
+            }
+            break;
+        }
+
+        let Some(depth) = contradiction else {
+            return Ok(None);
+        };
+        if self.env.json {
+            return Ok(Some(depth));
+        }
+        println!("|===| {} check result", self.bold.paint("Assumption"));
+        if depth == 0 {
+            println!(
+                "| - the assumptions are {} with the initial predicate, no initial state \
+                satisfies them",
+                self.red.paint("contradictory")
+            );
+        } else {
+            println!(
+                "| - the assumptions are {} with the system at depth {}, no execution of {} \
+                step(s) satisfies them",
+                self.red.paint("contradictory"),
+                depth,
+                depth
+            );
+        }
+        println!(
+            "|   candidates {} from depth {} on, whatever the results below say",
+            self.red.paint("hold vacuously"),
+            depth
+        );
+        println!("|===|");
+        println!();
+
+        Ok(Some(depth))
+    }
+}
@@ -0,0 +1,201 @@
+//! Backward reachability engine.
+//!
+//! State `i` of the unrolling is `i` transitions away from the falsification.
+
+mikino_api::prelude!();
+
+use crate::{cancel, smt, timeout, Check, Outcome, Trace};
+
+impl<'env> Check<'env> {
+    /// Runs the backward engine.
+    pub fn backward(&self, max: Option<usize>) -> Res<()> {
+        let mut proved = Vec::new();
+        let mut falsified = Vec::new();
+        let mut unknown = Vec::new();
+
+        self.env.progress(|s| {
+            format!(
+                "running {} analysis for {} candidate(s)...",
+                s.bold.paint("backward"),
+                self.sys.po_s().len()
+            )
+        });
+
+        let cancel = cancel::Token::new();
+        for (idx, candidate) in self.sys.po_s().keys().enumerate() {
+            let outcome = self
+                .backward_candidate(max, idx, candidate, &cancel)
+                .chain_err(|| {
+                    format!(
+                        "during backward analysis for candidate `{}`",
+                        self.bold.paint(candidate)
+                    )
+                })?;
+            match outcome {
+                Outcome::Proved(depth) => {
+                    self.settle("backward", candidate, "proved", Some(depth))?;
+                    proved.push((candidate.as_str(), depth))
+                }
+                Outcome::Falsified(trace) => {
+                    println!(
+                        "found a {} at depth {}:",
+                        self.red.paint("falsification"),
+                        self.bold.paint((trace.len() - 1).to_string())
+                    );
+                    self.present_trace(&self.sys, candidate, &trace, &[], true)?;
+                    self.settle("backward", candidate, "falsified", Some(trace.len() - 1))?;
+                    falsified.push(candidate.as_str())
+                }
+                Outcome::Unknown => {
+                    self.settle("backward", candidate, "unknown", None)?;
+                    unknown.push(candidate.as_str())
+                }
+            }
+        }
+
+        println!();
+        println!("|===| {} result", self.bold.paint("Backward analysis"));
+        if !proved.is_empty() {
+            println!(
+                "| - the following candidate(s) {} in all reachable states",
+                self.green.paint("hold")
+            );
+            for (candidate, depth) in &proved {
+                println!(
+                    "|   `{}` (fixpoint at depth {})",
+                    self.green.paint(*candidate),
+                    depth
+                )
+            }
+        }
+        if !falsified.is_empty() {
+            if !proved.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsifiable")
+            );
+            for candidate in &falsified {
+                println!("|   `{}`", self.red.paint(*candidate))
+            }
+        }
+        if !unknown.is_empty() {
+            if !proved.is_empty() || !falsified.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - could {} conclude for the following candidate(s) {}",
+                self.bold.paint("not"),
+                timeout::unknown_bound(max, self.env.timed_out())
+            );
+            for candidate in &unknown {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        println!("|");
+        if !falsified.is_empty() {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else if !unknown.is_empty() {
+            println!("| - system {}", self.red.paint("might be unsafe"))
+        } else {
+            println!(
+                "| - system is {}, all reachable states verify the candidate(s)",
+                self.green.paint("safe")
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+
+    /// Backward analysis for a single candidate.
+    ///
+    /// Yields [`Outcome::Unknown`] if `cancel` is raised or a query exceeds the
+    /// `--query_timeout`.
+    pub fn backward_candidate(
+        &self,
+        max: Option<usize>,
+        idx: usize,
+        candidate: &str,
+        cancel: &cancel::Token,
+    ) -> Res<Outcome> {
+        let sys = &self.sys;
+        let def = sys.po_s().get(candidate).ok_or_else(|| {
+            format!(
+                "failed to retrieve definition for candidate `{}`",
+                candidate,
+            )
+        })?;
+        let bad = |step: usize| format!("(not {})", smt::Unroll::new(def, step));
+
+        let mut solver = self.solver(&format!("backward_{}.smt2", idx))?;
+        solver.kill_on(cancel);
+        solver.intent(&format!(
+            "backward setup for candidate `{}`: falsification at state 0",
+            candidate
+        ))?;
+        solver.declare_state(sys, 0)?;
+        solver.assert(bad(0))?;
+
+        let mut depth = 0;
+        loop {
+            if self.cancelled(cancel) {
+                return Ok(Outcome::Unknown);
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "checking `{}` at depth {}",
+                        s.bold.paint(candidate),
+                        s.under.paint(depth.to_string())
+                    )
+                });
+            }
+
+            if depth > 0 {
+                solver.intent(&format!(
+                    "backward check for candidate `{}`: simple path of length {} to a \
+                    falsification, without falsifications in between?",
+                    candidate, depth
+                ))?;
+                solver.declare_state(sys, depth)?;
+                solver.assert(smt::Unroll::backward(sys.trans(), depth - 1))?;
+                solver.assert(smt::Unroll::new(def, depth))?;
+                for prev in 0..depth {
+                    solver.assert(smt::distinct_states(sys, prev, depth))?
+                }
+                match solver.check_sat_or_unknown()? {
+                    Some(false) => return Ok(Outcome::Proved(depth)),
+                    Some(true) => (),
+                    None => return Ok(Outcome::Unknown),
+                }
+            }
+
+            solver.intent(&format!(
+                "backward check for candidate `{}`: initial state {} step(s) away from a \
+                falsification?",
+                candidate, depth
+            ))?;
+            solver.push()?;
+            solver.assert_init(sys, depth)?;
+            let Some(sat) = solver.check_sat_or_unknown()? else {
+                return Ok(Outcome::Unknown);
+            };
+            if sat {
+                let mut trace = Trace::new();
+                for state in (0..=depth).rev() {
+                    let values = solver.get_state(sys, state)?;
+                    trace.push((depth - state, smt::pretty_state(&values)))
+                }
+                return Ok(Outcome::Falsified(trace));
+            }
+            solver.pop()?;
+
+            if max.map(|max| depth >= max).unwrap_or(false) {
+                return Ok(Outcome::Unknown);
+            }
+            depth += 1
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Baselines, for regression tracking.
+
+mikino_api::prelude!();
+
+use toml::value::{Table, Value};
+
+use crate::{budget::Verdict, conf::Conf, revisions::Status, Run};
+
+/// Status of the candidates of a verdict.
+pub fn statuses(verdict: &Verdict) -> Map<String, Status> {
+    let mut res = Map::new();
+    for (candidates, status) in [
+        (&verdict.proved, Status::Proved),
+        (&verdict.falsified, Status::Falsified(None)),
+        (&verdict.unknown, Status::Unknown),
+    ] {
+        for candidate in candidates {
+            res.insert(candidate.clone(), status);
+        }
+    }
+    res
+}
+
+impl Run {
+    /// Saves the statuses of the candidates of a verdict as a baseline.
+    pub fn save_baseline(&self, path: &str, verdict: &Verdict) -> Res<()> {
+        let mut candidates = Table::new();
+        for (candidate, status) in statuses(verdict) {
+            let status = match status {
+                Status::Proved => "proved",
+                Status::Falsified(_) => "falsified",
+                Status::Unknown => "unknown",
+            };
+            candidates.insert(candidate, Value::String(status.into()));
+        }
+        let mut table = Table::new();
+        table.insert("run_id".into(), Value::String(self.run_id.clone()));
+        table.insert("candidates".into(), Value::Table(candidates));
+        std::fs::write(path, Value::Table(table).to_string())
+            .chain_err(|| format!("while writing baseline `{}`", path))?;
+        if self.verb > 0 {
+            self.progress(|s| format!("baseline written to `{}`", s.bold.paint(path)))
+        }
+        Ok(())
+    }
+
+    /// Loads the statuses of the candidates of a baseline.
+    pub fn load_baseline(&self, path: &str) -> Res<Map<String, Status>> {
+        let conf = Conf::load(path).chain_err(|| "while loading baseline")?;
+        let Some(Value::Table(candidates)) = conf.table.get("candidates") else {
+            bail!("baseline `{}` has no `candidates` table", path)
+        };
+        let mut res = Map::new();
+        for (candidate, status) in candidates {
+            let status = match status.as_str() {
+                Some("proved") => Status::Proved,
+                Some("falsified") => Status::Falsified(None),
+                Some("unknown") => Status::Unknown,
+                _ => bail!(
+                    "illegal status for candidate `{}` in baseline `{}`, \
+                    expected `proved`, `falsified` or `unknown`",
+                    candidate,
+                    path
+                ),
+            };
+            res.insert(candidate.clone(), status);
+        }
+        Ok(res)
+    }
+
+    /// Compares a verdict to a baseline, yields the number of regressions.
+    pub fn compare_baseline(&self, path: &str, verdict: &Verdict) -> Res<usize> {
+        let baseline = self.load_baseline(path)?;
+        let statuses = statuses(verdict);
+        if self.json {
+            return Ok(crate::revisions::changes(&baseline, &statuses)
+                .iter()
+                .filter(|(change, _)| change.is_regression())
+                .count());
+        }
+        println!();
+        Ok(self.present_changes("Baseline", &baseline, &statuses))
+    }
+}
@@ -0,0 +1,166 @@
+//! BMC on a subset of the depths.
+//!
+//! Falsifications are only looked for at the depths requested.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+
+use crate::{smt, status::StatusLine, Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Runs BMC, only checking for falsifications at some depths.
+    ///
+    /// `depths` must be sorted, depths above `max` are ignored.
+    pub fn bmc_depths(
+        &self,
+        max: Option<usize>,
+        depths: &[usize],
+        base: &BaseRes,
+        step: Option<&StepRes>,
+    ) -> Res<()> {
+        let bmc_res = if let Some(step) = step {
+            base.merge_base_with_step(step)
+                .chain_err(|| "during base/step result merge for BMC")?
+        } else {
+            base.as_inner().clone().into()
+        };
+        if bmc_res.all_falsified() {
+            return Ok(());
+        }
+        let depths: Vec<usize> = depths
+            .iter()
+            .cloned()
+            .filter(|depth| max.map(|max| *depth <= max).unwrap_or(true))
+            .collect();
+        let mut okay: Vec<&str> = bmc_res.okay.iter().cloned().collect();
+
+        self.env.progress(|s| {
+            format!(
+                "running {} at {} depth(s), looking for falsifications for {} candidate(s)...",
+                s.bold.paint("BMC"),
+                depths.len(),
+                okay.len()
+            )
+        });
+
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "bmc_depths.smt2")?;
+        solver.intent("BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+
+        let mut falsified = Vec::new();
+        let mut unrolled = 0;
+        let mut status = StatusLine::new(self.env);
+        for depth in depths.iter().cloned() {
+            if okay.is_empty() {
+                break;
+            }
+            status.update(|| {
+                format!(
+                    "{} at depth {}, {} candidate(s) left",
+                    self.bold.paint("BMC"),
+                    self.under.paint(depth.to_string()),
+                    okay.len()
+                )
+            });
+            while unrolled < depth {
+                unrolled += 1;
+                solver.declare_state(sys, unrolled)?;
+                solver.assert_trans(sys, unrolled - 1)?;
+            }
+            if self.env.verb > 0 && !status.is_active() {
+                self.env.progress(|s| {
+                    format!(
+                        "checking for falsifications at depth {}",
+                        s.under.paint(depth.to_string())
+                    )
+                });
+            }
+
+            let start = std::time::Instant::now();
+            let mut idx = 0;
+            while idx < okay.len() {
+                let candidate = okay[idx];
+                let def = sys.po_s().get(candidate).ok_or_else(|| {
+                    format!(
+                        "failed to retrieve definition for candidate `{}`",
+                        candidate,
+                    )
+                })?;
+                solver.intent(&format!(
+                    "BMC: falsification for candidate `{}` at depth {}?",
+                    candidate, depth
+                ))?;
+                solver.push()?;
+                solver.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+                if solver.check_sat()? {
+                    status.clear();
+                    let trace = solver.get_trace(sys, depth)?;
+                    println!(
+                        "found a {} at depth {}:",
+                        self.red.paint("falsification"),
+                        self.bold.paint(depth.to_string())
+                    );
+                    self.present_trace(sys, candidate, &trace, &[], true)?;
+                    self.settle("bmc", candidate, "falsified", Some(depth))?;
+                    falsified.push(okay.remove(idx));
+                } else {
+                    idx += 1
+                }
+                solver.pop()?;
+            }
+            self.record_depth(depth, start.elapsed())?;
+        }
+
+        status.clear();
+        if self.env.verb > 0 || !falsified.is_empty() {
+            println!()
+        }
+
+        println!("|===| {} result", self.bold.paint("Bmc"));
+        println!(
+            "| - checked depth(s) {}",
+            depths
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        println!("|");
+        if !okay.is_empty() {
+            println!(
+                "| - could {} find falsifications for the following candidate(s)",
+                self.bold.paint("not")
+            );
+            for candidate in &okay {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        if !okay.is_empty() && !falsified.is_empty() {
+            println!("|")
+        }
+        if !falsified.is_empty() {
+            println!(
+                "| - found a {} for the following candidate(s)",
+                self.red.paint("falsification")
+            );
+            for candidate in &falsified {
+                println!("|   `{}`", self.red.paint(*candidate))
+            }
+        }
+        println!("|");
+        if !base.cexs.is_empty() || !falsified.is_empty() {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else {
+            println!("| - system {}", self.red.paint("might be unsafe"));
+            println!(
+                "|   no falsification at the depth(s) checked was found for some candidate(s)"
+            );
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Induction strengthening from BMC bounds.
+//!
+//! BMC up to depth `d` is the base case of k-induction for any `k ≤ d + 1`.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Attempts to prove the candidates BMC could not falsify up to depth `depth`.
+    pub fn bmc_induction(&self, depth: usize, candidates: &[&str]) -> Res<()> {
+        if candidates.is_empty() {
+            return Ok(());
+        }
+        let max_k = depth + 1;
+
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "attempting {} of {} candidate(s) with k ≤ {}, from the BMC bound...",
+                s.bold.paint("k-induction"),
+                candidates.len(),
+                max_k
+            )
+        });
+
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Step, "bmc_induction.smt2")?;
+        solver.intent("k-induction from BMC bounds setup")?;
+        solver.declare_state(sys, 0)?;
+
+        let mut pending: Vec<&str> = candidates.to_vec();
+        let mut proved = Vec::new();
+        for k in 1..=max_k {
+            if pending.is_empty() {
+                break;
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "checking {} candidate(s) for k = {}",
+                        pending.len(),
+                        s.under.paint(k.to_string())
+                    )
+                });
+            }
+            solver.declare_state(sys, k)?;
+            solver.assert_trans(sys, k - 1)?;
+
+            let mut idx = 0;
+            while idx < pending.len() {
+                let candidate = pending[idx];
+                let def = sys.po_s().get(candidate).ok_or_else(|| {
+                    format!(
+                        "failed to retrieve definition for candidate `{}`",
+                        candidate,
+                    )
+                })?;
+                solver.intent(&format!(
+                    "k-induction from BMC bounds: is `{}` {}-inductive?",
+                    candidate, k
+                ))?;
+                solver.push()?;
+                for step in 0..k {
+                    solver.assert(smt::Unroll::new(def, step))?
+                }
+                solver.assert(format!("(not {})", smt::Unroll::new(def, k)))?;
+                let cti = solver.check_sat()?;
+                solver.pop()?;
+                if cti {
+                    idx += 1
+                } else {
+                    self.settle("bmc_induction", candidate, "proved", Some(k))?;
+                    proved.push((pending.remove(idx), k))
+                }
+            }
+        }
+
+        if self.env.verb > 0 {
+            println!()
+        }
+
+        println!(
+            "|===| {} from BMC bounds result",
+            self.bold.paint("K-induction")
+        );
+        if !proved.is_empty() {
+            println!(
+                "| - the following candidate(s) are {} and thus {} in all reachable states",
+                self.green.paint("k-inductive"),
+                self.green.paint("hold")
+            );
+            for (candidate, k) in &proved {
+                println!("|   `{}` (k = {})", self.green.paint(*candidate), k)
+            }
+        }
+        if !proved.is_empty() && !pending.is_empty() {
+            println!("|")
+        }
+        if !pending.is_empty() {
+            println!(
+                "| - could {} prove the following candidate(s) with k ≤ {}",
+                self.bold.paint("not"),
+                max_k
+            );
+            for candidate in &pending {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
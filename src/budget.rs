@@ -0,0 +1,229 @@
+//! Final status of the candidates, unknown budget and exit code.
+//!
+//! Exit code `0` if all candidates are proved, `1` if some are falsified, `2` if some are unknown.
+
+mikino_api::prelude!();
+
+use crate::{
+    json::{json_opt, json_str},
+    logging, Check, Run,
+};
+
+/// Exit codes.
+pub mod exit {
+    /// All candidates are proved, or the mode has no verdict.
+    pub const SAFE: i32 = 0;
+    /// Some candidates are falsified.
+    pub const FALSIFIED: i32 = 1;
+    /// Some candidates are unknown: not inductive, and not falsified.
+    pub const UNKNOWN: i32 = 2;
+    /// Mikino failed.
+    pub const ERROR: i32 = 3;
+
+    /// Token of an exit code, as printed by `--verdict_only`.
+    pub fn token(code: i32) -> &'static str {
+        match code {
+            FALSIFIED => "UNSAFE",
+            UNKNOWN => "UNKNOWN",
+            _ => "SAFE",
+        }
+    }
+}
+
+/// Final status of the candidates of one or more systems.
+#[derive(Debug, Clone, Default)]
+pub struct Verdict {
+    /// Proved candidates.
+    pub proved: Vec<String>,
+    /// Falsified candidates.
+    pub falsified: Vec<String>,
+    /// Candidates no analysis settled.
+    pub unknown: Vec<String>,
+    /// JSON report of each system, only with `--json`, see [`crate::json`].
+    pub json: Vec<String>,
+    /// Report cases of each system, only with `--report` or `--github_annotations`, see
+    /// [`crate::report`].
+    pub suites: Vec<crate::report::Suite>,
+    /// Weights of the candidates that have one, see [`crate::score`].
+    pub weights: Map<String, u64>,
+}
+impl Verdict {
+    /// Adds the candidates of another verdict.
+    pub fn extend(&mut self, other: Verdict) {
+        self.proved.extend(other.proved);
+        self.falsified.extend(other.falsified);
+        self.unknown.extend(other.unknown);
+        self.json.extend(other.json);
+        self.suites.extend(other.suites);
+        self.weights.extend(other.weights);
+    }
+
+    /// Exit code of a run, `max_unknown` is the unknown budget if any, `threshold` the safety
+    /// score threshold if any.
+    pub fn exit_code(&self, max_unknown: Option<usize>, threshold: Option<f64>) -> i32 {
+        if threshold.is_some_and(|threshold| self.score() >= threshold) {
+            exit::SAFE
+        } else if !self.falsified.is_empty() {
+            exit::FALSIFIED
+        } else if self.unknown.len() > max_unknown.unwrap_or(0) {
+            exit::UNKNOWN
+        } else {
+            exit::SAFE
+        }
+    }
+}
+
+/// Outcome of a candidate, as reported to [`Check::settle`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// Analysis reporting it.
+    pub phase: String,
+    /// Outcome of the analysis.
+    pub status: String,
+    /// Depth of the analysis, if any.
+    pub depth: Option<usize>,
+}
+
+impl<'env> Check<'env> {
+    /// Settles a candidate, and runs the post-PO hook if any.
+    ///
+    /// `phase` is the analysis settling the candidate, and `status` its outcome: only `proved`
+    /// and `falsified` settle it, other statuses only go to the hook. A falsification always
+    /// takes precedence over a proof. The last outcome of the candidate is recorded for the JSON
+    /// output, unless it would hide the status settling it; all outcomes are recorded for the
+    /// summary.
+    pub fn settle(
+        &self,
+        phase: &str,
+        candidate: &str,
+        status: &str,
+        depth: Option<usize>,
+    ) -> Res<()> {
+        let proved = match status {
+            "proved" => Some(true),
+            "falsified" => Some(false),
+            _ => None,
+        };
+        if let Some(proved) = proved {
+            self.record_settled(phase, candidate)?;
+            let mut settled = self
+                .settled
+                .lock()
+                .map_err(|_| "failed to record candidate status")?;
+            let entry = settled.entry(candidate.to_string()).or_insert(proved);
+            *entry = *entry && proved;
+        }
+        let mut outcomes = self
+            .outcomes
+            .lock()
+            .map_err(|_| "failed to record candidate outcome")?;
+        let outcome = Outcome {
+            phase: phase.into(),
+            status: status.into(),
+            depth,
+        };
+        self.history
+            .lock()
+            .map_err(|_| "failed to record candidate outcome")?
+            .push((candidate.to_string(), outcome.clone()));
+        let keep = match outcomes.get(candidate) {
+            Some(prev) if prev.status == "falsified" => true,
+            Some(prev) if prev.status == "proved" => proved.is_none(),
+            _ => false,
+        };
+        if !keep {
+            outcomes.insert(candidate.to_string(), outcome);
+        }
+        drop(outcomes);
+        self.env.log(logging::Level::Info, "outcome", || {
+            vec![
+                ("phase", json_str(phase)),
+                ("candidate", json_str(candidate)),
+                ("status", json_str(status)),
+                ("depth", json_opt(depth)),
+            ]
+        })?;
+        self.hook_post_po(phase, candidate, status, depth)
+    }
+
+    /// Final status of the candidates, prefixed with `<name>::` for system `name` if any.
+    pub fn verdict(&self, name: Option<&str>) -> Res<Verdict> {
+        let settled = self
+            .settled
+            .lock()
+            .map_err(|_| "failed to access candidate statuses")?;
+        let mut verdict = Verdict::default();
+        for candidate in self.sys.po_s().keys() {
+            let desc = match name {
+                Some(name) => format!("{}::{}", name, candidate),
+                None => candidate.clone(),
+            };
+            if let Some(weight) = self.weights.get(candidate) {
+                verdict.weights.insert(desc.clone(), *weight);
+            }
+            match settled.get(candidate) {
+                Some(true) => verdict.proved.push(desc),
+                Some(false) => verdict.falsified.push(desc),
+                None => verdict.unknown.push(desc),
+            }
+        }
+        Ok(verdict)
+    }
+}
+
+impl Run {
+    /// Reports the unknown budget result, yields `true` if the run passes.
+    pub fn unknown_budget(&self, verdict: &Verdict, max: usize) -> bool {
+        let pass = verdict.falsified.is_empty() && verdict.unknown.len() <= max;
+        if self.json {
+            return pass;
+        }
+        println!();
+        println!("|===| {} result", self.bold.paint("Unknown budget"));
+        println!(
+            "| - {} proved, {} falsified, {} unknown candidate(s), budget of {} unknown",
+            verdict.proved.len(),
+            verdict.falsified.len(),
+            verdict.unknown.len(),
+            max
+        );
+        if !verdict.unknown.is_empty() {
+            println!("|");
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("unknown (undischarged)")
+            );
+            for candidate in &verdict.unknown {
+                println!("|   `{}`", self.red.paint(candidate))
+            }
+        }
+        if !verdict.falsified.is_empty() {
+            println!("|");
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsified")
+            );
+            for candidate in &verdict.falsified {
+                println!("|   `{}`", self.red.paint(candidate))
+            }
+        }
+        println!("|");
+        if pass {
+            println!("| - run {}", self.green.paint("passes"))
+        } else if !verdict.falsified.is_empty() {
+            println!(
+                "| - run {}, some candidate(s) are falsified",
+                self.red.paint("fails")
+            )
+        } else {
+            println!(
+                "| - run {}, {} unknown candidate(s) exceed the budget of {}",
+                self.red.paint("fails"),
+                verdict.unknown.len(),
+                max
+            )
+        }
+        println!("|===|");
+        pass
+    }
+}
@@ -0,0 +1,82 @@
+//! Cancellation of a run.
+//!
+//! Analyses stop at their next query boundary, the candidates not settled end up unknown.
+
+mikino_api::prelude!();
+
+use std::{
+    process::Child,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use crate::{Check, Run};
+
+/// Cancellation token, cloning yields a handle to the same token.
+#[derive(Debug, Clone, Default)]
+pub struct Token {
+    /// True if the run is cancelled.
+    raised: Arc<AtomicBool>,
+    /// Solver processes to kill when the token is raised, see [`Self::watch`].
+    kids: Arc<Mutex<Vec<Weak<Mutex<Child>>>>>,
+}
+impl Token {
+    /// Constructor, not raised.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raises the token, cancelling the run and killing the solver processes it watches.
+    pub fn cancel(&self) {
+        self.raised.store(true, Ordering::Relaxed);
+        let kids = match self.kids.lock() {
+            Ok(mut kids) => std::mem::take(&mut *kids),
+            Err(_) => return,
+        };
+        for kid in kids.iter().filter_map(Weak::upgrade) {
+            kill(&kid)
+        }
+    }
+
+    /// Kills a solver process when the token is raised, right away if it already is.
+    ///
+    /// Queries pending on a killed solver fail, analyses must check the token before reporting
+    /// such failures.
+    pub fn watch(&self, kid: &Arc<Mutex<Child>>) {
+        if let Ok(mut kids) = self.kids.lock() {
+            kids.retain(|kid| kid.strong_count() > 0);
+            kids.push(Arc::downgrade(kid))
+        }
+        if self.is_cancelled() {
+            kill(kid)
+        }
+    }
+
+    /// True if the token is raised.
+    pub fn is_cancelled(&self) -> bool {
+        self.raised.load(Ordering::Relaxed)
+    }
+}
+
+/// Kills a solver process.
+fn kill(kid: &Mutex<Child>) {
+    if let Ok(mut kid) = kid.lock() {
+        let _ = kid.kill();
+    }
+}
+
+impl Run {
+    /// Handle to the cancellation token of the run.
+    pub fn cancel_token(&self) -> Token {
+        self.cancel.clone()
+    }
+}
+
+impl<'env> Check<'env> {
+    /// True if the run is cancelled, or if an analysis-local token is, see [`crate::portfolio`].
+    pub fn cancelled(&self, local: &Token) -> bool {
+        local.is_cancelled() || self.env.cancel.is_cancelled()
+    }
+}
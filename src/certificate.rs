@@ -0,0 +1,148 @@
+//! Inductive invariant certificates.
+//!
+//! A standalone SMT-LIB 2 script with the initiation and consecution queries, both `unsat`.
+
+mikino_api::prelude!();
+
+use std::{fs::File, io::Write};
+
+use crate::{smt, Check};
+
+impl<'env> Check<'env> {
+    /// Conjunction of all candidates at some step.
+    fn invariant_at(&self, step: usize) -> String {
+        let mut inv = "(and true".to_string();
+        for def in self.sys.po_s().values() {
+            inv.push_str(&format!(" {}", smt::Unroll::new(def, step)))
+        }
+        inv.push(')');
+        inv
+    }
+
+    /// The certificate's obligations, as descriptions and assertions.
+    fn obligations(&self) -> [(&'static str, Vec<String>); 2] {
+        let sys = &self.sys;
+        [
+            (
+                "initiation: the initial state(s) verify the invariant",
+                vec![
+                    smt::Unroll::new(sys.init(), 0).to_string(),
+                    "(not inv!0)".into(),
+                ],
+            ),
+            (
+                "consecution: the transition relation preserves the invariant",
+                vec![
+                    "inv!0".into(),
+                    smt::Unroll::new(sys.trans(), 0).to_string(),
+                    "(not inv!1)".into(),
+                ],
+            ),
+        ]
+    }
+
+    /// Writes a certificate for the conjunction of all candidates to `path`.
+    ///
+    /// Checks the certificate first, and does not write anything if it does not hold. This is the
+    /// case if some candidates are falsified, or are only proved by k-induction with `k > 1`.
+    pub fn certificate(&self, input: &str, path: &str) -> Res<()> {
+        let sys = &self.sys;
+        self.env.progress(|s| {
+            format!(
+                "checking inductive invariant {} for {} candidate(s)...",
+                s.bold.paint("certificate"),
+                sys.po_s().len()
+            )
+        });
+
+        let mut solver = self.solver("certificate.smt2")?;
+        solver.intent("certificate setup")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        for step in 0..=1 {
+            solver.command(format!(
+                "(define-fun inv!{} () Bool {})",
+                step,
+                self.invariant_at(step)
+            ))?
+        }
+        let mut failed = Vec::new();
+        for (desc, assertions) in self.obligations().iter() {
+            solver.intent(&format!("certificate: {}?", desc))?;
+            solver.push()?;
+            for assertion in assertions {
+                solver.assert(assertion)?
+            }
+            if solver.check_sat()? {
+                failed.push(*desc)
+            }
+            solver.pop()?;
+        }
+
+        println!();
+        println!("|===| {} result", self.bold.paint("Certificate"));
+        if !failed.is_empty() {
+            println!(
+                "| - the conjunction of the candidates is {}, no certificate written",
+                self.red.paint("not inductive")
+            );
+            for desc in &failed {
+                println!("|   {} fails", desc)
+            }
+            println!("|===|");
+            return Ok(());
+        }
+
+        let mut script = vec![
+            format!(
+                "; Inductive invariant certificate for system `{}`, generated by mikino v{}.",
+                input,
+                clap::crate_version!()
+            ),
+            format!("; Run {}.", self.env.run_id),
+            ";".into(),
+            "; The invariant is the conjunction of the candidates:".into(),
+        ];
+        for name in sys.po_s().keys() {
+            script.push(format!(";   `{}`", name))
+        }
+        script.push("; It is inductive if both `check-sat` queries below are `unsat`.".into());
+        script.push(String::new());
+        script.push("(set-logic ALL)".into());
+        for step in 0..=1 {
+            script.push(String::new());
+            script.push(format!("; State {}.", step));
+            script.extend(smt::state_decls(sys, step));
+            script.push(format!(
+                "(define-fun inv!{} () Bool {})",
+                step,
+                self.invariant_at(step)
+            ));
+        }
+        for (desc, assertions) in self.obligations().iter() {
+            script.push(String::new());
+            script.push(format!("; {}, expected `unsat`.", desc));
+            script.push("(push 1)".into());
+            for assertion in assertions {
+                script.push(format!("(assert {})", assertion))
+            }
+            script.push("(check-sat)".into());
+            script.push("(pop 1)".into());
+        }
+
+        let mut file = File::create(path)
+            .chain_err(|| format!("while creating certificate file `{}`", path))?;
+        for line in &script {
+            writeln!(file, "{}", line)
+                .chain_err(|| format!("while writing certificate to `{}`", path))?
+        }
+        println!(
+            "| - the conjunction of the candidates is {}",
+            self.green.paint("inductive")
+        );
+        println!("|   certificate written to `{}`", self.bold.paint(path));
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Counterexample store.
+//!
+//! Counterexamples get stable short IDs, `--cex_store <DIR>` saves them to `<DIR>`.
+
+mikino_api::prelude!();
+
+use toml::value::{Table, Value};
+
+use crate::{checkpoint::digest, conf::Conf, views::View, Check, Run, Trace};
+
+/// A stored counterexample.
+#[derive(Debug, Clone)]
+pub struct StoredCex {
+    /// ID of the counterexample.
+    pub id: String,
+    /// Run that found the counterexample.
+    pub run_id: String,
+    /// Candidate falsified.
+    pub candidate: String,
+    /// Text of the system.
+    pub system: String,
+    /// Views of the system.
+    pub views: Vec<View>,
+    /// Trace.
+    pub trace: Trace,
+}
+
+impl StoredCex {
+    /// File of a counterexample in the store.
+    fn path(dir: &str, id: &str) -> std::path::PathBuf {
+        let mut path = std::path::PathBuf::from(dir);
+        path.push(format!("{}.toml", id));
+        path
+    }
+
+    /// Saves the counterexample in the store, unless it is already there.
+    pub fn save(&self, dir: &str) -> Res<()> {
+        let path = Self::path(dir, &self.id);
+        if path.is_file() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(dir)
+            .chain_err(|| format!("while creating counterexample store `{}`", dir))?;
+        let strings =
+            |elems: &[String]| Value::Array(elems.iter().cloned().map(Value::String).collect());
+        let mut table = Table::new();
+        table.insert("id".into(), Value::String(self.id.clone()));
+        table.insert("run_id".into(), Value::String(self.run_id.clone()));
+        table.insert("candidate".into(), Value::String(self.candidate.clone()));
+        table.insert("system".into(), Value::String(self.system.clone()));
+        let views = self
+            .views
+            .iter()
+            .map(|view| {
+                let mut table = Table::new();
+                table.insert("name".into(), Value::String(view.name.clone()));
+                table.insert("vars".into(), strings(&view.vars));
+                table.insert("line".into(), Value::Integer(view.line as i64));
+                Value::Table(table)
+            })
+            .collect();
+        table.insert("views".into(), Value::Array(views));
+        let steps = self
+            .trace
+            .iter()
+            .map(|(step, values)| {
+                let mut table = Table::new();
+                table.insert("step".into(), Value::Integer(*step as i64));
+                let values = values
+                    .iter()
+                    .map(|(var, val)| strings(&[var.clone(), val.clone()]))
+                    .collect();
+                table.insert("values".into(), Value::Array(values));
+                Value::Table(table)
+            })
+            .collect();
+        table.insert("steps".into(), Value::Array(steps));
+        std::fs::write(&path, Value::Table(table).to_string())
+            .chain_err(|| format!("while writing counterexample `{}`", path.display()))
+    }
+
+    /// Loads a counterexample from the store, `id` can be a prefix of the ID.
+    pub fn load(dir: &str, id: &str) -> Res<Self> {
+        let mut path = Self::path(dir, id);
+        if !path.is_file() {
+            let entries = std::fs::read_dir(dir)
+                .chain_err(|| format!("while reading counterexample store `{}`", dir))?;
+            let mut matches = Vec::new();
+            for entry in entries {
+                let entry =
+                    entry.chain_err(|| format!("while reading counterexample store `{}`", dir))?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with(id) && name.ends_with(".toml") {
+                    matches.push(entry.path())
+                }
+            }
+            path = match matches.len() {
+                0 => bail!("no counterexample `{}` in store `{}`", id, dir),
+                1 => matches.pop().expect("length is 1"),
+                n => bail!(
+                    "ambiguous counterexample ID `{}`, {} counterexamples match",
+                    id,
+                    n
+                ),
+            }
+        }
+        let file = path.display().to_string();
+        let conf = Conf::load(&file).chain_err(|| "while loading stored counterexample")?;
+        let illegal = |key: &str| format!("illegal `{}` entry in counterexample `{}`", key, file);
+        let string =
+            |val: &Value, key: &str| val.as_str().map(String::from).ok_or_else(|| illegal(key));
+        let get = |key: &str| conf.table.get(key).ok_or_else(|| illegal(key));
+        let strings = |val: &Value, key: &str| -> Res<Vec<String>> {
+            let elems = val.as_array().ok_or_else(|| illegal(key))?;
+            Ok(elems
+                .iter()
+                .map(|elem| string(elem, key))
+                .collect::<Result<_, _>>()?)
+        };
+        let int = |val: Option<&Value>, key: &str| {
+            val.and_then(Value::as_integer)
+                .map(|n| n as usize)
+                .ok_or_else(|| illegal(key))
+        };
+
+        let mut views = Vec::new();
+        for view in get("views")?.as_array().ok_or_else(|| illegal("views"))? {
+            views.push(View {
+                name: string(view.get("name").ok_or_else(|| illegal("views"))?, "views")?,
+                vars: strings(view.get("vars").ok_or_else(|| illegal("views"))?, "views")?,
+                line: int(view.get("line"), "views")?,
+            })
+        }
+        let mut trace = Trace::new();
+        for step in get("steps")?.as_array().ok_or_else(|| illegal("steps"))? {
+            let mut values = Vec::new();
+            for pair in step
+                .get("values")
+                .and_then(Value::as_array)
+                .ok_or_else(|| illegal("steps"))?
+            {
+                match strings(pair, "steps")?.as_slice() {
+                    [var, val] => values.push((var.clone(), val.clone())),
+                    _ => return Err(illegal("steps").into()),
+                }
+            }
+            trace.push((int(step.get("step"), "steps")?, values))
+        }
+        Ok(Self {
+            id: string(get("id")?, "id")?,
+            run_id: string(get("run_id")?, "run_id")?,
+            candidate: string(get("candidate")?, "candidate")?,
+            system: string(get("system")?, "system")?,
+            views,
+            trace,
+        })
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Stable ID of a counterexample for a candidate, see [`cex_store`](crate::cex_store).
+    pub fn cex_id(&self, candidate: &str, trace: &Trace) -> String {
+        let mut desc = format!("{}\n{}\n", self.txt, candidate);
+        for (step, values) in trace {
+            desc.push_str(&format!("{}", step));
+            for (var, val) in values {
+                desc.push_str(&format!(" {}={}", var, val))
+            }
+            desc.push('\n')
+        }
+        digest(&desc)[..8].to_string()
+    }
+
+    /// Saves a counterexample in the store, if active.
+    pub fn store_cex(&self, candidate: &str, trace: &Trace) -> Res<()> {
+        let dir = match self.env.cex_store.as_ref() {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        StoredCex {
+            id: self.cex_id(candidate, trace),
+            run_id: self.env.run_id.clone(),
+            candidate: candidate.into(),
+            system: self.txt.clone(),
+            views: self.views.clone(),
+            trace: trace.clone(),
+        }
+        .save(dir)
+    }
+}
+
+impl Run {
+    /// Presents a stored counterexample again.
+    pub fn show_cex(&self, id: &str) -> Res<()> {
+        let dir = match self.cex_store.as_ref() {
+            Some(dir) => dir,
+            None => bail!("no counterexample store, see `--cex_store`"),
+        };
+        let cex = StoredCex::load(dir, id)?;
+        let mut check = Check::from_txt(self, &cex.system, &None)
+            .chain_err(|| format!("while parsing the system of counterexample `{}`", cex.id))?;
+        check.set_views(cex.views.clone())?;
+        println!(
+            "counterexample {} found by run {}:",
+            self.bold.paint(&cex.id),
+            self.gray.paint(&cex.run_id)
+        );
+        check.present_trace(&check.sys, &cex.candidate, &cex.trace, &[], true)
+    }
+}
@@ -0,0 +1,181 @@
+//! Checkpoints for long-running proof attempts, a TOML file ignored for other systems.
+
+mikino_api::prelude!();
+
+use std::sync::Mutex;
+
+use toml::value::{Table, Value};
+
+use crate::{conf::Conf, Check};
+
+/// Progress of the strengthening searches on a system.
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoint {
+    /// File the checkpoint is saved to.
+    pub path: String,
+    /// Digest of the system.
+    pub digest: String,
+    /// Candidates left by the lemma fixpoint, and the number of rounds, if any.
+    pub lemmas: Option<(Vec<String>, usize)>,
+    /// Last `k` simple-path k-induction reached for each candidate.
+    pub simple_path: Map<String, usize>,
+}
+
+/// Digest of the text of a system, stable across runs (64-bit FNV-1a).
+pub fn digest(txt: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in txt.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3)
+    }
+    format!("{:016x}", hash)
+}
+
+impl Checkpoint {
+    /// Loads the checkpoint of a system from `path`, empty if there is none or if it was saved
+    /// for another system.
+    ///
+    /// `txt` is the text of the system. Yields the checkpoint and whether it was resumed.
+    pub fn load(path: &str, txt: &str) -> Res<(Self, bool)> {
+        let mut res = Self {
+            path: path.into(),
+            digest: digest(txt),
+            ..Self::default()
+        };
+        if !std::path::Path::new(path).is_file() {
+            return Ok((res, false));
+        }
+        let conf = Conf::load(path).chain_err(|| "while loading checkpoint")?;
+        if conf.table.get("digest").and_then(Value::as_str) != Some(res.digest.as_str()) {
+            return Ok((res, false));
+        }
+        let illegal = |key: &str| format!("illegal `{}` entry in checkpoint `{}`", key, path);
+        if let Some(lemmas) = conf.table.get("lemmas") {
+            let candidates = lemmas
+                .get("candidates")
+                .and_then(Value::as_array)
+                .ok_or_else(|| illegal("lemmas"))?
+                .iter()
+                .map(|candidate| candidate.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| illegal("lemmas"))?;
+            let rounds = lemmas
+                .get("rounds")
+                .and_then(Value::as_integer)
+                .ok_or_else(|| illegal("lemmas"))?;
+            res.lemmas = Some((candidates, rounds as usize))
+        }
+        if let Some(simple_path) = conf.table.get("simple_path") {
+            let table = simple_path
+                .as_table()
+                .ok_or_else(|| illegal("simple_path"))?;
+            for (candidate, k) in table {
+                let k = k.as_integer().ok_or_else(|| illegal("simple_path"))?;
+                res.simple_path.insert(candidate.clone(), k as usize);
+            }
+        }
+        Ok((res, true))
+    }
+
+    /// Saves the checkpoint, atomically replacing the previous one.
+    pub fn save(&self) -> Res<()> {
+        let mut table = Table::new();
+        table.insert("digest".into(), Value::String(self.digest.clone()));
+        if let Some((candidates, rounds)) = self.lemmas.as_ref() {
+            let mut lemmas = Table::new();
+            lemmas.insert(
+                "candidates".into(),
+                Value::Array(candidates.iter().cloned().map(Value::String).collect()),
+            );
+            lemmas.insert("rounds".into(), Value::Integer(*rounds as i64));
+            table.insert("lemmas".into(), Value::Table(lemmas));
+        }
+        if !self.simple_path.is_empty() {
+            let simple_path = self
+                .simple_path
+                .iter()
+                .map(|(candidate, k)| (candidate.clone(), Value::Integer(*k as i64)))
+                .collect();
+            table.insert("simple_path".into(), Value::Table(simple_path));
+        }
+        let tmp = format!("{}.tmp", self.path);
+        std::fs::write(&tmp, Value::Table(table).to_string())
+            .chain_err(|| format!("while writing checkpoint `{}`", tmp))?;
+        std::fs::rename(&tmp, &self.path)
+            .chain_err(|| format!("while replacing checkpoint `{}`", self.path))?;
+        Ok(())
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Activates checkpoints, resuming from the one in `path` if it matches the system.
+    ///
+    /// `txt` is the text of the system.
+    pub fn set_checkpoint(&mut self, path: &str, txt: &str) -> Res<()> {
+        let (checkpoint, resumed) = Checkpoint::load(path, txt)?;
+        if resumed {
+            self.env.progress(|s| {
+                format!(
+                    "{} from checkpoint `{}`",
+                    s.bold.paint("resuming"),
+                    s.bold.paint(path)
+                )
+            })
+        }
+        self.checkpoint = Some(Mutex::new(checkpoint));
+        Ok(())
+    }
+
+    /// Applies `update` to the checkpoint and saves it, if checkpoints are active.
+    fn update_checkpoint(&self, update: impl FnOnce(&mut Checkpoint)) -> Res<()> {
+        if let Some(checkpoint) = self.checkpoint.as_ref() {
+            let mut checkpoint = checkpoint
+                .lock()
+                .map_err(|_| "failed to access checkpoint")?;
+            update(&mut checkpoint);
+            checkpoint.save()?
+        }
+        Ok(())
+    }
+
+    /// Candidates left by the lemma fixpoint and number of rounds in the checkpoint, if any.
+    pub fn resumed_lemmas(&self) -> Res<Option<(Vec<String>, usize)>> {
+        match self.checkpoint.as_ref() {
+            Some(checkpoint) => Ok(checkpoint
+                .lock()
+                .map_err(|_| "failed to access checkpoint")?
+                .lemmas
+                .clone()),
+            None => Ok(None),
+        }
+    }
+
+    /// Saves the candidates left by the lemma fixpoint after some rounds.
+    pub fn checkpoint_lemmas(&self, candidates: &[&str], rounds: usize) -> Res<()> {
+        self.update_checkpoint(|checkpoint| {
+            let candidates = candidates.iter().map(|c| c.to_string()).collect();
+            checkpoint.lemmas = Some((candidates, rounds))
+        })
+    }
+
+    /// Last `k` simple-path k-induction reached for a candidate in the checkpoint, `0` if none.
+    pub fn resumed_k(&self, candidate: &str) -> Res<usize> {
+        match self.checkpoint.as_ref() {
+            Some(checkpoint) => Ok(checkpoint
+                .lock()
+                .map_err(|_| "failed to access checkpoint")?
+                .simple_path
+                .get(candidate)
+                .cloned()
+                .unwrap_or(0)),
+            None => Ok(0),
+        }
+    }
+
+    /// Saves the last `k` simple-path k-induction reached for a candidate.
+    pub fn checkpoint_k(&self, candidate: &str, k: usize) -> Res<()> {
+        self.update_checkpoint(|checkpoint| {
+            checkpoint.simple_path.insert(candidate.into(), k);
+        })
+    }
+}
@@ -0,0 +1,260 @@
+//! Solver comparison.
+//!
+//! Runs the same queries with several solvers, reports disagreements and relative times.
+
+mikino_api::prelude!();
+
+use std::time::{Duration, Instant};
+
+use crate::{smt, Check};
+
+/// A query, asserted on top of the declaration of the states it mentions.
+pub struct Query {
+    /// Description.
+    pub desc: String,
+    /// Highest step mentioned.
+    pub depth: usize,
+    /// Assertions.
+    pub assertions: Vec<String>,
+}
+
+/// Verdict of a solver on a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Satisfiable.
+    Sat,
+    /// Unsatisfiable.
+    Unsat,
+    /// The solver failed.
+    Error(String),
+}
+impl Verdict {
+    /// Short description.
+    pub fn desc(&self) -> &str {
+        match self {
+            Self::Sat => "sat",
+            Self::Unsat => "unsat",
+            Self::Error(_) => "error",
+        }
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Queries comparing solvers: base and step checks for each candidate, and BMC checks up to
+    /// `bmc_max` if any.
+    pub fn comparison_queries(&self, bmc_max: Option<usize>) -> Vec<Query> {
+        let sys = &self.sys;
+        let init = smt::Unroll::new(sys.init(), 0).to_string();
+        let trans = |step: usize| smt::Unroll::new(sys.trans(), step).to_string();
+        let mut queries = Vec::new();
+        for (candidate, def) in sys.po_s().iter() {
+            let not_at = |step: usize| format!("(not {})", smt::Unroll::new(def, step));
+            queries.push(Query {
+                desc: format!("base `{}`", candidate),
+                depth: 0,
+                assertions: vec![init.clone(), not_at(0)],
+            });
+            queries.push(Query {
+                desc: format!("step `{}`", candidate),
+                depth: 1,
+                assertions: vec![smt::Unroll::new(def, 0).to_string(), trans(0), not_at(1)],
+            });
+            for depth in 1..=bmc_max.unwrap_or(0) {
+                let mut assertions = vec![init.clone()];
+                assertions.extend((0..depth).map(trans));
+                assertions.push(not_at(depth));
+                queries.push(Query {
+                    desc: format!("bmc `{}` at depth {}", candidate, depth),
+                    depth,
+                    assertions,
+                });
+            }
+        }
+        queries
+    }
+
+    /// Runs some queries with a solver, yields the verdict and time of each query.
+    ///
+    /// Once the solver fails, all remaining queries are errors.
+    pub fn run_queries(
+        &self,
+        solver_cmd: &str,
+        log_file: &str,
+        queries: &[Query],
+    ) -> Res<Vec<(Verdict, Duration)>> {
+        let sys = &self.sys;
+        let mut solver = self.solver_with_cmd(solver_cmd, log_file)?;
+        let max_depth = queries.iter().map(|query| query.depth).max().unwrap_or(0);
+        solver.intent("solver comparison setup")?;
+        for step in 0..=max_depth {
+            solver.declare_state(sys, step)?
+        }
+
+        let mut failed: Option<String> = None;
+        let mut res = Vec::with_capacity(queries.len());
+        for query in queries {
+            if let Some(err) = failed.as_ref() {
+                res.push((Verdict::Error(err.clone()), Duration::ZERO));
+                continue;
+            }
+            if self.env.verb > 0 {
+                self.env
+                    .progress(|s| format!("{}: {}", s.bold.paint(solver_cmd), query.desc));
+            }
+            let start = Instant::now();
+            let verdict: Res<bool> = (|| {
+                solver.intent(&format!("solver comparison: {}", query.desc))?;
+                solver.push()?;
+                for assertion in &query.assertions {
+                    solver.assert(assertion)?
+                }
+                let sat = solver.check_sat()?;
+                solver.pop()?;
+                Ok(sat)
+            })();
+            let time = start.elapsed();
+            let verdict = match verdict {
+                Ok(true) => Verdict::Sat,
+                Ok(false) => Verdict::Unsat,
+                Err(e) => {
+                    let msg = e.to_string();
+                    failed = Some(format!("solver failed on a previous query: {}", msg));
+                    Verdict::Error(msg)
+                }
+            };
+            res.push((verdict, time))
+        }
+        Ok(res)
+    }
+
+    /// Runs the same checks with each solver, and reports agreement and time ratios.
+    pub fn compare_solvers(&self, solvers: &[String], bmc_max: Option<usize>) -> Res<()> {
+        let queries = self.comparison_queries(bmc_max);
+        self.env.progress(|s| {
+            format!(
+                "{} {} solver(s) on {} quer(y/ies)...",
+                s.bold.paint("comparing"),
+                solvers.len(),
+                queries.len()
+            )
+        });
+
+        let mut results = Vec::with_capacity(solvers.len());
+        for (idx, solver) in solvers.iter().enumerate() {
+            let res = self
+                .run_queries(solver, &format!("compare_{}.smt2", idx), &queries)
+                .chain_err(|| format!("while running solver `{}`", solver))?;
+            results.push(res)
+        }
+
+        let mut disagreements = Vec::new();
+        for (q_idx, query) in queries.iter().enumerate() {
+            let verdicts: Vec<&Verdict> = results.iter().map(|res| &res[q_idx].0).collect();
+            let agree = verdicts.windows(2).all(|pair| pair[0] == pair[1]);
+            if !agree {
+                disagreements.push(q_idx)
+            } else if self.env.verb > 0 {
+                let times: Vec<String> = results
+                    .iter()
+                    .map(|res| format!("{:.3}s", res[q_idx].1.as_secs_f64()))
+                    .collect();
+                println!(
+                    "{}: {} ({})",
+                    query.desc,
+                    verdicts[0].desc(),
+                    times.join(", ")
+                )
+            }
+        }
+
+        if self.env.verb > 0 {
+            println!()
+        }
+        println!("|===| {} comparison result", self.bold.paint("Solver"));
+        println!("| - solvers");
+        for solver in solvers {
+            println!(
+                "|   `{}` ({})",
+                self.bold.paint(solver),
+                smt::Backend::of_cmd(solver).name()
+            )
+        }
+        println!("|");
+        if disagreements.is_empty() {
+            println!(
+                "| - verdicts {} on all {} quer(y/ies)",
+                self.green.paint("agree"),
+                queries.len()
+            )
+        } else {
+            println!(
+                "| - verdicts {} on {} of {} quer(y/ies)",
+                self.red.paint("disagree"),
+                disagreements.len(),
+                queries.len()
+            );
+            for q_idx in disagreements.iter().cloned() {
+                let verdicts: Vec<&str> = results.iter().map(|res| res[q_idx].0.desc()).collect();
+                println!("|   {}: {}", queries[q_idx].desc, verdicts.join(" / "));
+                for (solver, res) in solvers.iter().zip(results.iter()) {
+                    if let Verdict::Error(msg) = &res[q_idx].0 {
+                        println!("|     `{}`: {}", solver, msg)
+                    }
+                }
+            }
+        }
+        println!("|");
+        println!(
+            "| - time ratios relative to `{}`, over the queries all solvers answered",
+            self.bold.paint(&solvers[0])
+        );
+        let answered: Vec<usize> = (0..queries.len())
+            .filter(|q_idx| {
+                results
+                    .iter()
+                    .all(|res| !matches!(res[*q_idx].0, Verdict::Error(_)))
+            })
+            .collect();
+        let total = |res: &[(Verdict, Duration)]| -> f64 {
+            answered
+                .iter()
+                .map(|q_idx| res[*q_idx].1.as_secs_f64())
+                .sum()
+        };
+        let ref_total = total(&results[0]);
+        for (solver, res) in solvers.iter().zip(results.iter()).skip(1) {
+            let ratios: Vec<f64> = answered
+                .iter()
+                .filter_map(|q_idx| {
+                    let reference = results[0][*q_idx].1.as_secs_f64();
+                    let time = res[*q_idx].1.as_secs_f64();
+                    if reference > 0.0 && time > 0.0 {
+                        Some(time / reference)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            if ratios.is_empty() {
+                println!("|   `{}`: no comparable query", self.bold.paint(solver));
+                continue;
+            }
+            let geo_mean = (ratios.iter().map(|r| r.ln()).sum::<f64>() / ratios.len() as f64).exp();
+            let min = ratios.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = ratios.iter().cloned().fold(0.0, f64::max);
+            println!(
+                "|   `{}`: ×{:.2} geometric mean per query (min ×{:.2}, max ×{:.2}), \
+                total {:.3}s against {:.3}s",
+                self.bold.paint(solver),
+                geo_mean,
+                min,
+                max,
+                total(res),
+                ref_total,
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+//! Assume-guarantee compositional checking.
+//!
+//! Checks each component against its contract, then the main system against these contracts.
+
+mikino_api::prelude!();
+
+use std::path::PathBuf;
+
+use expr::Typ;
+
+use crate::{budget::Verdict, revisions::Status, text, Check, Run};
+
+/// Contract of a component, as expressions.
+struct Contract {
+    /// Component name.
+    name: String,
+    /// Assumptions of the component, in the syntax of the candidates.
+    assumptions: Vec<String>,
+    /// Guarantees proved under the assumptions.
+    guarantees: Vec<String>,
+}
+impl Contract {
+    /// Name of the variable tracking whether the assumptions held in all previous states.
+    fn assumed_var(&self) -> String {
+        format!("compose_{}_assumed", self.name)
+    }
+
+    /// Conjunction of some expressions.
+    fn conj(exprs: &[String]) -> String {
+        let exprs: Vec<String> = exprs.iter().map(|expr| format!("({})", expr)).collect();
+        exprs.join(" ⋀ ")
+    }
+}
+
+/// Syntax of a type in system files.
+fn typ_str(typ: Typ) -> &'static str {
+    match typ {
+        Typ::Bool => "bool",
+        Typ::Int => "int",
+        Typ::Rat => "rat",
+    }
+}
+
+impl Run {
+    /// Checks the components of a file against their contracts, and the `main` system against the
+    /// contracts of the components.
+    ///
+    /// Exits with the code of the verdict over the candidates of all the systems, see
+    /// [`Run::check_exit`].
+    pub fn compose(
+        &self,
+        input: &str,
+        main: &str,
+        bmc_max: usize,
+        smt_log: &Option<String>,
+    ) -> Res<()> {
+        let txt = crate::read_input(input)?;
+        let systems = text::systems(&txt)?;
+        if systems.is_empty() {
+            bail!(
+                "`{}` does not declare named systems, compose needs a main system and components",
+                input
+            )
+        }
+        let main_txt = match systems.iter().find(|(name, _, _)| name == main) {
+            Some((_, txt, _)) => txt.clone(),
+            None => {
+                let names: Vec<String> = systems
+                    .iter()
+                    .map(|(name, _, _)| format!("`{}`", name))
+                    .collect();
+                bail!(
+                    "unknown main system `{}`, `{}` declares {} (see `--main`)",
+                    main,
+                    input,
+                    names.join(", ")
+                )
+            }
+        };
+
+        let mut contracts = Vec::new();
+        let mut verdict = Verdict::default();
+        // Variables of all the systems, with their type.
+        let mut vars: Map<String, Typ> = Map::new();
+        for (name, txt, _) in systems.iter().filter(|(name, _, _)| name != main) {
+            let contract = self
+                .check_component(name, txt, bmc_max, smt_log, &mut vars, &mut verdict)
+                .chain_err(|| format!("while checking component `{}`", name))?;
+            contracts.push(contract);
+            println!()
+        }
+
+        println!("checking main system `{}`", self.bold.paint(main));
+        println!();
+        let main_txt = self.composed(&main_txt, &contracts, &mut vars)?;
+        let check = Check::from_txt(self, &main_txt, &component_log(smt_log, main)?)?;
+        let statuses = check.statuses(bmc_max)?;
+        add_statuses(&mut verdict, main, &statuses);
+        println!();
+        check.print_composition(&contracts, &statuses);
+        self.check_exit(&verdict, None, None)
+    }
+
+    /// Checks a component against its contract, yields the contract with the guarantees proved.
+    ///
+    /// Adds the variables of the component to `vars`, and the status of its candidates to
+    /// `verdict`.
+    fn check_component(
+        &self,
+        name: &str,
+        txt: &str,
+        bmc_max: usize,
+        smt_log: &Option<String>,
+        vars: &mut Map<String, Typ>,
+        verdict: &mut Verdict,
+    ) -> Res<Contract> {
+        println!("checking component `{}`", self.bold.paint(name));
+        println!();
+        let (component, assume) = text::take_block(txt, "assume");
+        let assumptions: Vec<String> = match assume {
+            Some(_) => text::elements(txt, "assume")?
+                .into_iter()
+                .map(|(start, end)| txt[start..end].to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+        let checked = if assumptions.is_empty() {
+            component.clone()
+        } else {
+            text::with_assumptions(&component, &assumptions)?
+        };
+        let check = Check::from_txt(self, &checked, &component_log(smt_log, name)?)?;
+        for var in check.sys.decls().iter() {
+            add_var(vars, var.id(), var.typ())?
+        }
+        let statuses = check.statuses(bmc_max)?;
+        add_statuses(verdict, name, &statuses);
+
+        let mut guarantees = Vec::new();
+        let (mut proved, mut falsified, mut unknown) = (Vec::new(), Vec::new(), Vec::new());
+        for (candidate, expr) in text::candidates(&component)? {
+            match statuses.get(&candidate) {
+                Some(Status::Proved) => {
+                    guarantees.push(expr);
+                    proved.push(candidate)
+                }
+                Some(Status::Falsified(_)) => falsified.push(candidate),
+                Some(Status::Unknown) | None => unknown.push(candidate),
+            }
+        }
+
+        println!();
+        println!(
+            "|===| Component `{}` {}",
+            self.bold.paint(name),
+            self.bold.paint("contract")
+        );
+        if assumptions.is_empty() {
+            println!("| - no assumptions")
+        } else {
+            println!("| - assuming");
+            for assumption in &assumptions {
+                println!("|   `{}`", check.sanitized_text(assumption))
+            }
+        }
+        for (candidates, desc) in [
+            (&proved, self.green.paint("hold").to_string()),
+            (&falsified, self.red.paint("are falsified").to_string()),
+            (
+                &unknown,
+                format!("could {} be proved", self.bold.paint("not")),
+            ),
+        ] {
+            if candidates.is_empty() {
+                continue;
+            }
+            println!("|");
+            println!("| - the following guarantee(s) {}", desc);
+            for candidate in candidates {
+                println!("|   `{}`", self.bold.paint(check.sanitized_text(candidate)))
+            }
+        }
+        if !falsified.is_empty() || !unknown.is_empty() {
+            println!("|");
+            println!("| - guarantees that do not hold are left out of the contract")
+        }
+        println!("|===|");
+
+        Ok(Contract {
+            name: name.into(),
+            assumptions,
+            guarantees,
+        })
+    }
+
+    /// Main system constrained by the contracts of the components.
+    ///
+    /// Declares the variables of the components the main system does not declare. A contract
+    /// constrains a state where its assumptions held so far to verify its guarantees. Checking
+    /// the assumptions of the components becomes a candidate of the main system.
+    fn composed(
+        &self,
+        main: &str,
+        contracts: &[Contract],
+        vars: &mut Map<String, Typ>,
+    ) -> Res<String> {
+        let sys = parse::trans(main).chain_err(|| "while parsing the main system")?;
+        let declared: Set<String> = sys.decls().iter().map(|var| var.id().to_string()).collect();
+        for var in sys.decls().iter() {
+            add_var(vars, var.id(), var.typ())?
+        }
+        let var_names: Set<String> = vars.keys().cloned().collect();
+
+        let mut decls = String::new();
+        for (var, typ) in vars.iter().filter(|(var, _)| !declared.contains(*var)) {
+            decls.push_str(&format!("\n    {}: {},", var, typ_str(*typ)))
+        }
+        let (mut init, mut trans) = (String::new(), String::new());
+        let mut candidates = text::candidates(main)?;
+        for contract in contracts.iter() {
+            if contract.guarantees.is_empty() {
+                continue;
+            }
+            let guarantee = Contract::conj(&contract.guarantees);
+            let next_guarantee = text::prime(&guarantee, &var_names);
+            if contract.assumptions.is_empty() {
+                init.push_str(&format!("\n    {},", guarantee));
+                trans.push_str(&format!("\n    {},", next_guarantee));
+                continue;
+            }
+            let assumption = Contract::conj(&contract.assumptions);
+            let next_assumption = text::prime(&assumption, &var_names);
+            let assumed = contract.assumed_var();
+            decls.push_str(&format!("\n    {}: bool,", assumed));
+            init.push_str(&format!(
+                "\n    {},\n    if {} {{ {} }} else {{ true }},",
+                assumed, assumption, guarantee
+            ));
+            trans.push_str(&format!(
+                "\n    '{} = ({} ⋀ {}),\n    if '{} ⋀ {} {{ {} }} else {{ true }},",
+                assumed, assumed, assumption, assumed, next_assumption, next_guarantee
+            ));
+            candidates.push((format!("assumptions of {}", contract.name), assumption));
+        }
+
+        let mut res = text::with_candidates(main, &candidates)?;
+        for (keyword, conj) in [("svars", &decls), ("init", &init), ("trans", &trans)] {
+            let (start, _) = text::block(&res, keyword)?;
+            res.insert_str(start, conj)
+        }
+        Ok(res)
+    }
+}
+
+/// Adds a variable to the variables of the systems, fails if its type changes.
+fn add_var(vars: &mut Map<String, Typ>, var: &str, typ: Typ) -> Res<()> {
+    match vars.get(var) {
+        Some(known) if *known != typ => bail!(
+            "variable `{}` is declared with types `{}` and `{}`",
+            var,
+            typ_str(*known),
+            typ_str(typ)
+        ),
+        Some(_) => (),
+        None => {
+            vars.insert(var.to_string(), typ);
+        }
+    }
+    Ok(())
+}
+
+/// Adds the status of the candidates of a system to a verdict.
+fn add_statuses(verdict: &mut Verdict, system: &str, statuses: &Map<String, Status>) {
+    for (candidate, status) in statuses.iter() {
+        let desc = format!("{}::{}", system, candidate);
+        match status {
+            Status::Proved => verdict.proved.push(desc),
+            Status::Falsified(_) => verdict.falsified.push(desc),
+            Status::Unknown => verdict.unknown.push(desc),
+        }
+    }
+}
+
+/// SMT log directory of a system of the composition, if any.
+fn component_log(smt_log: &Option<String>, name: &str) -> Res<Option<String>> {
+    let Some(dir) = smt_log.as_ref() else {
+        return Ok(None);
+    };
+    let mut dir = PathBuf::from(dir);
+    dir.push(name);
+    let dir = dir.display().to_string();
+    std::fs::create_dir_all(&dir)
+        .chain_err(|| format!("while creating SMT log directory `{}`", dir))?;
+    Ok(Some(dir))
+}
+
+impl<'env> Check<'env> {
+    /// Prints the result of the main system's check against the contracts.
+    fn print_composition(&self, contracts: &[Contract], statuses: &Map<String, Status>) {
+        let assumption_of = |candidate: &str| {
+            contracts.iter().find(|contract| {
+                !contract.assumptions.is_empty()
+                    && !contract.guarantees.is_empty()
+                    && candidate == format!("assumptions of {}", contract.name)
+            })
+        };
+        let (mut proved, mut unproved) = (Vec::new(), Vec::new());
+        let (mut met, mut unmet) = (Vec::new(), Vec::new());
+        for (candidate, status) in statuses.iter() {
+            match (assumption_of(candidate), status) {
+                (Some(contract), Status::Proved) => met.push(contract.name.as_str()),
+                (Some(contract), _) => unmet.push(contract.name.as_str()),
+                (None, Status::Proved) => proved.push(candidate.as_str()),
+                (None, _) => unproved.push(candidate.as_str()),
+            }
+        }
+
+        println!("|===| {} result", self.bold.paint("Composition"));
+        println!(
+            "| - {} contract(s) used",
+            contracts
+                .iter()
+                .filter(|contract| !contract.guarantees.is_empty())
+                .count()
+        );
+        if !met.is_empty() {
+            println!("|");
+            println!(
+                "| - the assumptions of the following component(s) are {}",
+                self.green.paint("met")
+            );
+            for name in &met {
+                println!("|   `{}`", self.green.paint(*name))
+            }
+        }
+        if !unmet.is_empty() {
+            println!("|");
+            println!(
+                "| - the assumptions of the following component(s) {} be met, \
+                their guarantees may not hold",
+                self.red.paint("might not")
+            );
+            for name in &unmet {
+                println!("|   `{}`", self.red.paint(*name))
+            }
+        }
+        if !proved.is_empty() {
+            println!("|");
+            println!(
+                "| - the following candidate(s) {} in the composition",
+                self.green.paint("hold")
+            );
+            for candidate in &proved {
+                println!("|   `{}`", self.green.paint(self.sanitized_text(candidate)))
+            }
+        }
+        if !unproved.is_empty() {
+            println!("|");
+            println!(
+                "| - the following candidate(s) could {} be proved from the contracts",
+                self.bold.paint("not")
+            );
+            for candidate in &unproved {
+                println!("|   `{}`", self.bold.paint(self.sanitized_text(candidate)))
+            }
+        }
+        println!("|");
+        if unproved.is_empty() && unmet.is_empty() {
+            println!(
+                "| - system is {}, all reachable states verify the candidate(s)",
+                self.green.paint("safe")
+            )
+        } else {
+            println!("| - system {}", self.red.paint("might be unsafe"))
+        }
+        println!("|===|")
+    }
+}
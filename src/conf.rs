@@ -0,0 +1,536 @@
+//! Configuration file and environment variables, layered under the command line.
+
+mikino_api::prelude!();
+
+use toml::value::{Table, Value};
+
+use crate::mode::App;
+
+/// Default configuration file.
+pub const DEFAULT_FILE: &str = "mikino.toml";
+
+/// Key standing for occurrences of `-v`.
+const VERBOSITY: &str = "verbosity";
+
+/// Environment variables setting options, and the configuration key they stand for.
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("MIKINO_Z3_CMD", "z3_cmd"),
+    ("MIKINO_SMT_LOG", "smt_log"),
+    ("MIKINO_COLOR", "color"),
+    ("MIKINO_VERB", VERBOSITY),
+];
+
+/// Configuration.
+pub struct Conf {
+    /// File the configuration was loaded from.
+    pub file: String,
+    /// Top-level table.
+    pub table: Table,
+}
+impl Conf {
+    /// Loads a configuration file.
+    pub fn load(file: &str) -> Res<Self> {
+        let content = std::fs::read_to_string(file)
+            .chain_err(|| format!("while reading configuration file `{}`", file))?;
+        let table = match content.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => bail!("configuration file `{}` is not a table", file),
+            Err(e) => bail!("while parsing configuration file `{}`: {}", file, e),
+        };
+        Ok(Self {
+            file: file.into(),
+            table,
+        })
+    }
+
+    /// Loads the default configuration file, if it exists.
+    pub fn load_default() -> Res<Option<Self>> {
+        if std::path::Path::new(DEFAULT_FILE).is_file() {
+            Self::load(DEFAULT_FILE).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Defaults, the top-level options.
+    pub fn defaults(&self) -> Table {
+        self.table
+            .iter()
+            .filter(|(key, _)| *key != "profile")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Retrieves a profile.
+    pub fn profile(&self, name: &str) -> Res<&Table> {
+        match self
+            .table
+            .get("profile")
+            .and_then(|profiles| profiles.get(name))
+        {
+            Some(Value::Table(profile)) => Ok(profile),
+            Some(_) => bail!(
+                "profile `{}` in configuration file `{}` is not a table",
+                name,
+                self.file
+            ),
+            None => bail!(
+                "unknown profile `{}`, not found in configuration file `{}`",
+                name,
+                self.file
+            ),
+        }
+    }
+}
+
+/// Value of an option of the command line, if any.
+fn option_value(args: &[String], long: &str) -> Option<String> {
+    let flag = format!("--{}", long);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if *arg == flag {
+            return args.next().cloned();
+        } else if let Some(val) = arg.strip_prefix(&format!("{}=", flag)) {
+            return Some(val.into());
+        }
+    }
+    None
+}
+
+/// Options set by the environment variables, see [`ENV_VARS`].
+pub fn env_options() -> Res<Table> {
+    let mut table = Table::new();
+    for (var, key) in ENV_VARS {
+        let val = match std::env::var(var) {
+            Ok(val) if !val.trim().is_empty() => val.trim().to_string(),
+            Ok(_) | Err(std::env::VarError::NotPresent) => continue,
+            Err(std::env::VarError::NotUnicode(_)) => {
+                bail!("environment variable `{}` is not valid unicode", var)
+            }
+        };
+        let value = match *key {
+            "color" => match val.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" | "always" => Value::Boolean(true),
+                "0" | "false" | "no" | "off" | "never" => Value::Boolean(false),
+                _ => bail!(
+                    "unexpected value for environment variable `{}`: `{}`",
+                    var,
+                    val
+                ),
+            },
+            VERBOSITY => match val.parse::<i64>() {
+                Ok(n) if n >= 0 => Value::Integer(n),
+                _ => bail!(
+                    "unexpected value for environment variable `{}`: `{}`, expected an integer",
+                    var,
+                    val
+                ),
+            },
+            _ => Value::String(val),
+        };
+        table.insert(key.to_string(), value);
+    }
+    Ok(table)
+}
+
+/// Name of the profile requested on the command line, if any.
+pub fn profile_name(args: &[String]) -> Option<String> {
+    option_value(args, "profile")
+}
+
+/// Configuration file requested on the command line, if any.
+pub fn config_file(args: &[String]) -> Option<String> {
+    option_value(args, "config")
+}
+
+/// Turns a table of options into command-line arguments, one entry per option.
+///
+/// Boolean options are flags, arrays yield one occurrence of the option per element. Entries are
+/// named after the long name of their option, `verbosity` for `-v`.
+pub fn table_to_args(table: &Table) -> Res<Vec<(String, Vec<String>)>> {
+    fn value_to_arg(key: &str, value: &Value) -> Res<String> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Integer(i) => Ok(i.to_string()),
+            Value::Float(f) => Ok(f.to_string()),
+            _ => bail!("unexpected value for option `{}`: `{}`", key, value),
+        }
+    }
+
+    let mut res = Vec::with_capacity(table.len());
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            (VERBOSITY, Value::Integer(n)) if *n >= 0 => {
+                res.push((key.clone(), vec!["-v".to_string(); *n as usize]));
+                continue;
+            }
+            (VERBOSITY, _) => bail!("unexpected value for `{}`: `{}`", key, value),
+            ("color", Value::Boolean(color)) => {
+                if !color {
+                    res.push(("no_color".into(), vec!["--no_color".into()]))
+                }
+                continue;
+            }
+            ("color", _) => bail!("unexpected value for `color`: `{}`", value),
+            _ => (),
+        }
+        let flag = format!("--{}", key);
+        let mut args = vec![];
+        match value {
+            Value::Boolean(false) => continue,
+            Value::Boolean(true) => args.push(flag),
+            Value::Array(values) => {
+                for value in values {
+                    args.push(flag.clone());
+                    args.push(value_to_arg(key, value)?)
+                }
+            }
+            value => {
+                args.push(flag);
+                args.push(value_to_arg(key, value)?)
+            }
+        }
+        res.push((key.clone(), args))
+    }
+    Ok(res)
+}
+
+/// Applies the environment variables, the profile and the configuration defaults to the
+/// command-line arguments.
+pub fn apply(app: &App, args: &mut Vec<String>) -> Res<()> {
+    let env = env_options()?;
+    let conf = match config_file(args) {
+        Some(file) => Some(Conf::load(&file)?),
+        None => Conf::load_default()?,
+    };
+    let profile = match profile_name(args) {
+        Some(profile) => {
+            let conf = conf.as_ref().ok_or_else(|| {
+                format!(
+                    "cannot apply profile `{}`, no `{}` file in the current directory",
+                    profile, DEFAULT_FILE
+                )
+            })?;
+            Some(conf.profile(&profile)?)
+        }
+        None => None,
+    };
+    let defaults = conf.as_ref().map(Conf::defaults);
+    apply_layers(app, &env, profile, defaults.as_ref(), args)
+}
+
+/// Applies the environment options, then the profile, then the defaults.
+///
+/// Options the environment sets are dropped from the profile and the defaults, since some values
+/// such as `color = true` yield no argument.
+fn apply_layers(
+    app: &App,
+    env: &Table,
+    profile: Option<&Table>,
+    defaults: Option<&Table>,
+    args: &mut Vec<String>,
+) -> Res<()> {
+    let unset = |table: &Table| -> Table {
+        table
+            .iter()
+            .filter(|(key, _)| !env.contains_key(*key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    };
+    apply_options(app, env, true, args)?;
+    if let Some(profile) = profile {
+        apply_options(app, &unset(profile), false, args)?
+    }
+    if let Some(defaults) = defaults {
+        apply_options(app, &unset(defaults), true, args)?
+    }
+    Ok(())
+}
+
+/// Index of the subcommand in the command-line arguments, if any.
+///
+/// Skips the values of the top-level options, so that `--z3_cmd check` is not a subcommand.
+fn subcommand_index(app: &App, args: &[String]) -> Option<usize> {
+    let takes_value = |arg: &clap::Arg| arg.is_takes_value_set();
+    let mut idx = 1;
+    while idx < args.len() {
+        let arg = &args[idx];
+        if arg == "--" {
+            return None;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=')
+                && app
+                    .get_arguments()
+                    .any(|arg| arg.get_long() == Some(long) && takes_value(arg))
+            {
+                idx += 1
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|shorts| !shorts.is_empty()) {
+            // In a group such as `-vl <FILE>`, the first option taking a value takes the rest.
+            let value_at = shorts.char_indices().find(|(_, short)| {
+                app.get_arguments()
+                    .any(|arg| arg.get_short() == Some(*short) && takes_value(arg))
+            });
+            if let Some((pos, short)) = value_at {
+                if pos + short.len_utf8() == shorts.len() {
+                    idx += 1
+                }
+            }
+        } else {
+            return app.find_subcommand(arg).map(|_| idx);
+        }
+        idx += 1
+    }
+    None
+}
+
+/// Inserts some options in the command-line arguments.
+///
+/// Top-level options go before the subcommand, subcommand options right after it. Options already
+/// present in `args` are left untouched. Options the subcommand does not have are ignored if
+/// `defaults`, and an error otherwise.
+pub fn apply_options(
+    app: &App,
+    options: &Table,
+    defaults: bool,
+    args: &mut Vec<String>,
+) -> Res<()> {
+    // Options given so far, `Err` on `--help` and `--version`.
+    let given = match app
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(args.iter())
+    {
+        Ok(given) => given,
+        Err(_) => return Ok(()),
+    };
+    let sub_idx = subcommand_index(app, args);
+    let sub = sub_idx.and_then(|idx| app.find_subcommand(&args[idx]));
+    let sub_given = sub.and_then(|sub| given.subcommand_matches(sub.get_name()));
+    let long_id = |cmd: &App, key: &str| {
+        cmd.get_arguments()
+            .find(|arg| arg.get_long() == Some(key))
+            .map(|arg| arg.get_id())
+    };
+
+    let mut top_args = vec![];
+    let mut sub_args = vec![];
+    for (key, opt_args) in table_to_args(options)? {
+        if key == VERBOSITY {
+            if given.occurrences_of("VERB") == 0 && given.occurrences_of("QUIET") == 0 {
+                top_args.extend(opt_args)
+            }
+            continue;
+        }
+        if let Some(id) = long_id(app, &key) {
+            if given.occurrences_of(id) == 0 {
+                top_args.extend(opt_args)
+            }
+        } else if let Some(id) = sub.and_then(|sub| long_id(sub, &key)) {
+            if sub_given
+                .map(|given| given.occurrences_of(id) == 0)
+                .unwrap_or(true)
+            {
+                sub_args.extend(opt_args)
+            }
+        } else if defaults
+            && app
+                .get_subcommands()
+                .any(|sub| long_id(sub, &key).is_some())
+        {
+            continue;
+        } else if defaults {
+            bail!("configuration option `{}` is not a legal option", key)
+        } else {
+            bail!("profile option `{}` is not a legal option here", key)
+        }
+    }
+
+    if let Some(idx) = sub_idx {
+        let tail = args.split_off(idx + 1);
+        args.extend(sub_args);
+        args.extend(tail);
+    }
+    let tail = args.split_off(1.min(args.len()));
+    args.extend(top_args);
+    args.extend(tail);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::Arg;
+
+    fn app() -> App {
+        App::new("mikino")
+            .args(&[
+                Arg::new("NO_COLOR").long("no_color"),
+                Arg::new("VERB").short('v').multiple_occurrences(true),
+                Arg::new("QUIET").short('q'),
+                Arg::new("LOG").short('l').takes_value(true),
+                Arg::new("Z3_CMD").long("z3_cmd").takes_value(true),
+            ])
+            .subcommand(
+                App::new("check")
+                    .arg(Arg::new("BMC_MAX").long("bmc_max").takes_value(true))
+                    .arg(Arg::new("INPUT")),
+            )
+    }
+
+    fn table(txt: &str) -> Table {
+        match txt.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            res => panic!("unexpected table `{}`: {:?}", txt, res),
+        }
+    }
+
+    fn apply_to(options: &str, args: &[&str]) -> Vec<String> {
+        let mut args = args.iter().map(|arg| arg.to_string()).collect();
+        apply_options(&app(), &table(options), true, &mut args).unwrap();
+        args
+    }
+
+    #[test]
+    fn verbosity() {
+        let args = apply_to(
+            "verbosity = 2",
+            &["mikino", "-l", "./qa-logs", "check", "-v2.mkn"],
+        );
+        assert_eq!(
+            args,
+            ["mikino", "-v", "-v", "-l", "./qa-logs", "check", "-v2.mkn"]
+        );
+        let args = apply_to("verbosity = 2", &["mikino", "-qv", "check", "sys.mkn"]);
+        assert_eq!(args, ["mikino", "-qv", "check", "sys.mkn"]);
+    }
+
+    #[test]
+    fn subcommand_after_option_values() {
+        let args = apply_to(
+            "bmc_max = 5",
+            &["mikino", "--z3_cmd", "check", "check", "sys.mkn"],
+        );
+        assert_eq!(
+            args,
+            [
+                "mikino",
+                "--z3_cmd",
+                "check",
+                "check",
+                "--bmc_max",
+                "5",
+                "sys.mkn"
+            ]
+        );
+        let args = apply_to(
+            "bmc_max = 5",
+            &["mikino", "-vl", "check", "check", "sys.mkn"],
+        );
+        assert_eq!(
+            args,
+            [
+                "mikino",
+                "-vl",
+                "check",
+                "check",
+                "--bmc_max",
+                "5",
+                "sys.mkn"
+            ]
+        );
+    }
+
+    #[test]
+    fn given_options_take_precedence() {
+        let options = "z3_cmd = \"z3-4.12\"\nbmc_max = 5\ncolor = false";
+        let args = apply_to(
+            options,
+            &[
+                "mikino",
+                "--z3_cmd=z3",
+                "--no_color",
+                "check",
+                "--bmc_max",
+                "7",
+                "sys.mkn",
+            ],
+        );
+        assert_eq!(
+            args,
+            [
+                "mikino",
+                "--z3_cmd=z3",
+                "--no_color",
+                "check",
+                "--bmc_max",
+                "7",
+                "sys.mkn"
+            ]
+        );
+        let args = apply_to(options, &["mikino", "check", "sys.mkn"]);
+        assert_eq!(
+            args,
+            [
+                "mikino",
+                "--no_color",
+                "--z3_cmd",
+                "z3-4.12",
+                "check",
+                "--bmc_max",
+                "5",
+                "sys.mkn"
+            ]
+        );
+    }
+
+    #[test]
+    fn layers() {
+        let layered = |env: &str, profile: &str, defaults: &str| {
+            let mut args = vec!["mikino".to_string(), "check".to_string()];
+            let (profile, defaults) = (table(profile), table(defaults));
+            apply_layers(
+                &app(),
+                &table(env),
+                Some(&profile),
+                Some(&defaults),
+                &mut args,
+            )
+            .unwrap();
+            args
+        };
+        assert_eq!(
+            layered(
+                "color = true\nverbosity = 0",
+                "",
+                "color = false\nverbosity = 2"
+            ),
+            ["mikino", "check"]
+        );
+        assert_eq!(
+            layered("verbosity = 1", "verbosity = 2", "verbosity = 3"),
+            ["mikino", "-v", "check"]
+        );
+        assert_eq!(
+            layered(
+                "z3_cmd = \"z3-env\"",
+                "bmc_max = 5",
+                "bmc_max = 7\nz3_cmd = \"z3\""
+            ),
+            ["mikino", "--z3_cmd", "z3-env", "check", "--bmc_max", "5"]
+        );
+    }
+
+    #[test]
+    fn illegal_options() {
+        let mut args = vec!["mikino".to_string(), "check".to_string()];
+        assert!(apply_options(&app(), &table("bmc = true"), true, &mut args).is_err());
+        assert!(apply_options(
+            &app(),
+            &table("bmc_max = 5"),
+            false,
+            &mut vec!["mikino".into()]
+        )
+        .is_err());
+    }
+}
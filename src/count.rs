@@ -0,0 +1,201 @@
+//! Reachable state counting, and exploration coverage of BMC.
+//!
+//! Exact for small finite systems, estimated from random runs otherwise.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check};
+
+impl<'env> Check<'env> {
+    /// Runs the count mode for BMC up to `bmc_max`.
+    ///
+    /// Falls back to sampling with `runs` runs of `steps` transitions if there are more than
+    /// `max_states` reachable states.
+    pub fn count(
+        &self,
+        bmc_max: usize,
+        max_states: usize,
+        steps: usize,
+        runs: usize,
+        seed: Option<u64>,
+    ) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "enumerating {} states, at most {}...",
+                s.under.paint("reachable"),
+                max_states
+            )
+        });
+        let reached = self
+            .try_reachable_states(max_states)
+            .chain_err(|| "during reachable state enumeration")?;
+        match reached {
+            Some(reached) => {
+                let depths: Vec<usize> = (0..reached.states.len())
+                    .map(|idx| reached.depth_of(idx))
+                    .collect();
+                let diameter = depths.iter().cloned().max().unwrap_or(0);
+                let explored = depths.iter().filter(|depth| **depth <= bmc_max).count();
+
+                println!("|===| {} result", self.bold.paint("Reachable states"));
+                println!(
+                    "| - {} reachable state(s) (exact), the deepest one is reached in {} step(s)",
+                    self.bold.paint(reached.states.len().to_string()),
+                    diameter
+                );
+                for depth in 0..=diameter {
+                    let count = depths.iter().filter(|d| **d == depth).count();
+                    println!("|   depth {: >3}: {} new state(s)", depth, count)
+                }
+                println!("|");
+                self.report_explored(explored, reached.states.len(), bmc_max, true);
+                if diameter <= bmc_max {
+                    println!(
+                        "|   BMC up to depth {} is {}: it explores all reachable states",
+                        bmc_max,
+                        self.green.paint("complete")
+                    )
+                } else {
+                    println!(
+                        "|   BMC needs depth {} to explore all reachable states",
+                        self.bold.paint(diameter.to_string())
+                    )
+                }
+                println!("|===|");
+                Ok(())
+            }
+            None => self
+                .count_sampling(bmc_max, max_states, steps, runs, seed)
+                .chain_err(|| "during reachable state sampling"),
+        }
+    }
+
+    /// Prints the fraction of the reachable states BMC explores.
+    fn report_explored(&self, explored: usize, total: usize, bmc_max: usize, exact: bool) {
+        let percent = if total == 0 {
+            100.0
+        } else {
+            100.0 * explored as f64 / total as f64
+        };
+        let style = if explored == total {
+            self.green
+        } else {
+            self.red
+        };
+        println!(
+            "| - BMC up to depth {} explores {} of the {}reachable states ({} of {})",
+            bmc_max,
+            style.paint(format!("{:.1}%", percent)),
+            if exact { "" } else { "sampled " },
+            explored,
+            total
+        );
+    }
+
+    /// Estimates the number of reachable states and the fraction BMC explores by sampling.
+    fn count_sampling(
+        &self,
+        bmc_max: usize,
+        max_states: usize,
+        steps: usize,
+        runs: usize,
+        seed: Option<u64>,
+    ) -> Res<()> {
+        let sys = &self.sys;
+        self.env.progress(|s| {
+            format!(
+                "more than {} reachable states, {} {} run(s) of {} step(s)...",
+                max_states,
+                s.bold.paint("sampling"),
+                runs,
+                steps
+            )
+        });
+        let mut rng = self.rng(seed);
+        let mut solver = self.solver("count_sampling.smt2")?;
+        solver.intent("count: sampling setup")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+
+        // Sampled states with the depth of their first occurrence, for each half of the runs.
+        let mut halves: [Map<smt::State, usize>; 2] = [Map::new(), Map::new()];
+        for run in 0..runs {
+            solver.intent(&format!("count: run {}, initial state", run + 1))?;
+            let half = &mut halves[run % 2];
+            let mut state = self.random_init(&mut solver, &mut rng)?;
+            for step in 0..=steps {
+                let depth = half.entry(state.clone()).or_insert(step);
+                *depth = (*depth).min(step);
+                if step == steps {
+                    break;
+                }
+                solver.intent(&format!("count: run {}, step {}", run + 1, step + 1))?;
+                match self.random_successor(&mut solver, &mut rng, &state, None)? {
+                    Some(next) => state = next,
+                    None => break,
+                }
+            }
+        }
+        let [fst, snd] = halves;
+        let common = fst.keys().filter(|state| snd.contains_key(*state)).count();
+        let (fst_len, snd_len) = (fst.len(), snd.len());
+        let mut sampled = fst;
+        for (state, depth) in snd {
+            let known = sampled.entry(state).or_insert(depth);
+            *known = (*known).min(depth)
+        }
+
+        // States first seen deeper than `bmc_max` may still be reachable in fewer steps.
+        let mut bounded = self.solver("count_bmc.smt2")?;
+        bounded.intent(&format!("count: unrolling up to depth {}", bmc_max))?;
+        bounded.declare_state(sys, 0)?;
+        bounded.assert_init(sys, 0)?;
+        for step in 1..=bmc_max {
+            bounded.declare_state(sys, step)?;
+            bounded.assert_trans(sys, step - 1)?
+        }
+        let mut explored = 0;
+        for (state, depth) in &sampled {
+            if *depth <= bmc_max {
+                explored += 1;
+                continue;
+            }
+            bounded.intent("count: is the sampled state reachable within the BMC depth?")?;
+            let reachable: Vec<String> = (0..=bmc_max)
+                .map(|step| smt::state_at(state, step))
+                .collect();
+            if !bounded.unsat(&[format!("(or {})", reachable.join(" "))])? {
+                explored += 1
+            }
+        }
+
+        println!("|===| {} result", self.bold.paint("Reachable states"));
+        println!(
+            "| - more than {} reachable states, {} distinct state(s) sampled in {} run(s)",
+            max_states,
+            sampled.len(),
+            runs
+        );
+        if common > 0 {
+            let estimate = (fst_len * snd_len) as f64 / common as f64;
+            println!(
+                "| - about {} reachable state(s) (capture-recapture estimate)",
+                self.bold
+                    .paint(format!("{:.0}", estimate.max(max_states as f64)))
+            )
+        } else {
+            println!(
+                "| - the two halves of the runs share no state, too few samples to estimate the \
+                number of reachable states"
+            )
+        }
+        println!("|");
+        self.report_explored(explored, sampled.len(), bmc_max, false);
+        println!(
+            "|   sampling is biased toward the states random runs favor, these are {}",
+            self.bold.paint("rough estimates")
+        );
+        println!("|===|");
+        Ok(())
+    }
+}
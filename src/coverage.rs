@@ -0,0 +1,208 @@
+//! Transition relation coverage.
+//!
+//! Branches are the ones of the `if`s and the disjuncts, with the condition they are taken under.
+
+mikino_api::prelude!();
+
+use expr::{Expr, Op, Typ};
+
+use crate::{smt, Check, Trace};
+
+/// A branch of the transition relation.
+pub struct Branch {
+    /// Human-readable description.
+    pub desc: String,
+    /// Condition under which the branch is taken, unrolled at step `0`.
+    pub cond: String,
+    /// Conjuncts of the condition, with their polarity.
+    pub guard: Vec<(Expr, bool)>,
+}
+impl Branch {
+    /// Condition under which the branch is taken, unrolled at some step.
+    pub fn cond_at(&self, step: usize) -> String {
+        let conjuncts: Vec<String> = self
+            .guard
+            .iter()
+            .map(|(cond, pos)| {
+                let cond = smt::Unroll::new(cond, step).to_string();
+                if *pos {
+                    cond
+                } else {
+                    format!("(not {})", cond)
+                }
+            })
+            .collect();
+        conj(&conjuncts)
+    }
+}
+
+/// Conjunction of some SMT-LIB formulas.
+fn conj(path: &[String]) -> String {
+    match path.len() {
+        0 => "true".into(),
+        1 => path[0].clone(),
+        _ => format!("(and {})", path.join(" ")),
+    }
+}
+
+/// Branch taken under the conjunction of some conditions.
+fn branch(desc: String, path: &[(Expr, bool)]) -> Branch {
+    let mut branch = Branch {
+        desc,
+        cond: String::new(),
+        guard: path.to_vec(),
+    };
+    branch.cond = branch.cond_at(0);
+    branch
+}
+
+/// Collects the branches of an expression, `path` are the conditions of the enclosing branches.
+fn collect(expr: &Expr, path: &mut Vec<(Expr, bool)>, res: &mut Vec<Branch>) {
+    let Expr::App { op, args } = expr else {
+        return;
+    };
+    match (op, args.as_slice()) {
+        (Op::Ite, [cnd, thn, els]) => {
+            collect(cnd, path, res);
+            for (desc, pos, sub) in [
+                (format!("if `{}`", cnd), true, thn),
+                (format!("else of `{}`", cnd), false, els),
+            ] {
+                path.push((cnd.clone(), pos));
+                res.push(branch(desc, path));
+                collect(sub, path, res);
+                path.pop();
+            }
+        }
+        (Op::Or, args) if args.len() > 1 => {
+            for arg in args {
+                path.push((arg.clone(), true));
+                res.push(branch(format!("disjunct `{}`", arg), path));
+                collect(arg, path, res);
+                path.pop();
+            }
+        }
+        (_, args) => {
+            for arg in args {
+                collect(arg, path, res)
+            }
+        }
+    }
+}
+
+/// Branches of the transition relation.
+pub fn branches(trans: &Expr) -> Vec<Branch> {
+    let mut res = Vec::new();
+    collect(trans, &mut Vec::new(), &mut res);
+    res
+}
+
+/// SMT-LIB value of a value as it appears in traces.
+fn smt_value(val: &str, typ: Typ) -> String {
+    let val = val.trim_end_matches(" (completed)");
+    let num = |n: &str| match (n.strip_prefix('-'), typ) {
+        (Some(abs), Typ::Rat) => format!("(- {}.0)", abs),
+        (Some(abs), _) => format!("(- {})", abs),
+        (None, Typ::Rat) => format!("{}.0", n),
+        (None, _) => n.into(),
+    };
+    match (typ, val.split_once('/')) {
+        (Typ::Bool, _) => val.into(),
+        (_, Some((n, d))) => format!("(/ {} {})", num(n.trim()), num(d.trim())),
+        (_, None) => num(val),
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Assertion fixing the values of a trace's state at some step.
+    pub fn trace_state_at(&self, values: &[(String, String)], step: usize) -> String {
+        let mut conj = "(and true".to_string();
+        for var in self.sys.decls().iter() {
+            if let Some((_, val)) = values.iter().find(|(id, _)| id == var.id()) {
+                conj.push_str(&format!(
+                    " (= {} {})",
+                    smt::svar(var.id(), step),
+                    smt_value(val, var.typ())
+                ))
+            }
+        }
+        conj.push(')');
+        conj
+    }
+
+    /// Reports the branches of the transition relation some traces exercise.
+    pub fn coverage(&self, traces: &[&Trace]) -> Res<()> {
+        let branches = branches(self.sys.trans());
+        let transitions: usize = traces
+            .iter()
+            .map(|trace| trace.len().saturating_sub(1))
+            .sum();
+        self.env.progress(|s| {
+            format!(
+                "computing {} of {} branch(es) over {} transition(s)...",
+                s.bold.paint("coverage"),
+                branches.len(),
+                transitions
+            )
+        });
+
+        let mut hits = vec![0; branches.len()];
+        if !branches.is_empty() && transitions > 0 {
+            let mut solver = self.solver("coverage.smt2")?;
+            solver.intent("coverage setup")?;
+            solver.declare_state(&self.sys, 0)?;
+            solver.declare_state(&self.sys, 1)?;
+            for trace in traces {
+                for pair in trace.windows(2) {
+                    solver.push()?;
+                    solver.assert(self.trace_state_at(&pair[0].1, 0))?;
+                    solver.assert(self.trace_state_at(&pair[1].1, 1))?;
+                    for (branch, hits) in branches.iter().zip(hits.iter_mut()) {
+                        solver.intent(&format!(
+                            "coverage: does the transition exercise {}?",
+                            branch.desc
+                        ))?;
+                        if !solver.unsat(std::slice::from_ref(&branch.cond))? {
+                            *hits += 1
+                        }
+                    }
+                    solver.pop()?;
+                }
+            }
+        }
+
+        let covered = hits.iter().filter(|hits| **hits > 0).count();
+        let percent = |n: usize, total: usize| (n * 100).checked_div(total).unwrap_or(0);
+        println!("|===| {} result", self.bold.paint("Coverage"));
+        if branches.is_empty() {
+            println!("| - the transition relation has no branches");
+        } else {
+            println!(
+                "| - {}/{} branch(es) exercised ({}%) over {} transition(s)",
+                covered,
+                branches.len(),
+                self.bold
+                    .paint(percent(covered, branches.len()).to_string()),
+                transitions
+            );
+            println!("|");
+            println!("|   {: >11}  {: >4}  branch", "transitions", "%");
+            for (branch, hits) in branches.iter().zip(hits.iter()) {
+                let line = format!(
+                    "{: >11}  {: >3}%  {}",
+                    hits,
+                    percent(*hits, transitions),
+                    branch.desc
+                );
+                if *hits > 0 {
+                    println!("|   {}", self.green.paint(line))
+                } else {
+                    println!("|   {}", self.red.paint(line))
+                }
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
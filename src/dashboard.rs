@@ -0,0 +1,119 @@
+//! One-screen dashboard for runs over several systems.
+
+mikino_api::prelude!();
+
+use std::time::Duration;
+
+use crate::{budget::Verdict, Run};
+
+/// Results of a system.
+#[derive(Debug, Clone)]
+pub struct Row {
+    /// Name of the system.
+    pub name: String,
+    /// Number of proved candidates.
+    pub proved: usize,
+    /// Number of falsified candidates.
+    pub falsified: usize,
+    /// Number of unknown candidates.
+    pub unknown: usize,
+    /// Time spent checking the system.
+    pub time: Duration,
+}
+impl Row {
+    /// Constructor from the verdict of a system.
+    pub fn new(name: &str, verdict: &Verdict, time: Duration) -> Self {
+        Self {
+            name: name.into(),
+            proved: verdict.proved.len(),
+            falsified: verdict.falsified.len(),
+            unknown: verdict.unknown.len(),
+            time,
+        }
+    }
+}
+
+impl Run {
+    /// Prints the dashboard of some systems.
+    pub fn print_dashboard(&self, rows: &[Row]) {
+        let mut rows: Vec<&Row> = rows.iter().collect();
+        rows.sort_by(|lft, rgt| {
+            (rgt.falsified, rgt.unknown)
+                .cmp(&(lft.falsified, lft.unknown))
+                .then_with(|| lft.name.cmp(&rgt.name))
+        });
+        let seconds = |time: &Duration| format!("{:.3}s", time.as_secs_f64());
+
+        let header = ["system", "proved", "falsified", "unknown", "time"];
+        let cells: Vec<[String; 5]> = rows
+            .iter()
+            .map(|row| {
+                [
+                    row.name.clone(),
+                    row.proved.to_string(),
+                    row.falsified.to_string(),
+                    row.unknown.to_string(),
+                    seconds(&row.time),
+                ]
+            })
+            .collect();
+        let mut widths = header.map(str::len);
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len())
+            }
+        }
+        // Cells are padded before painting, escape sequences would break the alignment.
+        let pad = |idx: usize, cell: &str| {
+            if idx == 0 {
+                format!("{: <1$}", cell, widths[idx])
+            } else {
+                format!("{: >1$}", cell, widths[idx])
+            }
+        };
+
+        println!();
+        println!(
+            "|===| {}, {} system(s)",
+            self.bold.paint("Dashboard"),
+            rows.len()
+        );
+        let header: Vec<String> = header
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| format!(" {} ", self.bold.paint(pad(idx, cell))))
+            .collect();
+        println!("|{}", header.join("|"));
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        println!("|{}", rule.join("+"));
+        for (row, cells) in rows.iter().zip(cells.iter()) {
+            let cells: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(idx, cell)| {
+                    let cell = pad(idx, cell);
+                    let cell = match idx {
+                        0 if row.falsified > 0 => self.red.paint(cell).to_string(),
+                        0 if row.unknown == 0 => self.green.paint(cell).to_string(),
+                        2 if row.falsified > 0 => self.red.paint(cell).to_string(),
+                        3 if row.unknown > 0 => self.bold.paint(cell).to_string(),
+                        _ => cell,
+                    };
+                    format!(" {} ", cell)
+                })
+                .collect();
+            println!("|{}", cells.join("|"))
+        }
+
+        if let Some(top) = self.top {
+            let mut slowest = rows.clone();
+            slowest.sort_by_key(|row| std::cmp::Reverse(row.time));
+            println!("|");
+            println!("| - {} slowest system(s)", top.min(slowest.len()));
+            for row in slowest.into_iter().take(top) {
+                println!("|   {: >10}  {}", seconds(&row.time), row.name)
+            }
+        }
+        println!("|===|");
+    }
+}
@@ -0,0 +1,104 @@
+//! Dead branch detection, branches of the transition relation no reachable state enables.
+
+mikino_api::prelude!();
+
+use crate::{coverage, Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Reports the branches of the transition relation enabled in no reachable state up to depth
+    /// `max`.
+    pub fn dead_branches(&self, max: usize) -> Res<()> {
+        let sys = &self.sys;
+        let branches = coverage::branches(sys.trans());
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "looking for {} in {} branch(es) of the transition relation...",
+                s.bold.paint("dead branches"),
+                branches.len()
+            )
+        });
+
+        // Depth of the first state enabling each branch, if any.
+        let mut enabled: Vec<Option<usize>> = vec![None; branches.len()];
+        if !branches.is_empty() {
+            let mut solver = self.phase_solver(Phase::Bmc, "dead_branches.smt2")?;
+            solver.intent("dead branches BMC setup: initial state(s)")?;
+            solver.declare_state(sys, 0)?;
+            solver.assert_init(sys, 0)?;
+            for depth in 0..=max {
+                if enabled.iter().all(Option::is_some) {
+                    break;
+                }
+                if self.env.verb > 0 {
+                    self.env.progress(|s| {
+                        format!(
+                            "looking for enabled branches at depth {}",
+                            s.under.paint(depth.to_string())
+                        )
+                    });
+                }
+                solver.declare_state(sys, depth + 1)?;
+                solver.assert_trans(sys, depth)?;
+                for (branch, enabled) in branches.iter().zip(enabled.iter_mut()) {
+                    if enabled.is_some() {
+                        continue;
+                    }
+                    solver.intent(&format!(
+                        "dead branches: is {} enabled at depth {}?",
+                        branch.desc, depth
+                    ))?;
+                    if !solver.unsat(&[branch.cond_at(depth)])? {
+                        *enabled = Some(depth)
+                    }
+                }
+            }
+        }
+
+        let dead: Vec<&coverage::Branch> = branches
+            .iter()
+            .zip(enabled.iter())
+            .filter(|(_, enabled)| enabled.is_none())
+            .map(|(branch, _)| branch)
+            .collect();
+        println!("|===| {} check result", self.bold.paint("Dead branch"));
+        if branches.is_empty() {
+            println!("| - the transition relation has no branches")
+        } else if dead.is_empty() {
+            println!(
+                "| - all {} branch(es) of the transition relation are {} in reachable states",
+                branches.len(),
+                self.green.paint("enabled")
+            )
+        } else {
+            println!(
+                "| - {}/{} branch(es) of the transition relation are {}, enabled in no reachable \
+                state up to depth {}",
+                dead.len(),
+                branches.len(),
+                self.red.paint("dead"),
+                max
+            );
+            for branch in &dead {
+                println!("|   {}", self.red.paint(&branch.desc))
+            }
+        }
+        if self.env.verb > 0 {
+            let alive: Vec<(&coverage::Branch, usize)> = branches
+                .iter()
+                .zip(enabled.iter())
+                .filter_map(|(branch, enabled)| enabled.map(|depth| (branch, depth)))
+                .collect();
+            if !alive.is_empty() {
+                println!("|");
+                println!("| - first enabled at depth");
+                for (branch, depth) in alive {
+                    println!("|   {: >5}  {}", depth, branch.desc)
+                }
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
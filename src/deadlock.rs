@@ -0,0 +1,140 @@
+//! Deadlock detection, reachable states without successor.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+
+use crate::{smt, Check, Phase};
+
+impl<'env> Check<'env> {
+    /// SMT-LIB formula stating that the state at some step has no successor.
+    fn deadlock_at(&self, step: usize) -> String {
+        let mut binders = String::new();
+        for var in self.sys.decls().iter() {
+            binders.push_str(&format!(
+                " ({} {})",
+                smt::svar(var.id(), step + 1),
+                smt::sort(var.typ())
+            ))
+        }
+        let trans = smt::Unroll::new(self.sys.trans(), step);
+        if binders.is_empty() {
+            format!("(not {})", trans)
+        } else {
+            format!("(not (exists ({}) {}))", binders.trim(), trans)
+        }
+    }
+
+    /// Tries to prove that no state verifying the invariants is deadlocked.
+    fn total(&self, invariants: &[&str]) -> Res<bool> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Step, "deadlock_total.smt2")?;
+        solver.intent("deadlock: invariants")?;
+        solver.declare_state(sys, 0)?;
+        for invariant in invariants {
+            solver.assert(smt::Unroll::new(&sys.po_s()[*invariant], 0))?
+        }
+        solver.intent("deadlock: is there a state with no successor?")?;
+        solver.unsat(&[self.deadlock_at(0)])
+    }
+
+    /// Looks for a reachable deadlocked state up to depth `max`.
+    fn reachable_deadlock(&self, max: usize) -> Res<Option<crate::Trace>> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "deadlock_bmc.smt2")?;
+        solver.intent("deadlock BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        for depth in 0..=max {
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "looking for a deadlock at depth {}",
+                        s.under.paint(depth.to_string())
+                    )
+                });
+            }
+            solver.intent(&format!("deadlock: deadlocked state at depth {}?", depth))?;
+            solver.push()?;
+            solver.assert(self.deadlock_at(depth))?;
+            let trace = if solver.check_sat()? {
+                Some(solver.get_trace(sys, depth)?)
+            } else {
+                None
+            };
+            solver.pop()?;
+            if trace.is_some() {
+                return Ok(trace);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that the transition relation is total in the reachable states.
+    ///
+    /// The candidates proved by induction are assumed when trying to prove it; BMC looks for a
+    /// reachable deadlock up to depth `max` otherwise.
+    pub fn deadlock(&self, base: &BaseRes, step: &StepRes, max: usize) -> Res<()> {
+        let invariants: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "checking the transition relation for {}...",
+                s.bold.paint("deadlocks")
+            )
+        });
+
+        let total = self
+            .total(&invariants)
+            .chain_err(|| "while proving the transition relation is total")?;
+        let deadlock = if total {
+            None
+        } else {
+            self.reachable_deadlock(max)
+                .chain_err(|| "while looking for a reachable deadlock")?
+        };
+
+        println!("|===| {} check result", self.bold.paint("Deadlock"));
+        if total {
+            print!(
+                "| - the transition relation is {} in the reachable states",
+                self.green.paint("total")
+            );
+            if invariants.is_empty() {
+                println!()
+            } else {
+                println!(", assuming the {} proved candidate(s)", invariants.len())
+            }
+        } else if let Some(trace) = &deadlock {
+            println!(
+                "| - found a {} at depth {}, the following trace has no successor",
+                self.red.paint("reachable deadlock"),
+                trace.len() - 1
+            );
+            println!("|===|");
+            self.present_steps(&self.sys, trace, true)?;
+            println!("  |=|");
+            self.record_cex("deadlock", trace)?;
+            return Ok(());
+        } else {
+            println!(
+                "| - {} reachable deadlock up to depth {}, but the transition relation could not \
+                be proved total",
+                self.green.paint("no"),
+                max
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
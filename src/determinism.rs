@@ -0,0 +1,175 @@
+//! Determinism check, reachable states with two distinct successors.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+
+use crate::{smt, Check, Phase, Trace};
+
+/// A reachable nondeterministic state: the trace leading to it and two distinct successors.
+type Witness = (Trace, Vec<(String, String)>, Vec<(String, String)>);
+
+impl<'env> Check<'env> {
+    /// Asserts that the states at `step + 1` and `step + 2` are distinct successors of the state
+    /// at `step`, in the current scope.
+    fn assert_two_successors(&self, solver: &mut smt::Solver, step: usize) -> Res<()> {
+        let sys = &self.sys;
+        let (fst, snd) = (format!("@{}", step + 1), format!("@{}", step + 2));
+        solver.declare_state(sys, step + 1)?;
+        solver.declare_state(sys, step + 2)?;
+        let trans = smt::SExpr::parse(&smt::Unroll::new(sys.trans(), step).to_string())?;
+        solver.assert(trans.to_string())?;
+        solver.assert(trans.rename(&fst, &snd).to_string())?;
+        let distinct: Vec<String> = sys
+            .decls()
+            .iter()
+            .map(|var| {
+                format!(
+                    "(not (= {} {}))",
+                    smt::svar(var.id(), step + 1),
+                    smt::svar(var.id(), step + 2)
+                )
+            })
+            .collect();
+        if distinct.is_empty() {
+            solver.assert("false")
+        } else {
+            solver.assert(format!("(or {})", distinct.join(" ")))
+        }
+    }
+
+    /// Tries to prove that no state verifying the invariants is nondeterministic.
+    fn deterministic(&self, invariants: &[&str]) -> Res<bool> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Step, "determinism_proof.smt2")?;
+        solver.intent("determinism: invariants")?;
+        solver.declare_state(sys, 0)?;
+        for invariant in invariants {
+            solver.assert(smt::Unroll::new(&sys.po_s()[*invariant], 0))?
+        }
+        solver.intent("determinism: is there a state with two distinct successors?")?;
+        self.assert_two_successors(&mut solver, 0)?;
+        Ok(!solver.check_sat()?)
+    }
+
+    /// Looks for a reachable nondeterministic state up to depth `max`.
+    fn reachable_nondeterminism(&self, max: usize) -> Res<Option<Witness>> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "determinism_bmc.smt2")?;
+        solver.intent("determinism BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        for depth in 0..=max {
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "looking for a nondeterministic state at depth {}",
+                        s.under.paint(depth.to_string())
+                    )
+                });
+            }
+            solver.intent(&format!(
+                "determinism: state with two distinct successors at depth {}?",
+                depth
+            ))?;
+            solver.push()?;
+            self.assert_two_successors(&mut solver, depth)?;
+            let witness = if solver.check_sat()? {
+                let trace = solver.get_trace(sys, depth)?;
+                let fst = smt::pretty_state(&solver.get_state(sys, depth + 1)?);
+                let snd = smt::pretty_state(&solver.get_state(sys, depth + 2)?);
+                Some((trace, fst, snd))
+            } else {
+                None
+            };
+            solver.pop()?;
+            if witness.is_some() {
+                return Ok(witness);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that the transition relation is deterministic in the reachable states.
+    ///
+    /// The candidates proved by induction are assumed when trying to prove it; BMC looks for a
+    /// reachable nondeterministic state up to depth `max` otherwise.
+    pub fn determinism(&self, base: &BaseRes, step: &StepRes, max: usize) -> Res<()> {
+        let invariants: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "checking the transition relation for {}...",
+                s.bold.paint("determinism")
+            )
+        });
+
+        let deterministic = self
+            .deterministic(&invariants)
+            .chain_err(|| "while proving the transition relation is deterministic")?;
+        let witness = if deterministic {
+            None
+        } else {
+            self.reachable_nondeterminism(max)
+                .chain_err(|| "while looking for a reachable nondeterministic state")?
+        };
+
+        println!("|===| {} check result", self.bold.paint("Determinism"));
+        if deterministic {
+            print!(
+                "| - the transition relation is {} in the reachable states",
+                self.green.paint("deterministic")
+            );
+            if invariants.is_empty() {
+                println!()
+            } else {
+                println!(", assuming the {} proved candidate(s)", invariants.len())
+            }
+        } else if let Some((trace, fst, snd)) = &witness {
+            println!(
+                "| - found a {} at depth {}, it has two distinct successors",
+                self.red.paint("reachable nondeterministic state"),
+                trace.len() - 1
+            );
+            println!("|===|");
+            self.present_steps(&self.sys, trace, true)?;
+            let max_id_len = self.sys.decls().max_id_len();
+            let (fst, snd) = (self.sanitized_values(fst), self.sanitized_values(snd));
+            for (idx, (successor, other)) in [(&fst, &snd), (&snd, &fst)].iter().enumerate() {
+                println!(
+                    "  |=| Successor {}",
+                    self.under.paint((idx + 1).to_string())
+                );
+                for (var, val) in successor.iter() {
+                    let var_str = format!("{: >1$}", var, max_id_len);
+                    if other.iter().any(|(id, v)| id == var && v != val) {
+                        println!("  | {} = {}", self.red.paint(var_str), val)
+                    } else {
+                        println!("  | {} = {}", self.bold.paint(var_str), val)
+                    }
+                }
+            }
+            println!("  |=|");
+            return Ok(());
+        } else {
+            println!(
+                "| - {} reachable nondeterministic state up to depth {}, but the transition \
+                relation could not be proved deterministic",
+                self.green.paint("no"),
+                max
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+//! Deterministic queries, byte-identical SMT logs across runs.
+
+mikino_api::prelude!();
+
+use crate::{mode::Engine, sim::Rng, Check, Mode, Run};
+
+/// Seed of the random sampling when none is given.
+const SEED: u64 = 0;
+
+impl Run {
+    /// Fails if the mode does not support `--deterministic_queries`.
+    pub fn deterministic_supported(&self) -> Res<()> {
+        if let Mode::Check {
+            engine: Engine::Portfolio,
+            ..
+        } = &self.mode
+        {
+            bail!("`--deterministic_queries` is not supported by the portfolio engine")
+        }
+        Ok(())
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Random number generator for some seed, see [`crate::deterministic`].
+    pub fn rng(&self, seed: Option<u64>) -> Rng {
+        if self.env.deterministic_queries {
+            Rng::new(seed.or(Some(SEED)))
+        } else {
+            Rng::new(seed)
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Input distributions for random simulation.
+//!
+//! `#[dist(...)]` annotations attach a distribution to the variables of a declaration.
+
+mikino_api::prelude!();
+
+use expr::Typ;
+
+use crate::{sim::Rng, text, Check};
+
+/// Annotation prefix.
+const ANNOTATION: &str = "#[dist";
+
+/// A distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dist {
+    /// Integers of a range, bounds included.
+    Uniform(i64, i64),
+    /// Booleans, `true` with some probability.
+    Bernoulli(f64),
+    /// Some values.
+    Choice(Vec<String>),
+}
+
+impl Dist {
+    /// Parses a distribution such as `uniform(0, 100)`.
+    pub fn parse(desc: &str) -> Result<Self, String> {
+        let desc = desc.trim();
+        let (name, args) = desc
+            .strip_suffix(')')
+            .and_then(|desc| desc.split_once('('))
+            .ok_or_else(|| format!("expected `<DISTRIBUTION>(<ARGS>)`, found `{}`", desc))?;
+        let args: Vec<&str> = args
+            .split(',')
+            .map(str::trim)
+            .filter(|arg| !arg.is_empty())
+            .collect();
+        let int = |arg: &str| {
+            arg.parse::<i64>()
+                .map_err(|_| format!("expected an integer, found `{}`", arg))
+        };
+        match (name.trim(), args.as_slice()) {
+            ("uniform", [lo, hi]) => {
+                let (lo, hi) = (int(lo)?, int(hi)?);
+                if lo > hi {
+                    return Err(format!("empty range `uniform({}, {})`", lo, hi));
+                }
+                Ok(Self::Uniform(lo, hi))
+            }
+            ("bernoulli", [p]) => match p.parse::<f64>() {
+                Ok(p) if (0.0..=1.0).contains(&p) => Ok(Self::Bernoulli(p)),
+                _ => Err(format!("expected a probability in `[0, 1]`, found `{}`", p)),
+            },
+            ("choice", vals) if !vals.is_empty() => Ok(Self::Choice(
+                vals.iter().map(|val| val.to_string()).collect(),
+            )),
+            ("uniform", _) => Err("`uniform` expects two bounds".into()),
+            ("bernoulli", _) => Err("`bernoulli` expects a probability".into()),
+            ("choice", _) => Err("`choice` expects at least one value".into()),
+            (name, _) => Err(format!(
+                "unknown distribution `{}`, expected `uniform`, `bernoulli` or `choice`",
+                name
+            )),
+        }
+    }
+
+    /// Fails if the distribution does not produce values of some type.
+    pub fn check(&self, typ: Typ) -> Result<(), String> {
+        match (self, typ) {
+            (Self::Uniform(_, _), Typ::Int | Typ::Rat) | (Self::Bernoulli(_), Typ::Bool) => Ok(()),
+            (Self::Choice(vals), _) => {
+                for val in vals {
+                    let (legal, typ) = match typ {
+                        Typ::Bool => (val == "true" || val == "false", "bool"),
+                        Typ::Int => (val.parse::<i64>().is_ok(), "int"),
+                        Typ::Rat => (val.parse::<f64>().is_ok(), "rat"),
+                    };
+                    if !legal {
+                        return Err(format!("`{}` is not a legal {} value", val, typ));
+                    }
+                }
+                Ok(())
+            }
+            (Self::Uniform(_, _), _) => Err("`uniform` only applies to int and rat".into()),
+            (Self::Bernoulli(_), _) => Err("`bernoulli` only applies to bool".into()),
+        }
+    }
+
+    /// Random SMT-LIB value of some type, the distribution must [`check`](Self::check) it.
+    pub fn sample(&self, rng: &mut Rng, typ: Typ) -> String {
+        match self {
+            Self::Uniform(lo, hi) => {
+                let span = (*hi as i128 - *lo as i128 + 1) as u64;
+                let offset = if span == 0 {
+                    rng.next_u64()
+                } else {
+                    rng.below(span)
+                };
+                smt_num(&(*lo as i128 + offset as i128).to_string(), typ)
+            }
+            Self::Bernoulli(p) => ((rng.below(1_000_000) as f64) < p * 1_000_000.0).to_string(),
+            Self::Choice(vals) => {
+                let val = &vals[rng.below(vals.len() as u64) as usize];
+                match typ {
+                    Typ::Bool => val.clone(),
+                    Typ::Int | Typ::Rat => smt_num(val, typ),
+                }
+            }
+        }
+    }
+}
+impl std::fmt::Display for Dist {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Uniform(lo, hi) => write!(fmt, "uniform({}, {})", lo, hi),
+            Self::Bernoulli(p) => write!(fmt, "bernoulli({})", p),
+            Self::Choice(vals) => write!(fmt, "choice({})", vals.join(", ")),
+        }
+    }
+}
+
+/// SMT-LIB number of some type from its decimal representation.
+fn smt_num(num: &str, typ: Typ) -> String {
+    let (neg, abs) = match num.strip_prefix('-') {
+        Some(abs) => (true, abs),
+        None => (false, num),
+    };
+    let abs = if typ == Typ::Rat && !abs.contains('.') {
+        format!("{}.0", abs)
+    } else {
+        abs.to_string()
+    };
+    if neg {
+        format!("(- {})", abs)
+    } else {
+        abs
+    }
+}
+
+/// A distribution attached to a variable.
+#[derive(Debug, Clone)]
+pub struct VarDist {
+    /// Variable.
+    pub var: String,
+    /// Distribution.
+    pub dist: Dist,
+    /// Line of the annotation.
+    pub line: usize,
+}
+
+/// Removes the `#[dist(...)]` annotations of a system, yields the new system and the
+/// distributions.
+pub fn strip(txt: &str) -> Res<(String, Vec<VarDist>)> {
+    if !txt.contains(ANNOTATION) {
+        return Ok((txt.into(), Vec::new()));
+    }
+    let mut res = txt.to_string();
+    let mut dists = Vec::new();
+    for (start, end) in text::elements(txt, "svars")?.into_iter().rev() {
+        let elm = &txt[start..end];
+        if !elm.starts_with(ANNOTATION) {
+            continue;
+        }
+        let line = txt[..start].matches('\n').count() + 1;
+        let illegal = |msg: String| format!("illegal annotation at line {}: {}", line, msg);
+        let rest = elm[ANNOTATION.len()..].trim_start();
+        let close = rest
+            .find(")]")
+            .ok_or_else(|| illegal("expected `#[dist(<DISTRIBUTION>)]`".into()))?;
+        let desc = rest[..close]
+            .strip_prefix('(')
+            .ok_or_else(|| illegal("expected `#[dist(<DISTRIBUTION>)]`".into()))?;
+        let dist = Dist::parse(desc).map_err(illegal)?;
+        let decl = &rest[close + 2..];
+        let names = decl.split_once(':').map(|(names, _)| names).unwrap_or(decl);
+        let names: Vec<&str> = names.split_whitespace().collect();
+        if names.is_empty() {
+            bail!(
+                "expected a variable declaration after the annotation at line {}",
+                line
+            )
+        }
+        for var in names.into_iter().rev() {
+            dists.push(VarDist {
+                var: var.into(),
+                dist: dist.clone(),
+                line,
+            })
+        }
+        res.replace_range(start..end - decl.len(), "");
+    }
+    dists.reverse();
+    Ok((res, dists))
+}
+
+impl<'env> Check<'env> {
+    /// Sets the input distributions of the system, fails if they mention unknown variables, if
+    /// their type does not match, or if a variable has several distributions.
+    pub fn set_dists(&mut self, dists: Vec<VarDist>) -> Res<()> {
+        for VarDist { var, dist, line } in dists {
+            let decl = match self.sys.decls().iter().find(|decl| decl.id() == var) {
+                Some(decl) => decl,
+                None => bail!(
+                    "distribution at line {} mentions unknown variable `{}`",
+                    line,
+                    var
+                ),
+            };
+            if let Err(e) = dist.check(decl.typ()) {
+                bail!(
+                    "illegal distribution for variable `{}` at line {}: {}",
+                    var,
+                    line,
+                    e
+                )
+            }
+            if self.dists.insert(var.clone(), dist).is_some() {
+                bail!("variable `{}` has more than one distribution", var)
+            }
+        }
+        Ok(())
+    }
+
+    /// Random SMT-LIB value for a variable, from its distribution if any.
+    pub fn sample(&self, rng: &mut Rng, var: &str, typ: Typ) -> String {
+        match self.dists.get(var) {
+            Some(dist) => dist.sample(rng, typ),
+            None => rng.value(typ),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Counterexamples as state machines in the DOT format, projected onto a mode variable.
+
+mikino_api::prelude!();
+
+use std::{fs::File, io::Write};
+
+use crate::{
+    json::{json_str, json_trace},
+    Check, Trace,
+};
+
+/// Escapes a string for a DOT label.
+pub fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Value of `var` at each step of a trace.
+fn project<'a>(var: &str, trace: &'a Trace) -> Res<Vec<(usize, &'a str)>> {
+    trace
+        .iter()
+        .map(|(step, values)| {
+            values
+                .iter()
+                .find(|(id, _)| id == var)
+                .map(|(_, val)| (*step, val.trim_end_matches(" (completed)")))
+                .ok_or_else(|| {
+                    format!("mode variable `{}` does not appear in the trace", var).into()
+                })
+        })
+        .collect()
+}
+
+/// Renders counterexamples as state machines projected onto the mode variable `var`.
+pub fn cexs_to_dot(var: &str, cexs: &[(String, Trace)]) -> Res<String> {
+    let mut dot = format!(
+        "digraph cexs {{\n    label = \"counterexample(s) projected onto `{}`\";\n    \
+        node [shape = ellipse];\n",
+        escape(var)
+    );
+    for (idx, (candidate, trace)) in cexs.iter().enumerate() {
+        let modes = project(var, trace)?;
+        let node = |val: &str| format!("\"cex{}: {}\"", idx, escape(val));
+
+        dot.push_str(&format!(
+            "\n    subgraph cluster_{} {{\n        label = \"falsification of `{}`\";\n",
+            idx,
+            escape(candidate)
+        ));
+        let mut states: Vec<&str> = Vec::new();
+        for (_, val) in &modes {
+            if !states.contains(val) {
+                states.push(val)
+            }
+        }
+        let last = modes.last().map(|(_, val)| *val);
+        for val in &states {
+            let shape = if Some(*val) == last {
+                ", shape = doublecircle"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "        {} [label = \"{}\"{}];\n",
+                node(val),
+                escape(val),
+                shape
+            ))
+        }
+        if let Some((step, val)) = modes.first() {
+            dot.push_str(&format!(
+                "        \"cex{} init\" [shape = point];\n        \"cex{} init\" -> {} \
+                [label = \"{}\"];\n",
+                idx,
+                idx,
+                node(val),
+                step
+            ))
+        }
+
+        // Transitions between the same values are merged, their steps are listed.
+        let mut edges: Vec<(&str, &str, Vec<usize>)> = Vec::new();
+        for pair in modes.windows(2) {
+            let ((_, src), (step, tgt)) = (pair[0], pair[1]);
+            match edges.iter_mut().find(|(s, t, _)| *s == src && *t == tgt) {
+                Some((_, _, steps)) => steps.push(step),
+                None => edges.push((src, tgt, vec![step])),
+            }
+        }
+        for (src, tgt, steps) in edges {
+            let steps: Vec<String> = steps.iter().map(usize::to_string).collect();
+            dot.push_str(&format!(
+                "        {} -> {} [label = \"{}\"];\n",
+                node(src),
+                node(tgt),
+                steps.join(", ")
+            ))
+        }
+        dot.push_str("    }\n")
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+impl<'env> Check<'env> {
+    /// Records a counterexample, saves it in the counterexample store (see [`crate::cex_store`]),
+    /// reports it in the event stream (see [`crate::events`]) and rewrites the DOT file if DOT
+    /// output is active.
+    pub fn record_cex(&self, candidate: &str, trace: &Trace) -> Res<()> {
+        let mut cexs = self
+            .cexs
+            .lock()
+            .map_err(|_| "failed to record counterexample")?;
+        cexs.push((candidate.into(), trace.clone()));
+        self.store_cex(candidate, trace)?;
+        self.env.event("cex-found", || {
+            vec![
+                ("candidate", json_str(&self.sanitized_text(candidate))),
+                (
+                    "depth",
+                    trace.last().map(|(step, _)| *step).unwrap_or(0).to_string(),
+                ),
+                ("trace", json_trace(&self.sanitized_trace(trace))),
+                ("cex_id", json_str(&self.cex_id(candidate, trace))),
+            ]
+        });
+        let (path, var) = match (self.env.cex_dot.as_ref(), self.env.mode_var.as_ref()) {
+            (Some(path), Some(var)) => (path, var),
+            _ => return Ok(()),
+        };
+        if !self.sys.decls().iter().any(|decl| decl.id() == var) {
+            bail!("unknown mode variable `{}`", var)
+        }
+        let sanitized: Vec<(String, Trace)> = cexs
+            .iter()
+            .map(|(candidate, trace)| (self.sanitized_text(candidate), self.sanitized_trace(trace)))
+            .collect();
+        let dot = cexs_to_dot(&self.sanitized_text(var), &sanitized)?;
+        let mut file =
+            File::create(path).chain_err(|| format!("while creating DOT file `{}`", path))?;
+        file.write_all(dot.as_bytes())
+            .chain_err(|| format!("while writing counterexample(s) to DOT file `{}`", path))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Event stream, for tools following long runs.
+//!
+//! `--events` streams JSON events on stdout as the run goes, instead of the reports.
+
+mikino_api::prelude!();
+
+use std::time::Instant;
+
+use crate::{
+    json::{json_array, json_object, json_str},
+    Run,
+};
+
+/// Event stream of a run.
+#[derive(Debug, Clone, Copy)]
+pub struct Events {
+    /// Start of the run.
+    start: Instant,
+}
+impl Events {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// JSON array of some candidate names.
+pub fn json_names(names: impl IntoIterator<Item = impl AsRef<str>>) -> String {
+    let names: Vec<String> = names
+        .into_iter()
+        .map(|name| json_str(name.as_ref()))
+        .collect();
+    json_array(&names)
+}
+
+impl Run {
+    /// Prints an event, if `--events` is active; `fields` are JSON values.
+    pub fn event(&self, event: &str, fields: impl FnOnce() -> Vec<(&'static str, String)>) {
+        let Some(events) = self.events.as_ref() else {
+            return;
+        };
+        let mut record = vec![
+            ("event", json_str(event)),
+            (
+                "elapsed",
+                format!("{:.3}", events.start.elapsed().as_secs_f64()),
+            ),
+        ];
+        record.extend(fields());
+        println!("{}", json_object(&record))
+    }
+}
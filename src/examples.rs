@@ -0,0 +1,188 @@
+//! Runnable end-to-end examples, as shell scripts running on the demo system.
+
+mikino_api::prelude!();
+
+use expr::Typ;
+
+use crate::Run;
+
+/// Workflow names, in the order they are printed in.
+pub const WORKFLOWS: [&str; 8] = [
+    "check", "bmc", "reach", "script", "sim", "testsgen", "mutate", "count",
+];
+
+/// File the demo system is written to.
+const DEMO_SYS: &str = "demo.mkn";
+/// File the demo script is written to.
+const DEMO_SCRIPT: &str = "demo_script.rs";
+
+/// An end-to-end workflow.
+#[derive(Debug, Clone)]
+pub struct Workflow {
+    /// Name of the workflow, one of [`WORKFLOWS`].
+    pub name: &'static str,
+    /// Short description.
+    pub desc: &'static str,
+    /// Commands running the workflow, with a description for each of them.
+    pub commands: Vec<(&'static str, String)>,
+}
+
+/// Reach target over the variables of the demo system.
+fn demo_target() -> Res<String> {
+    let sys = parse::trans(mikino_api::TRANS_DEMO).chain_err(|| "while parsing demo system")?;
+    let decls = sys.decls();
+    if let Some(var) = decls.iter().find(|var| var.typ() == Typ::Int) {
+        return Ok(format!("{} >= 5", var.id()));
+    }
+    if let Some(var) = decls.iter().find(|var| var.typ() == Typ::Bool) {
+        return Ok(var.id().to_string());
+    }
+    bail!("demo system has no integer or boolean variable to build a reach target from")
+}
+
+/// Builds all the workflows.
+pub fn workflows() -> Res<Vec<Workflow>> {
+    let demo = (
+        "write the demo system",
+        format!("mikino demo --force {}", DEMO_SYS),
+    );
+    let target = demo_target()?;
+    let sys = |cmd: &str| format!("mikino {} {}", cmd, DEMO_SYS);
+
+    Ok(vec![
+        Workflow {
+            name: "check",
+            desc:
+                "proves the candidates by induction, BMC falsifies the ones that are not inductive",
+            commands: vec![
+                demo.clone(),
+                ("induction only", sys("check")),
+                (
+                    "induction, then BMC up to depth 10",
+                    sys("check --bmc --bmc_max 10"),
+                ),
+            ],
+        },
+        Workflow {
+            name: "bmc",
+            desc: "looks for falsifications of the candidates, up to some depth",
+            commands: vec![
+                demo.clone(),
+                ("BMC up to depth 10", sys("bmc --bmc_max 10")),
+                (
+                    "BMC at depths 3, 7 and 12 to 15 only",
+                    sys("bmc --bmc_depths 3,7,12-15"),
+                ),
+            ],
+        },
+        Workflow {
+            name: "reach",
+            desc: "looks for a trace reaching a target, or proves it unreachable",
+            commands: vec![
+                demo.clone(),
+                (
+                    "trace reaching the target",
+                    sys(&format!("reach --target \"{}\" --bmc_max 20", target)),
+                ),
+                (
+                    "proof the target is unreachable, if it is",
+                    sys(&format!("reach --target \"{}\" --prove", target)),
+                ),
+            ],
+        },
+        Workflow {
+            name: "script",
+            desc: "runs a hsmt script interacting with the solver",
+            commands: vec![
+                (
+                    "write the demo script",
+                    format!("mikino demo --force --script {}", DEMO_SCRIPT),
+                ),
+                ("run it", format!("mikino script {}", DEMO_SCRIPT)),
+            ],
+        },
+        Workflow {
+            name: "sim",
+            desc: "random concrete executions, evaluating the candidates along the way",
+            commands: vec![
+                demo.clone(),
+                (
+                    "5 reproducible runs of 10 steps",
+                    sys("sim --steps 10 --runs 5 --seed 42"),
+                ),
+                (
+                    "runs biased toward falsifications",
+                    sys("sim --fuzz --seed 42"),
+                ),
+            ],
+        },
+        Workflow {
+            name: "testsgen",
+            desc: "test vectors from falsifications and simulation runs",
+            commands: vec![
+                demo.clone(),
+                (
+                    "CSV test vectors in `tests/`",
+                    sys("testsgen -o tests --format csv"),
+                ),
+            ],
+        },
+        Workflow {
+            name: "mutate",
+            desc: "checks the candidates catch mutations of the system",
+            commands: vec![
+                demo.clone(),
+                ("mutations of the candidates", sys("mutate --bmc_max 10")),
+                (
+                    "mutations of the transition relation",
+                    sys("mutate --trans --bmc_max 10"),
+                ),
+            ],
+        },
+        Workflow {
+            name: "count",
+            desc: "counts the reachable states, and the fraction BMC explores",
+            commands: vec![
+                demo,
+                (
+                    "exploration of BMC up to depth 10",
+                    sys("count --bmc_max 10"),
+                ),
+            ],
+        },
+    ])
+}
+
+impl Run {
+    /// Prints the commands of some workflow, or of all of them if `None`.
+    pub fn examples(&self, workflow: Option<&str>) -> Res<()> {
+        let workflows = workflows()?;
+        let mut first = true;
+        for wf in &workflows {
+            if workflow.map(|name| name != wf.name).unwrap_or(false) {
+                continue;
+            }
+            if !first {
+                println!()
+            }
+            first = false;
+            println!(
+                "{}",
+                self.gray
+                    .paint(format!("# {} workflow: {}", wf.name, wf.desc))
+            );
+            for (desc, cmd) in &wf.commands {
+                println!("{}", self.gray.paint(format!("#   {}", desc)));
+                println!("{}", self.bold.paint(cmd))
+            }
+        }
+        if first {
+            bail!(
+                "unknown workflow `{}`, expected one of {}",
+                workflow.unwrap_or(""),
+                WORKFLOWS.join(", ")
+            )
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! Human-readable description of the step check.
+
+mikino_api::prelude!();
+
+use crate::{almost_safe, Check};
+
+/// Next-state version of an expression, `v` becomes `'v` for each variable `v`.
+fn next(txt: &str, vars: &[&str]) -> String {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut res = String::with_capacity(txt.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, res: &mut String| {
+        if vars.contains(&token.as_str()) {
+            res.push('\'')
+        }
+        res.push_str(token);
+        token.clear()
+    };
+    for c in txt.chars() {
+        if is_ident(c) {
+            token.push(c)
+        } else {
+            flush(&mut token, &mut res);
+            res.push(c)
+        }
+    }
+    flush(&mut token, &mut res);
+    res
+}
+
+impl<'env> Check<'env> {
+    /// Prints the hypotheses and the negated goal of the step check, for each candidate.
+    pub fn explain_step(&self) {
+        let sys = &self.sys;
+        let vars: Vec<&str> = sys.decls().iter().map(|var| var.id()).collect();
+        let names: Vec<String> = sys
+            .po_s()
+            .keys()
+            .map(|candidate| self.sanitized_text(candidate))
+            .collect();
+        let max_len = names.iter().map(String::len).max().unwrap_or(0);
+
+        println!("|===| {} check hypotheses", self.bold.paint("Step"));
+        println!(
+            "| the step check considers {} state at step `k`, reachable or not, and asks the \
+            solver for",
+            self.bold.paint("any")
+        );
+        println!("| a successor at step `k + 1` where `'v` is the value of `v` at step `k + 1`");
+        println!("|");
+        println!(
+            "| - {} all candidates hold at step `k`",
+            self.green.paint("assuming")
+        );
+        for (candidate, def) in names.iter().zip(sys.po_s().values()) {
+            println!(
+                "|     {: >2$}: {}",
+                candidate,
+                self.sanitized_text(&def.to_string()),
+                max_len
+            )
+        }
+        println!(
+            "| - {} the transition relation holds from step `k` to step `k + 1`",
+            self.green.paint("assuming")
+        );
+        for conjunct in almost_safe::conjuncts(sys.trans()) {
+            println!("|     {}", self.sanitized_text(&conjunct.to_string()))
+        }
+        println!("|");
+        println!(
+            "| - then, for each candidate, {} its negation at step `k + 1`",
+            self.red.paint("asserting")
+        );
+        for (candidate, def) in names.iter().zip(sys.po_s().values()) {
+            println!(
+                "|     {: >2$}: ¬({})",
+                candidate,
+                self.sanitized_text(&next(&def.to_string(), &vars)),
+                max_len
+            )
+        }
+        println!("|");
+        println!(
+            "| `unsat` means the candidate is {}, `sat` yields a counterexample to induction \
+            that",
+            self.green.paint("inductive")
+        );
+        println!(
+            "| may start from an {} state, in which case the candidate needs strengthening",
+            self.red.paint("unreachable")
+        );
+        println!("|===|");
+        println!();
+    }
+}
@@ -0,0 +1,234 @@
+//! Explicit-state reachability engine for small finite systems.
+//!
+//! Enumerates the reachable states breadth-first, at most `--max_states` of them.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check, Trace};
+
+/// Reachable states, with the information needed to rebuild shortest traces.
+#[derive(Default)]
+pub struct Reached {
+    /// Reachable states, in breadth-first order.
+    pub states: Vec<smt::State>,
+    /// Index of the predecessor of each state, `None` for initial states.
+    pub preds: Vec<Option<usize>>,
+    /// Index of each state in `states`.
+    index: Map<smt::State, usize>,
+}
+impl Reached {
+    /// Adds a state, returns `false` if it was already known.
+    pub fn add(&mut self, state: smt::State, pred: Option<usize>) -> bool {
+        if self.index.contains_key(&state) {
+            return false;
+        }
+        self.index.insert(state.clone(), self.states.len());
+        self.states.push(state);
+        self.preds.push(pred);
+        true
+    }
+
+    /// Depth of a state: length of the shortest path from an initial state.
+    pub fn depth_of(&self, mut idx: usize) -> usize {
+        let mut depth = 0;
+        while let Some(pred) = self.preds[idx] {
+            idx = pred;
+            depth += 1
+        }
+        depth
+    }
+
+    /// Shortest trace from an initial state to some state.
+    pub fn trace_to(&self, mut idx: usize) -> Trace {
+        let mut rev = vec![smt::pretty_state(&self.states[idx])];
+        while let Some(pred) = self.preds[idx] {
+            idx = pred;
+            rev.push(smt::pretty_state(&self.states[idx]))
+        }
+        rev.into_iter().rev().enumerate().collect()
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Computes the reachable states of the system, fails if there are more than `max`.
+    pub fn reachable_states(&self, max: usize) -> Res<Reached> {
+        match self.try_reachable_states(max)? {
+            Some(reached) => Ok(reached),
+            None => bail!(
+                "the system has more than {} reachable state(s), the `explicit` engine \
+                only supports small finite systems; `--max_states` raises the limit",
+                max
+            ),
+        }
+    }
+
+    /// Computes the reachable states of the system, `None` if there are more than `max`.
+    pub fn try_reachable_states(&self, max: usize) -> Res<Option<Reached>> {
+        let sys = &self.sys;
+        let mut solver = self.solver("explicit.smt2")?;
+        solver.intent("reachability setup")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        let mut reached = Reached::default();
+
+        solver.intent("reachability: enumerating initial states")?;
+        solver.push()?;
+        solver.assert_init(sys, 0)?;
+        while solver.check_sat()? {
+            let state = solver.get_state(sys, 0)?;
+            solver.assert(format!("(not {})", smt::state_at(&state, 0)))?;
+            reached.add(state, None);
+            if reached.states.len() > max {
+                return Ok(None);
+            }
+        }
+        solver.pop()?;
+
+        solver.assert_trans(sys, 0)?;
+        let mut next = 0;
+        while next < reached.states.len() {
+            let pred = next;
+            next += 1;
+            if self.env.verb > 1 {
+                self.env.progress(|_| {
+                    format!(
+                        "computing successors of state #{} ({} known state(s))",
+                        pred,
+                        reached.states.len()
+                    )
+                });
+            }
+            solver.intent(&format!(
+                "reachability: enumerating successors of state #{}",
+                pred
+            ))?;
+            solver.push()?;
+            solver.assert(smt::state_at(&reached.states[pred], 0))?;
+            while solver.check_sat()? {
+                let state = solver.get_state(sys, 1)?;
+                solver.assert(format!("(not {})", smt::state_at(&state, 1)))?;
+                reached.add(state, Some(pred));
+                if reached.states.len() > max {
+                    return Ok(None);
+                }
+            }
+            solver.pop()?;
+        }
+
+        Ok(Some(reached))
+    }
+
+    /// Runs the explicit-state reachability engine, enumerating at most `max_states` states.
+    pub fn explicit(&self, max_states: usize) -> Res<()> {
+        let sys = &self.sys;
+
+        if self.env.verb > 0 {
+            self.env
+                .progress(|s| format!("computing {} states...", s.under.paint("reachable")))
+        }
+        let reached = self
+            .reachable_states(max_states)
+            .chain_err(|| "during reachable state computation")?;
+        let depth = (0..reached.states.len())
+            .map(|idx| reached.depth_of(idx))
+            .max()
+            .unwrap_or(0);
+
+        let mut solver = self.solver("explicit_candidates.smt2")?;
+        solver.intent(&format!(
+            "reachability: restricting to the {} reachable state(s)",
+            reached.states.len()
+        ))?;
+        solver.declare_state(sys, 0)?;
+        let mut is_reachable = "(or false".to_string();
+        for state in &reached.states {
+            is_reachable.push(' ');
+            is_reachable.push_str(&smt::state_at(state, 0))
+        }
+        is_reachable.push(')');
+        solver.assert(is_reachable)?;
+
+        let mut okay = Vec::new();
+        let mut falsified = Vec::new();
+        for (candidate, def) in sys.po_s().iter() {
+            solver.intent(&format!(
+                "reachability: is candidate `{}` falsified by a reachable state?",
+                candidate
+            ))?;
+            solver.push()?;
+            solver.assert(format!("(not {})", smt::Unroll::new(def, 0)))?;
+            let cex = if solver.check_sat()? {
+                Some(solver.get_state(sys, 0)?)
+            } else {
+                None
+            };
+            solver.pop()?;
+            match cex {
+                None => {
+                    self.settle("explicit", candidate, "proved", None)?;
+                    okay.push(candidate.as_str())
+                }
+                Some(state) => {
+                    let idx = *reached
+                        .index
+                        .get(&state)
+                        .ok_or("solver produced an unknown falsifying state")?;
+                    let trace = reached.trace_to(idx);
+                    println!(
+                        "found a {} at depth {}:",
+                        self.red.paint("falsification"),
+                        self.bold.paint((trace.len() - 1).to_string())
+                    );
+                    self.present_trace(sys, candidate, &trace, &[], true)?;
+                    self.settle("explicit", candidate, "falsified", Some(trace.len() - 1))?;
+                    falsified.push(candidate.as_str())
+                }
+            }
+        }
+
+        if !falsified.is_empty() {
+            println!()
+        }
+
+        println!("|===| {} result", self.bold.paint("Explicit reachability"));
+        println!(
+            "| - fixpoint reached: {} reachable state(s), all reached in {} step(s) or less",
+            self.bold.paint(reached.states.len().to_string()),
+            depth
+        );
+        println!("|");
+        if !okay.is_empty() {
+            println!(
+                "| - the following candidate(s) {} in all reachable states",
+                self.green.paint("hold")
+            );
+            for candidate in &okay {
+                println!("|   `{}`", self.green.paint(*candidate))
+            }
+        }
+        if !okay.is_empty() && !falsified.is_empty() {
+            println!("|")
+        }
+        if !falsified.is_empty() {
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsifiable")
+            );
+            for candidate in &falsified {
+                println!("|   `{}`", self.red.paint(*candidate))
+            }
+        }
+        println!("|");
+        if falsified.is_empty() {
+            println!(
+                "| - system is {}, all reachable states verify the candidate(s)",
+                self.green.paint("safe")
+            )
+        } else {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
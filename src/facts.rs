@@ -0,0 +1,191 @@
+//! Auxiliary facts `assert_in_trans(<expr>)` inside the transition relation.
+//!
+//! Facts are checked against the conjuncts before them, then assumed.
+
+mikino_api::prelude!();
+
+use crate::{smt, text, Check, Run};
+
+/// Annotation keyword.
+pub const ANNOTATION: &str = "assert_in_trans";
+
+/// A fact of the transition relation.
+pub struct Fact {
+    /// Expression of the fact.
+    pub expr: String,
+    /// Byte range of the annotation in the text of the system.
+    pub range: (usize, usize),
+    /// Line of the annotation.
+    pub line: usize,
+}
+
+/// Expression of a conjunct if it is a fact, *i.e.* of the form `assert_in_trans(<expr>)`.
+fn fact_expr(conj: &str) -> Option<&str> {
+    let inner = conj
+        .strip_prefix(ANNOTATION)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    // The parentheses must be the ones of the annotation, not `assert_in_trans(a) ∧ (b)`.
+    let mut depth = 0isize;
+    for char in inner.chars() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+        if depth < 0 {
+            return None;
+        }
+    }
+    Some(inner.trim())
+}
+
+/// Facts of a system, in order.
+pub fn facts(txt: &str) -> Res<Vec<Fact>> {
+    if !txt.contains(ANNOTATION) {
+        return Ok(Vec::new());
+    }
+    let mut res = Vec::new();
+    for (start, end) in text::elements(txt, "trans")? {
+        let conj = &txt[start..end];
+        let line = txt[..start].matches('\n').count() + 1;
+        match fact_expr(conj) {
+            Some(expr) if expr.contains(ANNOTATION) => {
+                bail!("illegal nested `{}` at line {}", ANNOTATION, line)
+            }
+            Some(expr) => res.push(Fact {
+                expr: expr.into(),
+                range: (start, end),
+                line,
+            }),
+            None if conj.contains(ANNOTATION) => bail!(
+                "`{}` at line {} must be a conjunct of the transition relation on its own",
+                ANNOTATION,
+                line
+            ),
+            None => (),
+        }
+    }
+    Ok(res)
+}
+
+/// Replaces the facts by their expression if kept, by `true` otherwise.
+pub fn with_facts(txt: &str, facts: &[Fact], keep: &[bool]) -> String {
+    let mut res = txt.to_string();
+    for (fact, keep) in facts.iter().zip(keep.iter()).rev() {
+        let (start, end) = fact.range;
+        let replacement = if *keep {
+            format!("({})", fact.expr)
+        } else {
+            "true".into()
+        };
+        res.replace_range(start..end, &replacement)
+    }
+    res
+}
+
+/// The system restricted to the conjuncts of the transition relation before some fact.
+///
+/// `holds` indicates which of the facts before `idx` hold, the others are not assumed.
+fn before(txt: &str, facts: &[Fact], idx: usize, holds: &[bool]) -> Res<String> {
+    let start = facts[idx].range.0;
+    let mut conjs = Vec::new();
+    for (lft, rgt) in text::elements(txt, "trans")? {
+        if lft >= start {
+            break;
+        }
+        match facts.iter().position(|fact| fact.range.0 == lft) {
+            Some(fact) if holds[fact] => conjs.push(format!("({})", facts[fact].expr)),
+            Some(_) => (),
+            None => conjs.push(txt[lft..rgt].to_string()),
+        }
+    }
+    if conjs.is_empty() {
+        conjs.push("true".into())
+    }
+    text::with_block(txt, "trans", &conjs.join(",\n    "))
+}
+
+impl Run {
+    /// Checks the facts of a system, yields which ones hold.
+    ///
+    /// Assumptions are added to the transition relation before the check, see
+    /// [`text::with_assumptions`].
+    pub fn check_trans_facts(
+        &self,
+        txt: &str,
+        facts: &[Fact],
+        assume: &[String],
+        smt_log: &Option<String>,
+    ) -> Res<Vec<bool>> {
+        self.progress(|s| {
+            format!(
+                "checking {} transition {}...",
+                facts.len(),
+                s.bold.paint("fact(s)")
+            )
+        });
+        let mut holds = Vec::with_capacity(facts.len());
+        for (idx, fact) in facts.iter().enumerate() {
+            let mut pre = before(txt, facts, idx, &holds)?;
+            if !assume.is_empty() {
+                pre = text::with_assumptions(&pre, assume)?
+            }
+            let pre = Check::from_txt(self, &pre, smt_log)
+                .chain_err(|| format!("while checking the fact at line {}", fact.line))?;
+            let fact_sys = parse::trans(&text::with_block(txt, "trans", &fact.expr)?)
+                .chain_err(|| format!("while parsing the fact at line {}", fact.line))?;
+
+            let mut solver = pre.solver(&format!("trans_fact_{}.smt2", idx))?;
+            solver.intent(&format!(
+                "transition fact at line {}: implied by the conjuncts before it?",
+                fact.line
+            ))?;
+            solver.declare_state(&pre.sys, 0)?;
+            solver.declare_state(&pre.sys, 1)?;
+            holds.push(solver.unsat(&[
+                smt::Unroll::new(pre.sys.trans(), 0).to_string(),
+                format!("(not {})", smt::Unroll::new(fact_sys.trans(), 0)),
+            ])?)
+        }
+
+        if self.json {
+            return Ok(holds);
+        }
+        println!("|===| {} result", self.bold.paint("Transition facts"));
+        let report = |desc: String, keep: bool| {
+            let mut any = false;
+            for (fact, _) in facts.iter().zip(holds.iter()).filter(|(_, h)| **h == keep) {
+                if !any {
+                    println!("{}", desc);
+                    any = true
+                }
+                println!("|   line {}: `{}`", fact.line, self.bold.paint(&fact.expr))
+            }
+            any
+        };
+        let proved = report(
+            format!(
+                "| - the following fact(s) {} and are assumed",
+                self.green.paint("hold")
+            ),
+            true,
+        );
+        if proved && holds.contains(&false) {
+            println!("|")
+        }
+        report(
+            format!(
+                "| - the following fact(s) are {} by the conjuncts before them, and are not \
+                assumed",
+                self.red.paint("not implied")
+            ),
+            false,
+        );
+        println!("|===|");
+        println!();
+
+        Ok(holds)
+    }
+}
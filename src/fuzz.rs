@@ -0,0 +1,147 @@
+//! Falsification-directed fuzzing.
+//!
+//! Successors are pushed toward the falsification of a candidate by minimizing a distance to it.
+
+mikino_api::prelude!();
+
+use expr::Expr;
+
+use crate::{sim::Rng, smt, Check};
+
+/// Maximum number of distance improvements for a single successor.
+const DESCENT: usize = 16;
+
+/// Negation of an arithmetic comparison operator.
+fn negate(op: &str) -> Option<&'static str> {
+    match op {
+        "<" => Some(">="),
+        "<=" => Some(">"),
+        ">" => Some("<="),
+        ">=" => Some("<"),
+        _ => None,
+    }
+}
+
+/// Minimum of some terms.
+fn min(terms: Vec<String>) -> String {
+    let mut terms = terms.into_iter();
+    let first = terms.next().unwrap_or_else(|| "0".into());
+    terms.fold(first, |min, term| {
+        format!("(ite (<= {} {}) {} {})", min, term, min, term)
+    })
+}
+
+/// Sum of some terms.
+fn sum(terms: Vec<String>) -> String {
+    match terms.len() {
+        0 => "0".into(),
+        1 => terms.into_iter().next().expect("length is 1"),
+        _ => format!("(+ {})", terms.join(" ")),
+    }
+}
+
+/// Distance of an SMT-LIB formula to evaluating to `target`.
+///
+/// Non-negative term that is `0` exactly when the formula evaluates to `target`.
+pub fn distance(expr: &smt::SExpr, target: bool) -> String {
+    if let smt::SExpr::List(elems) = expr {
+        if let Some((smt::SExpr::Atom(op), args)) = elems.split_first() {
+            let dists = |target: bool| args.iter().map(|arg| distance(arg, target)).collect();
+            match (op.as_str(), args) {
+                ("not", [arg]) => return distance(arg, !target),
+                ("and", _) if target => return sum(dists(true)),
+                ("and", _) => return min(dists(false)),
+                ("or", _) if target => return min(dists(true)),
+                ("or", _) => return sum(dists(false)),
+                ("=>", [lhs, rhs]) if target => {
+                    return min(vec![distance(lhs, false), distance(rhs, true)])
+                }
+                ("=>", [lhs, rhs]) => return sum(vec![distance(lhs, true), distance(rhs, false)]),
+                (op, [lhs, rhs]) if negate(op).is_some() => {
+                    let op = if target {
+                        op
+                    } else {
+                        negate(op).expect("comparison operator")
+                    };
+                    let gap = match op {
+                        "<" => format!("(+ (- {} {}) 1)", lhs, rhs),
+                        "<=" => format!("(- {} {})", lhs, rhs),
+                        ">" => format!("(+ (- {} {}) 1)", rhs, lhs),
+                        _ => format!("(- {} {})", rhs, lhs),
+                    };
+                    return format!("(ite ({} {} {}) 0 {})", op, lhs, rhs, gap);
+                }
+                _ => (),
+            }
+        }
+    }
+    if target {
+        format!("(ite {} 0 1)", expr)
+    } else {
+        format!("(ite {} 1 0)", expr)
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Fuzzed successor of the state at step `0`, `None` if there is none.
+    ///
+    /// The state at step `0` and the transition relation must be asserted in `solver`. `targets`
+    /// are the candidates to falsify.
+    pub fn fuzz_state(
+        &self,
+        solver: &mut smt::Solver,
+        rng: &mut Rng,
+        targets: &[&Expr],
+    ) -> Res<Option<smt::State>> {
+        if targets.is_empty() {
+            return self.random_state(solver, rng, 1);
+        }
+
+        solver.intent("fuzz: does a successor falsify a candidate?")?;
+        let mut falsification = "(or".to_string();
+        for def in targets {
+            falsification.push_str(&format!(" (not {})", smt::Unroll::new(def, 1)))
+        }
+        falsification.push(')');
+        solver.push()?;
+        solver.assert(falsification)?;
+        let state = self.random_state(solver, rng, 1)?;
+        solver.pop()?;
+        if state.is_some() {
+            return Ok(state);
+        }
+
+        // Keep some randomness so that runs do not all follow the same path.
+        if rng.below(4) == 0 {
+            return self.random_state(solver, rng, 1);
+        }
+
+        let target = targets[rng.below(targets.len() as u64) as usize];
+        let dist = distance(
+            &smt::SExpr::parse(&smt::Unroll::new(target, 1).to_string())?,
+            false,
+        );
+        solver.intent("fuzz: successor closer to the falsification of a candidate")?;
+        if !solver.check_sat()? {
+            return Ok(None);
+        }
+        let mut best = solver.get_value(&dist)?;
+        let mut scopes = 0;
+        for _ in 0..DESCENT {
+            solver.push()?;
+            solver.assert(format!("(< {} {})", dist, best))?;
+            if solver.check_sat()? {
+                best = solver.get_value(&dist)?;
+                scopes += 1
+            } else {
+                solver.pop()?;
+                break;
+            }
+        }
+        let state = self.random_state(solver, rng, 1)?;
+        for _ in 0..scopes {
+            solver.pop()?
+        }
+        Ok(state)
+    }
+}
@@ -0,0 +1,69 @@
+//! GitHub Actions annotations.
+
+mikino_api::prelude!();
+
+use crate::{budget::Verdict, report, Mode, Run};
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// An `error` workflow command.
+fn error(file: &str, line: Option<usize>, title: &str, msg: &str) -> String {
+    let mut props = format!("file={}", escape_property(file));
+    if let Some(line) = line {
+        props.push_str(&format!(",line={}", line))
+    }
+    props.push_str(&format!(",title={}", escape_property(title)));
+    format!("::error {}::{}", props, escape_data(msg))
+}
+
+impl Run {
+    /// Prints the annotations of the falsified candidates of a check run, if
+    /// `--github_annotations` is active.
+    pub fn annotate(&self, verdict: &Verdict) {
+        if !self.github_annotations {
+            return;
+        }
+        for suite in &verdict.suites {
+            for (case, msg) in suite.falsifications() {
+                let mut text = format!("candidate `{}` is falsified ({})", case.candidate, msg);
+                if let Some(trace) = case.trace.as_ref() {
+                    text.push_str(&format!(
+                        "\ncounterexample: {}",
+                        report::trace_summary(trace)
+                    ))
+                }
+                println!(
+                    "{}",
+                    error(
+                        &suite.file,
+                        suite.lines.get(&case.candidate).cloned(),
+                        &format!("{}: falsified candidate", suite.name),
+                        &text
+                    )
+                )
+            }
+        }
+    }
+
+    /// Prints the annotation of an error, if `--github_annotations` is active.
+    ///
+    /// `errors` are the plain-text error messages.
+    pub fn annotate_error(&self, errors: &[String]) {
+        if !self.github_annotations {
+            return;
+        }
+        if let Mode::Check { input, .. } = &self.mode {
+            println!("{}", error(input, None, "mikino error", &errors.join("\n")))
+        }
+    }
+}
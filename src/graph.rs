@@ -0,0 +1,173 @@
+//! Structure of a system as a graph, in the DOT or Mermaid format.
+
+mikino_api::prelude!();
+
+use expr::{Expr, Op};
+use trans::Sys;
+
+use crate::{dot::escape, mode::GraphFormat, Check, Run};
+
+/// Structure of a system.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    /// State variables, in declaration order.
+    pub vars: Vec<String>,
+    /// Dependencies, pairs of a current-state variable and a next-state variable depending on it.
+    pub deps: Set<(String, String)>,
+    /// Candidates and the variables they reference.
+    pub candidates: Vec<(String, Set<String>)>,
+}
+
+/// Collects the variables of an expression, with `true` for next-state variables.
+fn collect_vars(expr: &Expr, res: &mut Set<(String, bool)>) {
+    match expr {
+        Expr::Cst(_) => (),
+        Expr::Var(var) => {
+            res.insert((var.id().to_string(), var.is_next()));
+        }
+        Expr::App { args, .. } => {
+            for arg in args {
+                collect_vars(arg, res)
+            }
+        }
+    }
+}
+
+/// Top-level conjuncts of an expression.
+fn conjuncts<'a>(expr: &'a Expr, res: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::App { op: Op::And, args } => {
+            for arg in args {
+                conjuncts(arg, res)
+            }
+        }
+        expr => res.push(expr),
+    }
+}
+
+impl Graph {
+    /// Structure of a system.
+    pub fn new(sys: &Sys) -> Self {
+        let vars = sys
+            .decls()
+            .iter()
+            .map(|decl| decl.id().to_string())
+            .collect();
+
+        let mut deps = Set::new();
+        let mut trans = Vec::new();
+        conjuncts(sys.trans(), &mut trans);
+        for conjunct in trans {
+            let mut vars = Set::new();
+            collect_vars(conjunct, &mut vars);
+            for (next, _) in vars.iter().filter(|(_, is_next)| *is_next) {
+                for (curr, _) in vars.iter().filter(|(_, is_next)| !*is_next) {
+                    deps.insert((curr.clone(), next.clone()));
+                }
+            }
+        }
+
+        let candidates = sys
+            .po_s()
+            .iter()
+            .map(|(candidate, def)| {
+                let mut vars = Set::new();
+                collect_vars(def, &mut vars);
+                let vars = vars.into_iter().map(|(var, _)| var).collect();
+                (candidate.clone(), vars)
+            })
+            .collect();
+
+        Self {
+            vars,
+            deps,
+            candidates,
+        }
+    }
+
+    /// Renders the graph in the DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph system {\n    node [shape = ellipse];\n".to_string();
+        for var in &self.vars {
+            dot.push_str(&format!("    \"{}\";\n", escape(var)))
+        }
+        for (candidate, _) in &self.candidates {
+            dot.push_str(&format!(
+                "    \"candidate {}\" [label = \"{}\", shape = box];\n",
+                escape(candidate),
+                escape(candidate)
+            ))
+        }
+        dot.push('\n');
+        for (curr, next) in &self.deps {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\";\n",
+                escape(curr),
+                escape(next)
+            ))
+        }
+        for (candidate, vars) in &self.candidates {
+            for var in vars {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"candidate {}\" [style = dashed];\n",
+                    escape(var),
+                    escape(candidate)
+                ))
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a Mermaid flowchart.
+    ///
+    /// Nodes get generated identifiers, `v<N>` for variables and `c<N>` for candidates, names only
+    /// appear in labels.
+    pub fn to_mermaid(&self) -> String {
+        let label = |s: &str| s.replace('"', "#quot;");
+        let var_node = |var: &str| {
+            self.vars
+                .iter()
+                .position(|v| v == var)
+                .map(|idx| format!("v{}", idx))
+                .unwrap_or_else(|| format!("\"{}\"", label(var)))
+        };
+        let mut res = "flowchart LR\n".to_string();
+        for (idx, var) in self.vars.iter().enumerate() {
+            res.push_str(&format!("    v{}([\"{}\"])\n", idx, label(var)))
+        }
+        for (idx, (candidate, _)) in self.candidates.iter().enumerate() {
+            res.push_str(&format!("    c{}[\"{}\"]\n", idx, label(candidate)))
+        }
+        for (curr, next) in &self.deps {
+            res.push_str(&format!("    {} --> {}\n", var_node(curr), var_node(next)))
+        }
+        for (idx, (_, vars)) in self.candidates.iter().enumerate() {
+            for var in vars {
+                res.push_str(&format!("    {} -.-> c{}\n", var_node(var), idx))
+            }
+        }
+        res
+    }
+}
+
+impl Run {
+    /// Renders the structure of a system, to `output` if any, to stdout otherwise.
+    pub fn graph(&self, input: &str, output: &Option<String>, format: GraphFormat) -> Res<()> {
+        let check = Check::new(self, input, &None)?;
+        let graph = Graph::new(&check.sys);
+        let graph = match format {
+            GraphFormat::Dot => graph.to_dot(),
+            GraphFormat::Mermaid => graph.to_mermaid(),
+        };
+        match output {
+            Some(path) => {
+                std::fs::write(path, graph)
+                    .chain_err(|| format!("while writing graph to `{}`", path))?;
+                self.progress(|s| format!("graph written to `{}`", s.bold.paint(path)))
+            }
+            None => print!("{}", graph),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! Cost-guided BMC.
+//!
+//! A greedy descent maximizing `--guide` before exhaustive BMC.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+use expr::{Expr, Op};
+
+use crate::{smt, text, Check, Phase};
+
+/// Parses the cost expression of a guided BMC, `txt` is the text of the system.
+pub fn cost(txt: &str, guide: &str) -> Res<Expr> {
+    let candidate = ("guide".to_string(), format!("({}) = ({})", guide, guide));
+    let sys = parse::trans(&text::with_candidates(txt, &[candidate])?)
+        .chain_err(|| format!("while parsing guide `{}`", guide))?;
+    match sys.po_s().values().next() {
+        Some(Expr::App { op: Op::Eq, args }) if args.len() == 2 => Ok(args[0].clone()),
+        _ => bail!(
+            "failed to retrieve the cost expression of guide `{}`",
+            guide
+        ),
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Guided descent, yields the costs of the states of the descent.
+    ///
+    /// Candidates falsified are moved from `okay` to `falsified`, with their depth.
+    fn guided_descent(
+        &self,
+        max: usize,
+        cost: &Expr,
+        okay: &mut Vec<&str>,
+        falsified: &mut Vec<(String, usize)>,
+    ) -> Res<Vec<String>> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "guided_descent.smt2")?;
+        let mut costs = Vec::new();
+        for depth in 0..=max {
+            if okay.is_empty() {
+                break;
+            }
+            solver.declare_state(sys, depth)?;
+            if depth == 0 {
+                solver.intent("guided descent: initial state maximizing the cost")?;
+                solver.assert_init(sys, 0)?
+            } else {
+                solver.intent(&format!(
+                    "guided descent: successor maximizing the cost at depth {}",
+                    depth
+                ))?;
+                solver.assert_trans(sys, depth - 1)?
+            }
+            let unrolled = smt::Unroll::new(cost, depth).to_string();
+            solver.push()?;
+            solver.maximize(&unrolled)?;
+            if !solver.check_sat()? {
+                solver.pop()?;
+                break;
+            }
+            let state = solver.get_state(sys, depth)?;
+            costs.push(solver.get_value(&unrolled)?.to_value());
+            solver.pop()?;
+            solver.assert(smt::state_at(&state, depth))?;
+
+            let mut idx = 0;
+            while idx < okay.len() {
+                let candidate = okay[idx];
+                let def = sys.po_s().get(candidate).ok_or_else(|| {
+                    format!(
+                        "failed to retrieve definition for candidate `{}`",
+                        candidate,
+                    )
+                })?;
+                solver.intent(&format!(
+                    "guided descent: does candidate `{}` hold at depth {}?",
+                    candidate, depth
+                ))?;
+                solver.push()?;
+                solver.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+                if solver.check_sat()? {
+                    let trace = solver.get_trace(sys, depth)?;
+                    println!(
+                        "guided descent found a {} at depth {}:",
+                        self.red.paint("falsification"),
+                        self.bold.paint(depth.to_string())
+                    );
+                    self.present_trace(sys, candidate, &trace, &[], true)?;
+                    self.settle("bmc", candidate, "falsified", Some(depth))?;
+                    falsified.push((okay.remove(idx).to_string(), depth));
+                } else {
+                    idx += 1
+                }
+                solver.pop()?;
+            }
+        }
+        Ok(costs)
+    }
+
+    /// Runs cost-guided BMC up to depth `max`, `cost` is the expression to maximize.
+    pub fn guided_bmc(
+        &self,
+        max: Option<usize>,
+        cost: &Expr,
+        base: &BaseRes,
+        step: Option<&StepRes>,
+    ) -> Res<()> {
+        let max = match max {
+            Some(max) => max,
+            None => bail!("`--guide` requires a BMC bound, use `--bmc_max`"),
+        };
+        let bmc_res = if let Some(step) = step {
+            base.merge_base_with_step(step)
+                .chain_err(|| "during base/step result merge for BMC")?
+        } else {
+            base.as_inner().clone().into()
+        };
+        if bmc_res.all_falsified() {
+            return Ok(());
+        }
+        let mut okay: Vec<&str> = bmc_res.okay.iter().cloned().collect();
+
+        self.env.progress(|s| {
+            format!(
+                "running {} descent up to depth {}, looking for falsifications for {} \
+                candidate(s)...",
+                s.bold.paint("guided"),
+                max,
+                okay.len()
+            )
+        });
+        let mut falsified = Vec::new();
+        let costs = self
+            .guided_descent(max, cost, &mut okay, &mut falsified)
+            .chain_err(|| "during guided descent")?;
+        let descended = falsified.len();
+
+        if !okay.is_empty() {
+            self.env.progress(|s| {
+                format!(
+                    "running {}, looking for falsifications for {} candidate(s)...",
+                    s.bold.paint("BMC"),
+                    okay.len()
+                )
+            });
+        }
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "guided_bmc.smt2")?;
+        solver.intent("guided BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        for depth in 0..=max {
+            if okay.is_empty() {
+                break;
+            }
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "checking for falsifications at depth {}",
+                        s.under.paint(depth.to_string())
+                    )
+                });
+            }
+
+            let start = std::time::Instant::now();
+            let mut idx = 0;
+            while idx < okay.len() {
+                let candidate = okay[idx];
+                let def = sys.po_s().get(candidate).ok_or_else(|| {
+                    format!(
+                        "failed to retrieve definition for candidate `{}`",
+                        candidate,
+                    )
+                })?;
+                solver.intent(&format!(
+                    "guided BMC: falsification for candidate `{}` at depth {}?",
+                    candidate, depth
+                ))?;
+                solver.push()?;
+                solver.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+                solver.maximize(smt::Unroll::new(cost, depth))?;
+                if solver.check_sat()? {
+                    let trace = solver.get_trace(sys, depth)?;
+                    println!(
+                        "found a {} at depth {}:",
+                        self.red.paint("falsification"),
+                        self.bold.paint(depth.to_string())
+                    );
+                    self.present_trace(sys, candidate, &trace, &[], true)?;
+                    self.settle("bmc", candidate, "falsified", Some(depth))?;
+                    falsified.push((okay.remove(idx).to_string(), depth));
+                } else {
+                    idx += 1
+                }
+                solver.pop()?;
+            }
+            self.record_depth(depth, start.elapsed())?;
+        }
+        for candidate in &okay {
+            self.settle("bmc", candidate, "unfalsified", Some(max))?
+        }
+
+        if self.env.verb > 0 || !falsified.is_empty() {
+            println!()
+        }
+
+        println!("|===| {} result", self.bold.paint("Guided bmc"));
+        match (costs.first(), costs.last()) {
+            (Some(first), Some(last)) => println!(
+                "| - guided descent reached depth {}, cost went from {} to {}",
+                costs.len() - 1,
+                first,
+                self.bold.paint(last)
+            ),
+            _ => println!("| - guided descent found no initial state"),
+        }
+        if descended > 0 {
+            println!(
+                "|   it falsified {} candidate(s) without exhaustive BMC",
+                descended
+            )
+        }
+        println!("|");
+        if !okay.is_empty() {
+            println!(
+                "| - could {} find falsifications for the following candidate(s)",
+                self.bold.paint("not")
+            );
+            for candidate in &okay {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        if !okay.is_empty() && !falsified.is_empty() {
+            println!("|")
+        }
+        if !falsified.is_empty() {
+            println!(
+                "| - found a {} for the following candidate(s)",
+                self.red.paint("falsification")
+            );
+            for (candidate, depth) in &falsified {
+                println!("|   `{}` (depth {})", self.red.paint(candidate), depth)
+            }
+        }
+        println!("|");
+        if !base.cexs.is_empty() || !falsified.is_empty() {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else {
+            println!("| - system {}", self.red.paint("might be unsafe"));
+            println!(
+                "|   no falsification in {} was found for some candidate(s)",
+                self.bold.paint(format!("{} step(s) or less", max))
+            );
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Highlighting of the values that change in traces.
+
+mikino_api::prelude!();
+
+use ansi_term::Style;
+
+use crate::Check;
+
+/// When changed values are highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    /// At high verbosity.
+    Auto,
+    /// Always.
+    Always,
+    /// Never.
+    Never,
+}
+
+impl When {
+    /// Names of the policies.
+    pub const NAMES: [&'static str; 3] = ["auto", "always", "never"];
+
+    /// Policy from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// True if changed values are highlighted at some verbosity level.
+    pub fn is_active(self, verb: usize) -> bool {
+        match self {
+            Self::Auto => verb > 1,
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Style of the value of a variable in a trace, `prev` are the values of the previous step
+    /// if any. `None` if the value is not highlighted.
+    pub fn change_style(
+        &self,
+        var: &str,
+        val: &str,
+        prev: Option<&[(String, String)]>,
+    ) -> Option<Style> {
+        if !self.env.highlight_changes {
+            return None;
+        }
+        let prev_val = prev.and_then(|prev| prev.iter().find(|(v, _)| v == var));
+        match prev_val {
+            Some((_, prev_val)) if prev_val == val => Some(self.gray),
+            Some(_) => Some(self.code),
+            None => None,
+        }
+    }
+
+    /// Value of a variable as it should be presented in a trace, see [`Self::change_style`].
+    pub fn highlighted(&self, var: &str, val: &str, prev: Option<&[(String, String)]>) -> String {
+        match self.change_style(var, val, prev) {
+            Some(style) => style.paint(val).to_string(),
+            None => val.into(),
+        }
+    }
+}
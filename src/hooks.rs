@@ -0,0 +1,80 @@
+//! Hooks, external commands run at phase boundaries.
+//!
+//! Hooks run through `sh -c` and receive a one-line JSON payload on their standard input.
+
+mikino_api::prelude!();
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::{json::json_str, Check, Run};
+
+impl Run {
+    /// Runs a hook command with some JSON payload on its standard input.
+    pub fn hook(&self, name: &str, cmd: &str, payload: &str) -> Res<()> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .env("MIKINO_HOOK", name)
+            .stdin(Stdio::piped())
+            .spawn()
+            .chain_err(|| format!("while running {} hook `{}`", name, cmd))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The hook may not read its payload, ignore broken pipes.
+            let _ = writeln!(stdin, "{}", payload);
+        }
+        let status = child
+            .wait()
+            .chain_err(|| format!("while waiting for {} hook `{}`", name, cmd))?;
+        if !status.success() {
+            bail!("{} hook `{}` failed ({})", name, cmd, status)
+        }
+        Ok(())
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Runs the pre-check hook, if any, `input` describes the system checked.
+    pub fn hook_pre_check(&self, input: &str) -> Res<()> {
+        let Some(cmd) = self.env.hook_pre_check.as_ref() else {
+            return Ok(());
+        };
+        let candidates: Vec<String> = self.sys.po_s().keys().map(|c| json_str(c)).collect();
+        let payload = format!(
+            "{{\"hook\": \"pre_check\", \"run_id\": {}, \"input\": {}, \"candidates\": [{}]}}",
+            json_str(&self.env.run_id),
+            json_str(input),
+            candidates.join(", ")
+        );
+        self.env.hook("pre_check", cmd, &payload)
+    }
+
+    /// Runs the post-PO hook, if any, once an analysis is done with a candidate.
+    ///
+    /// Analyses call [`Self::settle`] rather than this function directly.
+    pub fn hook_post_po(
+        &self,
+        phase: &str,
+        candidate: &str,
+        status: &str,
+        depth: Option<usize>,
+    ) -> Res<()> {
+        let Some(cmd) = self.env.hook_post_po.as_ref() else {
+            return Ok(());
+        };
+        let payload = format!(
+            "{{\"hook\": \"post_po\", \"run_id\": {}, \"phase\": {}, \"candidate\": {}, \
+            \"status\": {}, \"depth\": {}}}",
+            json_str(&self.env.run_id),
+            json_str(phase),
+            json_str(candidate),
+            json_str(status),
+            depth
+                .map(|depth| depth.to_string())
+                .unwrap_or_else(|| "null".into())
+        );
+        self.env.hook("post_po", cmd, &payload)
+    }
+}
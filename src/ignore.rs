@@ -0,0 +1,113 @@
+//! Candidates parked with an `#[ignore(reason = "...")]` annotation.
+
+mikino_api::prelude!();
+
+use crate::{text, Run};
+
+/// Annotation prefix.
+const ANNOTATION: &str = "#[ignore";
+
+/// An ignored candidate.
+pub struct Ignored {
+    /// Name of the candidate.
+    pub name: String,
+    /// Reason it is ignored.
+    pub reason: String,
+    /// Line of the annotation.
+    pub line: usize,
+}
+
+/// Parses an annotated candidate, yields the annotation's length, the reason and the name.
+fn annotated(elm: &str, line: usize) -> Res<(usize, String, String)> {
+    let illegal = || {
+        format!(
+            "illegal annotation at line {}, expected `#[ignore(reason = \"...\")]`",
+            line
+        )
+    };
+    let rest = elm[ANNOTATION.len()..].trim_start();
+    let rest = rest.strip_prefix('(').ok_or_else(illegal)?.trim_start();
+    let rest = rest
+        .strip_prefix("reason")
+        .ok_or_else(illegal)?
+        .trim_start();
+    let rest = rest.strip_prefix('=').ok_or_else(illegal)?.trim_start();
+    let rest = rest.strip_prefix('"').ok_or_else(illegal)?;
+    let end = rest.find('"').ok_or_else(illegal)?;
+    let reason = rest[..end].to_string();
+    let rest = rest[end + 1..].trim_start();
+    let rest = rest.strip_prefix(')').ok_or_else(illegal)?.trim_start();
+    let rest = rest.strip_prefix(']').ok_or_else(illegal)?;
+    let len = elm.len() - rest.len();
+
+    let name = rest
+        .trim_start()
+        .strip_prefix('"')
+        .and_then(|rest| rest.find('"').map(|end| rest[..end].to_string()))
+        .ok_or_else(|| {
+            format!(
+                "expected a candidate name after the annotation at line {}",
+                line
+            )
+        })?;
+    Ok((len, reason, name))
+}
+
+/// Removes the `#[ignore(...)]` annotations of a system.
+///
+/// Ignored candidates are removed too, unless `include`. Yields the new system and the ignored
+/// candidates, which is empty if `include`.
+pub fn strip(txt: &str, include: bool) -> Res<(String, Vec<Ignored>)> {
+    if !txt.contains(ANNOTATION) {
+        return Ok((txt.into(), Vec::new()));
+    }
+    let mut res = txt.to_string();
+    let mut ignored = Vec::new();
+    for (start, end) in text::elements(txt, "candidates")?.into_iter().rev() {
+        let elm = &txt[start..end];
+        if !elm.starts_with(ANNOTATION) {
+            continue;
+        }
+        let line = txt[..start].matches('\n').count() + 1;
+        let (len, reason, name) = annotated(elm, line)?;
+        if include {
+            res.replace_range(start..start + len, "");
+            continue;
+        }
+        // Remove the comma separating the candidate from the next one, if any.
+        let after = txt[end..].trim_start();
+        let end = if after.starts_with(',') {
+            txt.len() - after.len() + 1
+        } else {
+            end
+        };
+        res.replace_range(start..end, "");
+        ignored.push(Ignored { name, reason, line })
+    }
+    ignored.reverse();
+    Ok((res, ignored))
+}
+
+impl Run {
+    /// Lists the ignored candidates, if any, unless the output is JSON.
+    pub fn report_ignored(&self, ignored: &[Ignored]) {
+        if ignored.is_empty() || self.json {
+            return;
+        }
+        println!("|===| {}", self.bold.paint("Ignored candidates"));
+        println!(
+            "| - the following candidate(s) are {}, `--include_ignored` checks them",
+            self.bold.paint("not checked")
+        );
+        for Ignored { name, reason, line } in ignored {
+            println!(
+                "|   `{}` (line {}): {}",
+                self.bold.paint(name),
+                line,
+                reason
+            )
+        }
+        println!("|===|");
+        println!()
+    }
+}
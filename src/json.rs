@@ -0,0 +1,276 @@
+//! JSON output.
+//!
+//! `--json` prints a single JSON document on stdout instead of the reports.
+
+mikino_api::prelude!();
+
+use crate::{
+    budget::Verdict, facts::Fact, ignore::Ignored, metadata::Metadata, mode::Engine, Check, Mode,
+    Run, Trace,
+};
+
+/// Escapes a string for JSON.
+pub fn json_str(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for char in s.chars() {
+        match char {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            char if char.is_control() => res.push_str(&format!("\\u{:04x}", char as u32)),
+            char => res.push(char),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// JSON value of a mikino value: booleans and integers as is, anything else as a string.
+pub fn json_value(val: &str) -> String {
+    if val == "true" || val == "false" || val.parse::<i64>().is_ok() {
+        val.into()
+    } else {
+        json_str(val)
+    }
+}
+
+/// JSON value of an optional integer, `null` if none.
+pub fn json_opt(val: Option<usize>) -> String {
+    val.map(|val| val.to_string())
+        .unwrap_or_else(|| "null".into())
+}
+
+/// JSON array of some JSON values.
+pub fn json_array(elems: &[String]) -> String {
+    format!("[{}]", elems.join(", "))
+}
+
+/// JSON object from some pairs of keys and JSON values.
+pub fn json_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, val)| format!("{}: {}", json_str(key), val))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// JSON representation of a trace.
+pub fn json_trace(trace: &Trace) -> String {
+    let steps: Vec<String> = trace
+        .iter()
+        .map(|(step, values)| {
+            let state: Vec<(&str, String)> = values
+                .iter()
+                .map(|(var, val)| {
+                    (
+                        var.as_str(),
+                        json_value(val.trim_end_matches(" (completed)")),
+                    )
+                })
+                .collect();
+            json_object(&[("step", step.to_string()), ("state", json_object(&state))])
+        })
+        .collect();
+    json_array(&steps)
+}
+
+impl Run {
+    /// Fails if the check mode uses analyses the JSON output, and thus `--events`, does not
+    /// support.
+    pub fn json_supported(&self) -> Res<()> {
+        let Mode::Check {
+            bmc_depths,
+            eventually,
+            guide,
+            bmc_induction,
+            simple_path,
+            explain_step,
+            lemmas,
+            suggest,
+            almost_safe,
+            check_vacuity,
+            check_deadlock,
+            check_determinism,
+            check_dead_branches,
+            check_stuttering,
+            certificate,
+            coverage,
+            engine,
+            ..
+        } = &self.mode
+        else {
+            bail!(
+                "`{}` is only supported by the check and bmc modes",
+                self.machine_flag()
+            )
+        };
+        let unsupported = [
+            ("--bmc_depths", bmc_depths.is_some()),
+            ("--eventually", !eventually.is_empty()),
+            ("--guide", guide.is_some()),
+            ("--bmc_induction", *bmc_induction),
+            ("--simple_path", simple_path.is_some()),
+            ("--explain_step", *explain_step),
+            ("--lemmas", *lemmas),
+            ("--suggest", *suggest),
+            ("--almost_safe", *almost_safe),
+            ("--check_vacuity", *check_vacuity),
+            ("--check_deadlock", *check_deadlock),
+            ("--check_determinism", *check_determinism),
+            ("--check_dead_branches", *check_dead_branches),
+            ("--check_stuttering", *check_stuttering),
+            ("--certificate", certificate.is_some()),
+            ("--coverage", *coverage),
+            ("--engine", *engine != Engine::Induction),
+        ];
+        for (flag, used) in unsupported.iter() {
+            if *used {
+                bail!("`{}` does not support `{}`", self.machine_flag(), flag)
+            }
+        }
+        Ok(())
+    }
+
+    /// Flag activating the machine output, `--json` or `--events`.
+    pub fn machine_flag(&self) -> &'static str {
+        if self.events.is_some() {
+            "--events"
+        } else {
+            "--json"
+        }
+    }
+
+    /// Prints the JSON document of a check run, if `--json` is active.
+    pub fn print_json(&self, meta: &Metadata, verdict: &Verdict) {
+        if !self.json || self.events.is_some() {
+            return;
+        }
+        let mut summary = vec![
+            ("proved", verdict.proved.len().to_string()),
+            ("falsified", verdict.falsified.len().to_string()),
+            ("unknown", verdict.unknown.len().to_string()),
+        ];
+        if let Mode::Check {
+            score_threshold: Some(_),
+            ..
+        } = &self.mode
+        {
+            summary.push(("score", format!("{:.2}", verdict.score())))
+        }
+        let summary = json_object(&summary);
+        println!(
+            "{}",
+            json_object(&[
+                ("run_id", json_str(&self.run_id)),
+                ("metadata", meta.to_json()),
+                ("systems", json_array(&verdict.json)),
+                ("summary", summary),
+            ])
+        )
+    }
+}
+
+impl<'env> Check<'env> {
+    /// JSON report of a system once all its phases ran.
+    ///
+    /// `input` describes the system, `sanity` is the result of [`Self::sanity`].
+    pub fn json_report(
+        &self,
+        input: &str,
+        sanity: &str,
+        ignored: &[Ignored],
+        facts: &[(&Fact, bool)],
+    ) -> Res<String> {
+        let settled = self
+            .settled
+            .lock()
+            .map_err(|_| "failed to access candidate statuses")?;
+        let outcomes = self
+            .outcomes
+            .lock()
+            .map_err(|_| "failed to access candidate outcomes")?;
+        let cexs = self
+            .cexs
+            .lock()
+            .map_err(|_| "failed to access counterexamples")?;
+        let timings = self
+            .timings
+            .lock()
+            .map_err(|_| "failed to access phase timings")?;
+
+        let candidates: Vec<String> = self
+            .sys
+            .po_s()
+            .keys()
+            .map(|candidate| {
+                let status = match settled.get(candidate) {
+                    Some(true) => "proved",
+                    Some(false) => "falsified",
+                    None => "unknown",
+                };
+                let (phase, detail, depth) = match outcomes.get(candidate) {
+                    Some(outcome) => (
+                        json_str(&outcome.phase),
+                        json_str(&outcome.status),
+                        outcome.depth,
+                    ),
+                    None => ("null".into(), "null".into(), None),
+                };
+                let cex = cexs.iter().find(|(name, _)| name == candidate);
+                let trace = cex
+                    .map(|(_, trace)| json_trace(&self.sanitized_trace(trace)))
+                    .unwrap_or_else(|| "null".into());
+                let cex_id = cex
+                    .map(|(_, trace)| json_str(&self.cex_id(candidate, trace)))
+                    .unwrap_or_else(|| "null".into());
+                json_object(&[
+                    ("name", json_str(&self.sanitized_text(candidate))),
+                    ("status", json_str(status)),
+                    ("detail", detail),
+                    ("phase", phase),
+                    ("depth", json_opt(depth)),
+                    ("trace", trace),
+                    ("cex_id", cex_id),
+                ])
+            })
+            .collect();
+        let ignored: Vec<String> = ignored
+            .iter()
+            .map(|Ignored { name, reason, line }| {
+                json_object(&[
+                    ("name", json_str(name)),
+                    ("reason", json_str(reason)),
+                    ("line", line.to_string()),
+                ])
+            })
+            .collect();
+        let facts: Vec<String> = facts
+            .iter()
+            .map(|(fact, holds)| {
+                json_object(&[
+                    ("fact", json_str(&fact.expr)),
+                    ("line", fact.line.to_string()),
+                    ("holds", holds.to_string()),
+                ])
+            })
+            .collect();
+        let timings: Vec<(&str, String)> = timings
+            .iter()
+            .map(|(phase, time)| (phase.as_str(), format!("{:.3}", time.as_secs_f64())))
+            .collect();
+
+        let mut fields = vec![
+            ("input", json_str(input)),
+            ("sanity", json_str(sanity)),
+            ("ignored", json_array(&ignored)),
+            ("facts", json_array(&facts)),
+            ("candidates", json_array(&candidates)),
+            ("timings", json_object(&timings)),
+        ];
+        if self.env.stats {
+            fields.push(("stats", self.json_stats()?))
+        }
+        Ok(json_object(&fields))
+    }
+}
@@ -0,0 +1,167 @@
+//! Lasso detection for bounded liveness.
+//!
+//! A path looping back to one of its states where `p` never holds falsifies `eventually p`.
+
+mikino_api::prelude!();
+
+use crate::{smt, text, Check, Phase, Trace};
+
+/// Lasso search for some `eventually` objectives.
+pub struct Lasso {
+    /// System whose candidates are the objectives.
+    pub sys: trans::Sys,
+    /// Objectives with no lasso found yet.
+    pub pending: Vec<String>,
+    /// Objectives falsified by a lasso, with the length of the lasso.
+    pub falsified: Vec<(String, usize)>,
+    /// Solver, the initial state(s) and transitions up to `next - 1` are asserted.
+    solver: smt::Solver,
+    /// Next lasso length to check.
+    next: usize,
+}
+impl Lasso {
+    /// Constructor, from the text of the system and the objectives' expressions.
+    pub fn new(check: &Check, txt: &str, objectives: &[String]) -> Res<Self> {
+        let candidates: Vec<(String, String)> = objectives
+            .iter()
+            .map(|expr| (format!("eventually {}", expr), expr.clone()))
+            .collect();
+        let sys = parse::trans(&text::with_candidates(txt, &candidates)?)
+            .chain_err(|| "while parsing `eventually` objectives")?;
+        let pending = sys.po_s().keys().cloned().collect();
+        let mut solver = check.phase_solver(Phase::Bmc, "lasso.smt2")?;
+        solver.intent("lasso setup")?;
+        Ok(Self {
+            sys,
+            pending,
+            falsified: Vec::new(),
+            solver,
+            next: 0,
+        })
+    }
+
+    /// True if all objectives are falsified.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Looks for lassos of length up to `depth` for the pending objectives.
+    ///
+    /// Yields the newly falsified objectives, their lasso, and the step the lasso loops back to.
+    pub fn check_up_to(&mut self, depth: usize) -> Res<Vec<(String, Trace, usize)>> {
+        let mut res = Vec::new();
+        while self.next <= depth && !self.is_done() {
+            let step = self.next;
+            self.next += 1;
+            self.solver.declare_state(&self.sys, step)?;
+            if step == 0 {
+                self.solver.assert_init(&self.sys, 0)?
+            } else {
+                self.solver.assert_trans(&self.sys, step - 1)?
+            }
+            res.extend(self.check(step)?)
+        }
+        Ok(res)
+    }
+
+    /// Looks for lassos of length `depth`, the unrolling must reach `depth`.
+    fn check(&mut self, depth: usize) -> Res<Vec<(String, Trace, usize)>> {
+        let sys = &self.sys;
+        let mut res = Vec::new();
+        let mut idx = 0;
+        while idx < self.pending.len() {
+            let objective = &self.pending[idx];
+            let def = sys.po_s().get(objective).ok_or_else(|| {
+                format!(
+                    "failed to retrieve definition for objective `{}`",
+                    objective,
+                )
+            })?;
+            self.solver.intent(&format!(
+                "lasso: is `{}` falsified by a lasso of length {}?",
+                objective, depth
+            ))?;
+            self.solver.push()?;
+            // State `depth + 1` closes the loop.
+            self.solver.declare_state(sys, depth + 1)?;
+            self.solver.assert_trans(sys, depth)?;
+            let mut closes = "(or".to_string();
+            for step in 0..=depth {
+                closes.push_str(&format!(
+                    " (not {})",
+                    smt::distinct_states(sys, step, depth + 1)
+                ));
+                self.solver
+                    .assert(format!("(not {})", smt::Unroll::new(def, step)))?
+            }
+            closes.push(')');
+            self.solver.assert(closes)?;
+
+            if self.solver.check_sat()? {
+                let mut trace = self.solver.get_trace(sys, depth + 1)?;
+                let (_, closing) = trace.pop().expect("trace has at least two states");
+                let loop_to = trace
+                    .iter()
+                    .position(|(_, values)| *values == closing)
+                    .unwrap_or(depth);
+                self.solver.pop()?;
+                let objective = self.pending.remove(idx);
+                self.falsified.push((objective.clone(), depth));
+                res.push((objective, trace, loop_to))
+            } else {
+                self.solver.pop()?;
+                idx += 1
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Presents a lasso falsifying an `eventually` objective.
+    pub fn present_lasso(
+        &self,
+        lasso: &Lasso,
+        objective: &str,
+        trace: &Trace,
+        loop_to: usize,
+    ) -> Res<()> {
+        println!(
+            "found a {} of length {}, looping back to step {}:",
+            self.red.paint("lasso"),
+            self.bold.paint((trace.len() - 1).to_string()),
+            self.bold.paint(loop_to.to_string())
+        );
+        self.present_trace(&lasso.sys, objective, trace, &[], true)
+    }
+
+    /// Reports the result of the lasso search.
+    pub fn lasso_result(&self, lasso: &Lasso, max: Option<usize>) {
+        println!("|===| {} result", self.bold.paint("Bounded liveness"));
+        if !lasso.pending.is_empty() {
+            println!(
+                "| - could {} find lassos for the following objective(s) in {} step(s) or less",
+                self.bold.paint("not"),
+                max.expect(
+                    "[fatal] cannot have lasso search with no max end with pending objectives"
+                )
+            );
+            for objective in &lasso.pending {
+                println!("|   `{}`", self.bold.paint(objective))
+            }
+        }
+        if !lasso.pending.is_empty() && !lasso.falsified.is_empty() {
+            println!("|")
+        }
+        if !lasso.falsified.is_empty() {
+            println!(
+                "| - found a {} for the following objective(s)",
+                self.red.paint("lasso")
+            );
+            for (objective, depth) in &lasso.falsified {
+                println!("|   `{}` (length {})", self.red.paint(objective), depth)
+            }
+        }
+        println!("|===|");
+    }
+}
@@ -0,0 +1,124 @@
+//! Terminal-width-aware layout, only when stdout is a terminal.
+
+mikino_api::prelude!();
+
+/// Narrowest width text is wrapped or truncated to, whatever the width of the terminal.
+pub const MIN_WIDTH: usize = 16;
+
+/// Marker starting the continuation lines of a value.
+pub const CONTINUATION: &str = "↳";
+
+/// Marker ending a truncated value.
+pub const TRUNCATED: &str = "…";
+
+/// Width of the terminal stdout is, `None` if stdout is not a terminal.
+pub fn terminal_width() -> Option<usize> {
+    if !atty::is(atty::Stream::Stdout) {
+        return None;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|cols| cols.trim().parse::<usize>().ok())
+        .filter(|cols| *cols > 0)
+        .or_else(reported_width)
+}
+
+/// Width the terminal reports.
+#[cfg(unix)]
+fn reported_width() -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 {
+        Some(size.ws_col as usize).filter(|cols| *cols > 0)
+    } else {
+        None
+    }
+}
+
+/// Width the terminal reports, not supported on this platform.
+#[cfg(not(unix))]
+fn reported_width() -> Option<usize> {
+    None
+}
+
+/// Wraps some text, `first` is the width of the first line and `rest` the one of the others.
+///
+/// Breaks lines at spaces when possible, and in the middle of words longer than a line. Widths
+/// are in characters, and at least [`MIN_WIDTH`].
+pub fn wrap(txt: &str, first: usize, rest: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut len = 0;
+    for word in txt.split(' ') {
+        let width = if lines.is_empty() { first } else { rest }.max(MIN_WIDTH);
+        let word_len = word.chars().count();
+        if len > 0 && len + 1 + word_len > width {
+            lines.push(std::mem::take(&mut line));
+            len = 0;
+        }
+        if len > 0 {
+            line.push(' ');
+            len += 1;
+        }
+        for c in word.chars() {
+            let width = if lines.is_empty() { first } else { rest }.max(MIN_WIDTH);
+            if len >= width {
+                lines.push(std::mem::take(&mut line));
+                len = 0;
+            }
+            line.push(c);
+            len += 1;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Truncates some text to `width` characters, the last one being [`TRUNCATED`] if it is too long.
+pub fn truncate(txt: &str, width: usize) -> String {
+    if txt.chars().count() <= width {
+        return txt.into();
+    }
+    let mut res: String = txt.chars().take(width.saturating_sub(1)).collect();
+    res.push_str(TRUNCATED);
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_at_spaces() {
+        assert_eq!(
+            wrap("the quick brown fox jumps over the lazy dog", 20, 16),
+            ["the quick brown fox", "jumps over the", "lazy dog"]
+        );
+        assert_eq!(wrap("short text", 80, 80), ["short text"]);
+        assert_eq!(wrap("", 80, 80), [""]);
+    }
+
+    #[test]
+    fn wrap_long_words() {
+        let word = "é".repeat(20);
+        assert_eq!(wrap(&word, 16, 16), ["é".repeat(16), "é".repeat(4)]);
+        assert_eq!(
+            wrap(&format!("x = {}", "a".repeat(30)), 16, 20),
+            ["x =", &"a".repeat(20), &"a".repeat(10)]
+        );
+    }
+
+    #[test]
+    fn wrap_min_width() {
+        assert_eq!(wrap("abc def ghi", 0, 0), ["abc def ghi"]);
+        assert_eq!(
+            wrap("abcd efgh ijkl mnop", 1, 1),
+            ["abcd efgh ijkl", "mnop"]
+        );
+    }
+
+    #[test]
+    fn truncation() {
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("too long", 5), "too …");
+    }
+}
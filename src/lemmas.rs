@@ -0,0 +1,177 @@
+//! Incremental proof of non-inductive candidates using lemmas.
+//!
+//! Candidates falsified in the post-state are dropped until the others are inductive together.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+
+use crate::{smt, Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Attempts to prove the non-inductive candidates that hold in the initial states.
+    pub fn lemmas(&self, base: &BaseRes, step: &StepRes) -> Res<()> {
+        let lemmas: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        let candidates: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| !step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "attempting to prove {} candidate(s) with {} {}...",
+                candidates.len(),
+                lemmas.len(),
+                s.bold.paint("lemma(s)")
+            )
+        });
+
+        let (proved, rounds) = self
+            .lemmas_fixpoint(&lemmas, candidates.clone(), true)
+            .chain_err(|| "during lemma-based induction")?;
+        let failed: Vec<&str> = candidates
+            .iter()
+            .filter(|candidate| !proved.contains(candidate))
+            .cloned()
+            .collect();
+
+        for candidate in &proved {
+            self.settle("lemmas", candidate, "proved", None)?
+        }
+
+        if self.env.verb > 0 {
+            println!()
+        }
+
+        println!("|===| {} induction result", self.bold.paint("Lemma-based"));
+        println!(
+            "| - fixpoint reached after {} round(s), using {} lemma(s)",
+            rounds,
+            lemmas.len()
+        );
+        println!("|");
+        if !proved.is_empty() {
+            println!(
+                "| - the following candidate(s) are {} and thus {} in all reachable states",
+                self.green.paint("inductive together"),
+                self.green.paint("hold")
+            );
+            for candidate in &proved {
+                println!("|   `{}`", self.green.paint(*candidate))
+            }
+        }
+        if !failed.is_empty() {
+            if !proved.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - the following candidate(s) are {}, even with lemmas",
+                self.red.paint("not inductive")
+            );
+            for candidate in &failed {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+
+    /// Drops the candidates falsified in the post-state until a fixpoint is reached.
+    ///
+    /// Yields the candidates left at the fixpoint and the number of rounds. If `checkpoint`,
+    /// resumes from and saves to the checkpoint, see [`crate::checkpoint`].
+    pub fn lemmas_fixpoint<'a>(
+        &self,
+        lemmas: &[&str],
+        mut candidates: Vec<&'a str>,
+        checkpoint: bool,
+    ) -> Res<(Vec<&'a str>, usize)> {
+        let sys = &self.sys;
+        let def = |candidate: &str| {
+            sys.po_s().get(candidate).ok_or_else(|| {
+                format!(
+                    "failed to retrieve definition for candidate `{}`",
+                    candidate,
+                )
+            })
+        };
+
+        let mut solver = self.phase_solver(Phase::Step, "lemmas.smt2")?;
+        solver.intent(&format!(
+            "lemma-based induction setup: {} lemma(s) in the pre- and post-state",
+            lemmas.len()
+        ))?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        solver.assert_trans(sys, 0)?;
+        for lemma in lemmas {
+            let def = def(lemma)?;
+            solver.assert(smt::Unroll::new(def, 0))?;
+            solver.assert(smt::Unroll::new(def, 1))?;
+        }
+
+        let mut rounds = 0;
+        if checkpoint {
+            if let Some((left, resumed_rounds)) = self.resumed_lemmas()? {
+                candidates.retain(|candidate| left.iter().any(|left| left == candidate));
+                rounds = resumed_rounds
+            }
+        }
+        loop {
+            rounds += 1;
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "round {}: assuming {} candidate(s)",
+                        s.under.paint(rounds.to_string()),
+                        candidates.len()
+                    )
+                });
+            }
+
+            solver.intent(&format!(
+                "lemma-based induction, round {}: assuming the {} remaining candidate(s)",
+                rounds,
+                candidates.len()
+            ))?;
+            solver.push()?;
+            for candidate in &candidates {
+                solver.assert(smt::Unroll::new(def(candidate)?, 0))?
+            }
+            let mut dropped = Set::new();
+            for candidate in &candidates {
+                solver.intent(&format!(
+                    "lemma-based induction, round {}: is `{}` falsified in the post-state?",
+                    rounds, candidate
+                ))?;
+                solver.push()?;
+                solver.assert(format!("(not {})", smt::Unroll::new(def(candidate)?, 1)))?;
+                if solver.check_sat()? {
+                    dropped.insert(*candidate);
+                }
+                solver.pop()?;
+            }
+            solver.pop()?;
+
+            if dropped.is_empty() {
+                return Ok((candidates, rounds));
+            }
+            candidates.retain(|candidate| !dropped.contains(candidate));
+            if checkpoint {
+                self.checkpoint_lemmas(&candidates, rounds)?
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Selective SMT logging, by candidate and phase.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check, Phase};
+
+/// Filter on the SMT logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Candidates whose queries are logged, all of them if empty.
+    pub pos: Set<String>,
+    /// Phases logged, all of them if empty.
+    pub phases: Set<&'static str>,
+}
+
+impl LogFilter {
+    /// Phase names.
+    pub const PHASES: [&'static str; 4] = ["base", "step", "bmc", "other"];
+
+    /// Parses a filter description such as `po=overflow,phase=step`.
+    pub fn parse(desc: &str) -> Result<Self, String> {
+        let mut res = Self::default();
+        for entry in desc.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, val) = match entry.split_once('=') {
+                Some((key, val)) => (key.trim(), val.trim()),
+                None => return Err(format!("expected `<KEY>=<VALUE>`, found `{}`", entry)),
+            };
+            match key {
+                "po" if !val.is_empty() => {
+                    res.pos.insert(val.into());
+                }
+                "phase" => match Self::PHASES.iter().find(|phase| **phase == val) {
+                    Some(phase) => {
+                        res.phases.insert(phase);
+                    }
+                    None => {
+                        return Err(format!(
+                            "unknown phase `{}`, expected one of {}",
+                            val,
+                            Self::PHASES.join(", ")
+                        ))
+                    }
+                },
+                "po" => return Err("illegal empty candidate name in `po=`".into()),
+                _ => {
+                    return Err(format!(
+                        "unknown filter key `{}`, expected `po` or `phase`",
+                        key
+                    ))
+                }
+            }
+        }
+        if res.pos.is_empty() && res.phases.is_empty() {
+            return Err(format!("illegal empty filter `{}`", desc));
+        }
+        Ok(res)
+    }
+
+    /// True if the solvers of a phase log.
+    pub fn logs_phase(&self, phase: Phase) -> bool {
+        let name = match phase {
+            Phase::Base => "base",
+            Phase::Step => "step",
+            Phase::Bmc => "bmc",
+            Phase::Other => "other",
+        };
+        self.phases.is_empty() || self.phases.contains(name)
+    }
+
+    /// Applies the candidate filter, if any, to a solver; `candidates` are all the candidates of
+    /// the system.
+    pub fn apply<'a>(
+        &self,
+        solver: &mut smt::Solver,
+        candidates: impl Iterator<Item = &'a String>,
+    ) {
+        if self.pos.is_empty() {
+            return;
+        }
+        let others = candidates
+            .filter(|candidate| !self.pos.contains(*candidate))
+            .cloned()
+            .collect();
+        solver.set_log_filter(self.pos.clone(), others)
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Fails if the SMT log filter mentions candidates the system does not have.
+    pub fn check_log_filter(&self) -> Res<()> {
+        if let Some(filter) = self.env.smt_log_only.as_ref() {
+            for po in &filter.pos {
+                if !self.sys.po_s().contains_key(po) {
+                    bail!("`--smt_log_only` mentions unknown candidate `{}`", po)
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! File logging, for post-mortem diagnosis of long runs.
+//!
+//! `--log_file` streams JSON records, one per line, independently of the console verbosity.
+
+mikino_api::prelude!();
+
+use std::{
+    fs::File,
+    io::Write,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{json, Run};
+
+/// Log levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Errors.
+    Error,
+    /// Main events.
+    Info,
+    /// Solver interactions.
+    Debug,
+}
+impl Level {
+    /// Level names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &["error", "info", "debug"];
+
+    /// Level from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+
+    /// Name of the level.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Info => "info",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+/// Log file of a run.
+///
+/// Cloning yields a handle to the same log file.
+#[derive(Clone)]
+pub struct Logger {
+    /// Log file.
+    file: Arc<Mutex<File>>,
+    /// Most detailed level written.
+    level: Level,
+    /// Start of the run.
+    start: Instant,
+}
+impl Logger {
+    /// Creates the log file.
+    pub fn new(path: &str, level: Level) -> Res<Self> {
+        let file =
+            File::create(path).chain_err(|| format!("while creating log file `{}`", path))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            level,
+            start: Instant::now(),
+        })
+    }
+
+    /// True if records of some level are written.
+    pub fn logs(&self, level: Level) -> bool {
+        level <= self.level
+    }
+
+    /// Writes a record, if its level is written; `fields` are JSON values.
+    pub fn log(
+        &self,
+        level: Level,
+        event: &str,
+        fields: impl FnOnce() -> Vec<(&'static str, String)>,
+    ) -> Res<()> {
+        if !self.logs(level) {
+            return Ok(());
+        }
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs_f64())
+            .unwrap_or(0.0);
+        let mut record = vec![
+            ("time", format!("{:.3}", time)),
+            (
+                "elapsed",
+                format!("{:.3}", self.start.elapsed().as_secs_f64()),
+            ),
+            ("level", json::json_str(level.name())),
+            ("event", json::json_str(event)),
+        ];
+        record.extend(fields());
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| "log file is unavailable, a thread panicked while writing it")?;
+        writeln!(file, "{}", json::json_object(&record)).chain_err(|| "while writing log file")
+    }
+}
+
+impl Run {
+    /// Writes a log record, if file logging is active, see [`Logger::log`].
+    pub fn log(
+        &self,
+        level: Level,
+        event: &str,
+        fields: impl FnOnce() -> Vec<(&'static str, String)>,
+    ) -> Res<()> {
+        match self.logger.as_ref() {
+            Some(logger) => logger.log(level, event, fields),
+            None => Ok(()),
+        }
+    }
+}
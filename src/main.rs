@@ -2,9 +2,17 @@
 
 mikino_api::prelude!();
 
-use std::{collections::BTreeSet as Set, io::Write, ops::Deref, path::PathBuf};
+use std::{
+    collections::BTreeSet as Set,
+    io::Write,
+    ops::Deref,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use check::{BaseRes, CheckRes, StepRes};
+use expr::Expr;
 use trans::Sys;
 
 use ansi_term::{Colour, Style};
@@ -15,15 +23,171 @@ macro_rules! prelude {
     { pub } => { pub use crate::prelude::*; };
 }
 
+pub mod almost_safe;
+pub mod assumptions;
+pub mod backward;
+pub mod baseline;
+pub mod bmc_depths;
+pub mod bmc_induction;
+pub mod budget;
+pub mod cancel;
+pub mod certificate;
+pub mod cex_store;
+pub mod checkpoint;
+pub mod compare;
+pub mod compose;
+pub mod conf;
+pub mod count;
+pub mod coverage;
+pub mod dashboard;
+pub mod dead_branches;
+pub mod deadlock;
+pub mod determinism;
+pub mod deterministic;
+pub mod dist;
+pub mod dot;
+pub mod events;
+pub mod examples;
+pub mod explain;
+pub mod explicit;
+pub mod facts;
+pub mod fuzz;
+pub mod github;
+pub mod graph;
+pub mod guided;
+pub mod highlight;
+pub mod hooks;
+pub mod ignore;
+pub mod json;
+pub mod lasso;
+pub mod layout;
+pub mod lemmas;
+pub mod log_filter;
+pub mod logging;
+pub mod metadata;
+pub mod metrics;
 pub mod mode;
+pub mod mutate;
+pub mod mute;
+pub mod notify;
+pub mod pager;
+pub mod portfolio;
+pub mod projection;
+pub mod qe;
+pub mod query;
+pub mod reach;
+pub mod render;
+pub mod report;
+pub mod revisions;
+pub mod sanitize;
+pub mod sanity;
+pub mod scenario;
+pub mod score;
+pub mod shrink;
+pub mod sim;
+pub mod smt;
+pub mod stats;
+pub mod status;
+pub mod stutter;
+pub mod suggest;
+pub mod summary;
+pub mod testsgen;
+pub mod text;
+pub mod timeout;
+pub mod trace_format;
+pub mod vacuity;
+pub mod value_format;
+pub mod views;
+pub mod whats_new;
 
-use mode::Mode;
+use mode::{Engine, Mode};
 
 /// Entry point.
 pub fn main() {
     Run::new().launch()
 }
 
+/// Exits on a command-line error, with code `0` for `--help` and `--version`.
+///
+/// Clap exits with code `2` on errors, which means unknown candidates for mikino, see
+/// [`budget::exit`].
+fn clap_exit(e: clap::Error) -> ! {
+    let code = if e.use_stderr() {
+        budget::exit::ERROR
+    } else {
+        budget::exit::SAFE
+    };
+    let _ = e.print();
+    std::process::exit(code)
+}
+
+/// Path of a per-system output file, `out.smt2` becomes `out.<name>.smt2` for system `<name>`.
+pub fn system_path(path: &str, name: Option<&str>) -> String {
+    let name = match name {
+        Some(name) => name,
+        None => return path.into(),
+    };
+    let path = std::path::Path::new(path);
+    let mut file = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    file.push('.');
+    file.push_str(name);
+    if let Some(ext) = path.extension() {
+        file.push('.');
+        file.push_str(&ext.to_string_lossy())
+    }
+    path.with_file_name(file).display().to_string()
+}
+
+/// Analysis phases, each can use its own solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Base checks.
+    Base,
+    /// Step checks.
+    Step,
+    /// BMC.
+    Bmc,
+    /// Everything else.
+    Other,
+}
+impl Phase {
+    /// Name of the phase.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::Step => "step",
+            Self::Bmc => "BMC",
+            Self::Other => "analysis",
+        }
+    }
+}
+
+/// Generates a unique run identifier.
+///
+/// Made of the current time in milliseconds since the Unix epoch and the process identifier, both
+/// in hexadecimal, for instance `18b2f7a3c41-3e8f`. Appears in the console output, SMT log directory
+/// names and SMT transcripts so that the artifacts of a run can be correlated.
+pub fn run_id() -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("{:x}-{:x}", millis, std::process::id())
+}
+
+/// Reads an input file.
+pub fn read_input(input: &str) -> Res<String> {
+    use std::{fs::OpenOptions, io::Read};
+
+    let mut file = OpenOptions::new().read(true).open(input)?;
+    let mut txt = String::new();
+    file.read_to_string(&mut txt)?;
+    Ok(txt)
+}
+
 /// Post-run structure.
 pub struct PostRun<'sys> {
     pub env: Run,
@@ -33,12 +197,112 @@ pub struct PostRun<'sys> {
 
 /// Run environment.
 pub struct Run {
+    /// Unique identifier of this run, see [`run_id`].
+    pub run_id: String,
     /// Output styles (for coloring).
     pub styles: Styles,
+    /// Output styles for stderr.
+    pub err_styles: Styles,
+    /// True if progress goes to stderr, see [`Self::progress`].
+    pub progress_to_stderr: bool,
     /// Verbosity.
     pub verb: usize,
     /// Z3 command.
     pub z3_cmd: String,
+    /// Solver command for the base phase, `z3_cmd` if none.
+    pub base_solver: Option<String>,
+    /// Solver command for the step phase, `z3_cmd` if none.
+    pub step_solver: Option<String>,
+    /// Solver command for the BMC phase, `z3_cmd` if none.
+    pub bmc_solver: Option<String>,
+    /// File to save the shrunk failing query to when a solver fails, if any.
+    pub shrink: Option<String>,
+    /// DOT file to write counterexamples to, projected onto `mode_var`.
+    pub cex_dot: Option<String>,
+    /// Number of steps presented at the beginning and at the end of long traces, if any.
+    pub cex_max_steps: Option<usize>,
+    /// Counterexample store directory, if active, see [`cex_store`].
+    pub cex_store: Option<String>,
+    /// Mode variable counterexamples are projected onto in `cex_dot`.
+    pub mode_var: Option<String>,
+    /// True if candidates annotated with `#[ignore(...)]` are checked anyway.
+    pub include_ignored: bool,
+    /// Command run before a system is checked, see [`hooks`].
+    pub hook_pre_check: Option<String>,
+    /// Command run when a phase settles a candidate, see [`hooks`].
+    pub hook_post_po: Option<String>,
+    /// Mapping file traces are sanitized with before they are output, see [`sanitize`].
+    pub sanitize_cex: Option<String>,
+    /// Views expanded in traces, all of them if none, see [`views`].
+    pub views: Option<Vec<String>>,
+    /// Base of the integer values in traces, see [`value_format`].
+    pub value_format: value_format::Base,
+    /// True if the values that changed since the previous step are highlighted in traces, see
+    /// [`highlight`].
+    pub highlight_changes: bool,
+    /// Expressions traces are projected on, if any, see [`projection`].
+    pub cex_project: Vec<String>,
+    /// Layout of the traces, see [`trace_format`].
+    pub trace_format: trace_format::Format,
+    /// True if mikino always exits with code `0`, see [`budget::exit`].
+    pub exit_zero: bool,
+    /// True if results are output as JSON instead of human-readable reports, see [`json`].
+    ///
+    /// Also true with `--events`, which uses the same machine output minus the JSON document.
+    pub json: bool,
+    /// True if the verdict is the only output on stdout, see [`mute`] and [`Self::check_exit`].
+    pub verdict_only: bool,
+    /// Event stream, if active, see [`events`].
+    pub events: Option<events::Events>,
+    /// Report files to write, see [`report`].
+    pub reports: Vec<report::Report>,
+    /// External report renderers, see [`render`].
+    pub renderers: Vec<render::External>,
+    /// File the statuses of the candidates are saved to as a baseline, if any, see [`baseline`].
+    pub save_baseline: Option<String>,
+    /// Baseline the statuses of the candidates are compared to, if any, see [`baseline`].
+    pub baseline: Option<String>,
+    /// File the plain text verdict is written to, if any, see [`report`].
+    pub output: Option<String>,
+    /// True if GitHub Actions annotations are output, see [`github`].
+    pub github_annotations: bool,
+    /// True if timing statistics are output, see [`stats`].
+    pub stats: bool,
+    /// Number of slowest systems listed by the dashboard, if any, see [`dashboard`].
+    pub top: Option<usize>,
+    /// True if live status lines are disabled, see [`status`].
+    pub no_progress: bool,
+    /// True if the output goes through a pager, see [`pager`].
+    pub pager: bool,
+    /// True if stdout is a terminal, before the pager redirects it.
+    pub stdout_tty: bool,
+    /// URL the verdict is POSTed to at the end of the run, if any, see [`notify`].
+    pub notify_url: Option<String>,
+    /// Filter on the SMT logs, if any, see [`log_filter`].
+    pub smt_log_only: Option<log_filter::LogFilter>,
+    /// Log file, if any, see [`logging`].
+    pub logger: Option<logging::Logger>,
+    /// True if SMT logs go to a sub-directory named after the run, see [`Self::smt_log_dir`].
+    pub smt_log_per_run: bool,
+    /// Optional human-readable SMT transcript file.
+    pub smt_transcript: Option<String>,
+    /// Width of the terminal stdout is, if any, see [`layout`].
+    pub width: Option<usize>,
+    /// True if the SMT queries do not vary from one run to the next, see [`deterministic`].
+    pub deterministic_queries: bool,
+    /// Policy for the values missing from the solver's models.
+    pub model_completion: smt::Completion,
+    /// Time limit of each SMT query in milliseconds, if any.
+    ///
+    /// Candidate-wise analyses (`--engine portfolio`, `backward`, `--simple_path`) treat a query
+    /// exceeding it as `unknown` for the candidate, other analyses fail. The base, step and BMC
+    /// checks `mikino_api` handles get the limit through Z3's `-t` option, a query exceeding it
+    /// stops the check and leaves the candidates it did not settle `unknown`.
+    pub query_timeout: Option<u64>,
+    /// Cancellation token of the run, see [`cancel`].
+    pub cancel: cancel::Token,
+    /// Wall-clock limit of the run, if any, see [`timeout`].
+    pub timeout: Option<Duration>,
     /// Run mode.
     pub mode: Mode,
 }
@@ -52,7 +316,7 @@ impl Run {
     /// Constructor, handles CLAP.
     pub fn new() -> Self {
         use clap::*;
-        let app = clap::Command::new("mikino")
+        let mut app = clap::Command::new("mikino")
             .version(crate_version!())
             .author(crate_authors!())
             .about(
@@ -72,112 +336,1008 @@ impl Run {
                     .takes_value(true)
                     .default_value("z3")
                     .help("specifies the command to run Z3"),
+                Arg::new("BASE_SOLVER")
+                    .long("base_solver")
+                    .takes_value(true)
+                    .value_name("CMD")
+                    .help(
+                        "specifies the solver command for base checks, `--z3_cmd` by default; \
+                        Z3, CVC5 and Yices are recognized from the name of the binary",
+                    ),
+                Arg::new("STEP_SOLVER")
+                    .long("step_solver")
+                    .takes_value(true)
+                    .value_name("CMD")
+                    .help("specifies the solver command for step checks, `--z3_cmd` by default"),
+                Arg::new("BMC_SOLVER")
+                    .long("bmc_solver")
+                    .takes_value(true)
+                    .value_name("CMD")
+                    .help("specifies the solver command for BMC, `--z3_cmd` by default"),
+                Arg::new("SHRINK")
+                    .long("shrink")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "when a solver crashes or answers a query with something malformed, \
+                        minimizes the query and saves it to <FILE>, for solver bug reports",
+                    ),
+                Arg::new("CEX_DOT")
+                    .long("cex_dot")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .requires("MODE_VAR")
+                    .help(
+                        "writes counterexamples to <FILE> in the DOT format, as state machines \
+                        projected onto the `--mode_var` variable",
+                    ),
+                Arg::new("CEX_MAX_STEPS")
+                    .long("cex_max_steps")
+                    .takes_value(true)
+                    .value_name("INT")
+                    .validator(mode::cla::validate_int)
+                    .help(
+                        "only shows the first and last <INT> steps of longer counterexamples, \
+                        full traces are still part of the JSON, report and DOT outputs",
+                    ),
+                Arg::new("CEX_STORE")
+                    .long("cex_store")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .help(
+                        "saves counterexamples to <DIR>, where `show-cex` and `scenario` read \
+                        them from; `none` disables the store",
+                    ),
+                Arg::new("MODE_VAR")
+                    .long("mode_var")
+                    .takes_value(true)
+                    .value_name("VAR")
+                    .requires("CEX_DOT")
+                    .help("mode variable counterexamples are projected onto in `--cex_dot`"),
+                Arg::new("INCLUDE_IGNORED")
+                    .long("include_ignored")
+                    .help("checks the candidates annotated with `#[ignore(reason = \"...\")]`"),
+                Arg::new("NO_PROGRESS")
+                    .long("no_progress")
+                    .help("Disables the live status line of BMC, only rendered in terminals"),
+                Arg::new("NO_PAGER")
+                    .long("no_pager")
+                    .help("Never pipes the output of the check and bmc modes through `$PAGER`"),
                 Arg::new("QUIET")
                     .short('q')
                     .help("Quiet output, only shows the final result (/!\\ hides counterexamples)"),
                 mode::cla::smt_log_arg(),
+                mode::cla::smt_log_per_run_arg(),
+                Arg::new("SMT_LOG_ONLY")
+                    .long("smt_log_only")
+                    .takes_value(true)
+                    .value_name("FILTERS")
+                    .validator(|desc| log_filter::LogFilter::parse(desc).map(|_| ()))
+                    .help(
+                        "Restricts SMT logging to some candidates and phases, \
+                        for instance `po=overflow,phase=step`; phases are `base`, `step`, `bmc` \
+                        and `other`",
+                    ),
+                Arg::new("PROFILE")
+                    .long("profile")
+                    .takes_value(true)
+                    .value_name("NAME")
+                    .help(
+                        "Applies the options of a profile from the `mikino.toml` configuration \
+                        file, options on the command line take precedence",
+                    ),
+                Arg::new("CONFIG")
+                    .long("config")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Configuration file holding option defaults and profiles, \
+                        `mikino.toml` in the current directory if it exists",
+                    ),
+                Arg::new("SMT_TRANSCRIPT")
+                    .long("smt_transcript")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Writes a human-readable transcript of the solver interactions, \
+                        annotated with the intent of each query and its result",
+                    ),
+                Arg::new("DETERMINISTIC_QUERIES")
+                    .long("deterministic_queries")
+                    .help(
+                        "Makes SMT logs and transcripts byte-identical across runs with the same \
+                        options and solver: no run identifier in transcripts, seed 0 for random \
+                        sampling unless `--seed` is given",
+                    ),
+                Arg::new("LOG_FILE")
+                    .long("log_file")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Streams timestamped log records to <FILE>, one JSON object per line, \
+                        independently of the console verbosity",
+                    ),
+                Arg::new("LOG_LEVEL")
+                    .long("log_level")
+                    .takes_value(true)
+                    .possible_values(logging::Level::NAMES)
+                    .default_value("info")
+                    .value_name("LEVEL")
+                    .help(
+                        "Records written to the `--log_file`: `error`s only, main events \
+                        (`info`), or also solver interactions (`debug`)",
+                    ),
+                Arg::new("SANITIZE_CEX")
+                    .long("sanitize_cex")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Renames identifiers and rescales values in the counterexamples output \
+                        (reports, DOT files, test vectors) according to the `[rename]` and \
+                        `[scale]` tables of the TOML mapping file <FILE>",
+                    ),
+                Arg::new("VIEWS")
+                    .long("views")
+                    .takes_value(true)
+                    .value_name("NAMES")
+                    .help(
+                        "Comma-separated views expanded in traces, the other ones are collapsed \
+                        to the variables that changed; `none` collapses all views",
+                    ),
+                Arg::new("VALUE_FORMAT")
+                    .long("value_format")
+                    .takes_value(true)
+                    .possible_values(value_format::Base::NAMES)
+                    .default_value("dec")
+                    .value_name("BASE")
+                    .help(
+                        "Base of the integer values in traces, decimal, hexadecimal or binary; \
+                        `#[format(<BASE>)]` annotations override it for some variables",
+                    ),
+                Arg::new("HIGHLIGHT_CHANGES")
+                    .long("highlight_changes")
+                    .takes_value(true)
+                    .possible_values(highlight::When::NAMES)
+                    .default_value("auto")
+                    .value_name("WHEN")
+                    .help(
+                        "Accents the values of traces that changed since the previous step and \
+                        dims the other ones; `auto` only does so at high verbosity (`-v`)",
+                    ),
+                Arg::new("CEX_PROJECT")
+                    .long("cex_project")
+                    .takes_value(true)
+                    .value_name("EXPRS")
+                    .conflicts_with("SANITIZE_CEX")
+                    .help(
+                        "Presents traces solely as the values of some comma-separated state \
+                        expressions at each step, hiding the variables",
+                    ),
+                Arg::new("TRACE_FORMAT")
+                    .long("trace_format")
+                    .takes_value(true)
+                    .possible_values(trace_format::Format::NAMES)
+                    .default_value("blocks")
+                    .value_name("FORMAT")
+                    .help(
+                        "Layout of the traces, one block per step or a table with one column per \
+                        step",
+                    ),
+                Arg::new("HOOK_PRE_CHECK")
+                    .long("hook_pre_check")
+                    .takes_value(true)
+                    .value_name("CMD")
+                    .help(
+                        "Runs <CMD> before checking a system, with a JSON payload listing its \
+                        candidates on its standard input",
+                    ),
+                Arg::new("HOOK_POST_PO")
+                    .long("hook_post_po")
+                    .takes_value(true)
+                    .value_name("CMD")
+                    .help(
+                        "Runs <CMD> whenever induction or BMC settles a candidate, with a JSON \
+                        payload describing the outcome on its standard input",
+                    ),
+                Arg::new("EXIT_ZERO").long("exit_zero").help(
+                    "Always exits with code 0; otherwise the check, BMC and compose modes exit \
+                    with 1 if some candidates are falsified, 2 if some are unknown, and errors \
+                    exit with 3",
+                ),
+                Arg::new("JSON").long("json").help(
+                    "Outputs the results of the check and BMC modes as a single JSON \
+                        document on stdout, progress goes to stderr",
+                ),
+                Arg::new("VERDICT_ONLY")
+                    .long("verdict_only")
+                    .conflicts_with_all(&["JSON", "EVENTS"])
+                    .help(
+                        "Only outputs the verdict of the check and BMC modes on stdout, \
+                        `SAFE`, `UNSAFE` or `UNKNOWN`, progress and errors go to stderr",
+                    ),
+                Arg::new("EVENTS")
+                    .long("events")
+                    .conflicts_with_all(&["JSON", "VERDICT_ONLY"])
+                    .help(
+                        "Streams the progress of the check and BMC modes on stdout as \
+                        newline-delimited JSON events, progress goes to stderr",
+                    ),
+                Arg::new("REPORT")
+                    .long("report")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .value_name("FORMAT=FILE")
+                    .validator(|desc| report::Report::parse(desc).map(|_| ()))
+                    .help(
+                        "Writes the results of the check and BMC modes to <FILE> in some format, \
+                        `junit` (JUnit XML), `sarif` (SARIF 2.1.0), `html` (self-contained \
+                        HTML), `md` (Markdown), `text` (plain text) or the name of a \
+                        `--renderer`; can be repeated",
+                    ),
+                Arg::new("SAVE_BASELINE")
+                    .long("save_baseline")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Saves the final status of each candidate of the check and BMC modes to \
+                        <FILE>, for later use with `--baseline`",
+                    ),
+                Arg::new("BASELINE")
+                    .long("baseline")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Compares the final status of each candidate of the check and BMC modes \
+                        to the baseline <FILE>, the exit code only reflects regressions: 1 if \
+                        some candidates are newly falsified or no longer proved, 0 otherwise",
+                    ),
+                Arg::new("RENDERER")
+                    .long("renderer")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .value_name("NAME=CMD")
+                    .validator(|desc| render::External::parse(desc).map(|_| ()))
+                    .help(
+                        "Registers an external report renderer, `--report <NAME>=<FILE>` feeds the \
+                        results as JSON to <CMD> and writes its output to <FILE>; can be repeated",
+                    ),
+                Arg::new("OUTPUT")
+                    .long("output")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help(
+                        "Writes the final verdict of the check and BMC modes to <FILE> as plain \
+                        text, progress goes to stderr",
+                    ),
+                Arg::new("GITHUB_ANNOTATIONS")
+                    .long("github_annotations")
+                    .help(
+                        "Prints GitHub Actions workflow commands annotating falsified candidates \
+                        and errors, shown inline in pull request diffs",
+                    ),
+                Arg::new("STATS").long("stats").help(
+                    "Prints the time spent in each phase, at each BMC depth, and until each \
+                    candidate is settled after the check and bmc modes, with counts of the \
+                    solver interactions; also part of the JSON output",
+                ),
+                Arg::new("TOP")
+                    .long("top")
+                    .takes_value(true)
+                    .value_name("INT")
+                    .validator(mode::cla::validate_int)
+                    .help(
+                        "Lists the <INT> slowest systems in the dashboard that ends runs over \
+                        several named systems",
+                    ),
+                Arg::new("TIMEOUT")
+                    .long("timeout")
+                    .takes_value(true)
+                    .value_name("DURATION")
+                    .validator(|s| timeout::parse_duration(s).map(|_| ()))
+                    .help(
+                        "Bounds the total time of the check and bmc modes, for instance `30s` or \
+                        `5m`; candidates not settled by then are reported as `timeout`",
+                    ),
+                Arg::new("QUERY_TIMEOUT")
+                    .long("query_timeout")
+                    .takes_value(true)
+                    .value_name("MS")
+                    .validator(|s| {
+                        mode::cla::validate_int(s).and_then(|()| match s {
+                            "0" => Err("expected a positive number of milliseconds".into()),
+                            _ => Ok(()),
+                        })
+                    })
+                    .help(
+                        "Time limit of each SMT query in milliseconds, forwarded to the solver; \
+                        candidate-wise analyses report the candidate as unknown when a query \
+                        exceeds it, base, step and BMC stop and leave the candidates unknown, \
+                        other analyses fail",
+                    ),
+                Arg::new("NOTIFY_URL")
+                    .long("notify_url")
+                    .takes_value(true)
+                    .value_name("URL")
+                    .help(
+                        "POSTs a JSON summary of the final verdict to <URL> at the end of the \
+                        check and bmc modes, with `curl`: run id, input, verdict token, number \
+                        and names of the candidates of each status, and run metadata; a failed \
+                        notification is a warning",
+                    ),
+                Arg::new("MODEL_COMPLETION")
+                    .long("model_completion")
+                    .takes_value(true)
+                    .possible_values(smt::Completion::NAMES)
+                    .default_value("solver")
+                    .value_name("POLICY")
+                    .help(
+                        "Handling of the variables the solver omits in counterexamples: \
+                        fill in a `default` value, raise an `error`, or ask the `solver` \
+                        to complete them; completed values are marked in traces",
+                    ),
             ])
             .subcommands(mode::Mode::subcommands())
             .subcommand_required(true)
             .color(clap::ColorChoice::Auto);
 
-        let matches = app.get_matches();
+        let mut args: Vec<String> = std::env::args().collect();
+        if let Err(e) = conf::apply(&app, &mut args) {
+            clap_exit(app.error(ErrorKind::InvalidValue, e))
+        }
+
+        let matches = app
+            .try_get_matches_from(args)
+            .unwrap_or_else(|e| clap_exit(e));
         let color = matches.occurrences_of("NO_COLOR") == 0;
         let verb = ((matches.occurrences_of("VERB") + 1) % 4) as usize;
         let quiet = matches.occurrences_of("QUIET") > 0;
+        let no_progress = matches.is_present("NO_PROGRESS");
         let z3_cmd = matches
             .value_of("Z3_CMD")
             .expect("argument with default value")
             .into();
+        let base_solver = matches.value_of("BASE_SOLVER").map(String::from);
+        let step_solver = matches.value_of("STEP_SOLVER").map(String::from);
+        let bmc_solver = matches.value_of("BMC_SOLVER").map(String::from);
+        let shrink = matches.value_of("SHRINK").map(String::from);
+        let cex_dot = matches.value_of("CEX_DOT").map(String::from);
+        let mode_var = matches.value_of("MODE_VAR").map(String::from);
+        let cex_store = matches
+            .value_of("CEX_STORE")
+            .filter(|dir| *dir != "none")
+            .map(String::from);
+        let cex_max_steps = matches.value_of("CEX_MAX_STEPS").map(|n| {
+            n.parse::<usize>()
+                .expect("[clap] argument validation did not catch integer parsing error")
+        });
+        let include_ignored = matches.is_present("INCLUDE_IGNORED");
         let smt_log = mode::cla::get_smt_log(&matches);
-        let verb = if quiet {
+        let smt_log_only = matches.value_of("SMT_LOG_ONLY").map(|desc| {
+            log_filter::LogFilter::parse(desc)
+                .expect("[clap] argument validation did not catch log filter error")
+        });
+        let log_level = matches
+            .value_of("LOG_LEVEL")
+            .and_then(logging::Level::from_name)
+            .expect("argument with default value");
+        let logger = matches.value_of("LOG_FILE").map(|path| {
+            logging::Logger::new(path, log_level).unwrap_or_else(|e| {
+                let msg = e.pretty(&Styles::new_no_color());
+                clap_exit(clap::Error::raw(ErrorKind::Io, format!("{}\n", msg)))
+            })
+        });
+        let smt_log_per_run = mode::cla::get_smt_log_per_run(&matches);
+        let smt_transcript = matches.value_of("SMT_TRANSCRIPT").map(String::from);
+        let deterministic_queries = matches.is_present("DETERMINISTIC_QUERIES");
+        let sanitize_cex = matches.value_of("SANITIZE_CEX").map(String::from);
+        let views = matches.value_of("VIEWS").map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .collect()
+        });
+        let value_format = matches
+            .value_of("VALUE_FORMAT")
+            .map(|base| value_format::Base::parse(base).expect("argument with possible values"))
+            .expect("argument with default value");
+        let cex_project = matches
+            .value_of("CEX_PROJECT")
+            .map(projection::split)
+            .unwrap_or_default();
+        let trace_format = matches
+            .value_of("TRACE_FORMAT")
+            .and_then(trace_format::Format::from_name)
+            .expect("argument with default value");
+        let hook_pre_check = matches.value_of("HOOK_PRE_CHECK").map(String::from);
+        let hook_post_po = matches.value_of("HOOK_POST_PO").map(String::from);
+        let exit_zero = matches.is_present("EXIT_ZERO");
+        let verdict_only = matches.is_present("VERDICT_ONLY");
+        let events = if matches.is_present("EVENTS") {
+            Some(events::Events::new())
+        } else {
+            None
+        };
+        let json = matches.is_present("JSON") || events.is_some();
+        let mut reports: Vec<report::Report> = matches
+            .values_of("REPORT")
+            .map(|descs| {
+                descs
+                    .map(|desc| {
+                        report::Report::parse(desc)
+                            .expect("[clap] argument validation did not catch report error")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let renderers = matches
+            .values_of("RENDERER")
+            .map(|descs| {
+                descs
+                    .map(|desc| {
+                        render::External::parse(desc)
+                            .expect("[clap] argument validation did not catch renderer error")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let save_baseline = matches.value_of("SAVE_BASELINE").map(String::from);
+        let baseline = matches.value_of("BASELINE").map(String::from);
+        let output = matches.value_of("OUTPUT").map(String::from);
+        if let Some(path) = output.as_ref() {
+            reports.push(report::Report {
+                format: report::Format::Text,
+                path: path.clone(),
+            })
+        }
+        let github_annotations = matches.is_present("GITHUB_ANNOTATIONS");
+        let stats = matches.is_present("STATS");
+        let top = matches.value_of("TOP").map(|n| {
+            n.parse::<usize>()
+                .expect("[clap] argument validation did not catch integer parsing error")
+        });
+        let notify_url = matches.value_of("NOTIFY_URL").map(String::from);
+        let query_timeout = matches.value_of("QUERY_TIMEOUT").map(|n| {
+            n.parse::<u64>()
+                .expect("[clap] argument validation did not catch integer parsing error")
+        });
+        let timeout = matches.value_of("TIMEOUT").map(|s| {
+            timeout::parse_duration(s)
+                .expect("[clap] argument validation did not catch duration parsing error")
+        });
+        let model_completion = matches
+            .value_of("MODEL_COMPLETION")
+            .and_then(smt::Completion::from_name)
+            .expect("argument with default value");
+        let verb = if quiet || json {
             0
         } else if verb > 4 {
             4
         } else {
             verb
         };
+        let highlight_changes = matches
+            .value_of("HIGHLIGHT_CHANGES")
+            .and_then(highlight::When::from_name)
+            .expect("argument with default value")
+            .is_active(verb);
 
         let mode =
             mode::Mode::from_clap(smt_log, &matches).expect("[clap] could not recognize mode");
 
+        // Progress goes to stderr when stdout is redirected but stderr is not, when stdout is
+        // reserved for the JSON output or the verdict, or when the verdict goes to a file.
+        let progress_to_stderr = json
+            || verdict_only
+            || output.is_some()
+            || !atty::is(atty::Stream::Stdout) && atty::is(atty::Stream::Stderr);
+        let width = if json { None } else { layout::terminal_width() };
+        let stdout_tty = atty::is(atty::Stream::Stdout);
+        let pager = !matches.is_present("NO_PAGER")
+            && !json
+            && !verdict_only
+            && matches!(mode, Mode::Check { .. })
+            && stdout_tty;
+
         Self {
+            run_id: run_id(),
             styles: Styles::new(color),
+            err_styles: Styles::new_for(color, atty::Stream::Stderr),
+            progress_to_stderr,
             verb,
             z3_cmd,
+            base_solver,
+            step_solver,
+            bmc_solver,
+            shrink,
+            cex_dot,
+            cex_max_steps,
+            cex_store,
+            mode_var,
+            include_ignored,
+            sanitize_cex,
+            views,
+            value_format,
+            highlight_changes,
+            cex_project,
+            trace_format,
+            exit_zero,
+            json,
+            verdict_only,
+            events,
+            reports,
+            renderers,
+            save_baseline,
+            baseline,
+            output,
+            github_annotations,
+            stats,
+            top,
+            no_progress,
+            pager,
+            stdout_tty,
+            notify_url,
+            hook_pre_check,
+            hook_post_po,
+            smt_log_only,
+            logger,
+            smt_log_per_run,
+            smt_transcript,
+            width,
+            deterministic_queries,
+            model_completion,
+            query_timeout,
+            cancel: cancel::Token::new(),
+            timeout,
             mode,
         }
     }
 
+    /// Solver command for a phase.
+    pub fn solver_cmd(&self, phase: Phase) -> &str {
+        let cmd = match phase {
+            Phase::Base => self.base_solver.as_ref(),
+            Phase::Step => self.step_solver.as_ref(),
+            Phase::Bmc => self.bmc_solver.as_ref(),
+            Phase::Other => None,
+        };
+        cmd.unwrap_or(&self.z3_cmd)
+    }
+
+    /// Solver configuration for a phase handled by `mikino_api`, which only supports Z3.
+    ///
+    /// Passes the `--query_timeout`, if any, to Z3.
+    pub fn api_solver_conf(&self, phase: Phase) -> Res<SmtConf> {
+        let cmd = self.solver_cmd(phase);
+        let backend = smt::Backend::of_cmd(cmd);
+        if backend != smt::Backend::Z3 {
+            bail!(
+                "the {} phase only supports Z3, cannot use {} (`{}`)",
+                phase.name(),
+                backend.name(),
+                cmd
+            )
+        }
+        let mut conf = z3_cmd_to_conf(cmd)?;
+        if let Some(ms) = self.query_timeout {
+            conf.option(format!("-t:{}", ms));
+        }
+        Ok(conf)
+    }
+
+    /// Prints a progress line.
+    ///
+    /// Progress goes to stderr when stdout is redirected but stderr is a terminal, so that reports
+    /// piped to files stay clean while progress remains visible. `msg` receives the styles for the
+    /// stream the line goes to.
+    pub fn progress(&self, msg: impl FnOnce(&Styles) -> String) {
+        if self.progress_to_stderr {
+            eprintln!("{}", msg(&self.err_styles))
+        } else {
+            println!("{}", msg(&self.styles))
+        }
+    }
+
     /// Launches whatever the user told us to do.
     pub fn launch(&self) {
+        if self.pager {
+            pager::start()
+        }
+        if self.verdict_only {
+            mute::start()
+        }
         if let Err(e) = self.run() {
-            println!("|===| {}", self.red.paint("Error"));
-            for (e_idx, e) in e.into_iter().enumerate() {
-                for (l_idx, line) in e.pretty(&self.styles).lines().enumerate() {
-                    let pref = if e_idx == 0 {
-                        "| "
-                    } else if l_idx == 0 {
-                        "| - "
-                    } else {
-                        "|   "
-                    };
-                    println!("{}{}", pref, line);
-                }
+            self.fail(e)
+        }
+        pager::wait()
+    }
+
+    /// Reports an error, and exits with the error code unless `--exit_zero` is active.
+    fn fail(&self, e: Error) {
+        if self.events.is_some() {
+            let styles = Styles::new_no_color();
+            let errors: Vec<String> = e
+                .into_iter()
+                .map(|e| json::json_str(&e.pretty(&styles)))
+                .collect();
+            let _ = self.log(logging::Level::Error, "error", || {
+                vec![("error", json::json_array(&errors))]
+            });
+            self.event("error", || vec![("error", json::json_array(&errors))]);
+            return self.error_exit();
+        }
+        if self.verdict_only {
+            for e in e.into_iter() {
+                eprintln!("{}", e.pretty(&self.err_styles))
             }
-            println!("|===|");
+            return self.error_exit();
+        }
+        if self.json {
+            let styles = Styles::new_no_color();
+            let errors: Vec<String> = e
+                .into_iter()
+                .map(|e| json::json_str(&e.pretty(&styles)))
+                .collect();
+            let _ = self.log(logging::Level::Error, "error", || {
+                vec![("error", json::json_array(&errors))]
+            });
+            println!(
+                "{}",
+                json::json_object(&[
+                    ("run_id", json::json_str(&self.run_id)),
+                    ("error", json::json_array(&errors)),
+                ])
+            );
+            return self.error_exit();
+        }
+        println!(
+            "|===| {} (run {})",
+            self.red.paint("Error"),
+            self.gray.paint(&self.run_id)
+        );
+        let mut plain = Vec::new();
+        for (e_idx, e) in e.into_iter().enumerate() {
+            if self.github_annotations || self.logger.is_some() {
+                plain.push(e.pretty(&Styles::new_no_color()))
+            }
+            for (l_idx, line) in e.pretty(&self.styles).lines().enumerate() {
+                let pref = if e_idx == 0 {
+                    "| "
+                } else if l_idx == 0 {
+                    "| - "
+                } else {
+                    "|   "
+                };
+                println!("{}{}", pref, line);
+            }
+        }
+        println!("|===|");
+        let _ = self.log(logging::Level::Error, "error", || {
+            let errors: Vec<String> = plain.iter().map(|e| json::json_str(e)).collect();
+            vec![("error", json::json_array(&errors))]
+        });
+        self.annotate_error(&plain);
+        self.error_exit()
+    }
+
+    /// Ends the run once its results are reported, or after an error while reporting them.
+    pub fn end(&self, res: Res<()>) -> ! {
+        if let Err(e) = res {
+            self.fail(e)
+        }
+        pager::wait();
+        std::process::exit(budget::exit::SAFE)
+    }
+
+    /// Exits with the error code, see [`budget::exit`], unless `--exit_zero` is active.
+    fn error_exit(&self) {
+        if !self.exit_zero {
+            pager::wait();
+            std::process::exit(budget::exit::ERROR)
+        }
+    }
+
+    /// Prints the run identifier.
+    pub fn announce(&self) {
+        if self.verb > 0 {
+            self.progress(|s| format!("run {}", s.gray.paint(&self.run_id)))
         }
     }
 
+    /// Creates the SMT log directory of this run, if any.
+    ///
+    /// With `--smt_log_per_run`, logs go to a sub-directory of `smt_log` named after the run
+    /// identifier, so that logs of different runs do not overwrite each other.
+    pub fn smt_log_dir(&self, smt_log: &Option<String>) -> Res<Option<String>> {
+        let smt_log = match smt_log {
+            Some(smt_log) => smt_log,
+            None => return Ok(None),
+        };
+        let mut dir = PathBuf::from(smt_log);
+        if self.smt_log_per_run {
+            dir.push(format!("run-{}", self.run_id))
+        }
+        let dir = dir.display().to_string();
+        if !std::path::Path::new(&dir).exists() {
+            std::fs::create_dir_all(&dir)
+                .chain_err(|| format!("while recursively creating SMT log directory `{}`", dir))?
+        }
+        Ok(Some(dir))
+    }
+
     /// Runs the mode.
     pub fn run(&self) -> Res<()> {
+        self.log(logging::Level::Info, "run", || {
+            let args: Vec<String> = std::env::args().map(|arg| json::json_str(&arg)).collect();
+            vec![
+                ("run_id", json::json_str(&self.run_id)),
+                ("args", json::json_array(&args)),
+            ]
+        })?;
+        if self.json && !matches!(self.mode, Mode::Check { .. }) {
+            bail!(
+                "`{}` is only supported by the check and bmc modes",
+                self.machine_flag()
+            )
+        }
+        if self.verdict_only && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--verdict_only` is only supported by the check and bmc modes")
+        }
+        if self.output.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--output` is only supported by the check and bmc modes")
+        }
+        if !self.reports.is_empty() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--report` is only supported by the check and bmc modes")
+        }
+        // Fail on unknown report formats before checking anything.
+        for report in &self.reports {
+            self.renderer(&report.format)?;
+        }
+        if self.save_baseline.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--save_baseline` is only supported by the check and bmc modes")
+        }
+        if self.baseline.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--baseline` is only supported by the check and bmc modes")
+        }
+        if self.github_annotations && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--github_annotations` is only supported by the check and bmc modes")
+        }
+        if self.github_annotations && self.json {
+            bail!(
+                "`--github_annotations` is not supported with `{}`",
+                self.machine_flag()
+            )
+        }
+        if self.stats && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--stats` is only supported by the check and bmc modes")
+        }
+        if self.deterministic_queries {
+            self.deterministic_supported()?
+        }
+        if self.top.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--top` is only supported by the check and bmc modes")
+        }
+        if self.notify_url.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--notify_url` is only supported by the check and bmc modes")
+        }
+        if self.timeout.is_some() && !matches!(self.mode, Mode::Check { .. }) {
+            bail!("`--timeout` is only supported by the check and bmc modes")
+        }
+        self.start_timeout();
         match &self.mode {
             Mode::Check {
                 input,
                 smt_log,
-                induction,
-                bmc,
-                bmc_max,
+                system,
+                ..
             } => {
-                if let Some(smt_log) = smt_log {
-                    if !std::path::Path::new(smt_log).exists() {
-                        std::fs::create_dir_all(smt_log).chain_err(|| {
-                            format!("while recursively creating SMT log directory `{}`", smt_log)
-                        })?
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let txt = read_input(input)?;
+                let systems = text::systems(&txt)?;
+                if systems.is_empty() {
+                    if let Some(name) = system {
+                        bail!(
+                            "cannot select system `{}`, `{}` does not declare named systems",
+                            name,
+                            input
+                        )
                     }
+                    let done = budget::Verdict::default();
+                    let verdict = self.check_system(input, None, txt, 1, &smt_log, &done)?;
+                    return self.conclude(&verdict);
                 }
-                let check = Check::new(self, input, smt_log)?;
-                let (base, step) = if *induction {
-                    let (base, step) = check.run()?;
-                    (base, Some(step))
-                } else {
-                    (CheckRes::new(&check.sys).into(), None)
+
+                let systems = match system {
+                    Some(name) => {
+                        let names: Vec<String> = systems
+                            .iter()
+                            .map(|(name, _, _)| format!("`{}`", name))
+                            .collect();
+                        let selected: Vec<(String, String, usize)> = systems
+                            .into_iter()
+                            .filter(|(sys_name, _, _)| sys_name == name)
+                            .collect();
+                        if selected.is_empty() {
+                            bail!(
+                                "unknown system `{}`, `{}` declares {}",
+                                name,
+                                input,
+                                names.join(", ")
+                            )
+                        }
+                        selected
+                    }
+                    None => systems,
                 };
-                if *bmc {
-                    if *induction {
+                let mut verdict = budget::Verdict::default();
+                let mut rows = Vec::with_capacity(systems.len());
+                for (idx, (name, txt, first_line)) in systems.into_iter().enumerate() {
+                    if self.json {
+                        self.progress(|s| format!("checking system `{}`", s.bold.paint(&name)))
+                    } else {
+                        if idx > 0 {
+                            println!()
+                        }
+                        println!("checking system `{}`", self.bold.paint(&name));
                         println!();
                     }
-                    check.bmc(bmc_max.clone(), &base, step.as_ref())?
+                    let smt_log = match smt_log.as_ref() {
+                        Some(dir) => {
+                            let mut dir = PathBuf::from(dir);
+                            dir.push(&name);
+                            let dir = dir.display().to_string();
+                            std::fs::create_dir_all(&dir).chain_err(|| {
+                                format!("while creating SMT log directory `{}`", dir)
+                            })?;
+                            Some(dir)
+                        }
+                        None => None,
+                    };
+                    let start = Instant::now();
+                    let sys_verdict = self
+                        .check_system(input, Some(&name), txt, first_line, &smt_log, &verdict)
+                        .chain_err(|| format!("while checking system `{}`", name))?;
+                    rows.push(dashboard::Row::new(&name, &sys_verdict, start.elapsed()));
+                    verdict.extend(sys_verdict)
                 }
-                Ok(())
+                if rows.len() > 1 && !self.json {
+                    self.print_dashboard(&rows)
+                }
+                self.conclude(&verdict)
             }
             Mode::Script {
                 input,
                 smt_log,
                 verb,
             } => {
-                if let Some(smt_log) = smt_log {
-                    if !std::path::Path::new(smt_log).exists() {
-                        std::fs::create_dir_all(smt_log).chain_err(|| {
-                            format!("while recursively creating SMT log directory `{}`", smt_log)
-                        })?
-                    }
-                }
-
-                run_script(self, input, smt_log, *verb)
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                run_script(self, input, &smt_log, *verb)
                     .chain_err(|| format!("running `{}` script", self.styles.bold.paint(input)))
             }
-            Mode::Demo { target, check } => self.write_demo(target, *check),
+            Mode::Reach {
+                input,
+                smt_log,
+                target,
+                bmc_max,
+                prove,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let (txt, views) = views::strip(&read_input(input)?)?;
+                let (txt, formats) = value_format::strip(&txt)?;
+                let (txt, dists) = dist::strip(&txt)?;
+                let txt = query::with_target(&txt, target)?;
+                let mut check = Check::from_txt(self, &txt, &smt_log)?;
+                check.set_views(views)?;
+                check.set_dists(dists)?;
+                check.set_formats(formats)?;
+                check.check_log_filter()?;
+                check.query(target, *bmc_max, *prove)
+            }
+            Mode::CompareSolvers {
+                input,
+                smt_log,
+                solvers,
+                bmc_max,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let check = Check::new(self, input, &smt_log)?;
+                check.compare_solvers(solvers, *bmc_max)
+            }
+            Mode::Compare {
+                old,
+                new,
+                smt_log,
+                bmc_max,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                self.compare(old, new, &smt_log, bmc_max.unwrap_or(10))
+            }
+            Mode::Compose {
+                input,
+                smt_log,
+                main,
+                bmc_max,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                self.compose(input, main, bmc_max.unwrap_or(10), &smt_log)
+            }
+            Mode::Sim {
+                input,
+                smt_log,
+                steps,
+                runs,
+                seed,
+                fuzz,
+                coverage,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let check = Check::new(self, input, &smt_log)?;
+                check.simulate(*steps, *runs, *seed, *fuzz, *coverage)
+            }
+            Mode::TestsGen {
+                input,
+                smt_log,
+                output,
+                format,
+                bmc_max,
+                steps,
+                runs,
+                seed,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let check = Check::new(self, input, &smt_log)?;
+                check.testsgen(output, *format, *bmc_max, *steps, *runs, *seed)
+            }
+            Mode::Mutate {
+                input,
+                smt_log,
+                bmc_max,
+                trans,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let check = Check::new(self, input, &smt_log)?;
+                if *trans {
+                    check.mutate_trans(*bmc_max)
+                } else {
+                    check.mutate(*bmc_max)
+                }
+            }
+            Mode::Count {
+                input,
+                smt_log,
+                bmc_max,
+                max_states,
+                steps,
+                runs,
+                seed,
+            } => {
+                self.announce();
+                let smt_log = self.smt_log_dir(smt_log)?;
+                let check = Check::new(self, input, &smt_log)?;
+                check.count(*bmc_max, *max_states, *steps, *runs, *seed)
+            }
+            Mode::Demo {
+                target,
+                check,
+                force,
+            } => self.write_demo(target, *check, *force),
+            Mode::Examples { workflow } => self.examples(workflow.as_deref()),
+            Mode::ShowCex { id } => self.show_cex(id),
+            Mode::Scenario { id, steps, vars } => self.scenario(id, *steps, vars),
+            Mode::Graph {
+                input,
+                output,
+                format,
+            } => self.graph(input, output, *format),
+            Mode::WhatsNew { since } => self.whats_new(since),
             Mode::Parse { input } => {
                 let _check = Check::new(self, input, &None)?;
                 Ok(())
@@ -185,16 +1345,367 @@ impl Run {
         }
     }
 
+    /// Reports the verdict of a check run: JSON output, reports, notification and annotations,
+    /// then exits with the code of the verdict, see [`Self::check_exit`].
+    pub fn conclude(&self, verdict: &budget::Verdict) -> Res<()> {
+        let Mode::Check {
+            input,
+            max_unknown,
+            score_threshold,
+            ..
+        } = &self.mode
+        else {
+            bail!("[fatal] trying to conclude a run outside of check mode")
+        };
+        let meta = self.metadata(input)?;
+        self.print_json(&meta, verdict);
+        self.write_reports(&meta, verdict)?;
+        self.notify(&meta, verdict);
+        self.annotate(verdict);
+        self.check_exit(verdict, *max_unknown, *score_threshold)
+    }
+
+    /// Applies the unknown budget and the score threshold, if any, and exits with the code of the
+    /// verdict unless it is `0` or `--exit_zero` is active. Prints the verdict first with `--verdict_only`.
+    ///
+    /// With `--baseline`, the exit code only reflects regressions, see [`baseline`]. Saves the
+    /// baseline with `--save_baseline`.
+    ///
+    /// See [`budget`] and [`score`].
+    pub fn check_exit(
+        &self,
+        verdict: &budget::Verdict,
+        max_unknown: Option<usize>,
+        score_threshold: Option<f64>,
+    ) -> Res<()> {
+        if let Some(max) = max_unknown {
+            self.unknown_budget(verdict, max);
+        }
+        if let Some(threshold) = score_threshold {
+            self.safety_score(verdict, threshold);
+        }
+        let token_code = verdict.exit_code(max_unknown, score_threshold);
+        if self.verdict_only {
+            mute::stop();
+            println!("{}", budget::exit::token(token_code))
+        }
+        self.event("final-verdict", || {
+            vec![
+                ("verdict", json::json_str(budget::exit::token(token_code))),
+                ("proved", verdict.proved.len().to_string()),
+                ("falsified", verdict.falsified.len().to_string()),
+                ("unknown", verdict.unknown.len().to_string()),
+            ]
+        });
+        let code = match self.baseline.as_ref() {
+            Some(path) => {
+                if self.compare_baseline(path, verdict)? > 0 {
+                    budget::exit::FALSIFIED
+                } else {
+                    budget::exit::SAFE
+                }
+            }
+            None => token_code,
+        };
+        if let Some(path) = self.save_baseline.as_ref() {
+            self.save_baseline(path, verdict)?
+        }
+        self.log(logging::Level::Info, "verdict", || {
+            vec![
+                ("proved", verdict.proved.len().to_string()),
+                ("falsified", verdict.falsified.len().to_string()),
+                ("unknown", verdict.unknown.len().to_string()),
+                ("exit_code", code.to_string()),
+            ]
+        })?;
+        if code != budget::exit::SAFE && !self.exit_zero {
+            pager::wait();
+            std::process::exit(code)
+        }
+        Ok(())
+    }
+
+    /// Runs the check mode on a system, `name` is the name of the system if the file declares
+    /// several ones.
+    ///
+    /// `first_line` is the line of `input` the text of the system starts at, for the locations
+    /// in reports, `done` the final status of the systems checked before, see [`Check::watched`].
+    /// Yields the final status of its candidates.
+    pub fn check_system(
+        &self,
+        input: &str,
+        name: Option<&str>,
+        txt: String,
+        first_line: usize,
+        smt_log: &Option<String>,
+        done: &budget::Verdict,
+    ) -> Res<budget::Verdict> {
+        let Mode::Check {
+            assume,
+            induction,
+            bmc,
+            bmc_max,
+            bmc_depths,
+            eventually,
+            guide,
+            bmc_induction,
+            simple_path,
+            explain_step,
+            lemmas,
+            suggest,
+            almost_safe,
+            check_vacuity,
+            check_deadlock,
+            check_determinism,
+            check_dead_branches,
+            check_stuttering,
+            certificate,
+            checkpoint,
+            coverage,
+            engine,
+            max_states,
+            ..
+        } = &self.mode
+        else {
+            bail!("[fatal] trying to check a system outside of check mode")
+        };
+        if self.json {
+            self.json_supported()?
+        }
+        let label = match name {
+            Some(name) => format!("{}::{}", input, name),
+            None => input.to_string(),
+        };
+        let lines = if !self.records_cases() {
+            Map::new()
+        } else {
+            text::candidate_lines(&txt, first_line)?
+        };
+        let (txt, weights) = score::strip(&txt)?;
+        let (txt, ignored) = ignore::strip(&txt, self.include_ignored)?;
+        self.report_ignored(&ignored);
+        let (txt, views) = views::strip(&txt)?;
+        let (txt, formats) = value_format::strip(&txt)?;
+        let (mut txt, dists) = dist::strip(&txt)?;
+        let trans_facts = facts::facts(&txt)?;
+        let mut facts_hold = Vec::new();
+        if !trans_facts.is_empty() {
+            facts_hold = self.check_trans_facts(&txt, &trans_facts, assume, smt_log)?;
+            txt = facts::with_facts(&txt, &trans_facts, &facts_hold)
+        }
+        let unassumed = txt.clone();
+        if !assume.is_empty() {
+            if self.verb > 0 {
+                for assumption in assume {
+                    self.progress(|s| format!("assuming `{}`", s.bold.paint(assumption)))
+                }
+            }
+            txt = text::with_assumptions(&txt, assume)?
+        }
+        let mut check = Check::from_txt(self, &txt, smt_log)?;
+        check.set_views(views)?;
+        check.set_dists(dists)?;
+        check.set_formats(formats)?;
+        check.set_weights(weights)?;
+        // Candidates of a filter may belong to some of the systems only.
+        if name.is_none() {
+            check.check_log_filter()?
+        }
+        if let Some(path) = checkpoint {
+            check.set_checkpoint(&system_path(path, name), &txt)?
+        }
+        check.hook_pre_check(&label)?;
+        let sanity = check.sanity().chain_err(|| "during sanity checks")?;
+        let analyses = || -> Res<()> {
+            if !assume.is_empty() && sanity == "ok" {
+                check
+                    .assumptions(&unassumed, bmc_max.unwrap_or(10))
+                    .chain_err(|| "while checking the assumptions")?;
+            }
+            match engine {
+                Engine::Induction => (),
+                Engine::Reach => check.reach()?,
+                Engine::Explicit => check.explicit(*max_states)?,
+                Engine::Backward => check.backward(*bmc_max)?,
+                Engine::Portfolio => check.portfolio(*bmc_max)?,
+                Engine::Qe => check.qe_image(*bmc_max)?,
+            }
+            if *engine != Engine::Induction {
+                return Ok(());
+            }
+            // A cancelled run skips the analyses, earlier systems may have used up the timeout.
+            let (base, step) = if *induction && !self.cancel.is_cancelled() {
+                if *explain_step {
+                    check.explain_step()
+                }
+                match check.run()? {
+                    Some((base, step)) => {
+                        if *lemmas {
+                            check.lemmas(&base, &step)?
+                        }
+                        if *suggest {
+                            check.suggest(&base, &step)?
+                        }
+                        if *almost_safe {
+                            check.almost_safe(&base, &step, bmc_max.unwrap_or(10))?
+                        }
+                        if let Some(max_k) = simple_path {
+                            check.simple_path(*max_k, &base, &step)?
+                        }
+                        if *check_vacuity {
+                            check.vacuity()?
+                        }
+                        if *check_deadlock {
+                            check.deadlock(&base, &step, bmc_max.unwrap_or(10))?
+                        }
+                        if *check_determinism {
+                            check.determinism(&base, &step, bmc_max.unwrap_or(10))?
+                        }
+                        if *check_dead_branches {
+                            check.dead_branches(bmc_max.unwrap_or(10))?
+                        }
+                        if *check_stuttering {
+                            check.stuttering(&base, &step, eventually)?
+                        }
+                        if let Some(path) = certificate {
+                            check.certificate(&label, &system_path(path, name))?
+                        }
+                        (base, Some(step))
+                    }
+                    // A query exceeded the `--query_timeout`, BMC is skipped.
+                    None => (CheckRes::new(&check.sys).into(), None),
+                }
+            } else {
+                (CheckRes::new(&check.sys).into(), None)
+            };
+            let query_timed_out = *induction && step.is_none() && !self.cancel.is_cancelled();
+            if *bmc && !self.cancel.is_cancelled() && !query_timed_out {
+                if *induction && !self.json {
+                    println!();
+                }
+                match (bmc_depths, guide) {
+                    (Some(_), Some(_)) => {
+                        bail!("`--guide` is not supported with `--bmc_depths`")
+                    }
+                    (_, Some(_)) if !eventually.is_empty() => {
+                        bail!("`--eventually` objectives are not supported with `--guide`")
+                    }
+                    (_, Some(_)) if *bmc_induction => {
+                        bail!("`--bmc_induction` is not supported with `--guide`")
+                    }
+                    (Some(_), None) if !eventually.is_empty() => {
+                        bail!("`--eventually` objectives are not supported with `--bmc_depths`")
+                    }
+                    (Some(_), None) if *bmc_induction => {
+                        bail!("`--bmc_induction` is not supported with `--bmc_depths`")
+                    }
+                    (Some(depths), None) => check.timed("bmc", || {
+                        check.bmc_depths(*bmc_max, depths, &base, step.as_ref())
+                    })?,
+                    (None, Some(guide)) => {
+                        let cost = guided::cost(&txt, guide)?;
+                        check.timed("bmc", || {
+                            check.guided_bmc(*bmc_max, &cost, &base, step.as_ref())
+                        })?
+                    }
+                    (None, None) => {
+                        let lasso = if eventually.is_empty() {
+                            None
+                        } else {
+                            Some(lasso::Lasso::new(&check, &txt, eventually)?)
+                        };
+                        check.timed("bmc", || {
+                            check.bmc(bmc_max.clone(), &base, step.as_ref(), lasso, *bmc_induction)
+                        })?
+                    }
+                }
+            }
+            Ok(())
+        };
+        let finish = || -> Res<budget::Verdict> {
+            check.settle_timeouts()?;
+            if *engine != Engine::Induction {
+                if self.stats && !self.json {
+                    println!();
+                    check.print_stats()?
+                }
+                return check.verdict(name);
+            }
+            if !self.json && !check.sys.po_s().is_empty() {
+                println!();
+                check.print_summary()?
+            }
+            if *coverage {
+                println!();
+                let cexs = check
+                    .cexs
+                    .lock()
+                    .map_err(|_| "failed to access counterexamples for coverage")?;
+                let traces: Vec<&Trace> = cexs.iter().map(|(_, trace)| trace).collect();
+                check.coverage(&traces)?
+            }
+            if self.stats && !self.json {
+                println!();
+                check.print_stats()?
+            }
+            let mut verdict = check.verdict(name)?;
+            if self.json {
+                let facts: Vec<(&facts::Fact, bool)> =
+                    trans_facts.iter().zip(facts_hold.iter().cloned()).collect();
+                verdict
+                    .json
+                    .push(check.json_report(&label, sanity, &ignored, &facts)?)
+            }
+            if self.records_cases() {
+                verdict.suites.push(check.suite(&label, input, &lines)?)
+            }
+            Ok(verdict)
+        };
+        check.watched(done, analyses, finish)
+    }
+
+    /// Fails if `path` exists and must not be overwritten.
+    ///
+    /// Existing files are overwritten if `force`, or if the user confirms when both standard input
+    /// and output are terminals.
+    pub fn check_overwrite(&self, path: &str, force: bool) -> Res<()> {
+        if force || !std::path::Path::new(path).exists() {
+            return Ok(());
+        }
+        if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stdout) {
+            bail!(
+                "file `{}` already exists, use `--force` to overwrite it",
+                path
+            )
+        }
+        print!(
+            "file `{}` already exists, overwrite it? [y/N] ",
+            self.bold.paint(path)
+        );
+        std::io::stdout()
+            .flush()
+            .chain_err(|| "while flushing stdout")?;
+        let mut answer = String::new();
+        std::io::stdin()
+            .read_line(&mut answer)
+            .chain_err(|| "while reading confirmation")?;
+        match answer.trim() {
+            "y" | "Y" | "yes" => Ok(()),
+            _ => bail!("not overwriting file `{}`", path),
+        }
+    }
+
     /// Writes the demo system file somewhere.
     ///
-    /// If `!check`, generates the demo script instead.
-    pub fn write_demo(&self, target: &str, check: bool) -> Res<()> {
+    /// If `!check`, generates the demo script instead. See [`Self::check_overwrite`] for `force`.
+    pub fn write_demo(&self, target: &str, check: bool, force: bool) -> Res<()> {
         use std::fs::OpenOptions;
         let (desc, demo) = if check {
             ("system", mikino_api::TRANS_DEMO.as_bytes())
         } else {
             ("script", mikino_api::SCRIPT_DEMO.as_bytes())
         };
+        self.check_overwrite(target, force)?;
         println!(
             "writing demo {} to file `{}`",
             desc,
@@ -264,7 +1775,7 @@ pub fn run_script(
             )
         })?;
     if env.verb >= 3 {
-        println!("parsing {}", env.styles.green.paint("successful"));
+        env.progress(|s| format!("parsing {}", s.green.paint("successful")));
     }
 
     let mut runner = {
@@ -302,7 +1813,10 @@ pub fn run_script(
                 }
             }
             Step::Done(outcome @ Outcome::Panic { .. }) => {
-                eprintln!("{}", outcome.pretty(&script_content, &env.styles, with_pos));
+                eprintln!(
+                    "{}",
+                    outcome.pretty(&script_content, &env.err_styles, with_pos)
+                );
                 bail!(
                     "script `{}` panicked",
                     env.bold.paint(script_path.display().to_string())
@@ -319,14 +1833,62 @@ pub fn run_script(
     Ok(())
 }
 
+/// A trace for presentation: the value of each variable, for each step.
+pub type Trace = Vec<(usize, Vec<(String, String)>)>;
+
+/// Outcome of an analysis for a single candidate.
+pub enum Outcome {
+    /// Holds, proved at some depth.
+    Proved(usize),
+    /// Falsified, with a trace from an initial state.
+    Falsified(Trace),
+    /// Inconclusive: maximum depth reached, or analysis cancelled.
+    Unknown,
+}
+
 /// Check environment.
 pub struct Check<'env> {
     /// Run env.
     pub env: &'env Run,
     /// System to check.
     pub sys: Sys,
+    /// Text of the system.
+    pub txt: String,
     /// Optional SMT log directory.
     pub smt_log_dir: Option<String>,
+    /// Optional SMT transcript.
+    pub transcript: Option<smt::Transcript>,
+    /// Counterexamples recorded for DOT output and coverage, see [`Self::record_cex`].
+    pub cexs: Mutex<Vec<(String, Trace)>>,
+    /// Candidates settled so far, `true` if proved and `false` if falsified, see
+    /// [`Self::settle`].
+    pub settled: Mutex<Map<String, bool>>,
+    /// Last outcome of each candidate, for the JSON output, see [`Self::settle`].
+    pub outcomes: Mutex<Map<String, budget::Outcome>>,
+    /// All the outcomes reported, in order, for the summary, see [`Self::print_summary`].
+    pub history: Mutex<Vec<(String, budget::Outcome)>>,
+    /// Time spent in each phase, see [`Self::timed`].
+    pub timings: Mutex<Vec<(String, Duration)>>,
+    /// Cases recorded for the reports, see [`Self::record_case`].
+    pub cases: Mutex<Vec<report::Case>>,
+    /// Sanitizer for the traces output, if any.
+    pub sanitizer: Option<sanitize::Sanitizer>,
+    /// Views on the variables for trace presentation, see [`Self::set_views`].
+    pub views: Vec<views::View>,
+    /// Input distributions of the variables for simulation, see [`Self::set_dists`].
+    pub dists: Map<String, dist::Dist>,
+    /// Base of the integer values of some variables in traces, see [`Self::set_formats`].
+    pub formats: Map<String, value_format::Base>,
+    /// Weights of the candidates that have one, see [`Self::set_weights`].
+    pub weights: Map<String, u64>,
+    /// Expressions traces are projected on, with their text, see [`projection`].
+    pub projection: Vec<(String, Expr)>,
+    /// Checkpoint of the strengthening searches, if active, see [`Self::set_checkpoint`].
+    pub checkpoint: Option<Mutex<checkpoint::Checkpoint>>,
+    /// Timing statistics, see [`stats`].
+    pub stats: Mutex<stats::Stats>,
+    /// Telemetry of the solver interactions, see [`stats`].
+    pub telemetry: smt::Telemetry,
 }
 impl<'env> Deref for Check<'env> {
     type Target = Styles;
@@ -336,37 +1898,283 @@ impl<'env> Deref for Check<'env> {
 }
 impl<'env> Check<'env> {
     /// Constructor.
+    ///
+    /// Removes ignored candidates, see [`ignore`], and lists them. Also handles views, see
+    /// [`views`], input distributions, see [`dist`], and value formats, see [`value_format`].
     pub fn new(env: &'env Run, input: &str, smt_log_dir: &Option<String>) -> Res<Self> {
-        use std::{fs::OpenOptions, io::Read};
+        let (txt, ignored) = ignore::strip(&read_input(input)?, env.include_ignored)?;
+        env.report_ignored(&ignored);
+        let (txt, views) = views::strip(&txt)?;
+        let (txt, formats) = value_format::strip(&txt)?;
+        let (txt, dists) = dist::strip(&txt)?;
+        let mut check = Self::from_txt(env, &txt, smt_log_dir)?;
+        check.set_views(views)?;
+        check.set_dists(dists)?;
+        check.set_formats(formats)?;
+        check.check_log_filter()?;
+        Ok(check)
+    }
 
+    /// Constructor from the content of a system file.
+    pub fn from_txt(env: &'env Run, txt: &str, smt_log_dir: &Option<String>) -> Res<Self> {
         let smt_log_dir = smt_log_dir.clone();
-        let mut file = OpenOptions::new().read(true).open(input)?;
+        let sys = parse::trans(txt)?;
+        env.log(logging::Level::Info, "parse", || {
+            vec![
+                ("variables", sys.decls().iter().count().to_string()),
+                ("candidates", sys.po_s().len().to_string()),
+            ]
+        })?;
+        if env.verb >= 3 {
+            println!("|===| Parsing {}:", env.styles.green.paint("successful"));
+            for line in sys.to_ml_string().lines() {
+                println!("| {}", line)
+            }
+            println!("|===|");
+            println!()
+        }
+
+        let transcript = match env.smt_transcript.as_ref() {
+            Some(path) => {
+                let transcript = smt::Transcript::new(path)?;
+                if !env.deterministic_queries {
+                    transcript.note(&format!("run {}", env.run_id))?
+                }
+                Some(transcript)
+            }
+            None => None,
+        };
+        let projection = projection::parse(txt, &env.cex_project)?;
+        let sanitizer = env
+            .sanitize_cex
+            .as_deref()
+            .map(sanitize::Sanitizer::load)
+            .transpose()
+            .chain_err(|| "while loading counterexample sanitization mapping")?;
+
+        let check = Self {
+            env,
+            sys,
+            txt: txt.into(),
+            smt_log_dir,
+            transcript,
+            cexs: Mutex::new(Vec::new()),
+            settled: Mutex::new(Map::new()),
+            outcomes: Mutex::new(Map::new()),
+            history: Mutex::new(Vec::new()),
+            timings: Mutex::new(Vec::new()),
+            cases: Mutex::new(Vec::new()),
+            sanitizer,
+            views: Vec::new(),
+            dists: Map::new(),
+            formats: Map::new(),
+            weights: Map::new(),
+            projection,
+            checkpoint: None,
+            stats: Mutex::new(stats::Stats::new()),
+            telemetry: smt::Telemetry::default(),
+        };
+        env.event("parse-ok", || {
+            let candidates = check.sys.po_s().keys();
+            vec![
+                ("variables", check.sys.decls().iter().count().to_string()),
+                (
+                    "candidates",
+                    events::json_names(candidates.map(|c| check.sanitized_text(c))),
+                ),
+            ]
+        });
+        Ok(check)
+    }
+
+    /// Spawns a solver for the analyses not handled by `mikino_api`.
+    ///
+    /// The solver logs to `<smt_log_dir>/<log_file>` if SMT logging is active.
+    pub fn solver(&self, log_file: &str) -> Res<smt::Solver> {
+        self.phase_solver(Phase::Other, log_file)
+    }
+
+    /// Spawns a solver for some phase, see [`Self::solver`].
+    pub fn phase_solver(&self, phase: Phase, log_file: &str) -> Res<smt::Solver> {
+        self.spawn_solver(phase, self.env.solver_cmd(phase), log_file)
+    }
+
+    /// Spawns a solver from a solver command, see [`Self::solver`].
+    pub fn solver_with_cmd(&self, solver_cmd: &str, log_file: &str) -> Res<smt::Solver> {
+        self.spawn_solver(Phase::Other, solver_cmd, log_file)
+    }
+
+    /// SMT log directory of the solvers of a phase, `None` if SMT logging is inactive or
+    /// `--smt_log_only` filters the phase out.
+    pub fn phase_log_dir(&self, phase: Phase) -> Option<PathBuf> {
+        match self.env.smt_log_only.as_ref() {
+            Some(filter) if !filter.logs_phase(phase) => None,
+            _ => self.smt_log_dir.as_ref().map(PathBuf::from),
+        }
+    }
+
+    /// Spawns a solver for some phase from a solver command.
+    fn spawn_solver(&self, phase: Phase, solver_cmd: &str, log_file: &str) -> Res<smt::Solver> {
+        let tee = self.phase_log_dir(phase).map(|mut path| {
+            path.push(log_file);
+            path
+        });
+        let mut solver = smt::Solver::new(solver_cmd, tee)?;
+        if let Some(filter) = self.env.smt_log_only.as_ref() {
+            filter.apply(&mut solver, self.sys.po_s().keys())
+        }
+        solver.set_completion(self.env.model_completion);
+        solver.set_telemetry(self.telemetry.clone());
+        if let Some(logger) = self.env.logger.as_ref() {
+            if logger.logs(logging::Level::Debug) {
+                solver.set_logger(logger.clone())
+            }
+        }
+        if let Some(path) = self.env.shrink.as_ref() {
+            solver.set_shrink(path.into())
+        }
+        if let Some(ms) = self.env.query_timeout {
+            solver.set_query_timeout(ms)?
+        }
+        if let Some(transcript) = self.transcript.as_ref() {
+            solver.set_transcript(transcript.clone())
+        }
+        Ok(solver)
+    }
+
+    /// States an intent in the SMT transcript, if any.
+    pub fn intent(&self, txt: &str) -> Res<()> {
+        match self.transcript.as_ref() {
+            Some(transcript) => transcript.intent(txt),
+            None => Ok(()),
+        }
+    }
+    /// Adds a note to the SMT transcript, if any.
+    pub fn note(&self, txt: &str) -> Res<()> {
+        match self.transcript.as_ref() {
+            Some(transcript) => transcript.note(txt),
+            None => Ok(()),
+        }
+    }
+    /// Notes the outcome of a check for each candidate in the SMT transcript, if any.
+    pub fn note_res(&self, res: &CheckRes, okay: &str, falsified: &str) -> Res<()> {
+        if self.transcript.is_none() {
+            return Ok(());
+        }
+        for candidate in &res.okay {
+            self.note(&format!("`{}`: {}", candidate, okay))?
+        }
+        for candidate in res.cexs.keys() {
+            self.note(&format!("`{}`: {}", candidate, falsified))?
+        }
+        Ok(())
+    }
 
-        let mut txt = String::new();
-        file.read_to_string(&mut txt)?;
+    /// Runs some phase and records the time it takes.
+    pub fn timed<T>(&self, phase: &str, run: impl FnOnce() -> Res<T>) -> Res<T> {
+        let start = Instant::now();
+        let res = run()?;
+        let time = start.elapsed();
+        self.env.log(logging::Level::Info, "phase", || {
+            vec![
+                ("phase", json::json_str(phase)),
+                ("time", format!("{:.3}", time.as_secs_f64())),
+            ]
+        })?;
+        self.timings
+            .lock()
+            .map_err(|_| "failed to record phase timing")?
+            .push((phase.into(), time));
+        Ok(res)
+    }
 
-        let sys = parse::trans(&txt)?;
-        if env.verb >= 3 {
-            println!("|===| Parsing {}:", env.styles.green.paint("successful"));
-            for line in sys.to_ml_string().lines() {
-                println!("| {}", line)
+    /// Runs a phase `mikino_api` handles, `None` if it fails under the `--query_timeout`.
+    ///
+    /// Z3 answers `unknown` to the queries exceeding the limit, which `mikino_api` reports as
+    /// errors. The caller reports the candidates left, see [`Self::settle_query_timeout`].
+    pub fn api_phase<T>(&self, phase: &str, run: impl FnOnce() -> Res<T>) -> Res<Option<T>> {
+        match self.timed(phase, run) {
+            Ok(res) => Ok(Some(res)),
+            Err(e) if self.env.query_timeout.is_some() => {
+                self.env.log(logging::Level::Info, "query-timeout", || {
+                    vec![
+                        ("phase", json::json_str(phase)),
+                        ("error", json::json_str(&e.to_string())),
+                    ]
+                })?;
+                Ok(None)
             }
-            println!("|===|");
-            println!()
+            Err(e) => Err(e),
         }
-
-        Ok(Self {
-            env,
-            sys,
-            smt_log_dir,
-        })
     }
 
     /// Attemps to prove the candidates on a system.
-    pub fn run(&self) -> Res<(BaseRes, StepRes)> {
-        let base_res = self.base_check()?;
-        let step_res = self.step_check()?;
+    ///
+    /// `None` if a query exceeded the `--query_timeout`, the candidates left are then `unknown`.
+    pub fn run(&self) -> Res<Option<(BaseRes, StepRes)>> {
+        let Some(base_res) = self.api_phase("base", || self.base_check())? else {
+            self.settle_query_timeout("base")?;
+            return Ok(None);
+        };
+        self.env.event("base-done", || {
+            vec![
+                (
+                    "okay",
+                    events::json_names(base_res.okay.iter().map(|c| self.sanitized_text(c))),
+                ),
+                (
+                    "falsified",
+                    events::json_names(base_res.cexs.keys().map(|c| self.sanitized_text(c))),
+                ),
+            ]
+        });
+        let Some(step_res) = self.api_phase("step", || self.step_check())? else {
+            if self.env.json {
+                self.present_base_cexs(&self.sys, &base_res)?
+            }
+            for candidate in base_res.cexs.keys() {
+                self.settle("induction", candidate, "falsified", None)?
+            }
+            self.settle_query_timeout("step")?;
+            return Ok(None);
+        };
+        self.env.event("step-done", || {
+            vec![
+                (
+                    "okay",
+                    events::json_names(step_res.okay.iter().map(|c| self.sanitized_text(c))),
+                ),
+                (
+                    "falsified",
+                    events::json_names(step_res.cexs.keys().map(|c| self.sanitized_text(c))),
+                ),
+            ]
+        });
+
+        if self.env.json {
+            // Records the traces of the base counterexamples.
+            self.present_base_cexs(&self.sys, &base_res)?
+        } else {
+            self.report(&base_res, &step_res)
+        }
+
+        for candidate in self.sys.po_s().keys() {
+            let status = if base_res.cexs.contains_key(candidate.as_str()) {
+                "falsified"
+            } else if step_res.cexs.contains_key(candidate.as_str()) {
+                "not_inductive"
+            } else {
+                "proved"
+            };
+            self.settle("induction", candidate, status, None)?
+        }
+        self.record_induction_cases(&base_res, &step_res)?;
+
+        Ok(Some((base_res, step_res)))
+    }
 
+    /// Reports the result of an induction attempt.
+    pub fn report(&self, base_res: &BaseRes, step_res: &StepRes) {
         println!("|===| {} attempt result", self.bold.paint("Induction"));
 
         if base_res.has_falsifications() {
@@ -457,109 +2265,511 @@ impl<'env> Check<'env> {
             }
         }
 
+        self.triage_hints(base_res, step_res);
+
+        println!("|===|");
+    }
+
+    /// Explains what the base/step results of each failing candidate mean, and what to do next.
+    pub fn triage_hints(&self, base_res: &BaseRes, step_res: &StepRes) {
+        let mut failing: Set<&str> = base_res.cexs.keys().cloned().collect();
+        failing.extend(step_res.cexs.keys().cloned());
+        if failing.is_empty() {
+            return;
+        }
+
+        println!("|");
+        println!("| - {}:", self.bold.paint("next steps"));
+        for candidate in failing {
+            let (in_base, in_step) = (
+                base_res.cexs.contains_key(candidate),
+                step_res.cexs.contains_key(candidate),
+            );
+            let (meaning, action) = match (in_base, in_step) {
+                (true, true) => (
+                    "falsified in the initial state(s) and not inductive",
+                    "the falsification is real, fix the system or the candidate first",
+                ),
+                (true, false) => (
+                    "falsified in the initial state(s) only",
+                    "the initial predicate likely allows too much, fix `init` \
+                    or weaken the candidate",
+                ),
+                (false, true) => (
+                    "holds in the initial state(s) but is not inductive",
+                    "the counterexample might not be reachable, run BMC (`--bmc`) to look \
+                    for a real falsification, or strengthen it with a lemma (`--lemmas`)",
+                ),
+                (false, false) => unreachable!("candidate `{}` is not failing", candidate),
+            };
+            println!("|   `{}` is {}:", self.red.paint(candidate), meaning);
+            println!("|     {}", action);
+        }
+    }
+
+    /// Attempts to prove non-inductive candidates by k-induction with simple-path constraints.
+    ///
+    /// Only considers candidates that hold in the initial state(s) but are not inductive. The step
+    /// check for `k` forces the `k + 1` states of the path to be pairwise distinct, which makes
+    /// k-induction complete for finite-state systems given a big enough `k`.
+    pub fn simple_path(&self, max_k: usize, base: &BaseRes, step: &StepRes) -> Res<()> {
+        let candidates: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| !step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        println!();
+        println!(
+            "running {} k-induction up to k = {} for {} candidate(s)...",
+            self.bold.paint("simple-path"),
+            max_k,
+            candidates.len()
+        );
+
+        let mut proved = Vec::new();
+        let mut falsified = Vec::new();
+        let mut unknown = Vec::new();
+
+        let cancel = cancel::Token::new();
+        for (idx, candidate) in candidates.into_iter().enumerate() {
+            let res = self
+                .simple_path_candidate(Some(max_k), idx, candidate, &cancel, true)
+                .chain_err(|| {
+                    format!(
+                        "during simple-path k-induction for candidate `{}`",
+                        self.bold.paint(candidate)
+                    )
+                })?;
+            match res {
+                Outcome::Proved(k) => {
+                    self.settle("simple_path", candidate, "proved", Some(k))?;
+                    proved.push((candidate, k))
+                }
+                Outcome::Falsified(trace) => {
+                    self.settle("simple_path", candidate, "falsified", Some(trace.len() - 1))?;
+                    falsified.push((candidate, trace.len() - 1))
+                }
+                Outcome::Unknown => {
+                    self.settle("simple_path", candidate, "unknown", None)?;
+                    unknown.push(candidate)
+                }
+            }
+        }
+
+        if self.env.verb > 0 {
+            println!()
+        }
+
+        println!(
+            "|===| {} k-induction result",
+            self.bold.paint("Simple-path")
+        );
+        if !proved.is_empty() {
+            println!(
+                "| - the following candidate(s) are {} and thus {} in all reachable states",
+                self.green.paint("k-inductive"),
+                self.green.paint("hold")
+            );
+            for (candidate, k) in &proved {
+                println!("|   `{}` (k = {})", self.green.paint(*candidate), k)
+            }
+        }
+        if !falsified.is_empty() {
+            if !proved.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - the following candidate(s) are {} (run BMC to see a counterexample)",
+                self.red.paint("falsifiable")
+            );
+            for (candidate, depth) in &falsified {
+                println!("|   `{}` (depth {})", self.red.paint(*candidate), depth)
+            }
+        }
+        if !unknown.is_empty() {
+            if !proved.is_empty() || !falsified.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - could {} prove the following candidate(s) with k ≤ {}",
+                self.bold.paint("not"),
+                max_k
+            );
+            for candidate in &unknown {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
         println!("|===|");
 
-        Ok((base_res, step_res))
+        Ok(())
+    }
+
+    /// Simple-path k-induction for a single candidate.
+    ///
+    /// Yields [`Outcome::Proved`] with `k` if the candidate is k-inductive, and
+    /// [`Outcome::Unknown`] if `max_k` is reached, `cancel` is raised or a query exceeds the
+    /// `--query_timeout`. If `checkpoint`, resumes from and saves to the checkpoint, see
+    /// [`checkpoint`].
+    pub fn simple_path_candidate(
+        &self,
+        max_k: Option<usize>,
+        idx: usize,
+        candidate: &str,
+        cancel: &cancel::Token,
+        checkpoint: bool,
+    ) -> Res<Outcome> {
+        let sys = &self.sys;
+        let def = sys.po_s().get(candidate).ok_or_else(|| {
+            format!(
+                "failed to retrieve definition for candidate `{}`",
+                candidate,
+            )
+        })?;
+        let mut base = self.phase_solver(Phase::Base, &format!("simple_path_base_{}.smt2", idx))?;
+        base.kill_on(cancel);
+        base.intent(&format!(
+            "simple-path base setup for candidate `{}`: initial state(s)",
+            candidate
+        ))?;
+        base.declare_state(sys, 0)?;
+        base.assert_init(sys, 0)?;
+
+        let mut step = self.phase_solver(Phase::Step, &format!("simple_path_step_{}.smt2", idx))?;
+        step.kill_on(cancel);
+        step.intent(&format!(
+            "simple-path step setup for candidate `{}`",
+            candidate
+        ))?;
+        step.declare_state(sys, 0)?;
+
+        // `k`s up to `resume` neither proved nor falsified the candidate in a previous run.
+        let resume = if checkpoint {
+            self.resumed_k(candidate)?
+        } else {
+            0
+        };
+        let mut k = 0;
+        while max_k.map(|max_k| k < max_k).unwrap_or(true) {
+            k += 1;
+            if self.cancelled(cancel) {
+                return Ok(Outcome::Unknown);
+            }
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "checking `{}` for k = {}",
+                        s.bold.paint(candidate),
+                        s.under.paint(k.to_string())
+                    )
+                });
+            }
+
+            // Base: no falsification in `k - 1` transitions.
+            let depth = k - 1;
+            base.intent(&format!(
+                "simple-path base check for candidate `{}`, falsification at depth {}?",
+                candidate, depth
+            ))?;
+            if depth > 0 {
+                base.declare_state(sys, depth)?;
+                base.assert_trans(sys, depth - 1)?;
+            }
+            if k > resume {
+                base.push()?;
+                base.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+                match base.check_sat_or_unknown()? {
+                    Some(true) => return Ok(Outcome::Falsified(base.get_trace(sys, depth)?)),
+                    Some(false) => (),
+                    None => return Ok(Outcome::Unknown),
+                }
+                base.pop()?;
+            }
+
+            // Step: `k` states verifying the candidate and pairwise distinct, then a falsification.
+            step.intent(&format!(
+                "simple-path step check for candidate `{}`, k = {}: falsification at k after {} \
+                distinct state(s) verifying it?",
+                candidate, k, k
+            ))?;
+            step.declare_state(sys, k)?;
+            step.assert_trans(sys, k - 1)?;
+            step.assert(smt::Unroll::new(def, k - 1))?;
+            for prev in 0..k {
+                step.assert(smt::distinct_states(sys, prev, k))?
+            }
+            if k <= resume {
+                continue;
+            }
+            step.push()?;
+            step.assert(format!("(not {})", smt::Unroll::new(def, k)))?;
+            let Some(cti) = step.check_sat_or_unknown()? else {
+                return Ok(Outcome::Unknown);
+            };
+            step.pop()?;
+            if !cti {
+                return Ok(Outcome::Proved(k));
+            }
+            if checkpoint {
+                self.checkpoint_k(candidate, k)?
+            }
+        }
+
+        Ok(Outcome::Unknown)
     }
 
     /// Runs BMC.
-    pub fn bmc(&self, max: Option<usize>, base: &BaseRes, step: Option<&StepRes>) -> Res<()> {
+    ///
+    /// Also looks for lassos falsifying the `eventually` objectives of `lasso`, if any. If
+    /// `bmc_induction`, attempts to prove the candidates left by k-induction, see
+    /// [`Self::bmc_induction`].
+    pub fn bmc(
+        &self,
+        max: Option<usize>,
+        base: &BaseRes,
+        step: Option<&StepRes>,
+        mut lasso: Option<lasso::Lasso>,
+        bmc_induction: bool,
+    ) -> Res<()> {
         let bmc_res = if let Some(step) = step {
             base.merge_base_with_step(step)
                 .chain_err(|| "during base/step result merge for BMC")?
         } else {
             base.as_inner().clone().into()
         };
-        if bmc_res.all_falsified() {
+        let has_candidates = !bmc_res.all_falsified();
+        if !has_candidates && lasso.is_none() {
+            for candidate in self.sys.po_s().keys() {
+                self.record_case(
+                    "bmc",
+                    candidate,
+                    report::Status::Skipped("settled before BMC".into()),
+                    None,
+                )?
+            }
             return Ok(());
         }
 
-        println!(
-            "running {}, looking for falsifications for {} candidate(s)...",
-            self.bold.paint("BMC"),
-            bmc_res.okay.len()
-        );
+        self.env.progress(|s| {
+            format!(
+                "running {}, looking for falsifications for {} candidate(s)...",
+                s.bold.paint("BMC"),
+                bmc_res.okay.len()
+            )
+        });
 
-        let conf = z3_cmd_to_conf(&self.env.z3_cmd)?;
-        let tee = self.smt_log_dir.as_ref().map(std::path::PathBuf::from);
+        let conf = self.env.api_solver_conf(Phase::Bmc)?;
+        let tee = self.phase_log_dir(Phase::Bmc);
         let mut bmc = check::Bmc::new(&self.sys, conf, tee, bmc_res)?;
         let mut falsified = Set::new();
 
-        while !bmc.is_done() && max.map(|max| max >= bmc.next_check_step()).unwrap_or(true) {
-            let depth_str = bmc.next_check_step().to_string();
-            if self.env.verb > 0 {
-                println!(
-                    "checking for falsifications at depth {}",
-                    self.env.styles.under.paint(&depth_str)
-                );
-            }
-
-            let new_falsifications = bmc.next_check().chain_err(|| {
+        let mut status = status::StatusLine::new(self.env);
+        let mut next_depth = bmc.next_check_step();
+        let mut reached = None;
+        // Depth of the query that exceeded the `--query_timeout`, if any.
+        let mut query_timed_out = None;
+        while (!bmc.is_done() || lasso.as_ref().map(|l| !l.is_done()).unwrap_or(false))
+            && max.map(|max| max >= next_depth).unwrap_or(true)
+            && !self.env.cancel.is_cancelled()
+        {
+            let depth = if bmc.is_done() {
+                next_depth
+            } else {
+                bmc.next_check_step()
+            };
+            next_depth = depth + 1;
+            let depth_str = depth.to_string();
+            status.update(|| {
                 format!(
-                    "while checking for falsifications at depth {} in BMC",
-                    self.env.styles.under.paint(&depth_str)
+                    "{} at depth {}, {} candidate(s) left",
+                    self.bold.paint("BMC"),
+                    self.under.paint(&depth_str),
+                    bmc.res().okay.len()
                 )
-            })?;
+            });
+            if self.env.verb > 0 && !status.is_active() {
+                self.env.progress(|s| {
+                    format!(
+                        "checking for falsifications at depth {}",
+                        s.under.paint(&depth_str)
+                    )
+                });
+            }
+
+            if let Some(lasso) = lasso.as_mut() {
+                for (objective, trace, loop_to) in lasso.check_up_to(depth).chain_err(|| {
+                    format!(
+                        "while looking for lassos of length {} in BMC",
+                        self.env.styles.under.paint(&depth_str)
+                    )
+                })? {
+                    status.clear();
+                    self.present_lasso(lasso, &objective, &trace, loop_to)?
+                }
+            }
+            if bmc.is_done() {
+                continue;
+            }
+
+            self.intent(&format!(
+                "BMC: falsification for some candidate(s) at depth {}?",
+                depth_str
+            ))?;
+            let start = Instant::now();
+            let next_check = bmc.next_check();
+            self.record_api_call(start.elapsed())?;
+            let new_falsifications = match next_check {
+                Ok(new_falsifications) => new_falsifications,
+                // Z3 answers `unknown` to queries exceeding the `--query_timeout`.
+                Err(_) if self.env.query_timeout.is_some() => {
+                    query_timed_out = Some(depth);
+                    break;
+                }
+                Err(e) => {
+                    return Err(e).chain_err(|| {
+                        format!(
+                            "while checking for falsifications at depth {} in BMC",
+                            self.env.styles.under.paint(&depth_str)
+                        )
+                    })
+                }
+            };
+            let time = start.elapsed();
+            reached = Some(depth);
+            self.record_depth(depth, time)?;
+            self.env.event("bmc-depth", || {
+                vec![
+                    ("depth", depth.to_string()),
+                    ("time", format!("{:.3}", time.as_secs_f64())),
+                    ("left", bmc.res().okay.len().to_string()),
+                ]
+            });
 
             if new_falsifications {
                 for (candidate, cex) in bmc.res().cexs.iter() {
                     let is_new = falsified.insert(candidate.to_string());
                     if is_new {
-                        println!(
-                            "found a {} at depth {}:",
-                            self.red.paint("falsification"),
-                            self.env.styles.bold.paint(&depth_str)
-                        );
-                        self.present_cex(&self.sys, candidate, cex, true)?
+                        status.clear();
+                        self.note(&format!(
+                            "`{}`: falsified at depth {}",
+                            candidate, depth_str
+                        ))?;
+                        if !self.env.json {
+                            println!(
+                                "found a {} at depth {}:",
+                                self.red.paint("falsification"),
+                                self.env.styles.bold.paint(&depth_str)
+                            )
+                        }
+                        self.present_cex(&self.sys, candidate, cex, true)?;
+                        self.settle("bmc", candidate, "falsified", Some(depth))?;
+                        let status =
+                            report::Status::Failed(format!("falsified at depth {}", depth));
+                        let trace = self.cex_trace(cex, true)?;
+                        self.record_case("bmc", candidate, status, Some(trace))?
                     }
                 }
             }
         }
 
+        status.clear();
         let bmc_res = bmc.destroy()?;
+        // A cancelled BMC stops before `max`.
+        let reached = if self.env.cancel.is_cancelled() || query_timed_out.is_some() {
+            reached
+        } else {
+            max
+        };
+        for candidate in &bmc_res.okay {
+            self.settle("bmc", candidate, "unfalsified", reached)?
+        }
+        for candidate in self.sys.po_s().keys() {
+            if bmc_res.okay.contains(candidate.as_str()) {
+                self.record_case("bmc", candidate, report::Status::Passed, None)?
+            } else if !falsified.contains(candidate) {
+                self.record_case(
+                    "bmc",
+                    candidate,
+                    report::Status::Skipped("settled before BMC".into()),
+                    None,
+                )?
+            }
+        }
+
+        if self.env.json {
+            return Ok(());
+        }
 
         if self.env.verb > 0 || !bmc_res.cexs.is_empty() {
             println!()
         }
 
-        println!("|===| {} result", self.bold.paint("Bmc"));
-        if !bmc_res.okay.is_empty() {
-            println!(
-                "| - could {} find falsifications for the following candidate(s)",
-                self.bold.paint("not")
-            );
-            for candidate in &bmc_res.okay {
-                println!("|   `{}`", self.bold.paint(candidate as &str))
+        if has_candidates {
+            println!("|===| {} result", self.bold.paint("Bmc"));
+            if !bmc_res.okay.is_empty() {
+                println!(
+                    "| - could {} find falsifications for the following candidate(s)",
+                    self.bold.paint("not")
+                );
+                for candidate in &bmc_res.okay {
+                    println!("|   `{}`", self.bold.paint(candidate as &str))
+                }
             }
+            if !bmc_res.okay.is_empty() && !bmc_res.cexs.is_empty() {
+                println!("|")
+            }
+            if !bmc_res.cexs.is_empty() {
+                println!(
+                    "| - found a {} for the following candidate(s)",
+                    self.red.paint("falsification")
+                );
+                for candidate in bmc_res.cexs.keys() {
+                    println!("|   `{}`", self.red.paint(*candidate))
+                }
+            }
+            println!("|");
+            if !base.cexs.is_empty() || !bmc_res.cexs.is_empty() {
+                println!("| - system is {}", self.red.paint("unsafe"))
+            } else {
+                println!("| - system {}", self.red.paint("might be unsafe"),);
+                match reached {
+                    Some(depth) => println!(
+                        "|   no falsification in {} was found for some candidate(s)",
+                        self.bold.paint(format!("{} step(s) or less", depth)),
+                    ),
+                    None => println!("|   BMC was cancelled before checking any depth"),
+                }
+            }
+            if let Some(depth) = query_timed_out {
+                println!(
+                    "|   BMC stopped at depth {}: a query exceeded the time limit of {}ms, Z3 \
+                    answered `unknown` (timeout or incomplete)",
+                    depth,
+                    self.env.query_timeout.unwrap_or(0)
+                )
+            }
+            println!("|===|");
         }
-        if !bmc_res.okay.is_empty() && !bmc_res.cexs.is_empty() {
-            println!("|")
-        }
-        if !bmc_res.cexs.is_empty() {
-            println!(
-                "| - found a {} for the following candidate(s)",
-                self.red.paint("falsification")
-            );
-            for candidate in bmc_res.cexs.keys() {
-                println!("|   `{}`", self.red.paint(*candidate))
+        if let Some(lasso) = lasso.as_ref() {
+            if has_candidates {
+                println!()
             }
+            self.lasso_result(lasso, max)
         }
-        println!("|");
-        if !base.cexs.is_empty() || !bmc_res.cexs.is_empty() {
-            println!("| - system is {}", self.red.paint("unsafe"))
-        } else {
-            println!("| - system {}", self.red.paint("might be unsafe"),);
-            println!(
-                "|   no falsification in {} was found for some candidate(s)",
-                self.bold.paint(format!(
-                    "{} step(s) or less",
-                    max.expect(
-                        "[fatal] cannot have BMC with no max end with unfalsified candidates"
-                    ),
-                )),
-            );
+        if bmc_induction
+            && !bmc_res.okay.is_empty()
+            && !self.env.cancel.is_cancelled()
+            && query_timed_out.is_none()
+        {
+            let depth =
+                max.expect("[fatal] cannot have BMC with no max end with unfalsified candidates");
+            let candidates: Vec<&str> = bmc_res.okay.iter().cloned().collect();
+            self.bmc_induction(depth, &candidates)?
         }
-        println!("|===|");
 
         Ok(())
     }
@@ -567,13 +2777,19 @@ impl<'env> Check<'env> {
     /// Performs the base check.
     pub fn base_check(&self) -> Res<BaseRes> {
         if self.env.verb > 0 {
-            println!("checking {} case...", self.under.paint("base"))
+            self.env
+                .progress(|s| format!("checking {} case...", s.under.paint("base")))
         }
-        let conf = z3_cmd_to_conf(&self.env.z3_cmd)?;
-        let tee = self.smt_log_dir.as_ref().map(std::path::PathBuf::from);
+        self.intent("base check: do all candidates hold in the initial state(s)?")?;
+        let conf = self.env.api_solver_conf(Phase::Base)?;
+        let tee = self.phase_log_dir(Phase::Base);
         let mut base_checker =
             check::Base::new(&self.sys, conf, tee).chain_err(|| "during base checker creation")?;
-        let res = base_checker.check().chain_err(|| "during base check")?;
+        let start = Instant::now();
+        let res = base_checker.check();
+        self.record_api_call(start.elapsed())?;
+        let res = res.chain_err(|| "during base check")?;
+        self.note_res(&res, "holds", "falsified in the initial state(s)")?;
         if self.env.verb > 0 {
             if !res.has_falsifications() {
                 println!(
@@ -599,13 +2815,19 @@ impl<'env> Check<'env> {
     /// Performs the step check.
     pub fn step_check(&self) -> Res<StepRes> {
         if self.env.verb > 0 {
-            println!("checking {} case...", self.under.paint("step"))
+            self.env
+                .progress(|s| format!("checking {} case...", s.under.paint("step")))
         }
-        let conf = z3_cmd_to_conf(&self.env.z3_cmd)?;
-        let tee = self.smt_log_dir.as_ref().map(std::path::PathBuf::from);
+        self.intent("step check: are all candidates preserved by the transition relation?")?;
+        let conf = self.env.api_solver_conf(Phase::Step)?;
+        let tee = self.phase_log_dir(Phase::Step);
         let mut step_checker =
             check::Step::new(&self.sys, conf, tee).chain_err(|| "during step checker creation")?;
-        let res = step_checker.check().chain_err(|| "during step check")?;
+        let start = Instant::now();
+        let res = step_checker.check();
+        self.record_api_call(start.elapsed())?;
+        let res = res.chain_err(|| "during step check")?;
+        self.note_res(&res, "inductive", "not inductive")?;
         if self.env.verb > 0 {
             if !res.has_falsifications() {
                 println!(
@@ -650,19 +2872,127 @@ impl<'env> Check<'env> {
         cex: &check::cexs::Cex,
         is_base: bool,
     ) -> Res<()> {
-        let max_id_len = sys.decls().max_id_len();
+        let trace = self.cex_trace(cex, is_base)?;
+        let unexpected: Vec<_> = cex
+            .unexpected
+            .iter()
+            .map(|(desc, val)| (desc.to_string(), val.to_string()))
+            .collect();
+        self.present_trace(sys, candidate, &trace, &unexpected, is_base)
+    }
+    /// Presents a trace falsifying a candidate.
+    ///
+    /// The `unexpected` values are the ones the solver produced but that are not part of the trace.
+    pub fn present_trace(
+        &self,
+        sys: &trans::Sys,
+        candidate: &str,
+        trace: &Trace,
+        unexpected: &[(String, String)],
+        is_base: bool,
+    ) -> Res<()> {
+        if self.env.json {
+            return if is_base {
+                self.record_cex(candidate, trace)
+            } else {
+                Ok(())
+            };
+        }
         let def = sys.po_s().get(candidate).ok_or_else(|| {
             format!(
                 "failed to retrieve definition for candidate `{}`",
                 candidate,
             )
         })?;
+        let cex_id = if is_base {
+            format!(" [cex {}]", self.cex_id(candidate, trace))
+        } else {
+            String::new()
+        };
+        let name = self.sanitized_text(candidate);
+        let def = self.sanitized_text(&def.to_string());
+        let def_lines = match self.env.width {
+            Some(width) => layout::wrap(
+                &def,
+                width.saturating_sub(name.chars().count() + 7 + cex_id.chars().count()),
+                width.saturating_sub(4),
+            ),
+            None => vec![def],
+        };
         println!(
-            "- `{}` = {}",
-            self.red.paint(candidate),
-            self.bold.paint(format!("{}", def))
+            "- `{}` = {}{}",
+            self.red.paint(name),
+            self.bold.paint(&def_lines[0]),
+            self.gray.paint(cex_id)
         );
-        for (step, values) in &cex.trace {
+        for line in &def_lines[1..] {
+            println!("    {}", self.bold.paint(line))
+        }
+        self.present_steps(sys, trace, is_base)?;
+        if !unexpected.is_empty() {
+            println!("  |=| Z3 produced the following unexpected values");
+            for (desc, val) in unexpected {
+                println!(
+                    "  | {} = {}",
+                    self.red.paint(self.sanitized_text(desc)),
+                    val
+                );
+            }
+        }
+        if is_base {
+            println!(
+                "  |=| {}",
+                self.gray
+                    .paint(metrics::Metrics::new(sys, trace).to_string())
+            );
+        }
+        println!("  |=|");
+        if is_base {
+            self.record_cex(candidate, trace)?
+        }
+        Ok(())
+    }
+
+    /// Presents the steps of a trace, see [`Self::present_trace`].
+    ///
+    /// The trace is sanitized if `--sanitize_cex` is active, see [`sanitize`]. With
+    /// `--cex_max_steps <N>`, only the first and last `N` steps of longer traces are presented.
+    /// Integer values are formatted, see [`value_format`], and changed ones highlighted, see
+    /// [`highlight`]. With `--cex_project`, the values of the projection expressions replace the
+    /// ones of the variables, see [`projection`]. With `--trace_format table`, the trace is
+    /// presented as a table, see [`trace_format`]. Long values are wrapped to the width of the
+    /// terminal, see [`layout`].
+    pub fn present_steps(&self, sys: &trans::Sys, trace: &Trace, is_base: bool) -> Res<()> {
+        let trace = if self.projection.is_empty() {
+            self.sanitized_trace(&self.formatted_trace(trace))
+        } else {
+            self.projected_trace(trace)?
+        };
+        let max_id_len = trace
+            .iter()
+            .flat_map(|(_, values)| values.iter().map(|(var, _)| var.len()))
+            .fold(sys.decls().max_id_len(), usize::max);
+        let elided = match self.env.cex_max_steps {
+            Some(max) if trace.len() > 2 * max => max..(trace.len() - max),
+            _ => 0..0,
+        };
+        if self.env.trace_format == trace_format::Format::Table {
+            self.present_table(&trace, &elided, is_base);
+            return Ok(());
+        }
+        for (idx, (step, values)) in trace.iter().enumerate() {
+            if elided.contains(&idx) {
+                if idx == elided.start {
+                    println!(
+                        "  |=| {}",
+                        self.gray.paint(format!(
+                            "... {} step(s) elided (`--cex_max_steps`) ...",
+                            elided.len()
+                        ))
+                    )
+                }
+                continue;
+            }
             let step_str = if is_base {
                 format!("{}", self.under.paint(step.to_string()))
             } else {
@@ -673,20 +3003,149 @@ impl<'env> Check<'env> {
                 step_str
             };
             println!("  |=| Step {}", step_str);
-            for (var, cst) in values {
-                let var_str = format!("{: >1$}", var.id(), max_id_len);
-                println!("  | {} = {}", self.bold.paint(var_str), cst)
+            // No previous step to compare to right after elided steps.
+            let prev = idx
+                .checked_sub(1)
+                .filter(|prev| !elided.contains(prev))
+                .map(|prev| trace[prev].1.as_slice());
+            if !self.views.is_empty() && self.projection.is_empty() {
+                self.present_views(values, prev, max_id_len);
+                continue;
             }
-        }
-        if !cex.unexpected.is_empty() {
-            println!("  |=| Z3 produced the following unexpected values");
-            for (desc, val) in &cex.unexpected {
-                println!("  | {} = {}", self.red.paint(desc.to_string()), val);
+            for (var, val) in values {
+                let var_str = format!("{: >1$}", var, max_id_len);
+                let width = self
+                    .env
+                    .width
+                    .map(|width| width.saturating_sub(max_id_len + 7));
+                let lines = match width {
+                    Some(width) if val.chars().count() > width => layout::wrap(val, width, width),
+                    _ => {
+                        println!(
+                            "  | {} = {}",
+                            self.bold.paint(var_str),
+                            self.highlighted(var, val, prev)
+                        );
+                        continue;
+                    }
+                };
+                let style = self.change_style(var, val, prev).unwrap_or_default();
+                println!(
+                    "  | {} = {}",
+                    self.bold.paint(var_str),
+                    style.paint(&lines[0])
+                );
+                for line in &lines[1..] {
+                    println!(
+                        "  | {: >3$} {} {}",
+                        "",
+                        layout::CONTINUATION,
+                        style.paint(line),
+                        max_id_len
+                    )
+                }
             }
         }
-        println!("  |=|");
         Ok(())
     }
+
+    /// Trace of a counterexample of the main analyses, completed with `--model_completion`.
+    ///
+    /// Traces starting from the initial states (`from_init`) are completed under the initial
+    /// predicate, other ones under the transition relation only.
+    pub fn cex_trace(&self, cex: &check::cexs::Cex, from_init: bool) -> Res<Trace> {
+        let sys = &self.sys;
+        let mut trace = cex_trace(cex);
+        let first_missing = trace.iter().find_map(|(step, values)| {
+            sys.decls()
+                .iter()
+                .find(|var| !values.iter().any(|(known, _)| known == var.id()))
+                .map(|var| (*step, var.id()))
+        });
+        let Some((step, var)) = first_missing else {
+            return Ok(trace);
+        };
+
+        let completed = match self.env.model_completion {
+            smt::Completion::Error => bail!(
+                "the solver's model has no value for variable `{}` at step {} \
+                (see `--model_completion`)",
+                var,
+                step
+            ),
+            smt::Completion::Default => None,
+            smt::Completion::Solver => {
+                let mut solver = self.solver("completion.smt2")?;
+                solver.intent("completing a counterexample")?;
+                for step in cex.trace.keys() {
+                    solver.declare_state(sys, *step)?
+                }
+                if from_init {
+                    solver.assert_init(sys, 0)?
+                }
+                for (step, values) in cex.trace.iter() {
+                    if *step > 0 {
+                        solver.assert_trans(sys, *step - 1)?
+                    }
+                    for (var, val) in values.iter() {
+                        solver.assert(format!(
+                            "(= {} {})",
+                            smt::svar(var.id(), *step),
+                            smt::cst(val)
+                        ))?
+                    }
+                }
+                if !solver.check_sat()? {
+                    bail!(
+                        "[fatal] failed to complete a counterexample, its values are inconsistent"
+                    )
+                }
+                let mut states = Map::new();
+                for step in cex.trace.keys() {
+                    states.insert(*step, solver.get_state(sys, *step)?);
+                }
+                Some(states)
+            }
+        };
+
+        for (step, values) in trace.iter_mut() {
+            let mut full = Vec::new();
+            for var in sys.decls().iter() {
+                if let Some((_, val)) = values.iter().find(|(known, _)| known == var.id()) {
+                    full.push((var.id().to_string(), val.clone()));
+                    continue;
+                }
+                let val = match completed.as_ref() {
+                    Some(states) => states
+                        .get(step)
+                        .and_then(|state| state.iter().find(|(known, _)| known == var.id()))
+                        .map(|(_, val)| val.val.clone())
+                        .ok_or("[fatal] completed state is missing a variable")?,
+                    None => smt::default_value(var.typ()),
+                };
+                full.push((
+                    var.id().to_string(),
+                    format!("{} (completed)", val.to_value()),
+                ))
+            }
+            *values = full
+        }
+        Ok(trace)
+    }
+}
+
+/// Trace of a counterexample, as the solver produced it.
+pub fn cex_trace(cex: &check::cexs::Cex) -> Trace {
+    cex.trace
+        .iter()
+        .map(|(step, values)| {
+            let values = values
+                .iter()
+                .map(|(var, cst)| (var.id().to_string(), cst.to_string()))
+                .collect();
+            (*step, values)
+        })
+        .collect()
 }
 
 /// Stores the output styles.
@@ -758,10 +3217,15 @@ impl Styles {
         }
     }
 
-    /// Constructor.
-    #[cfg(any(feature = "force-color", not(windows)))]
+    /// Constructor, for stdout.
     pub fn new(color: bool) -> Self {
-        if color && atty::is(atty::Stream::Stdout) {
+        Self::new_for(color, atty::Stream::Stdout)
+    }
+
+    /// Constructor, colors are only active if `stream` is a terminal.
+    #[cfg(any(feature = "force-color", not(windows)))]
+    pub fn new_for(color: bool, stream: atty::Stream) -> Self {
+        if color && atty::is(stream) {
             Self::new_colored()
         } else {
             Self::new_no_color()
@@ -772,7 +3236,7 @@ impl Styles {
     ///
     /// This Windows version always produces colorless style.
     #[cfg(not(any(feature = "force-color", not(windows))))]
-    pub fn new(_: bool) -> Self {
+    pub fn new_for(_: bool, _: atty::Stream) -> Self {
         Self {
             bold: Style::new(),
             under: Style::new(),
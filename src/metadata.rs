@@ -0,0 +1,209 @@
+//! Run metadata, for auditable verification evidence.
+
+mikino_api::prelude!();
+
+use std::{
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    json::{json_array, json_object, json_str},
+    Run,
+};
+
+/// Metadata of a run.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    /// Identifier of the run.
+    pub run_id: String,
+    /// Version of mikino.
+    pub version: String,
+    /// Solver command.
+    pub solver: String,
+    /// Version the solver reports, if any.
+    pub solver_version: Option<String>,
+    /// Input file.
+    pub input: String,
+    /// SHA-256 digest of the input file, in hexadecimal.
+    pub input_sha256: String,
+    /// Date of the run, UTC, in RFC 3339 format.
+    pub date: String,
+    /// Command line.
+    pub command_line: Vec<String>,
+}
+impl Metadata {
+    /// Human-readable fields, for reports.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("run", self.run_id.clone()),
+            ("mikino", self.version.clone()),
+            (
+                "solver",
+                format!(
+                    "{} ({})",
+                    self.solver,
+                    self.solver_version.as_deref().unwrap_or("unknown version")
+                ),
+            ),
+            ("input", self.input.clone()),
+            ("input sha256", self.input_sha256.clone()),
+            ("date", self.date.clone()),
+            ("command line", self.command_line.join(" ")),
+        ]
+    }
+
+    /// JSON object.
+    pub fn to_json(&self) -> String {
+        let command_line: Vec<String> = self.command_line.iter().map(|arg| json_str(arg)).collect();
+        json_object(&[
+            ("run_id", json_str(&self.run_id)),
+            ("mikino_version", json_str(&self.version)),
+            ("solver", json_str(&self.solver)),
+            (
+                "solver_version",
+                self.solver_version
+                    .as_deref()
+                    .map(json_str)
+                    .unwrap_or_else(|| "null".into()),
+            ),
+            ("input", json_str(&self.input)),
+            ("input_sha256", json_str(&self.input_sha256)),
+            ("date", json_str(&self.date)),
+            ("command_line", json_array(&command_line)),
+        ])
+    }
+}
+
+/// First line of the output of `<solver> --version`, `None` if it fails.
+fn solver_version(solver_cmd: &str) -> Option<String> {
+    let bin = solver_cmd.split_whitespace().next()?;
+    let output = Command::new(bin)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+/// Current date, UTC, in RFC 3339 format.
+fn utc_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // Civil date from the number of days since 1970-01-01, Howard Hinnant's algorithm.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// SHA-256 digest of some bytes, in hexadecimal.
+pub fn sha256(bytes: &[u8]) -> String {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut hash: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    // Padding: a `1` bit, zeros, and the length in bits on 64 bits.
+    let mut msg = bytes.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0)
+    }
+    msg.extend_from_slice(&((bytes.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+    for chunk in msg.chunks(64) {
+        let mut w = [0u32; 64];
+        for (idx, word) in chunk.chunks(4).enumerate() {
+            w[idx] = u32::from_be_bytes([word[0], word[1], word[2], word[3]])
+        }
+        for idx in 16..64 {
+            let s0 =
+                w[idx - 15].rotate_right(7) ^ w[idx - 15].rotate_right(18) ^ (w[idx - 15] >> 3);
+            let s1 = w[idx - 2].rotate_right(17) ^ w[idx - 2].rotate_right(19) ^ (w[idx - 2] >> 10);
+            w[idx] = w[idx - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[idx - 7])
+                .wrapping_add(s1)
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hash;
+        for idx in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[idx])
+                .wrapping_add(w[idx]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (hash, val) in hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *hash = hash.wrapping_add(val)
+        }
+    }
+    hash.iter().map(|word| format!("{:08x}", word)).collect()
+}
+
+impl Run {
+    /// Metadata of the run, see [`crate::metadata`].
+    pub fn metadata(&self, input: &str) -> Res<Metadata> {
+        let content =
+            std::fs::read(input).chain_err(|| format!("while reading input file `{}`", input))?;
+        Ok(Metadata {
+            run_id: self.run_id.clone(),
+            version: clap::crate_version!().into(),
+            solver: self.z3_cmd.clone(),
+            solver_version: solver_version(&self.z3_cmd),
+            input: input.into(),
+            input_sha256: sha256(&content),
+            date: utc_now(),
+            command_line: std::env::args().collect(),
+        })
+    }
+}
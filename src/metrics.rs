@@ -0,0 +1,109 @@
+//! Counterexample metrics, to rank counterexamples by likely interest.
+
+mikino_api::prelude!();
+
+use std::fmt;
+
+use trans::Sys;
+
+use crate::{smt, Trace};
+
+/// Metrics of a counterexample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Step the candidate is falsified at.
+    pub depth: usize,
+    /// Number of variables at a boundary value in the falsifying state.
+    pub boundary: usize,
+    /// Number of input changes along the trace.
+    pub input_changes: usize,
+}
+
+/// Numeric value of a mikino value, `None` for booleans.
+fn numeric(val: &str) -> Option<f64> {
+    let val = val.trim_end_matches(" (completed)");
+    match val.split_once('/') {
+        Some((num, den)) => Some(num.trim().parse::<f64>().ok()? / den.trim().parse::<f64>().ok()?),
+        None => val.parse().ok(),
+    }
+}
+
+/// Numeric constants appearing in the system, and their opposites.
+pub fn constants(sys: &Sys) -> Vec<f64> {
+    let mut txt = format!("{} {}", sys.init(), sys.trans());
+    for def in sys.po_s().values() {
+        txt.push_str(&format!(" {}", def))
+    }
+    let mut res: Vec<f64> = txt
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|token| token.parse().ok())
+        .flat_map(|cst: f64| [cst, -cst])
+        .collect();
+    res.sort_by(|lft, rgt| lft.partial_cmp(rgt).expect("constants are not NaN"));
+    res.dedup();
+    res
+}
+
+/// Inputs of a system, the variables the transition relation does not mention in the next state.
+pub fn inputs(sys: &Sys) -> Vec<&str> {
+    let trans = smt::Unroll::new(sys.trans(), 0).to_string();
+    let tokens: Vec<&str> = trans
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .collect();
+    sys.decls()
+        .iter()
+        .map(|var| var.id())
+        .filter(|id| !tokens.contains(&smt::svar(id, 1).as_str()))
+        .collect()
+}
+
+impl Metrics {
+    /// Computes the metrics of a counterexample of a system.
+    pub fn new(sys: &Sys, trace: &Trace) -> Self {
+        let depth = trace.last().map(|(step, _)| *step).unwrap_or(0);
+
+        let constants = constants(sys);
+        let boundary = trace
+            .last()
+            .map(|(_, values)| {
+                values
+                    .iter()
+                    .filter_map(|(_, val)| numeric(val))
+                    .filter(|val| constants.iter().any(|cst| (val - cst).abs() <= 1.0))
+                    .count()
+            })
+            .unwrap_or(0);
+
+        let inputs = inputs(sys);
+        let value = |values: &[(String, String)], var: &str| {
+            values
+                .iter()
+                .find(|(id, _)| id == var)
+                .map(|(_, val)| val.trim_end_matches(" (completed)").to_string())
+        };
+        let input_changes = trace
+            .windows(2)
+            .map(|pair| {
+                inputs
+                    .iter()
+                    .filter(|input| value(&pair[0].1, input) != value(&pair[1].1, input))
+                    .count()
+            })
+            .sum();
+
+        Self {
+            depth,
+            boundary,
+            input_changes,
+        }
+    }
+}
+impl fmt::Display for Metrics {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "falsified at step {}, {} variable(s) at a boundary value, {} input change(s)",
+            self.depth, self.boundary, self.input_changes
+        )
+    }
+}
@@ -1,9 +1,102 @@
 //! Handles run modes.
 
-type App = clap::Command<'static>;
+pub type App = clap::Command<'static>;
 type Arg = clap::Arg<'static>;
 type Matches = clap::ArgMatches;
 
+/// Engines for the check mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Induction and/or BMC.
+    Induction,
+    /// Exact symbolic forward reachability, boolean systems only.
+    Reach,
+    /// Exact forward reachability by explicit state enumeration, small finite systems only.
+    Explicit,
+    /// Backward reachability from the candidates' falsifications.
+    Backward,
+    /// Races simple-path induction, BMC and backward reachability, per candidate.
+    Portfolio,
+    /// Exact images by quantifier elimination, small systems only.
+    Qe,
+}
+impl Engine {
+    /// Engine names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &[
+        "induction",
+        "reach",
+        "explicit",
+        "backward",
+        "portfolio",
+        "qe",
+    ];
+
+    /// Engine from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "induction" => Some(Self::Induction),
+            "reach" => Some(Self::Reach),
+            "explicit" => Some(Self::Explicit),
+            "backward" => Some(Self::Backward),
+            "portfolio" => Some(Self::Portfolio),
+            "qe" => Some(Self::Qe),
+            _ => None,
+        }
+    }
+}
+
+/// Output formats for the graph mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz.
+    Dot,
+    /// Mermaid flowchart, for Markdown documentation.
+    Mermaid,
+}
+impl GraphFormat {
+    /// Format names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &["dot", "mermaid"];
+
+    /// Format from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dot" => Some(Self::Dot),
+            "mermaid" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// Output formats for test vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestsFormat {
+    /// One line per step, one column per variable.
+    Csv,
+    /// An object with the origin of the trace and its steps.
+    Json,
+}
+impl TestsFormat {
+    /// Format names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &["csv", "json"];
+
+    /// Format from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// File extension.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
 /// Run modes.
 #[derive(Debug, Clone)]
 pub enum Mode {
@@ -11,9 +104,55 @@ pub enum Mode {
     Check {
         input: String,
         smt_log: Option<String>,
+        /// System to check if the file declares several ones, all of them if none.
+        system: Option<String>,
+        /// Assumptions conjoined to the initial predicate and the transition relation.
+        assume: Vec<String>,
         induction: bool,
         bmc: bool,
         bmc_max: Option<usize>,
+        /// Depths BMC checks, all of them (up to `bmc_max`) if none.
+        bmc_depths: Option<Vec<usize>>,
+        /// `eventually` objectives BMC looks for lassos for.
+        eventually: Vec<String>,
+        /// Cost expression guiding BMC toward the traces maximizing it, if any.
+        guide: Option<String>,
+        /// Attempts k-induction on the candidates BMC could not falsify, with `k` up to its bound.
+        bmc_induction: bool,
+        /// Maximum `k` for simple-path k-induction on non-inductive candidates, if any.
+        simple_path: Option<usize>,
+        /// Lists the hypotheses and negated goal of the step check for each candidate.
+        explain_step: bool,
+        /// Attempts to prove non-inductive candidates using the inductive ones as lemmas.
+        lemmas: bool,
+        /// Suggests strengthenings that make non-inductive candidates inductive.
+        suggest: bool,
+        /// Reports the fewest transition constraints to violate to falsify unproved candidates.
+        almost_safe: bool,
+        /// Reports the candidates that hold vacuously.
+        check_vacuity: bool,
+        /// Checks the transition relation has no reachable deadlocks.
+        check_deadlock: bool,
+        /// Checks the transition relation has no reachable nondeterministic states.
+        check_determinism: bool,
+        /// Checks every branch of the transition relation is enabled in some reachable state.
+        check_dead_branches: bool,
+        /// Analyzes whether the transition relation and the candidates allow stuttering.
+        check_stuttering: bool,
+        /// File to write an inductive invariant certificate to, if any.
+        certificate: Option<String>,
+        /// File the progress of the strengthening searches is saved to and resumed from, if any.
+        checkpoint: Option<String>,
+        /// Reports the branches of the transition relation the counterexamples exercise.
+        coverage: bool,
+        /// Maximum number of unknown candidates for the run to pass, if any.
+        max_unknown: Option<usize>,
+        /// Minimum safety score for the run to pass, if any.
+        score_threshold: Option<f64>,
+        /// Engine to use.
+        engine: Engine,
+        /// Maximum number of states the `explicit` engine enumerates.
+        max_states: usize,
     },
     /// Script mode, run user's script.
     Script {
@@ -21,10 +160,127 @@ pub enum Mode {
         smt_log: Option<String>,
         verb: usize,
     },
+    /// Reach mode, checks whether the `target` state predicate is reachable.
+    Reach {
+        input: String,
+        smt_log: Option<String>,
+        target: String,
+        bmc_max: Option<usize>,
+        /// Attempts to prove the target is unreachable by simple-path k-induction.
+        prove: bool,
+    },
+    /// Compare-solvers mode, runs the same checks with each of the `solvers`.
+    CompareSolvers {
+        input: String,
+        smt_log: Option<String>,
+        /// Solver commands, at least two.
+        solvers: Vec<String>,
+        bmc_max: Option<usize>,
+    },
+    /// Compare mode, checks two revisions of a system and reports the status changes.
+    Compare {
+        old: String,
+        new: String,
+        smt_log: Option<String>,
+        bmc_max: Option<usize>,
+    },
+    /// Compose mode, checks the components of a file against their contracts and the `main`
+    /// system against these contracts.
+    Compose {
+        input: String,
+        smt_log: Option<String>,
+        /// Name of the main system.
+        main: String,
+        bmc_max: Option<usize>,
+    },
+    /// Sim mode, random concrete executions of the system.
+    Sim {
+        input: String,
+        smt_log: Option<String>,
+        /// Number of transitions of each run.
+        steps: usize,
+        /// Number of runs.
+        runs: usize,
+        /// Seed for the random values, if any.
+        seed: Option<u64>,
+        /// Biases the runs toward falsifications.
+        fuzz: bool,
+        /// Reports the branches of the transition relation the runs exercise.
+        coverage: bool,
+    },
+    /// Testsgen mode, writes test vectors derived from BMC and simulation traces.
+    TestsGen {
+        input: String,
+        smt_log: Option<String>,
+        /// Directory the test vectors are written to.
+        output: String,
+        format: TestsFormat,
+        /// Maximum depth of the falsifications BMC looks for.
+        bmc_max: usize,
+        /// Number of transitions of each simulation run.
+        steps: usize,
+        /// Number of simulation runs.
+        runs: usize,
+        /// Seed for the random values, if any.
+        seed: Option<u64>,
+    },
+    /// Mutate mode, mutation analysis of the candidates.
+    Mutate {
+        input: String,
+        smt_log: Option<String>,
+        /// Maximum depth of the falsifications BMC looks for.
+        bmc_max: usize,
+        /// Mutates the transition relation instead of the candidates.
+        trans: bool,
+    },
+    /// Count mode, counts the reachable states and how many BMC explores.
+    Count {
+        input: String,
+        smt_log: Option<String>,
+        /// Depth of the BMC run the exploration is measured for.
+        bmc_max: usize,
+        /// Maximum number of states enumerated before falling back to sampling.
+        max_states: usize,
+        /// Number of transitions of each sampling run.
+        steps: usize,
+        /// Number of sampling runs.
+        runs: usize,
+        /// Seed for the random values, if any.
+        seed: Option<u64>,
+    },
     /// Demo mode, generate a demo system to `target` if `check`, otherwise generates a demo script.
-    Demo { check: bool, target: String },
+    Demo {
+        check: bool,
+        target: String,
+        /// Overwrites `target` without asking if it exists.
+        force: bool,
+    },
     /// Parse mode, does nothing but parse the system.
     Parse { input: String },
+    /// Examples mode, prints the commands of the end-to-end workflows, all of them if `None`.
+    Examples { workflow: Option<String> },
+    /// Show-cex mode, presents a counterexample from the store.
+    ShowCex { id: String },
+    /// Scenario mode, converts a counterexample from the store into constraints fixing its values.
+    Scenario {
+        id: String,
+        /// Number of steps fixed, all of them if none.
+        steps: Option<usize>,
+        /// Variables fixed, all of them if none.
+        vars: Option<Vec<String>>,
+    },
+    /// Graph mode, renders the structure of the system.
+    Graph {
+        input: String,
+        /// File the graph is written to, stdout if none.
+        output: Option<String>,
+        format: GraphFormat,
+    },
+    /// Whats-new mode, prints the changes since the last version seen.
+    WhatsNew {
+        /// Version to print the changes after, the last version seen if none.
+        since: Option<String>,
+    },
 }
 
 impl Mode {
@@ -35,7 +291,20 @@ impl Mode {
             cla::script_subcommand(),
             cla::demo(),
             cla::bmc_subcommand(),
+            cla::reach_subcommand(),
+            cla::compare_solvers_subcommand(),
+            cla::compare_subcommand(),
+            cla::compose_subcommand(),
+            cla::sim_subcommand(),
+            cla::testsgen_subcommand(),
+            cla::mutate_subcommand(),
+            cla::count_subcommand(),
             cla::parse_subcommand(),
+            cla::examples_subcommand(),
+            cla::show_cex_subcommand(),
+            cla::scenario_subcommand(),
+            cla::graph_subcommand(),
+            cla::whats_new_subcommand(),
         ]
     }
 
@@ -45,8 +314,21 @@ impl Mode {
             cla::try_check,
             cla::try_script,
             cla::try_bmc,
+            cla::try_reach,
+            cla::try_compare_solvers,
+            cla::try_compare,
+            cla::try_compose,
+            cla::try_sim,
+            cla::try_testsgen,
+            cla::try_mutate,
+            cla::try_count,
             cla::try_demo,
             cla::try_parse,
+            cla::try_examples,
+            cla::try_show_cex,
+            cla::try_scenario,
+            cla::try_graph,
+            cla::try_whats_new,
         ];
         for try_mode in &modes {
             let maybe_res = try_mode(smt_log.clone(), matches);
@@ -67,25 +349,80 @@ pub mod cla {
         pub const SCRIPT: &str = "script";
         pub const DEMO: &str = "demo";
         pub const BMC: &str = "bmc";
+        pub const REACH: &str = "reach";
+        pub const COMPARE_SOLVERS: &str = "compare-solvers";
+        pub const COMPARE: &str = "compare";
+        pub const COMPOSE: &str = "compose";
+        pub const SIM: &str = "sim";
+        pub const TESTSGEN: &str = "testsgen";
+        pub const MUTATE: &str = "mutate";
+        pub const COUNT: &str = "count";
         pub const PARSE: &str = "parse";
+        pub const EXAMPLES: &str = "examples";
+        pub const SHOW_CEX: &str = "show-cex";
+        pub const SCENARIO: &str = "scenario";
+        pub const GRAPH: &str = "graph";
+        pub const WHATS_NEW: &str = "whats-new";
     }
 
     mod arg {
         pub const BMC_KEY: &str = "BMC";
         pub const BMC_MAX_KEY: &str = "BMC_MAX";
+        pub const BMC_DEPTHS_KEY: &str = "BMC_DEPTHS";
+        pub const EVENTUALLY_KEY: &str = "EVENTUALLY";
+        pub const GUIDE_KEY: &str = "GUIDE";
+        pub const BMC_INDUCTION_KEY: &str = "BMC_INDUCTION";
+        pub const ASSUME_KEY: &str = "ASSUME";
+        pub const SIMPLE_PATH_KEY: &str = "SIMPLE_PATH";
+        pub const EXPLAIN_STEP_KEY: &str = "EXPLAIN_STEP";
+        pub const LEMMAS_KEY: &str = "LEMMAS";
+        pub const SUGGEST_KEY: &str = "SUGGEST";
+        pub const VACUITY_KEY: &str = "VACUITY";
+        pub const ALMOST_SAFE_KEY: &str = "ALMOST_SAFE";
+        pub const DEADLOCK_KEY: &str = "DEADLOCK";
+        pub const DETERMINISM_KEY: &str = "DETERMINISM";
+        pub const DEAD_BRANCHES_KEY: &str = "DEAD_BRANCHES";
+        pub const STUTTERING_KEY: &str = "STUTTERING";
+        pub const CERTIFICATE_KEY: &str = "CERTIFICATE";
+        pub const CHECKPOINT_KEY: &str = "CHECKPOINT";
+        pub const COVERAGE_KEY: &str = "COVERAGE";
+        pub const MAX_UNKNOWN_KEY: &str = "MAX_UNKNOWN";
+        pub const SCORE_THRESHOLD_KEY: &str = "SCORE_THRESHOLD";
+        pub const ENGINE_KEY: &str = "ENGINE";
+        pub const MAX_STATES_KEY: &str = "MAX_STATES";
+        pub const TARGET_KEY: &str = "TARGET";
+        pub const MAIN_KEY: &str = "MAIN";
+        pub const PROVE_KEY: &str = "PROVE";
+        pub const SOLVERS_KEY: &str = "SOLVERS";
+        pub const OLD_KEY: &str = "OLD";
+        pub const NEW_KEY: &str = "NEW";
+        pub const STEPS_KEY: &str = "STEPS";
+        pub const RUNS_KEY: &str = "RUNS";
+        pub const SEED_KEY: &str = "SEED";
+        pub const FUZZ_KEY: &str = "FUZZ";
+        pub const OUTPUT_KEY: &str = "OUTPUT";
+        pub const FORMAT_KEY: &str = "FORMAT";
+        pub const TRANS_KEY: &str = "TRANS";
         pub const SMT_LOG_KEY: &str = "SMT_LOG";
+        pub const SMT_LOG_PER_RUN_KEY: &str = "SMT_LOG_PER_RUN";
         pub const SYS_KEY: &str = "SYS_KEY";
+        pub const SYSTEM_KEY: &str = "SYSTEM";
         pub const SCRIPT_KEY: &str = "SCRIPT_KEY";
         pub const SCRIPT_VERBOSE_KEY: &str = "SCRIPT_VERBOSE";
         pub const DEMO_SCRIPT_KEY: &str = "DEMO_SCRIPT";
+        pub const FORCE_KEY: &str = "FORCE";
         pub const DEMO_TGT_KEY: &str = "DEMO_TGT";
+        pub const WORKFLOW_KEY: &str = "WORKFLOW";
+        pub const CEX_ID_KEY: &str = "CEX_ID";
+        pub const VARS_KEY: &str = "VARS";
+        pub const SINCE_KEY: &str = "SINCE";
     }
 
     fn bmc_max_arg() -> Arg {
         Arg::new(arg::BMC_MAX_KEY)
             .help(
                 "Maximum number of transitions ≥ 0 allowed from the \
-                initial state(s) in BMC, infinite by default",
+                initial state(s) in BMC and backward analysis, infinite by default",
             )
             .long("bmc_max")
             .validator(validate_int)
@@ -100,6 +437,205 @@ pub mod cla {
         })
     }
 
+    fn bmc_depths_arg() -> Arg {
+        Arg::new(arg::BMC_DEPTHS_KEY)
+            .help(
+                "Only checks the depths listed in BMC, for instance `3,7,12-20`; \
+                depths above `--bmc_max` are ignored",
+            )
+            .long("bmc_depths")
+            .validator(validate_depths)
+            .value_name("DEPTHS")
+    }
+    /// Yields the BMC depths, if any.
+    fn get_bmc_depths(matches: &Matches, mut if_present_do: impl FnMut()) -> Option<Vec<usize>> {
+        matches.value_of(arg::BMC_DEPTHS_KEY).map(|val| {
+            if_present_do();
+            parse_depths(val)
+                .unwrap_or_else(|e| panic!("[clap] unexpected value for BMC depths: {}", e))
+        })
+    }
+
+    fn eventually_arg() -> Arg {
+        Arg::new(arg::EVENTUALLY_KEY)
+            .help(
+                "Objective `eventually <EXPR>`, BMC looks for lassos on which <EXPR> never holds; \
+                can be repeated",
+            )
+            .long("eventually")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .value_name("EXPR")
+    }
+    /// Yields the `eventually` objectives.
+    fn get_eventually(matches: &Matches, mut if_present_do: impl FnMut()) -> Vec<String> {
+        let res: Vec<String> = matches
+            .values_of(arg::EVENTUALLY_KEY)
+            .map(|vals| vals.map(String::from).collect())
+            .unwrap_or_default();
+        if !res.is_empty() {
+            if_present_do()
+        }
+        res
+    }
+
+    fn guide_arg() -> Arg {
+        Arg::new(arg::GUIDE_KEY)
+            .help(
+                "Arithmetic cost expression over the state variables, BMC first follows the \
+                successors maximizing it up to `--bmc_max` before exhaustive BMC (Z3 only)",
+            )
+            .long("guide")
+            .value_name("EXPR")
+    }
+    /// Yields the guide expression, if any.
+    fn get_guide(matches: &Matches, mut if_present_do: impl FnMut()) -> Option<String> {
+        matches.value_of(arg::GUIDE_KEY).map(|val| {
+            if_present_do();
+            val.into()
+        })
+    }
+
+    fn assume_arg() -> Arg {
+        Arg::new(arg::ASSUME_KEY)
+            .help(
+                "Assumption conjoined to the initial predicate and the transition relation \
+                for this run; can be repeated",
+            )
+            .long("assume")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .value_name("EXPR")
+    }
+    /// Yields the assumptions.
+    fn get_assume(matches: &Matches) -> Vec<String> {
+        matches
+            .values_of(arg::ASSUME_KEY)
+            .map(|vals| vals.map(String::from).collect())
+            .unwrap_or_default()
+    }
+
+    fn bmc_induction_arg() -> Arg {
+        Arg::new(arg::BMC_INDUCTION_KEY)
+            .help(
+                "When BMC finds no falsification up to `--bmc_max`, attempts k-induction \
+                on the candidates left with k up to `--bmc_max + 1`, using BMC as the base case",
+            )
+            .long("bmc_induction")
+            .requires(arg::BMC_MAX_KEY)
+    }
+
+    fn coverage_arg() -> Arg {
+        Arg::new(arg::COVERAGE_KEY)
+            .help(
+                "Reports which branches of the transition relation (`if` branches and \
+                disjuncts) the traces produced exercise, as a coverage table",
+            )
+            .long("coverage")
+    }
+
+    fn simple_path_arg() -> Arg {
+        Arg::new(arg::SIMPLE_PATH_KEY)
+            .help(
+                "Attempts to prove non-inductive candidates by k-induction with \
+                simple-path constraints (pairwise distinct states), for k up to <INT>",
+            )
+            .long("simple_path")
+            .validator(validate_int)
+            .value_name("INT")
+    }
+    /// Yields the simple-path max `k`, if any.
+    fn get_simple_path(matches: &Matches) -> Option<usize> {
+        matches.value_of(arg::SIMPLE_PATH_KEY).map(|val| {
+            val.parse()
+                .unwrap_or_else(|_| panic!("[clap] unexpected value for simple path: `{}`", val))
+        })
+    }
+
+    fn max_unknown_arg() -> Arg {
+        Arg::new(arg::MAX_UNKNOWN_KEY)
+            .help(
+                "The run passes (exit code 0) if no candidate is falsified and at most <INT> \
+                candidates are unknown, all others being proved; fails with exit code 1 if \
+                some are falsified and 2 otherwise, the unknown candidates are listed either way",
+            )
+            .long("max_unknown")
+            .validator(validate_int)
+            .value_name("INT")
+    }
+    /// Yields the maximum number of unknown candidates, if any.
+    fn get_max_unknown(matches: &Matches) -> Option<usize> {
+        matches.value_of(arg::MAX_UNKNOWN_KEY).map(|val| {
+            val.parse()
+                .unwrap_or_else(|_| panic!("[clap] unexpected value for max unknown: `{}`", val))
+        })
+    }
+
+    fn score_threshold_arg() -> Arg {
+        Arg::new(arg::SCORE_THRESHOLD_KEY)
+            .help(
+                "The run passes (exit code 0) if the weighted share of proved candidates is at \
+                least <PERCENT>, candidates weigh 1 unless annotated with `#[weight(<INT>)]`",
+            )
+            .long("score_threshold")
+            .validator(validate_percent)
+            .value_name("PERCENT")
+            .conflicts_with(arg::MAX_UNKNOWN_KEY)
+    }
+    /// Yields the safety score threshold, if any.
+    fn get_score_threshold(matches: &Matches) -> Option<f64> {
+        matches.value_of(arg::SCORE_THRESHOLD_KEY).map(|val| {
+            val.parse().unwrap_or_else(|_| {
+                panic!("[clap] unexpected value for score threshold: `{}`", val)
+            })
+        })
+    }
+
+    fn engine_arg() -> Arg {
+        Arg::new(arg::ENGINE_KEY)
+            .help(
+                "Engine to use; `reach` computes the exact set of reachable states \
+                symbolically and only supports boolean systems (Z3 only), `explicit` enumerates \
+                them and only supports small finite systems (see `--max_states`), `backward` searches \
+                backward from the falsifications of the candidates (bounded by `--bmc_max` if \
+                any), \
+                `portfolio` races induction, BMC and backward analysis for each candidate, \
+                `qe` computes exact images by quantifier elimination (Z3 only, small systems, \
+                at most `--bmc_max` images if any)",
+            )
+            .long("engine")
+            .possible_values(Engine::NAMES)
+            .default_value("induction")
+            .value_name("ENGINE")
+    }
+    /// Yields the engine.
+    fn get_engine(matches: &Matches) -> Engine {
+        let name = matches
+            .value_of(arg::ENGINE_KEY)
+            .expect("argument with default value");
+        Engine::from_name(name).unwrap_or_else(|| panic!("[clap] unexpected engine `{}`", name))
+    }
+
+    fn max_states_arg() -> Arg {
+        Arg::new(arg::MAX_STATES_KEY)
+            .help(
+                "Maximum number of reachable states the `explicit` engine enumerates, it fails \
+                on systems with more reachable states",
+            )
+            .long("max_states")
+            .validator(validate_int)
+            .default_value("10000")
+            .value_name("INT")
+    }
+    /// Yields the maximum number of states the `explicit` engine enumerates.
+    fn get_max_states(matches: &Matches) -> usize {
+        let val = matches
+            .value_of(arg::MAX_STATES_KEY)
+            .expect("argument with default value");
+        val.parse()
+            .unwrap_or_else(|_| panic!("[clap] unexpected value for max states: `{}`", val))
+    }
+
     pub fn smt_log_arg() -> Arg {
         Arg::new(arg::SMT_LOG_KEY)
             .help("Activates SMT logging in the directory specified")
@@ -107,9 +643,20 @@ pub mod cla {
             .short('l')
             .value_name("DIR")
     }
+    pub fn smt_log_per_run_arg() -> Arg {
+        Arg::new(arg::SMT_LOG_PER_RUN_KEY)
+            .help(
+                "SMT logs go to a `run-<ID>` sub-directory of the `--smt_log` directory, \
+                named after the run identifier, so that runs do not overwrite each other's logs",
+            )
+            .long("smt_log_per_run")
+    }
     pub fn get_smt_log(matches: &Matches) -> Option<String> {
         matches.value_of(arg::SMT_LOG_KEY).map(String::from)
     }
+    pub fn get_smt_log_per_run(matches: &Matches) -> bool {
+        matches.is_present(arg::SMT_LOG_PER_RUN_KEY)
+    }
 
     fn sys_arg() -> Arg {
         Arg::new(arg::SYS_KEY)
@@ -124,6 +671,19 @@ pub mod cla {
             .into()
     }
 
+    fn system_arg() -> Arg {
+        Arg::new(arg::SYSTEM_KEY)
+            .help(
+                "Selects a system when the file declares several `system <NAME> { ... }`, \
+                all of them are checked and reported separately by default",
+            )
+            .long("sys")
+            .value_name("NAME")
+    }
+    fn get_system(matches: &Matches) -> Option<String> {
+        matches.value_of(arg::SYSTEM_KEY).map(String::from)
+    }
+
     fn script_arg() -> Arg {
         Arg::new(arg::SCRIPT_KEY)
             .help("Hsmt script to run (run `mikino demo -h` mode for details)")
@@ -148,8 +708,96 @@ pub mod cla {
                         looks for a falsification for candidates found to not be inductive",
                     )
                     .long("bmc"),
+                assume_arg(),
                 bmc_max_arg(),
+                bmc_depths_arg(),
+                eventually_arg(),
+                guide_arg(),
+                bmc_induction_arg(),
+                simple_path_arg(),
+                Arg::new(arg::EXPLAIN_STEP_KEY)
+                    .help(
+                        "Lists, in human-readable form, the formulas the step check assumes \
+                        and the one it asserts negated for each candidate",
+                    )
+                    .long("explain_step"),
+                Arg::new(arg::LEMMAS_KEY)
+                    .help(
+                        "Attempts to prove non-inductive candidates by assuming the inductive \
+                        ones in the step check, and the non-inductive ones together, until \
+                        a fixpoint is reached",
+                    )
+                    .long("lemmas"),
+                Arg::new(arg::SUGGEST_KEY)
+                    .help(
+                        "Tries simple strengthenings on non-inductive candidates: bounds on \
+                        each variable and relations between pairs of variables, excluding a \
+                        counterexample to induction; reports the ones that make them inductive",
+                    )
+                    .long("suggest"),
+                Arg::new(arg::ALMOST_SAFE_KEY)
+                    .help(
+                        "For candidates not proved by induction, finds the fewest transition \
+                        constraints (top-level conjuncts) to violate to falsify them in \
+                        `--bmc_max` steps or less (10 by default), with MaxSMT (Z3 only)",
+                    )
+                    .long("almost_safe"),
+                Arg::new(arg::VACUITY_KEY)
+                    .help(
+                        "Reports the candidates that hold vacuously: valid ones, ones implied \
+                        by the initial and transition predicates alone, and implications with \
+                        an unreachable antecedent",
+                    )
+                    .long("check_vacuity"),
+                Arg::new(arg::DEADLOCK_KEY)
+                    .help(
+                        "Checks the transition relation is total in the reachable states, \
+                        reports a trace to a deadlocked state (one with no successor) if any; \
+                        looks for it up to `--bmc_max` (10 by default)",
+                    )
+                    .long("check_deadlock"),
+                Arg::new(arg::DETERMINISM_KEY)
+                    .help(
+                        "Checks the transition relation is deterministic in the reachable \
+                        states, reports a trace to a state with two distinct successors if any; \
+                        looks for it up to `--bmc_max` (10 by default)",
+                    )
+                    .long("check_determinism"),
+                Arg::new(arg::DEAD_BRANCHES_KEY)
+                    .help(
+                        "Reports the branches of the transition relation (`if`s and disjuncts) \
+                        that no reachable state enables, which are usually specification bugs; \
+                        looks for enabling states up to `--bmc_max` (10 by default)",
+                    )
+                    .long("check_dead_branches"),
+                Arg::new(arg::STUTTERING_KEY)
+                    .help(
+                        "Reports whether the transition relation allows stuttering (steps \
+                        leaving all variables unchanged), and warns about candidates and \
+                        `eventually` objectives that implicitly assume it does or does not",
+                    )
+                    .long("check_stuttering"),
+                Arg::new(arg::CERTIFICATE_KEY)
+                    .help(
+                        "When all candidates are proved, writes their conjunction as an \
+                        inductive invariant to a standalone SMT-LIB 2 script any solver can audit",
+                    )
+                    .long("certificate")
+                    .value_name("FILE"),
+                Arg::new(arg::CHECKPOINT_KEY)
+                    .help(
+                        "Saves the progress of `--lemmas` and `--simple_path` to <FILE> as they \
+                        go, and resumes from it if it was saved for the same system",
+                    )
+                    .long("checkpoint")
+                    .value_name("FILE"),
+                coverage_arg(),
+                max_unknown_arg(),
+                score_threshold_arg(),
+                engine_arg(),
+                max_states_arg(),
                 smt_log_arg(),
+                system_arg(),
                 sys_arg(),
             ])
     }
@@ -158,16 +806,62 @@ pub mod cla {
 
         let input = get_sys(matches);
         let smt_log = get_smt_log(matches).or(smt_log);
+        let system = get_system(matches);
+        let assume = get_assume(matches);
 
         let mut bmc = matches.is_present(arg::BMC_KEY);
         let bmc_max = get_bmc_max(matches, || bmc = true);
+        let bmc_depths = get_bmc_depths(matches, || bmc = true);
+        let eventually = get_eventually(matches, || bmc = true);
+        let guide = get_guide(matches, || bmc = true);
+        let bmc_induction = matches.is_present(arg::BMC_INDUCTION_KEY);
+        let simple_path = get_simple_path(matches);
+        let explain_step = matches.is_present(arg::EXPLAIN_STEP_KEY);
+        let lemmas = matches.is_present(arg::LEMMAS_KEY);
+        let suggest = matches.is_present(arg::SUGGEST_KEY);
+        let almost_safe = matches.is_present(arg::ALMOST_SAFE_KEY);
+        let check_vacuity = matches.is_present(arg::VACUITY_KEY);
+        let check_deadlock = matches.is_present(arg::DEADLOCK_KEY);
+        let check_determinism = matches.is_present(arg::DETERMINISM_KEY);
+        let check_dead_branches = matches.is_present(arg::DEAD_BRANCHES_KEY);
+        let check_stuttering = matches.is_present(arg::STUTTERING_KEY);
+        let certificate = matches.value_of(arg::CERTIFICATE_KEY).map(String::from);
+        let checkpoint = matches.value_of(arg::CHECKPOINT_KEY).map(String::from);
+        let coverage = matches.is_present(arg::COVERAGE_KEY);
+        let max_unknown = get_max_unknown(matches);
+        let score_threshold = get_score_threshold(matches);
+        let engine = get_engine(matches);
+        let max_states = get_max_states(matches);
 
         Some(Mode::Check {
             input,
             smt_log,
+            system,
+            assume,
             induction: true,
             bmc,
             bmc_max,
+            bmc_depths,
+            eventually,
+            guide,
+            bmc_induction,
+            simple_path,
+            explain_step,
+            lemmas,
+            suggest,
+            almost_safe,
+            check_vacuity,
+            check_deadlock,
+            check_determinism,
+            check_dead_branches,
+            check_stuttering,
+            certificate,
+            checkpoint,
+            coverage,
+            max_unknown,
+            score_threshold,
+            engine,
+            max_states,
         })
     }
 
@@ -205,7 +899,8 @@ pub mod cla {
             .about(
                 "Generates a demo transition system file, \
                 recommended if you are just starting out. \
-                /!\\ OVERWRITES the target file.\n\n\
+                An existing target file is only overwritten with `--force`, \
+                or after confirmation in interactive sessions.\n\n\
                 Use `--script` to generate a demo script instead.",
             )
             .args(&[
@@ -213,6 +908,10 @@ pub mod cla {
                     .short('s')
                     .long("script")
                     .help("generate a demo **script**"),
+                Arg::new(arg::FORCE_KEY)
+                    .short('f')
+                    .long("force")
+                    .help("overwrite the target file if it exists"),
                 Arg::new(arg::DEMO_TGT_KEY)
                     .help("Path of the file to write the demo file to")
                     .required(true),
@@ -225,8 +924,13 @@ pub mod cla {
             .expect("[clap]: required argument cannot be absent")
             .into();
         let check = matches.occurrences_of(arg::DEMO_SCRIPT_KEY) == 0;
+        let force = matches.is_present(arg::FORCE_KEY);
 
-        Some(Mode::Demo { target, check })
+        Some(Mode::Demo {
+            target,
+            check,
+            force,
+        })
     }
 
     /// Subcommand for the bmc mode.
@@ -236,21 +940,480 @@ pub mod cla {
                 "Runs BMC (Bounded Model Checking) without induction. \
             Mikino will search for a falsification for each proof objective.",
             )
-            .args(&[bmc_max_arg(), smt_log_arg(), sys_arg()])
+            .args(&[
+                assume_arg(),
+                bmc_max_arg(),
+                bmc_depths_arg(),
+                eventually_arg(),
+                guide_arg(),
+                bmc_induction_arg(),
+                coverage_arg(),
+                smt_log_arg(),
+                system_arg(),
+                sys_arg(),
+            ])
     }
     pub fn try_bmc(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
         let matches = matches.subcommand_matches(mode::BMC)?;
         let bmc_max = get_bmc_max(matches, || ());
+        let bmc_depths = get_bmc_depths(matches, || ());
+        let eventually = get_eventually(matches, || ());
+        let guide = get_guide(matches, || ());
+        let bmc_induction = matches.is_present(arg::BMC_INDUCTION_KEY);
+        let coverage = matches.is_present(arg::COVERAGE_KEY);
         let smt_log = get_smt_log(matches).or(smt_log);
         let input = get_sys(matches);
+        let system = get_system(matches);
+        let assume = get_assume(matches);
         let induction = false;
         let bmc = true;
         Some(Mode::Check {
             input,
+            system,
             bmc,
             bmc_max,
+            bmc_depths,
+            eventually,
+            guide,
+            bmc_induction,
             induction,
             smt_log,
+            assume,
+            simple_path: None,
+            explain_step: false,
+            lemmas: false,
+            suggest: false,
+            almost_safe: false,
+            check_vacuity: false,
+            check_deadlock: false,
+            check_determinism: false,
+            check_dead_branches: false,
+            check_stuttering: false,
+            certificate: None,
+            checkpoint: None,
+            coverage,
+            max_unknown: None,
+            score_threshold: None,
+            engine: Engine::Induction,
+            max_states: 0,
+        })
+    }
+
+    /// Subcommand for the reach mode.
+    pub fn reach_subcommand() -> App {
+        Command::new(mode::REACH)
+            .about(
+                "Checks whether a state predicate is reachable, \
+                and prints a witness trace if it is.",
+            )
+            .args(&[
+                Arg::new(arg::TARGET_KEY)
+                    .help("State predicate to reach, in the syntax of the candidates")
+                    .long("target")
+                    .short('t')
+                    .value_name("EXPR")
+                    .required(true),
+                Arg::new(arg::PROVE_KEY)
+                    .help(
+                        "Also attempts to prove the target is unreachable by simple-path \
+                        k-induction, with k bounded by `--bmc_max` if any",
+                    )
+                    .long("prove"),
+                bmc_max_arg(),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_reach(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::REACH)?;
+        let target = matches
+            .value_of(arg::TARGET_KEY)
+            .expect("[clap] required target argument cannot be absent")
+            .into();
+        let bmc_max = get_bmc_max(matches, || ());
+        let prove = matches.is_present(arg::PROVE_KEY);
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::Reach {
+            input,
+            smt_log,
+            target,
+            bmc_max,
+            prove,
+        })
+    }
+
+    /// Subcommand for the compare-solvers mode.
+    pub fn compare_solvers_subcommand() -> App {
+        Command::new(mode::COMPARE_SOLVERS)
+            .about(
+                "Runs the same checks with several solvers, \
+                and reports verdict agreement and time ratios.",
+            )
+            .args(&[
+                Arg::new(arg::SOLVERS_KEY)
+                    .help(
+                        "Comma-separated solver commands, at least two; \
+                        Z3, CVC5 and Yices are recognized from the name of the binary",
+                    )
+                    .long("solvers")
+                    .value_name("CMDS")
+                    .validator(validate_solvers)
+                    .required(true),
+                Arg::new(arg::BMC_MAX_KEY)
+                    .help("Also compares BMC checks up to this depth")
+                    .long("bmc_max")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_compare_solvers(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::COMPARE_SOLVERS)?;
+        let solvers = parse_solvers(
+            matches
+                .value_of(arg::SOLVERS_KEY)
+                .expect("[clap] required solvers argument cannot be absent"),
+        );
+        let bmc_max = get_bmc_max(matches, || ());
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::CompareSolvers {
+            input,
+            smt_log,
+            solvers,
+            bmc_max,
+        })
+    }
+
+    /// Subcommand for the compare mode.
+    pub fn compare_subcommand() -> App {
+        Command::new(mode::COMPARE)
+            .about(
+                "Checks two revisions of a system, \
+                and reports the candidates whose status changed.",
+            )
+            .args(&[
+                Arg::new(arg::BMC_MAX_KEY)
+                    .help("Depth up to which BMC looks for falsifications, 10 by default")
+                    .long("bmc_max")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                smt_log_arg(),
+                Arg::new(arg::OLD_KEY)
+                    .help("Old revision of the system")
+                    .required(true)
+                    .value_name("OLD"),
+                Arg::new(arg::NEW_KEY)
+                    .help("New revision of the system")
+                    .required(true)
+                    .value_name("NEW"),
+            ])
+    }
+    pub fn try_compare(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::COMPARE)?;
+        let bmc_max = get_bmc_max(matches, || ());
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let get = |key: &str| {
+            matches
+                .value_of(key)
+                .expect("[clap] required revision argument cannot be absent")
+                .to_string()
+        };
+        Some(Mode::Compare {
+            old: get(arg::OLD_KEY),
+            new: get(arg::NEW_KEY),
+            smt_log,
+            bmc_max,
+        })
+    }
+
+    /// Subcommand for the compose mode.
+    pub fn compose_subcommand() -> App {
+        Command::new(mode::COMPOSE)
+            .about(
+                "Checks each named system of a file against its `assume` block and candidates, \
+                and the main system against the resulting contracts.",
+            )
+            .args(&[
+                Arg::new(arg::MAIN_KEY)
+                    .help("Name of the main system, the other systems are its components")
+                    .long("main")
+                    .default_value("main")
+                    .value_name("NAME"),
+                bmc_max_arg(),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_compose(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::COMPOSE)?;
+        let main = matches
+            .value_of(arg::MAIN_KEY)
+            .expect("[clap] main argument has a default value")
+            .into();
+        let bmc_max = get_bmc_max(matches, || ());
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::Compose {
+            input,
+            smt_log,
+            main,
+            bmc_max,
+        })
+    }
+
+    /// Subcommand for the sim mode.
+    pub fn sim_subcommand() -> App {
+        Command::new(mode::SIM)
+            .about(
+                "Generates random concrete executions of the system, \
+                and evaluates the candidates along the way.",
+            )
+            .args(&[
+                Arg::new(arg::STEPS_KEY)
+                    .help("Number of transitions of each run")
+                    .long("steps")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::RUNS_KEY)
+                    .help("Number of runs")
+                    .long("runs")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::SEED_KEY)
+                    .help("Seed for the random values, for reproducible runs")
+                    .long("seed")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                Arg::new(arg::FUZZ_KEY)
+                    .help(
+                        "Biases the runs toward falsifications: successors falsifying a \
+                        candidate are preferred, otherwise successors are pushed toward the \
+                        falsification of one of the candidates",
+                    )
+                    .long("fuzz"),
+                coverage_arg(),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_sim(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::SIM)?;
+        let int = |key: &str| {
+            matches.value_of(key).map(|val| {
+                usize::from_str_radix(val, 10)
+                    .expect("[clap] argument validation did not catch integer parsing error")
+            })
+        };
+        let steps = int(arg::STEPS_KEY).expect("argument with default value");
+        let runs = int(arg::RUNS_KEY).expect("argument with default value");
+        let seed = int(arg::SEED_KEY).map(|seed| seed as u64);
+        let fuzz = matches.is_present(arg::FUZZ_KEY);
+        let coverage = matches.is_present(arg::COVERAGE_KEY);
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::Sim {
+            input,
+            smt_log,
+            steps,
+            runs,
+            seed,
+            fuzz,
+            coverage,
+        })
+    }
+
+    /// Subcommand for the testsgen mode.
+    pub fn testsgen_subcommand() -> App {
+        Command::new(mode::TESTSGEN)
+            .about(
+                "Writes test vectors derived from traces: the shortest falsification of each \
+                candidate found by BMC, and random simulation runs.",
+            )
+            .args(&[
+                Arg::new(arg::OUTPUT_KEY)
+                    .help("Directory to write the test vectors to, created if needed")
+                    .short('o')
+                    .long("output")
+                    .value_name("DIR")
+                    .required(true),
+                Arg::new(arg::FORMAT_KEY)
+                    .help("Format of the test vectors")
+                    .long("format")
+                    .possible_values(TestsFormat::NAMES)
+                    .default_value("csv")
+                    .value_name("FORMAT"),
+                Arg::new(arg::BMC_MAX_KEY)
+                    .help("Maximum depth of the falsifications BMC looks for")
+                    .long("bmc_max")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::STEPS_KEY)
+                    .help("Number of transitions of each simulation run")
+                    .long("steps")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::RUNS_KEY)
+                    .help("Number of simulation runs")
+                    .long("runs")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::SEED_KEY)
+                    .help("Seed for the random values, for reproducible runs")
+                    .long("seed")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_testsgen(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::TESTSGEN)?;
+        let int = |key: &str| {
+            matches.value_of(key).map(|val| {
+                usize::from_str_radix(val, 10)
+                    .expect("[clap] argument validation did not catch integer parsing error")
+            })
+        };
+        let output = matches
+            .value_of(arg::OUTPUT_KEY)
+            .expect("[clap] required output argument cannot be absent")
+            .into();
+        let format = matches
+            .value_of(arg::FORMAT_KEY)
+            .and_then(TestsFormat::from_name)
+            .expect("argument with default value");
+        let bmc_max = int(arg::BMC_MAX_KEY).expect("argument with default value");
+        let steps = int(arg::STEPS_KEY).expect("argument with default value");
+        let runs = int(arg::RUNS_KEY).expect("argument with default value");
+        let seed = int(arg::SEED_KEY).map(|seed| seed as u64);
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::TestsGen {
+            input,
+            smt_log,
+            output,
+            format,
+            bmc_max,
+            steps,
+            runs,
+            seed,
+        })
+    }
+
+    /// Subcommand for the mutate mode.
+    pub fn mutate_subcommand() -> App {
+        Command::new(mode::MUTATE)
+            .about(
+                "Mutates the operators and constants of the candidates that hold, and reports \
+                the mutants that are still provable: the candidates might be weaker than intended.",
+            )
+            .args(&[
+                Arg::new(arg::BMC_MAX_KEY)
+                    .help("Maximum depth of the falsifications BMC looks for to kill mutants")
+                    .long("bmc_max")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::TRANS_KEY)
+                    .help(
+                        "Mutates the transition relation instead, and reports the mutants under \
+                        which the candidates that hold are still proved: the candidates do not \
+                        detect these changes",
+                    )
+                    .long("trans"),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_mutate(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::MUTATE)?;
+        let bmc_max = get_bmc_max(matches, || ()).expect("argument with default value");
+        let trans = matches.is_present(arg::TRANS_KEY);
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::Mutate {
+            input,
+            smt_log,
+            bmc_max,
+            trans,
+        })
+    }
+
+    /// Subcommand for the count mode.
+    pub fn count_subcommand() -> App {
+        Command::new(mode::COUNT)
+            .about(
+                "Counts the reachable states, exactly for small finite systems and approximately \
+                by sampling otherwise, and the fraction of them BMC explores up to some depth.",
+            )
+            .args(&[
+                Arg::new(arg::BMC_MAX_KEY)
+                    .help("Depth of the BMC run the exploration is measured for")
+                    .long("bmc_max")
+                    .validator(validate_int)
+                    .default_value("10")
+                    .value_name("INT"),
+                Arg::new(arg::MAX_STATES_KEY)
+                    .help(
+                        "Maximum number of reachable states enumerated, sampling is used \
+                        instead on systems with more reachable states",
+                    )
+                    .long("max_states")
+                    .validator(validate_int)
+                    .default_value("10000")
+                    .value_name("INT"),
+                Arg::new(arg::STEPS_KEY)
+                    .help("Number of transitions of each sampling run")
+                    .long("steps")
+                    .validator(validate_int)
+                    .default_value("20")
+                    .value_name("INT"),
+                Arg::new(arg::RUNS_KEY)
+                    .help("Number of sampling runs")
+                    .long("runs")
+                    .validator(validate_int)
+                    .default_value("20")
+                    .value_name("INT"),
+                Arg::new(arg::SEED_KEY)
+                    .help("Seed for the random values, for reproducible runs")
+                    .long("seed")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                smt_log_arg(),
+                sys_arg(),
+            ])
+    }
+    pub fn try_count(smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::COUNT)?;
+        let int = |key: &str| {
+            matches.value_of(key).map(|val| {
+                usize::from_str_radix(val, 10)
+                    .expect("[clap] argument validation did not catch integer parsing error")
+            })
+        };
+        let bmc_max = int(arg::BMC_MAX_KEY).expect("argument with default value");
+        let max_states = int(arg::MAX_STATES_KEY).expect("argument with default value");
+        let steps = int(arg::STEPS_KEY).expect("argument with default value");
+        let runs = int(arg::RUNS_KEY).expect("argument with default value");
+        let seed = int(arg::SEED_KEY).map(|seed| seed as u64);
+        let smt_log = get_smt_log(matches).or(smt_log);
+        let input = get_sys(matches);
+        Some(Mode::Count {
+            input,
+            smt_log,
+            bmc_max,
+            max_states,
+            steps,
+            runs,
+            seed,
         })
     }
 
@@ -266,6 +1429,212 @@ pub mod cla {
         Some(Mode::Parse { input })
     }
 
+    /// Subcommand for the examples mode.
+    pub fn examples_subcommand() -> App {
+        Command::new(mode::EXAMPLES)
+            .about(
+                "Prints runnable command sequences for each workflow, \
+                starting from the demo system written by `demo`",
+            )
+            .arg(
+                Arg::new(arg::WORKFLOW_KEY)
+                    .help("Workflow to print the commands of, all of them if absent")
+                    .possible_values(crate::examples::WORKFLOWS),
+            )
+    }
+    pub fn try_examples(_smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::EXAMPLES)?;
+        let workflow = matches.value_of(arg::WORKFLOW_KEY).map(String::from);
+        Some(Mode::Examples { workflow })
+    }
+
+    /// Subcommand for the show-cex mode.
+    pub fn show_cex_subcommand() -> App {
+        Command::new(mode::SHOW_CEX)
+            .about(
+                "Presents a counterexample from the store again, \
+                with the presentation options of this run",
+            )
+            .arg(
+                Arg::new(arg::CEX_ID_KEY)
+                    .help("ID of the counterexample, or a prefix of it")
+                    .required(true)
+                    .value_name("ID"),
+            )
+    }
+    pub fn try_show_cex(_smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::SHOW_CEX)?;
+        let id = matches
+            .value_of(arg::CEX_ID_KEY)
+            .expect("[clap]: required argument cannot be absent")
+            .into();
+        Some(Mode::ShowCex { id })
+    }
+
+    /// Subcommand for the scenario mode.
+    pub fn scenario_subcommand() -> App {
+        Command::new(mode::SCENARIO)
+            .about(
+                "Converts a counterexample from the store into constraints fixing its values \
+                step by step, to paste into the system as a regression scenario",
+            )
+            .args(&[
+                Arg::new(arg::CEX_ID_KEY)
+                    .help("ID of the counterexample, or a prefix of it")
+                    .required(true)
+                    .value_name("ID"),
+                Arg::new(arg::STEPS_KEY)
+                    .help("Number of steps fixed, all of them if absent")
+                    .long("steps")
+                    .validator(validate_int)
+                    .value_name("INT"),
+                Arg::new(arg::VARS_KEY)
+                    .help("Comma-separated variables fixed, typically the inputs, all of them if absent")
+                    .long("vars")
+                    .value_name("NAMES"),
+            ])
+    }
+    pub fn try_scenario(_smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::SCENARIO)?;
+        let id = matches
+            .value_of(arg::CEX_ID_KEY)
+            .expect("[clap]: required argument cannot be absent")
+            .into();
+        let steps = matches.value_of(arg::STEPS_KEY).map(|val| {
+            usize::from_str_radix(val, 10)
+                .expect("[clap] argument validation did not catch integer parsing error")
+        });
+        let vars = matches.value_of(arg::VARS_KEY).map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        });
+        Some(Mode::Scenario { id, steps, vars })
+    }
+
+    /// Subcommand for the graph mode.
+    pub fn graph_subcommand() -> App {
+        Command::new(mode::GRAPH)
+            .about(
+                "Renders the structure of the system as a graph: which variables the next value \
+                of each variable depends on, and which variables each candidate references",
+            )
+            .args(&[
+                Arg::new(arg::OUTPUT_KEY)
+                    .help("File to write the graph to, stdout if absent")
+                    .short('o')
+                    .long("output")
+                    .value_name("FILE"),
+                Arg::new(arg::FORMAT_KEY)
+                    .help("Format of the graph, Graphviz DOT or a Mermaid flowchart")
+                    .long("format")
+                    .possible_values(GraphFormat::NAMES)
+                    .default_value("dot")
+                    .value_name("FORMAT"),
+                sys_arg(),
+            ])
+    }
+    pub fn try_graph(_smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::GRAPH)?;
+        let output = matches.value_of(arg::OUTPUT_KEY).map(String::from);
+        let format = matches
+            .value_of(arg::FORMAT_KEY)
+            .and_then(GraphFormat::from_name)
+            .expect("argument with default value");
+        let input = get_sys(matches);
+        Some(Mode::Graph {
+            input,
+            output,
+            format,
+        })
+    }
+
+    /// Subcommand for the whats-new mode.
+    pub fn whats_new_subcommand() -> App {
+        Command::new(mode::WHATS_NEW)
+            .about(
+                "Prints the changes since the last version of mikino this command ran with, \
+                new subcommands and options first",
+            )
+            .args(&[Arg::new(arg::SINCE_KEY)
+                .help("Prints the changes after <VERSION>, without recording the current version")
+                .long("since")
+                .value_name("VERSION")])
+    }
+    pub fn try_whats_new(_smt_log: Option<String>, matches: &Matches) -> Option<Mode> {
+        let matches = matches.subcommand_matches(mode::WHATS_NEW)?;
+        let since = matches.value_of(arg::SINCE_KEY).map(String::from);
+        Some(Mode::WhatsNew { since })
+    }
+
+    /// Parses a list of depths such as `3,7,12-20`, yields sorted depths without duplicates.
+    pub fn parse_depths(s: &str) -> Result<Vec<usize>, String> {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("expected integer, found `{}`", n.trim()))
+        };
+        let mut depths = Vec::new();
+        for elm in s.split(',') {
+            if let Some((lbound, ubound)) = elm.split_once('-') {
+                let (lbound, ubound) = (parse(lbound)?, parse(ubound)?);
+                if lbound > ubound {
+                    return Err(format!("illegal empty range `{}`", elm.trim()));
+                }
+                depths.extend(lbound..=ubound)
+            } else {
+                depths.push(parse(elm)?)
+            }
+        }
+        depths.sort_unstable();
+        depths.dedup();
+        Ok(depths)
+    }
+
+    /// Returns an error if the input string is not a valid list of depths.
+    ///
+    /// Used by CLAP.
+    pub fn validate_depths(s: &str) -> Result<(), String> {
+        parse_depths(s).map(|_| ())
+    }
+
+    /// Parses a comma-separated list of solver commands.
+    pub fn parse_solvers(s: &str) -> Vec<String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|cmd| !cmd.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Returns an error if the input string does not list at least two solver commands.
+    ///
+    /// Used by CLAP.
+    pub fn validate_solvers(s: &str) -> Result<(), String> {
+        if parse_solvers(s).len() < 2 {
+            return Err(format!(
+                "expected at least two solver commands, found `{}`",
+                s
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the input string is not a percentage between `0` and `100`.
+    ///
+    /// Used by CLAP.
+    pub fn validate_percent(s: &str) -> Result<(), String> {
+        match s.parse::<f64>() {
+            Ok(val) if (0.0..=100.0).contains(&val) => Ok(()),
+            _ => Err(format!(
+                "expected a percentage in `[0, 100]`, found `{}`",
+                s
+            )),
+        }
+    }
+
     /// Returns an error if the input string is not a valid integer.
     ///
     /// Used by CLAP.
@@ -291,3 +1660,29 @@ pub mod cla {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::cla::parse_depths;
+
+    #[test]
+    fn depths() {
+        assert_eq!(parse_depths("3"), Ok(vec![3]));
+        assert_eq!(parse_depths("3,7, 12-14"), Ok(vec![3, 7, 12, 13, 14]));
+        assert_eq!(parse_depths("5-5"), Ok(vec![5]));
+    }
+
+    #[test]
+    fn depths_are_sorted_without_duplicates() {
+        assert_eq!(parse_depths("9,2-4,3,2"), Ok(vec![2, 3, 4, 9]));
+    }
+
+    #[test]
+    fn illegal_depths() {
+        assert!(parse_depths("").is_err());
+        assert!(parse_depths("3,,4").is_err());
+        assert!(parse_depths("-3").is_err());
+        assert!(parse_depths("4-2").is_err());
+        assert!(parse_depths("two").is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! Mutation analysis of the candidates, or of the transition relation with `--trans`.
+//!
+//! Mutants are killed by BMC, survive if proved, and are unknown otherwise.
+
+mikino_api::prelude!();
+
+use crate::{smt, smt::SExpr, Check, Phase};
+
+/// Replacements of an operator.
+fn replacements(op: &str) -> &'static [&'static str] {
+    match op {
+        "<" => &["<="],
+        "<=" => &["<"],
+        ">" => &[">="],
+        ">=" => &[">"],
+        "and" => &["or"],
+        "or" => &["and"],
+        "=>" => &["="],
+        _ => &[],
+    }
+}
+
+/// Mutants of an SMT-LIB formula, with a description of the mutation.
+fn mutants(expr: &SExpr) -> Vec<(String, SExpr)> {
+    let mut res = Vec::new();
+    match expr {
+        SExpr::Atom(atom) => {
+            if let Ok(n) = atom.parse::<u64>() {
+                res.push((
+                    format!("`{}` → `{}`", n, n + 1),
+                    SExpr::Atom((n + 1).to_string()),
+                ));
+                if n > 0 {
+                    res.push((
+                        format!("`{}` → `{}`", n, n - 1),
+                        SExpr::Atom((n - 1).to_string()),
+                    ))
+                }
+            }
+        }
+        SExpr::List(elems) => {
+            if let Some((SExpr::Atom(op), args)) = elems.split_first() {
+                for new_op in replacements(op) {
+                    let mut mutant = vec![SExpr::Atom(new_op.to_string())];
+                    mutant.extend(args.iter().cloned());
+                    res.push((format!("`{}` → `{}`", op, new_op), SExpr::List(mutant)))
+                }
+                if op == "and" && args.len() > 1 {
+                    for (idx, arg) in args.iter().enumerate() {
+                        let mut mutant = elems.clone();
+                        mutant[idx + 1] = SExpr::List(vec![SExpr::Atom("not".into()), arg.clone()]);
+                        res.push((
+                            format!("negated conjunct `{}`", arg.rename("@0", "")),
+                            SExpr::List(mutant),
+                        ))
+                    }
+                }
+            }
+            for (idx, elem) in elems.iter().enumerate().skip(1) {
+                for (desc, sub) in mutants(elem) {
+                    let mut mutant = elems.clone();
+                    mutant[idx] = sub;
+                    res.push((desc, SExpr::List(mutant)))
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Fate of a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fate {
+    /// Falsified by BMC at some depth.
+    Killed(usize),
+    /// Proved.
+    Survived,
+    /// Neither proved nor falsified.
+    Unknown,
+}
+
+/// A mutant of a candidate.
+struct Mutant {
+    candidate: String,
+    desc: String,
+    expr: SExpr,
+    fate: Fate,
+}
+
+impl<'env> Check<'env> {
+    /// Candidates that hold, inductive together.
+    fn invariants(&self) -> Res<Vec<&str>> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Base, "mutate_base.smt2")?;
+        solver.intent("mutate: candidates that hold in the initial states")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        let mut initial = Vec::new();
+        for (candidate, def) in sys.po_s().iter() {
+            solver.intent(&format!("mutate: does `{}` hold initially?", candidate))?;
+            if solver.unsat(&[format!("(not {})", smt::Unroll::new(def, 0))])? {
+                initial.push(candidate.as_str())
+            }
+        }
+        let (invariants, _) = self.lemmas_fixpoint(&[], initial, false)?;
+        Ok(invariants)
+    }
+
+    /// Tries to prove the mutants, relative to the invariants.
+    fn prove_mutants(&self, invariants: &[&str], mutants: &mut [Mutant]) -> Res<()> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Step, "mutate_step.smt2")?;
+        solver.intent("mutate: proof setup, invariants in the pre- and post-state")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        for invariant in invariants {
+            let def = &sys.po_s()[*invariant];
+            solver.assert(smt::Unroll::new(def, 0))?;
+            solver.assert(smt::Unroll::new(def, 1))?;
+        }
+        for mutant in mutants.iter_mut() {
+            solver.intent(&format!(
+                "mutate: does mutant `{}` of `{}` hold initially?",
+                mutant.desc, mutant.candidate
+            ))?;
+            solver.push()?;
+            solver.assert_init(sys, 0)?;
+            let init = solver.unsat(&[format!("(not {})", mutant.expr)])?;
+            solver.pop()?;
+            if !init {
+                continue;
+            }
+            solver.intent(&format!(
+                "mutate: is mutant `{}` of `{}` inductive?",
+                mutant.desc, mutant.candidate
+            ))?;
+            solver.push()?;
+            solver.assert_trans(sys, 0)?;
+            let step = solver.unsat(&[
+                mutant.expr.to_string(),
+                format!("(not {})", mutant.expr.rename("@0", "@1")),
+            ])?;
+            solver.pop()?;
+            if step {
+                mutant.fate = Fate::Survived
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks for falsifications of the mutants not proved, up to depth `max`.
+    fn kill_mutants(&self, max: usize, mutants: &mut [Mutant]) -> Res<()> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "mutate_bmc.smt2")?;
+        solver.intent("mutate: BMC setup, initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        for depth in 0..=max {
+            if mutants.iter().all(|mutant| mutant.fate != Fate::Unknown) {
+                break;
+            }
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            for mutant in mutants.iter_mut() {
+                if mutant.fate != Fate::Unknown {
+                    continue;
+                }
+                solver.intent(&format!(
+                    "mutate: falsification of mutant `{}` of `{}` at depth {}?",
+                    mutant.desc, mutant.candidate, depth
+                ))?;
+                let expr = mutant.expr.rename("@0", &format!("@{}", depth));
+                if !solver.unsat(&[format!("(not {})", expr)])? {
+                    mutant.fate = Fate::Killed(depth)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Transition relation at some step, with the variables of `trans` at steps `0` and `1`.
+    fn trans_at(trans: &SExpr, step: usize) -> SExpr {
+        trans
+            .rename("@1", &format!("@{}", step + 1))
+            .rename("@0", &format!("@{}", step))
+    }
+
+    /// Fate of a mutant of the transition relation, `max` is the BMC depth.
+    fn trans_mutant_fate(&self, invariants: &[&str], mutant: &SExpr, max: usize) -> Res<Fate> {
+        let sys = &self.sys;
+        let conj = |step: usize| {
+            let mut conj = "(and true".to_string();
+            for invariant in invariants {
+                conj.push(' ');
+                conj.push_str(&smt::Unroll::new(&sys.po_s()[*invariant], step).to_string())
+            }
+            conj.push(')');
+            conj
+        };
+
+        let mut solver = self.phase_solver(Phase::Step, "mutate_trans_step.smt2")?;
+        solver.intent("mutate trans: are the candidates inductive in the mutated system?")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        let step = solver.unsat(&[conj(0), mutant.to_string(), format!("(not {})", conj(1))])?;
+        if step {
+            return Ok(Fate::Survived);
+        }
+
+        let mut solver = self.phase_solver(Phase::Bmc, "mutate_trans_bmc.smt2")?;
+        solver.intent("mutate trans: BMC setup in the mutated system")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        for depth in 0..=max {
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert(Self::trans_at(mutant, depth - 1))?;
+            }
+            solver.intent(&format!(
+                "mutate trans: falsification in the mutated system at depth {}?",
+                depth
+            ))?;
+            if !solver.unsat(&[format!("(not {})", conj(depth))])? {
+                return Ok(Fate::Killed(depth));
+            }
+        }
+        Ok(Fate::Unknown)
+    }
+
+    /// Runs mutation analysis on the transition relation, BMC looks for falsifications of the
+    /// candidates in the mutated systems up to depth `bmc_max`.
+    pub fn mutate_trans(&self, bmc_max: usize) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "looking for the candidates that {}...",
+                s.bold.paint("hold")
+            )
+        });
+        let invariants = self
+            .invariants()
+            .chain_err(|| "while proving the original candidates")?;
+        if invariants.is_empty() {
+            bail!("none of the candidates could be proved, there is nothing to mutate against")
+        }
+
+        let trans = SExpr::parse(&smt::Unroll::new(self.sys.trans(), 0).to_string())?;
+        let mutants = mutants(&trans);
+        self.env.progress(|s| {
+            format!(
+                "analyzing {} {} of the transition relation...",
+                mutants.len(),
+                s.bold.paint("mutant(s)")
+            )
+        });
+        let mut fates = Vec::with_capacity(mutants.len());
+        for (idx, (desc, mutant)) in mutants.iter().enumerate() {
+            if self.env.verb > 1 {
+                self.env
+                    .progress(|_| format!("mutant {}/{}: {}", idx + 1, mutants.len(), desc))
+            }
+            let fate = self
+                .trans_mutant_fate(&invariants, mutant, bmc_max)
+                .chain_err(|| format!("while analyzing mutant {}", desc))?;
+            fates.push(fate)
+        }
+
+        let count = |pred: fn(&Fate) -> bool| fates.iter().filter(|fate| pred(fate)).count();
+        println!("|===| {} result", self.bold.paint("Transition mutation"));
+        println!(
+            "| - {} mutant(s) of the transition relation checked against {} candidate(s)",
+            mutants.len(),
+            invariants.len()
+        );
+        for candidate in &invariants {
+            println!("|   `{}`", self.bold.paint(*candidate))
+        }
+        println!(
+            "| - {} killed, {} survived, {} unknown",
+            self.green
+                .paint(count(|fate| matches!(fate, Fate::Killed(_))).to_string()),
+            self.red
+                .paint(count(|fate| *fate == Fate::Survived).to_string()),
+            count(|fate| *fate == Fate::Unknown)
+        );
+        for ((desc, _), fate) in mutants.iter().zip(fates.iter()) {
+            let fate = match fate {
+                Fate::Killed(_) => continue,
+                Fate::Survived => self.red.paint("survived"),
+                Fate::Unknown => self.bold.paint("unknown"),
+            };
+            println!("|   {} {}", fate, desc.replace("@1", "'").replace("@0", ""))
+        }
+        println!("|");
+        if count(|fate| *fate == Fate::Survived) > 0 {
+            println!(
+                "| - the candidates {} the surviving mutations, they either do not matter or \
+                the candidates are weaker than intended",
+                self.red.paint("do not detect")
+            )
+        } else {
+            println!(
+                "| - the candidates detect {} mutation up to depth {}",
+                self.green.paint("every"),
+                bmc_max
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+
+    /// Runs mutation analysis, BMC looks for falsifications of the mutants up to depth `bmc_max`.
+    pub fn mutate(&self, bmc_max: usize) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "looking for the candidates that {}...",
+                s.bold.paint("hold")
+            )
+        });
+        let invariants = self
+            .invariants()
+            .chain_err(|| "while proving the original candidates")?;
+
+        let mut mutated = Vec::new();
+        for candidate in &invariants {
+            let def = &self.sys.po_s()[*candidate];
+            let expr = SExpr::parse(&smt::Unroll::new(def, 0).to_string())?;
+            for (desc, expr) in mutants(&expr) {
+                mutated.push(Mutant {
+                    candidate: candidate.to_string(),
+                    desc,
+                    expr,
+                    fate: Fate::Unknown,
+                })
+            }
+        }
+        self.env.progress(|s| {
+            format!(
+                "analyzing {} {} of {} candidate(s)...",
+                mutated.len(),
+                s.bold.paint("mutant(s)"),
+                invariants.len()
+            )
+        });
+        self.prove_mutants(&invariants, &mut mutated)
+            .chain_err(|| "while proving mutants")?;
+        self.kill_mutants(bmc_max, &mut mutated)
+            .chain_err(|| "while looking for falsifications of mutants")?;
+
+        println!("|===| {} result", self.bold.paint("Mutation"));
+        let skipped: Vec<&String> = self
+            .sys
+            .po_s()
+            .keys()
+            .filter(|candidate| !invariants.contains(&candidate.as_str()))
+            .collect();
+        if !skipped.is_empty() {
+            println!(
+                "| - the following candidate(s) could {} be proved, they are not mutated",
+                self.red.paint("not")
+            );
+            for candidate in skipped {
+                println!("|   `{}`", self.bold.paint(candidate))
+            }
+            println!("|");
+        }
+        for candidate in &invariants {
+            let mutants: Vec<&Mutant> = mutated
+                .iter()
+                .filter(|mutant| mutant.candidate == *candidate)
+                .collect();
+            let count = |pred: fn(&Fate) -> bool| {
+                mutants.iter().filter(|mutant| pred(&mutant.fate)).count()
+            };
+            println!(
+                "| - `{}`: {} mutant(s), {} killed, {} survived, {} unknown",
+                self.bold.paint(*candidate),
+                mutants.len(),
+                self.green
+                    .paint(count(|fate| matches!(fate, Fate::Killed(_))).to_string()),
+                self.red
+                    .paint(count(|fate| *fate == Fate::Survived).to_string()),
+                count(|fate| *fate == Fate::Unknown)
+            );
+            for mutant in mutants {
+                let fate = match mutant.fate {
+                    Fate::Killed(_) => continue,
+                    Fate::Survived => self.red.paint("survived"),
+                    Fate::Unknown => self.bold.paint("unknown"),
+                };
+                println!(
+                    "|   {} {}: `{}`",
+                    fate,
+                    mutant.desc,
+                    mutant.expr.rename("@0", "")
+                )
+            }
+        }
+        let survived = mutated
+            .iter()
+            .filter(|mutant| mutant.fate == Fate::Survived)
+            .count();
+        println!("|");
+        if survived > 0 {
+            println!(
+                "| - {} mutant(s) {}, the candidate(s) might be weaker than intended",
+                survived,
+                self.red.paint("survived")
+            )
+        } else {
+            println!(
+                "| - {} mutant survived up to depth {}",
+                self.green.paint("no"),
+                bmc_max
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+//! Muted standard output, for `--verdict_only`.
+//!
+//! Only supported on unix systems, elsewhere the reports still go to stdout before the verdict.
+
+mikino_api::prelude!();
+
+use std::{io::Write, sync::Mutex};
+
+/// Duplicate of the original stdout while it is muted.
+static STDOUT: Mutex<Option<i32>> = Mutex::new(None);
+
+/// Redirects stdout to `/dev/null`.
+#[cfg(unix)]
+pub fn start() {
+    use std::os::unix::io::AsRawFd;
+
+    let null = match std::fs::OpenOptions::new().write(true).open("/dev/null") {
+        Ok(null) => null,
+        Err(_) => return,
+    };
+    let _ = std::io::stdout().flush();
+    let original = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if original < 0 {
+        return;
+    }
+    if unsafe { libc::dup2(null.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        unsafe { libc::close(original) };
+        return;
+    }
+    if let Ok(mut current) = STDOUT.lock() {
+        *current = Some(original)
+    }
+}
+
+/// Redirects stdout to `/dev/null`, not supported on this platform.
+#[cfg(not(unix))]
+pub fn start() {}
+
+/// Restores stdout, if muted.
+pub fn stop() {
+    let original = STDOUT.lock().ok().and_then(|mut current| current.take());
+    if let Some(original) = original {
+        let _ = std::io::stdout().flush();
+        #[cfg(unix)]
+        unsafe {
+            libc::dup2(original, libc::STDOUT_FILENO);
+            libc::close(original);
+        }
+    }
+}
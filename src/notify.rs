@@ -0,0 +1,91 @@
+//! Result notification, `--notify_url <URL>` POSTs a JSON summary of the verdict with `curl`.
+//!
+//! A failed notification is a warning, it does not change the exit code.
+
+mikino_api::prelude!();
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    budget::{exit, Verdict},
+    json::{json_array, json_object, json_str},
+    metadata::Metadata,
+    Mode, Run,
+};
+
+/// Time limit of the notification request, in seconds.
+const MAX_TIME: &str = "10";
+
+impl Run {
+    /// POSTs the JSON summary of a verdict to the `--notify_url`, if any, warns on failure.
+    pub fn notify(&self, meta: &Metadata, verdict: &Verdict) {
+        if let Err(e) = self.try_notify(meta, verdict) {
+            for e in e.into_iter() {
+                eprintln!(
+                    "{}: {}",
+                    self.err_styles.red.paint("warning"),
+                    e.pretty(&self.err_styles)
+                )
+            }
+        }
+    }
+
+    /// POSTs the JSON summary of a verdict to the `--notify_url`, if any.
+    fn try_notify(&self, meta: &Metadata, verdict: &Verdict) -> Res<()> {
+        let Some(url) = self.notify_url.as_ref() else {
+            return Ok(());
+        };
+        let (max_unknown, score_threshold) = match &self.mode {
+            Mode::Check {
+                max_unknown,
+                score_threshold,
+                ..
+            } => (*max_unknown, *score_threshold),
+            _ => (None, None),
+        };
+        let names = |names: &[String]| {
+            let names: Vec<String> = names.iter().map(|name| json_str(name)).collect();
+            json_array(&names)
+        };
+        let payload = json_object(&[
+            ("run_id", json_str(&self.run_id)),
+            ("input", json_str(&meta.input)),
+            (
+                "verdict",
+                json_str(exit::token(verdict.exit_code(max_unknown, score_threshold))),
+            ),
+            ("proved", verdict.proved.len().to_string()),
+            ("falsified", verdict.falsified.len().to_string()),
+            ("unknown", verdict.unknown.len().to_string()),
+            ("falsified_candidates", names(&verdict.falsified)),
+            ("unknown_candidates", names(&verdict.unknown)),
+            ("metadata", meta.to_json()),
+        ]);
+
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--request", "POST"])
+            .args(["--max-time", MAX_TIME])
+            .args(["--header", "Content-Type: application/json"])
+            .args(["--data-binary", "@-"])
+            .args(["--url", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .chain_err(|| format!("while running `curl` to notify `{}`", url))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(payload.as_bytes())
+                .chain_err(|| format!("while sending the notification to `{}`", url))?
+        }
+        let status = child
+            .wait()
+            .chain_err(|| format!("while waiting for the notification to `{}`", url))?;
+        if !status.success() {
+            bail!("failed to notify `{}` ({})", url, status)
+        }
+        Ok(())
+    }
+}
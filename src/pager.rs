@@ -0,0 +1,123 @@
+//! Automatic pager for the output of the check and BMC modes, `$PAGER` or `less`.
+//!
+//! Output goes through a relay thread: quitting the pager early discards the rest of the output.
+
+mikino_api::prelude!();
+
+use std::{
+    io::{Read, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::Mutex,
+    thread::JoinHandle,
+};
+
+/// Pager the output currently goes through and the thread relaying the output to it, if any.
+static PAGER: Mutex<Option<(Child, JoinHandle<()>)>> = Mutex::new(None);
+
+/// Command of the pager, `None` if paging is disabled by the environment.
+fn command() -> Option<String> {
+    match std::env::var("PAGER") {
+        Ok(cmd) if cmd.trim().is_empty() || cmd.trim() == "cat" => None,
+        Ok(cmd) => Some(cmd),
+        Err(_) => Some("less".into()),
+    }
+}
+
+/// Options of `less`: exits right away if the output fits the terminal, keeps colors, and leaves
+/// the output on screen once it exits.
+///
+/// `F` is added to the user's `LESS` options, if any.
+fn less_options() -> String {
+    match std::env::var("LESS") {
+        Ok(opts) if opts.contains('F') => opts,
+        Ok(opts) if opts.trim().is_empty() => "F".into(),
+        Ok(opts) => format!("{} -F", opts),
+        Err(_) => "FRX".into(),
+    }
+}
+
+/// Relays the output to the pager until stdout is closed, see [`wait`].
+///
+/// Once the pager exits, the rest of the output is discarded so that the run carries on.
+fn relay(mut output: impl Read, pager: ChildStdin) {
+    let mut pager = Some(pager);
+    let mut buf = [0; 4096];
+    loop {
+        let len = match output.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        if let Some(input) = pager.as_mut() {
+            if input.write_all(&buf[..len]).is_err() {
+                pager = None
+            }
+        }
+    }
+}
+
+/// Redirects stdout to a pager.
+///
+/// Output goes to stdout as usual if the pager cannot be spawned.
+#[cfg(unix)]
+pub fn start() {
+    use std::{fs::File, os::unix::io::FromRawFd};
+
+    let cmd = match command() {
+        Some(cmd) => cmd,
+        None => return,
+    };
+    let mut pager = Command::new("sh");
+    pager
+        .arg("-c")
+        .arg(&cmd)
+        .env("LESS", less_options())
+        .stdin(Stdio::piped());
+    let mut child = match pager.spawn() {
+        Ok(child) => child,
+        Err(_) => return,
+    };
+    let input = child
+        .stdin
+        .take()
+        .expect("pager spawned with a piped stdin");
+
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+        let _ = child.kill();
+        return;
+    }
+    let [read, write] = fds;
+    // Solvers and hooks must not inherit the read end.
+    unsafe { libc::fcntl(read, libc::F_SETFD, libc::FD_CLOEXEC) };
+    let _ = std::io::stdout().flush();
+    // Stdout now writes to the relay, which reads until stdout is closed, see `wait`.
+    let redirected = unsafe { libc::dup2(write, libc::STDOUT_FILENO) } >= 0;
+    unsafe { libc::close(write) };
+    let output = unsafe { File::from_raw_fd(read) };
+    if !redirected {
+        let _ = child.kill();
+        return;
+    }
+    let relay = std::thread::spawn(move || relay(output, input));
+    if let Ok(mut current) = PAGER.lock() {
+        *current = Some((child, relay))
+    }
+}
+
+/// Redirects stdout to a pager, not supported on this platform.
+#[cfg(not(unix))]
+pub fn start() {}
+
+/// Closes stdout and waits for the pager to exit, if any.
+pub fn wait() {
+    let pager = PAGER.lock().ok().and_then(|mut current| current.take());
+    if let Some((mut child, relay)) = pager {
+        let _ = std::io::stdout().flush();
+        #[cfg(unix)]
+        unsafe {
+            libc::close(libc::STDOUT_FILENO);
+        }
+        let _ = relay.join();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,247 @@
+//! Portfolio engine, races induction, BMC and backward reachability for each candidate.
+//!
+//! The first conclusive answer wins, the solvers of the other engines are then killed.
+
+mikino_api::prelude!();
+
+use std::sync::mpsc;
+
+use crate::{cancel, smt, timeout, Check, Outcome, Phase};
+
+/// Engines raced by the portfolio.
+pub const ENGINES: &[&str] = &["induction", "bmc", "backward"];
+
+impl<'env> Check<'env> {
+    /// Runs the portfolio engine.
+    pub fn portfolio(&self, max: Option<usize>) -> Res<()> {
+        let mut proved = Vec::new();
+        let mut falsified = Vec::new();
+        let mut unknown = Vec::new();
+
+        self.env.progress(|s| {
+            format!(
+                "running {} analysis ({}) for {} candidate(s)...",
+                s.bold.paint("portfolio"),
+                ENGINES.join(", "),
+                self.sys.po_s().len()
+            )
+        });
+
+        for (idx, candidate) in self.sys.po_s().keys().enumerate() {
+            let (outcome, engine) =
+                self.portfolio_candidate(max, idx, candidate)
+                    .chain_err(|| {
+                        format!(
+                            "during portfolio analysis for candidate `{}`",
+                            self.bold.paint(candidate)
+                        )
+                    })?;
+            match outcome {
+                Outcome::Proved(depth) => {
+                    self.settle(engine, candidate, "proved", Some(depth))?;
+                    proved.push((candidate.as_str(), engine, depth))
+                }
+                Outcome::Falsified(trace) => {
+                    println!(
+                        "{} found a {} at depth {}:",
+                        engine,
+                        self.red.paint("falsification"),
+                        self.bold.paint((trace.len() - 1).to_string())
+                    );
+                    self.present_trace(&self.sys, candidate, &trace, &[], true)?;
+                    self.settle(engine, candidate, "falsified", Some(trace.len() - 1))?;
+                    falsified.push((candidate.as_str(), engine))
+                }
+                Outcome::Unknown => {
+                    self.settle("portfolio", candidate, "unknown", None)?;
+                    unknown.push(candidate.as_str())
+                }
+            }
+        }
+
+        println!();
+        println!("|===| {} result", self.bold.paint("Portfolio"));
+        if !proved.is_empty() {
+            println!(
+                "| - the following candidate(s) {} in all reachable states",
+                self.green.paint("hold")
+            );
+            for (candidate, engine, depth) in &proved {
+                println!(
+                    "|   `{}` (proved by {} at depth {})",
+                    self.green.paint(*candidate),
+                    self.bold.paint(*engine),
+                    depth
+                )
+            }
+        }
+        if !falsified.is_empty() {
+            if !proved.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsifiable")
+            );
+            for (candidate, engine) in &falsified {
+                println!(
+                    "|   `{}` (falsified by {})",
+                    self.red.paint(*candidate),
+                    self.bold.paint(*engine)
+                )
+            }
+        }
+        if !unknown.is_empty() {
+            if !proved.is_empty() || !falsified.is_empty() {
+                println!("|")
+            }
+            println!(
+                "| - could {} conclude for the following candidate(s) {}",
+                self.bold.paint("not"),
+                timeout::unknown_bound(max, self.env.timed_out())
+            );
+            for candidate in &unknown {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        println!("|");
+        if !falsified.is_empty() {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else if !unknown.is_empty() {
+            println!("| - system {}", self.red.paint("might be unsafe"))
+        } else {
+            println!(
+                "| - system is {}, all reachable states verify the candidate(s)",
+                self.green.paint("safe")
+            )
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+
+    /// Races the engines on a single candidate.
+    ///
+    /// Yields the outcome and the engine that produced it. Errors are only reported if no engine
+    /// reached a conclusion.
+    pub fn portfolio_candidate(
+        &self,
+        max: Option<usize>,
+        idx: usize,
+        candidate: &str,
+    ) -> Res<(Outcome, &'static str)> {
+        // Raising it kills the solvers of the engines still running.
+        let cancel = cancel::Token::new();
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::scope(|scope| {
+            for engine in ENGINES {
+                let (sender, cancel) = (sender.clone(), &cancel);
+                scope.spawn(move || {
+                    let res = match *engine {
+                        "induction" => {
+                            self.simple_path_candidate(max, idx, candidate, cancel, false)
+                        }
+                        "bmc" => self.bmc_candidate(max, idx, candidate, cancel),
+                        "backward" => self.backward_candidate(max, idx, candidate, cancel),
+                        _ => unreachable!("unknown portfolio engine `{}`", engine),
+                    };
+                    // Receiver only hangs up once all engines are done.
+                    let _ = sender.send((*engine, res));
+                });
+            }
+            drop(sender);
+
+            let mut res = None;
+            let mut errors = Vec::new();
+            for (engine, outcome) in receiver {
+                match outcome {
+                    Ok(Outcome::Unknown) => (),
+                    Ok(outcome) => {
+                        if res.is_none() {
+                            if self.env.verb > 0 {
+                                self.env.progress(|s| {
+                                    format!(
+                                        "{} concluded on `{}`, cancelling other engines",
+                                        s.bold.paint(engine),
+                                        s.bold.paint(candidate)
+                                    )
+                                });
+                            }
+                            cancel.cancel();
+                            res = Some((outcome, engine))
+                        }
+                    }
+                    Err(e) => errors.push((engine, e)),
+                }
+            }
+
+            match res {
+                Some(res) => Ok(res),
+                None => match errors.into_iter().next() {
+                    Some((engine, e)) => {
+                        Err(e).chain_err(|| format!("in portfolio engine `{}`", engine))
+                    }
+                    None => Ok((Outcome::Unknown, "portfolio")),
+                },
+            }
+        })
+    }
+
+    /// Forward BMC for a single candidate.
+    ///
+    /// Never proves anything, yields [`Outcome::Unknown`] if `max` is reached, `cancel` is raised
+    /// or a query exceeds the `--query_timeout`.
+    pub fn bmc_candidate(
+        &self,
+        max: Option<usize>,
+        idx: usize,
+        candidate: &str,
+        cancel: &cancel::Token,
+    ) -> Res<Outcome> {
+        let sys = &self.sys;
+        let def = sys.po_s().get(candidate).ok_or_else(|| {
+            format!(
+                "failed to retrieve definition for candidate `{}`",
+                candidate,
+            )
+        })?;
+
+        let mut solver = self.phase_solver(Phase::Bmc, &format!("bmc_{}.smt2", idx))?;
+        solver.kill_on(cancel);
+        solver.intent(&format!(
+            "bmc setup for candidate `{}`: initial state(s)",
+            candidate
+        ))?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+
+        let mut depth = 0;
+        loop {
+            if self.cancelled(cancel) {
+                return Ok(Outcome::Unknown);
+            }
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            solver.intent(&format!(
+                "bmc check for candidate `{}`: falsification at depth {}?",
+                candidate, depth
+            ))?;
+            solver.push()?;
+            solver.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+            match solver.check_sat_or_unknown()? {
+                Some(true) => return Ok(Outcome::Falsified(solver.get_trace(sys, depth)?)),
+                Some(false) => (),
+                None => return Ok(Outcome::Unknown),
+            }
+            solver.pop()?;
+
+            if max.map(|max| depth >= max).unwrap_or(false) {
+                return Ok(Outcome::Unknown);
+            }
+            depth += 1
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Counterexample projection onto state expressions.
+
+mikino_api::prelude!();
+
+use expr::Expr;
+
+use crate::{smt, text, Check, Trace};
+
+/// Splits a comma-separated list of expressions, ignoring the commas between parentheses.
+pub fn split(exprs: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    let (mut depth, mut current) = (0, String::new());
+    for char in exprs.chars() {
+        match char {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => (),
+        }
+        current.push(char)
+    }
+    res.push(current);
+    res.into_iter()
+        .map(|expr| expr.trim().to_string())
+        .filter(|expr| !expr.is_empty())
+        .collect()
+}
+
+/// Parses the projection expressions in the context of a system.
+///
+/// Yields the expressions along with their text.
+pub fn parse(txt: &str, exprs: &[String]) -> Res<Vec<(String, Expr)>> {
+    if exprs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let candidates: Vec<(String, String)> = exprs
+        .iter()
+        .enumerate()
+        .map(|(idx, expr)| (format!("cex projection {}", idx), expr.clone()))
+        .collect();
+    let txt = text::with_candidates(txt, &candidates)
+        .chain_err(|| "while adding the `--cex_project` expressions to the system")?;
+    let sys = parse::trans(&txt).chain_err(|| {
+        format!(
+            "while parsing the `--cex_project` expressions `{}`",
+            exprs.join("`, `")
+        )
+    })?;
+    let mut res = Vec::with_capacity(exprs.len());
+    for ((name, _), expr) in candidates.iter().zip(exprs) {
+        match sys.po_s().get(name.as_str()) {
+            Some(def) => res.push((expr.clone(), def.clone())),
+            None => bail!(
+                "[fatal] failed to retrieve projection expression `{}`",
+                expr
+            ),
+        }
+    }
+    Ok(res)
+}
+
+impl<'env> Check<'env> {
+    /// Trace of the values of the projection expressions, see [`crate::projection`].
+    pub fn projected_trace(&self, trace: &Trace) -> Res<Trace> {
+        let mut solver = self.solver("cex_project.smt2")?;
+        solver.intent("counterexample projection setup")?;
+        for (idx, (_, values)) in trace.iter().enumerate() {
+            solver.declare_state(&self.sys, idx)?;
+            solver.assert(self.trace_state_at(values, idx))?;
+        }
+        solver.intent("counterexample projection: evaluating the expressions")?;
+        if !solver.check_sat()? {
+            bail!("[fatal] the values of the trace to project are inconsistent")
+        }
+        let mut res = Vec::with_capacity(trace.len());
+        for (idx, (step, _)) in trace.iter().enumerate() {
+            let mut values = Vec::with_capacity(self.projection.len());
+            for (desc, expr) in &self.projection {
+                let val = solver.get_value(&smt::Unroll::new(expr, idx).to_string())?;
+                values.push((desc.clone(), val.to_value()))
+            }
+            res.push((*step, values))
+        }
+        Ok(res)
+    }
+}
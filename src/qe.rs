@@ -0,0 +1,234 @@
+//! Exact image engine, by quantifier elimination with Z3's `qe` tactic.
+//!
+//! Only scales to small systems.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check};
+
+impl<'env> Check<'env> {
+    /// Computes the reachable states, at step `0`.
+    ///
+    /// Yields the states reachable in `k` steps or less for each `k` computed, and whether the
+    /// fixpoint was reached. Stops after `max` images if any, or when the run is cancelled.
+    pub fn qe_reachable(
+        &self,
+        solver: &mut smt::Solver,
+        max: Option<usize>,
+    ) -> Res<(Vec<smt::SExpr>, bool)> {
+        let sys = &self.sys;
+        let mut binders = String::new();
+        for var in sys.decls().iter() {
+            binders.push_str(&format!(
+                " ({} {})",
+                smt::svar(var.id(), 0),
+                smt::sort(var.typ())
+            ))
+        }
+        let trans = smt::Unroll::new(sys.trans(), 0).to_string();
+
+        solver.intent("qe: initial states")?;
+        let mut layers = vec![smt::SExpr::parse(
+            &solver.qe(smt::Unroll::new(sys.init(), 0))?,
+        )?];
+        while max.map(|max| layers.len() <= max).unwrap_or(true) && !self.env.cancel.is_cancelled()
+        {
+            let images = layers.len() - 1;
+            let reached = &layers[images];
+            if self.env.verb > 0 {
+                self.env.progress(|s| {
+                    format!(
+                        "computing image {}",
+                        s.under.paint((images + 1).to_string())
+                    )
+                });
+            }
+            solver.intent(&format!("qe: image {}", images + 1))?;
+            let image = solver.qe(format!(
+                "(exists ({}) (and {} {}))",
+                binders.trim(),
+                reached,
+                trans
+            ))?;
+            let image = smt::SExpr::parse(&image)?.rename("@1", "@0");
+
+            solver.intent(&format!("qe: is image {} new?", images + 1))?;
+            if solver.unsat(&[image.to_string(), format!("(not {})", reached)])? {
+                return Ok((layers, true));
+            }
+            let reached = smt::SExpr::parse(&solver.qe(format!("(or {} {})", reached, image))?)?;
+            layers.push(reached)
+        }
+        Ok((layers, false))
+    }
+
+    /// Runs the exact image engine, with at most `max` images if any.
+    pub fn qe_image(&self, max: Option<usize>) -> Res<()> {
+        self.image_engine("qe", max, false)
+    }
+
+    /// Exact image engine, shared by the `qe` and `reach` engines.
+    ///
+    /// Falsifications come with a trace from the initial states if `traces`, with the falsifying
+    /// state only otherwise.
+    pub fn image_engine(&self, engine: &str, max: Option<usize>, traces: bool) -> Res<()> {
+        let sys = &self.sys;
+        if self.env.verb > 0 {
+            self.env.progress(|s| {
+                format!(
+                    "computing {} states by quantifier elimination...",
+                    s.under.paint("reachable")
+                )
+            })
+        }
+        let mut solver = self.solver(&format!("{}.smt2", engine))?;
+        solver.intent(&format!("{} setup", engine))?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        let (layers, fixpoint) = self
+            .qe_reachable(&mut solver, max)
+            .chain_err(|| "during reachable state computation by quantifier elimination")?;
+        let images = layers.len() - 1;
+        let reached = &layers[images];
+
+        let mut okay = Vec::new();
+        let mut falsified = Vec::new();
+        for (candidate, def) in sys.po_s().iter() {
+            solver.intent(&format!(
+                "{}: is candidate `{}` falsified by a reachable state?",
+                engine, candidate
+            ))?;
+            let bad = format!("(not {})", smt::Unroll::new(def, 0));
+            let falsifiable = if traces {
+                match self.reach_trace(&mut solver, &layers, &bad)? {
+                    None => false,
+                    Some(trace) => {
+                        println!(
+                            "found a {} at depth {}:",
+                            self.red.paint("falsification"),
+                            self.bold.paint((trace.len() - 1).to_string())
+                        );
+                        self.present_trace(sys, candidate, &trace, &[], true)?;
+                        self.settle(engine, candidate, "falsified", Some(trace.len() - 1))?;
+                        true
+                    }
+                }
+            } else {
+                solver.push()?;
+                solver.assert(reached)?;
+                solver.assert(&bad)?;
+                let state = if solver.check_sat()? {
+                    Some(solver.get_state(sys, 0)?)
+                } else {
+                    None
+                };
+                solver.pop()?;
+                match state {
+                    None => false,
+                    Some(state) => {
+                        let max_id_len = sys.decls().max_id_len();
+                        println!(
+                            "found a {} falsifying `{}`:",
+                            self.red.paint("reachable state"),
+                            self.red.paint(candidate)
+                        );
+                        for (var, val) in self.sanitized_values(&smt::pretty_state(&state)) {
+                            let var = format!("{: >1$}", var, max_id_len);
+                            println!("  | {} = {}", self.bold.paint(var), val)
+                        }
+                        println!("  |=|");
+                        self.settle(engine, candidate, "falsified", None)?;
+                        true
+                    }
+                }
+            };
+            if falsifiable {
+                falsified.push(candidate.as_str())
+            } else {
+                let status = if fixpoint { "proved" } else { "unfalsified" };
+                self.settle(engine, candidate, status, Some(images))?;
+                okay.push(candidate.as_str())
+            }
+        }
+
+        if !falsified.is_empty() {
+            println!()
+        }
+        let title = if traces {
+            "Reachability"
+        } else {
+            "Exact image"
+        };
+        self.print_image_result(title, fixpoint, images, reached, &okay, &falsified);
+
+        Ok(())
+    }
+
+    /// Prints the result box of the exact image engines.
+    fn print_image_result(
+        &self,
+        title: &str,
+        fixpoint: bool,
+        images: usize,
+        reached: &smt::SExpr,
+        okay: &[&str],
+        falsified: &[&str],
+    ) {
+        println!("|===| {} result", self.bold.paint(title));
+        if fixpoint {
+            println!(
+                "| - fixpoint reached after {} image(s), the reachable states are",
+                self.bold.paint(images.to_string())
+            )
+        } else {
+            println!(
+                "| - {} reached, the states reachable in {} step(s) or less are",
+                self.red.paint("no fixpoint"),
+                images
+            )
+        }
+        println!("|   {}", reached.rename("@0", ""));
+        println!("|");
+        if !okay.is_empty() {
+            if fixpoint {
+                println!(
+                    "| - the following candidate(s) {} in all reachable states",
+                    self.green.paint("hold")
+                )
+            } else {
+                println!(
+                    "| - the following candidate(s) hold in all states reachable in {} step(s) \
+                    or less",
+                    images
+                )
+            }
+            for candidate in okay {
+                println!("|   `{}`", self.green.paint(*candidate))
+            }
+        }
+        if !okay.is_empty() && !falsified.is_empty() {
+            println!("|")
+        }
+        if !falsified.is_empty() {
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsifiable")
+            );
+            for candidate in falsified {
+                println!("|   `{}`", self.red.paint(*candidate))
+            }
+        }
+        println!("|");
+        if !falsified.is_empty() {
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else if fixpoint {
+            println!(
+                "| - system is {}, all reachable states verify the candidate(s)",
+                self.green.paint("safe")
+            )
+        } else {
+            println!("| - system {}", self.red.paint("might be unsafe"))
+        }
+        println!("|===|")
+    }
+}
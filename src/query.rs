@@ -0,0 +1,80 @@
+//! Reachability queries.
+//!
+//! The candidates are replaced by the negation of the target, a falsification is a witness.
+
+mikino_api::prelude!();
+
+use crate::{cancel, text, timeout, Check, Outcome};
+
+/// Name of the candidate standing for the target.
+pub const TARGET_CANDIDATE: &str = "reach target";
+
+/// Replaces the candidates of a system by the negation of a target.
+///
+/// Works on the text of the system, fails if it has no `candidates { ... }` block.
+pub fn with_target(txt: &str, target: &str) -> Res<String> {
+    text::with_candidates(txt, &[(TARGET_CANDIDATE.into(), format!("¬({})", target))])
+}
+
+impl<'env> Check<'env> {
+    /// Answers a reachability query, the system must come from [`with_target`].
+    pub fn query(&self, target: &str, max: Option<usize>, prove: bool) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "checking whether `{}` is {}...",
+                s.bold.paint(target),
+                s.under.paint("reachable")
+            )
+        });
+
+        let cancel = cancel::Token::new();
+        let outcome = if prove {
+            self.simple_path_candidate(max, 0, TARGET_CANDIDATE, &cancel, false)
+        } else {
+            self.bmc_candidate(max, 0, TARGET_CANDIDATE, &cancel)
+        }
+        .chain_err(|| format!("during reachability query for `{}`", target))?;
+
+        if let Outcome::Falsified(trace) = &outcome {
+            println!(
+                "found a {} at depth {}:",
+                self.green.paint("witness"),
+                self.bold.paint((trace.len() - 1).to_string())
+            );
+            self.present_trace(&self.sys, TARGET_CANDIDATE, trace, &[], true)?;
+        }
+
+        println!();
+        println!("|===| {} query result", self.bold.paint("Reachability"));
+        match outcome {
+            Outcome::Falsified(trace) => println!(
+                "| - `{}` is {} in {} step(s)",
+                self.bold.paint(target),
+                self.green.paint("reachable"),
+                trace.len() - 1
+            ),
+            Outcome::Proved(k) => println!(
+                "| - `{}` is {}, its negation is {}-inductive",
+                self.bold.paint(target),
+                self.red.paint("unreachable"),
+                k
+            ),
+            // A query exceeding the `--query_timeout` stops the search early.
+            Outcome::Unknown if max.is_some() && self.env.query_timeout.is_none() => println!(
+                "| - `{}` is {} {}",
+                self.bold.paint(target),
+                self.red.paint("not reachable"),
+                timeout::unknown_bound(max, false)
+            ),
+            Outcome::Unknown => println!(
+                "| - no witness for `{}` was found {}, reachability is {}",
+                self.bold.paint(target),
+                timeout::unknown_bound(max, true),
+                self.red.paint("unknown")
+            ),
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
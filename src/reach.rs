@@ -0,0 +1,70 @@
+//! Exact symbolic reachability engine for boolean systems.
+//!
+//! Runs the fixpoint of [`crate::qe`], boolean state spaces guarantee it terminates. Falsifications
+//! come with a shortest trace, rebuilt backwards through the images.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check, Trace};
+
+impl<'env> Check<'env> {
+    /// Shortest trace to a state verifying `bad`, `None` if no reachable state does.
+    ///
+    /// `layers[k]` are the states reachable in `k` steps or less, at step `0`.
+    pub fn reach_trace(
+        &self,
+        solver: &mut smt::Solver,
+        layers: &[smt::SExpr],
+        bad: &str,
+    ) -> Res<Option<Trace>> {
+        let sys = &self.sys;
+        let mut found = None;
+        for (depth, layer) in layers.iter().enumerate() {
+            solver.push()?;
+            solver.assert(layer)?;
+            solver.assert(bad)?;
+            if solver.check_sat()? {
+                found = Some((depth, solver.get_state(sys, 0)?))
+            }
+            solver.pop()?;
+            if found.is_some() {
+                break;
+            }
+        }
+        let Some((depth, mut state)) = found else {
+            return Ok(None);
+        };
+
+        let mut rev = vec![smt::pretty_state(&state)];
+        for layer in layers[..depth].iter().rev() {
+            solver.push()?;
+            solver.assert(layer)?;
+            solver.assert_trans(sys, 0)?;
+            solver.assert(smt::state_at(&state, 1))?;
+            if !solver.check_sat()? {
+                bail!("[fatal] reachable state has no predecessor in the previous image")
+            }
+            state = solver.get_state(sys, 0)?;
+            solver.pop()?;
+            rev.push(smt::pretty_state(&state))
+        }
+        Ok(Some(rev.into_iter().rev().enumerate().collect()))
+    }
+
+    /// Runs the exact symbolic reachability engine.
+    pub fn reach(&self) -> Res<()> {
+        if let Some(var) = self
+            .sys
+            .decls()
+            .iter()
+            .find(|var| var.typ() != expr::Typ::Bool)
+        {
+            bail!(
+                "the `reach` engine only supports boolean systems, variable `{}` is not boolean; \
+                the `explicit` engine supports small finite systems",
+                self.bold.paint(var.id())
+            )
+        }
+        self.image_engine("reach", None, true)
+    }
+}
@@ -0,0 +1,209 @@
+//! Report renderers, built-in and external.
+//!
+//! External renderers read the result model of the run as JSON on their standard input.
+
+mikino_api::prelude!();
+
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+};
+
+use crate::{
+    budget::Verdict,
+    json::{json_array, json_object, json_str, json_trace},
+    metadata::Metadata,
+    report::{self, Format, Status},
+    Run,
+};
+
+/// Version of the JSON result model.
+pub const MODEL_VERSION: usize = 1;
+
+/// Renders the result model of a run as a report.
+pub trait Renderer {
+    /// Renders a report, `meta` is the metadata of the run.
+    fn render(&self, meta: &Metadata, verdict: &Verdict) -> Res<String>;
+}
+
+impl Renderer for Format {
+    fn render(&self, meta: &Metadata, verdict: &Verdict) -> Res<String> {
+        let content = match self {
+            Format::Junit => report::junit(meta, &verdict.suites),
+            Format::Sarif => report::sarif(meta, &verdict.suites),
+            Format::Html => report::html(meta, &verdict.suites),
+            Format::Markdown => report::markdown(meta, verdict),
+            Format::Text => report::text(meta, verdict),
+            Format::External(name) => {
+                bail!("[fatal] external renderer `{}` used as a format", name)
+            }
+        };
+        Ok(content)
+    }
+}
+
+/// An external renderer, a command turning the JSON result model into a report.
+#[derive(Debug, Clone)]
+pub struct External {
+    /// Name of the renderer, used as a report format.
+    pub name: String,
+    /// Command.
+    pub cmd: String,
+}
+impl External {
+    /// Parses a `<NAME>=<CMD>` renderer description.
+    pub fn parse(desc: &str) -> Result<Self, String> {
+        let (name, cmd) = desc
+            .split_once('=')
+            .ok_or_else(|| format!("expected `<NAME>=<CMD>`, found `{}`", desc))?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("illegal renderer name `{}`", name));
+        }
+        if Format::NAMES.contains(&name) {
+            return Err(format!("cannot override built-in report format `{}`", name));
+        }
+        if cmd.trim().is_empty() {
+            return Err(format!("missing command in renderer `{}`", desc));
+        }
+        Ok(Self {
+            name: name.into(),
+            cmd: cmd.into(),
+        })
+    }
+}
+impl Renderer for External {
+    fn render(&self, meta: &Metadata, verdict: &Verdict) -> Res<String> {
+        let err = || format!("while running renderer `{}` (`{}`)", self.name, self.cmd);
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.cmd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .chain_err(err)?;
+        let model = json_model(meta, verdict);
+        // Write from another thread, the renderer may fill its output pipe before reading all
+        // its input.
+        let mut stdin = child.stdin.take().ok_or_else(err)?;
+        let writer = std::thread::spawn(move || {
+            // The renderer may not read the model, ignore broken pipes.
+            let _ = writeln!(stdin, "{}", model);
+        });
+        let mut content = String::new();
+        child
+            .stdout
+            .take()
+            .ok_or_else(err)?
+            .read_to_string(&mut content)
+            .chain_err(err)?;
+        let _ = writer.join();
+        let status = child.wait().chain_err(err)?;
+        if !status.success() {
+            bail!("renderer `{}` failed ({})", self.name, status)
+        }
+        Ok(content)
+    }
+}
+
+/// JSON result model of a run, the input of external renderers.
+pub fn json_model(meta: &Metadata, verdict: &Verdict) -> String {
+    let seconds = |time: &std::time::Duration| format!("{:.3}", time.as_secs_f64());
+    let systems: Vec<String> = verdict
+        .suites
+        .iter()
+        .map(|suite| {
+            let statuses: Vec<(&str, String)> = suite
+                .statuses
+                .iter()
+                .map(|(candidate, status)| (candidate.as_str(), json_str(status)))
+                .collect();
+            let lines: Vec<(&str, String)> = suite
+                .lines
+                .iter()
+                .map(|(candidate, line)| (candidate.as_str(), line.to_string()))
+                .collect();
+            let timings: Vec<(&str, String)> = suite
+                .timings
+                .iter()
+                .map(|(phase, time)| (phase.as_str(), seconds(time)))
+                .collect();
+            let cases: Vec<String> = suite
+                .cases
+                .iter()
+                .map(|case| {
+                    let (status, message) = match &case.status {
+                        Status::Passed => ("passed", "null".into()),
+                        Status::Failed(msg) => ("failed", json_str(msg)),
+                        Status::Skipped(msg) => ("skipped", json_str(msg)),
+                    };
+                    json_object(&[
+                        ("phase", json_str(case.phase)),
+                        ("candidate", json_str(&case.candidate)),
+                        ("status", json_str(status)),
+                        ("message", message),
+                        (
+                            "cex_id",
+                            case.cex_id
+                                .as_deref()
+                                .map(json_str)
+                                .unwrap_or_else(|| "null".into()),
+                        ),
+                        (
+                            "trace",
+                            case.trace
+                                .as_ref()
+                                .map(json_trace)
+                                .unwrap_or_else(|| "null".into()),
+                        ),
+                    ])
+                })
+                .collect();
+            json_object(&[
+                ("name", json_str(&suite.name)),
+                ("file", json_str(&suite.file)),
+                ("time", seconds(&suite.time)),
+                ("statuses", json_object(&statuses)),
+                ("lines", json_object(&lines)),
+                ("timings", json_object(&timings)),
+                ("cases", json_array(&cases)),
+            ])
+        })
+        .collect();
+    json_object(&[
+        ("version", MODEL_VERSION.to_string()),
+        ("run_id", json_str(&meta.run_id)),
+        ("metadata", meta.to_json()),
+        (
+            "summary",
+            json_object(&[
+                ("proved", verdict.proved.len().to_string()),
+                ("falsified", verdict.falsified.len().to_string()),
+                ("unknown", verdict.unknown.len().to_string()),
+            ]),
+        ),
+        ("systems", json_array(&systems)),
+    ])
+}
+
+impl Run {
+    /// Renderer of a report format.
+    pub fn renderer<'a>(&'a self, format: &'a Format) -> Res<&'a dyn Renderer> {
+        match format {
+            Format::External(name) => match self.renderers.iter().find(|r| &r.name == name) {
+                Some(renderer) => Ok(renderer),
+                None => bail!(
+                    "unknown report format `{}`, expected one of {} or a renderer registered \
+                    with `--renderer`",
+                    name,
+                    Format::NAMES.join(", ")
+                ),
+            },
+            format => Ok(format),
+        }
+    }
+}
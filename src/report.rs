@@ -0,0 +1,799 @@
+//! Report files, for tools consuming verification results.
+
+mikino_api::prelude!();
+
+use std::{fs::File, io::Write, time::Duration};
+
+use check::{BaseRes, StepRes};
+
+use crate::{
+    budget::Verdict,
+    json::{json_array, json_object, json_str},
+    metadata::Metadata,
+    Check, Run, Trace,
+};
+
+/// Report formats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// JUnit XML.
+    Junit,
+    /// SARIF 2.1.0.
+    Sarif,
+    /// Self-contained HTML.
+    Html,
+    /// Markdown.
+    Markdown,
+    /// Plain text.
+    Text,
+    /// External renderer, see [`crate::render`].
+    External(String),
+}
+impl Format {
+    /// Format names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &["junit", "sarif", "html", "md", "text"];
+
+    /// Format from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "junit" => Some(Self::Junit),
+            "sarif" => Some(Self::Sarif),
+            "html" => Some(Self::Html),
+            "md" => Some(Self::Markdown),
+            "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// A report to write.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Format of the report.
+    pub format: Format,
+    /// File to write the report to.
+    pub path: String,
+}
+impl Report {
+    /// Parses a `<FORMAT>=<FILE>` report description.
+    pub fn parse(desc: &str) -> Result<Self, String> {
+        let (format, path) = desc
+            .split_once('=')
+            .ok_or_else(|| format!("expected `<FORMAT>=<FILE>`, found `{}`", desc))?;
+        // Unknown formats are external renderers, checked once they are all registered.
+        let format = match Format::from_name(format) {
+            Some(format) => format,
+            None if !format.is_empty()
+                && format
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+            {
+                Format::External(format.into())
+            }
+            None => {
+                return Err(format!(
+                    "unknown report format `{}`, expected one of {} or a renderer name",
+                    format,
+                    Format::NAMES.join(", ")
+                ))
+            }
+        };
+        if path.is_empty() {
+            return Err(format!("missing file in report `{}`", desc));
+        }
+        Ok(Self {
+            format,
+            path: path.into(),
+        })
+    }
+}
+
+/// Status of a case.
+#[derive(Debug, Clone)]
+pub enum Status {
+    /// The phase succeeded.
+    Passed,
+    /// The phase failed, with a description of the failure.
+    Failed(String),
+    /// The phase did not check the candidate, with the reason why.
+    Skipped(String),
+}
+
+/// Outcome of a phase on a candidate.
+#[derive(Debug, Clone)]
+pub struct Case {
+    /// Phase.
+    pub phase: &'static str,
+    /// Candidate.
+    pub candidate: String,
+    /// Status.
+    pub status: Status,
+    /// Counterexample, for failed cases.
+    pub trace: Option<Trace>,
+    /// ID of the counterexample in the store, except for counterexamples to induction, see
+    /// [`crate::cex_store`].
+    pub cex_id: Option<String>,
+}
+
+/// Cases of a system.
+#[derive(Debug, Clone)]
+pub struct Suite {
+    /// Description of the system.
+    pub name: String,
+    /// File the system comes from.
+    pub file: String,
+    /// Line of each candidate in `file`, with sanitized candidate names.
+    pub lines: Map<String, usize>,
+    /// Cases, with sanitized candidate names and traces.
+    pub cases: Vec<Case>,
+    /// Final status of each candidate, `proved`, `falsified` or `unknown`.
+    pub statuses: Map<String, &'static str>,
+    /// Time spent in each phase.
+    pub timings: Vec<(String, Duration)>,
+    /// Time spent checking the system.
+    pub time: Duration,
+    /// The system, pretty-printed.
+    pub system: String,
+}
+impl Suite {
+    /// Number of failed cases.
+    pub fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| matches!(case.status, Status::Failed(_)))
+            .count()
+    }
+    /// Failed base and BMC cases, which falsify their candidate, with their failure message.
+    pub fn falsifications(&self) -> impl Iterator<Item = (&Case, &str)> {
+        self.cases
+            .iter()
+            .filter_map(|case| match (&case.status, case.phase) {
+                (Status::Failed(msg), "base") | (Status::Failed(msg), "bmc") => {
+                    Some((case, msg.as_str()))
+                }
+                _ => None,
+            })
+    }
+    /// Number of skipped cases.
+    pub fn skipped(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| matches!(case.status, Status::Skipped(_)))
+            .count()
+    }
+}
+
+/// Escapes a string for XML.
+pub fn xml_escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for char in s.chars() {
+        match char {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&apos;"),
+            char => res.push(char),
+        }
+    }
+    res
+}
+
+/// ` [cex <ID>]` if the case has a stored counterexample, empty otherwise.
+pub fn cex_id_suffix(case: &Case) -> String {
+    case.cex_id
+        .as_ref()
+        .map(|id| format!(" [cex {}]", id))
+        .unwrap_or_default()
+}
+
+/// Plain text version of a trace, one line per variable.
+pub fn trace_text(trace: &Trace) -> String {
+    let mut res = String::new();
+    for (step, values) in trace {
+        res.push_str(&format!("step {}\n", step));
+        for (var, val) in values {
+            res.push_str(&format!("  {} = {}\n", var, val))
+        }
+    }
+    res
+}
+
+/// JUnit XML report.
+pub fn junit(meta: &Metadata, suites: &[Suite]) -> String {
+    let tests: usize = suites.iter().map(|suite| suite.cases.len()).sum();
+    let failures: usize = suites.iter().map(Suite::failures).sum();
+    let skipped: usize = suites.iter().map(Suite::skipped).sum();
+    let time: f64 = suites.iter().map(|suite| suite.time.as_secs_f64()).sum();
+    let mut xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    xml.push_str(&format!(
+        "<testsuites name=\"mikino run {}\" tests=\"{}\" failures=\"{}\" errors=\"0\" \
+        skipped=\"{}\" time=\"{:.3}\">\n",
+        xml_escape(&meta.run_id),
+        tests,
+        failures,
+        skipped,
+        time
+    ));
+    for suite in suites {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" \
+            time=\"{:.3}\">\n",
+            xml_escape(&suite.name),
+            suite.cases.len(),
+            suite.failures(),
+            suite.skipped(),
+            suite.time.as_secs_f64()
+        ));
+        xml.push_str("    <properties>\n");
+        for (name, value) in meta.fields() {
+            xml.push_str(&format!(
+                "      <property name=\"{}\" value=\"{}\"/>\n",
+                name,
+                xml_escape(&value)
+            ))
+        }
+        xml.push_str("    </properties>\n");
+        for case in &suite.cases {
+            let open = format!(
+                "    <testcase classname=\"{}.{}\" name=\"{}\"",
+                xml_escape(&suite.name),
+                case.phase,
+                xml_escape(&case.candidate)
+            );
+            match &case.status {
+                Status::Passed => xml.push_str(&format!("{}/>\n", open)),
+                Status::Failed(msg) => {
+                    let trace = case.trace.as_ref().map(trace_text).unwrap_or_default();
+                    xml.push_str(&format!(
+                        "{}>\n      <failure message=\"{}\" type=\"{}\">{}</failure>\n    \
+                        </testcase>\n",
+                        open,
+                        xml_escape(msg),
+                        case.phase,
+                        xml_escape(&trace)
+                    ))
+                }
+                Status::Skipped(msg) => xml.push_str(&format!(
+                    "{}>\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+                    open,
+                    xml_escape(msg)
+                )),
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// One-line summary of a trace, for messages.
+pub fn trace_summary(trace: &Trace) -> String {
+    let steps: Vec<String> = trace
+        .iter()
+        .map(|(step, values)| {
+            let values: Vec<String> = values
+                .iter()
+                .map(|(var, val)| format!("{} = {}", var, val))
+                .collect();
+            format!("step {}: {}", step, values.join(", "))
+        })
+        .collect();
+    steps.join("; ")
+}
+
+/// SARIF 2.1.0 report.
+pub fn sarif(meta: &Metadata, suites: &[Suite]) -> String {
+    let mut results = Vec::new();
+    for suite in suites {
+        for (case, msg) in suite.falsifications() {
+            let mut text = format!(
+                "candidate `{}` of `{}` is falsified ({})",
+                case.candidate, suite.name, msg
+            );
+            if let Some(trace) = case.trace.as_ref() {
+                text.push_str(&format!(", counterexample: {}", trace_summary(trace)))
+            }
+            let mut location = vec![(
+                "artifactLocation",
+                json_object(&[("uri", json_str(&suite.file))]),
+            )];
+            if let Some(line) = suite.lines.get(&case.candidate) {
+                location.push(("region", json_object(&[("startLine", line.to_string())])))
+            }
+            results.push(json_object(&[
+                ("ruleId", json_str("falsified-candidate")),
+                ("level", json_str("error")),
+                ("message", json_object(&[("text", json_str(&text))])),
+                (
+                    "locations",
+                    json_array(&[json_object(&[("physicalLocation", json_object(&location))])]),
+                ),
+            ]))
+        }
+    }
+    let rule = json_object(&[
+        ("id", json_str("falsified-candidate")),
+        (
+            "shortDescription",
+            json_object(&[("text", json_str("Falsified candidate"))]),
+        ),
+        (
+            "fullDescription",
+            json_object(&[(
+                "text",
+                json_str("A reachable state of the system falsifies the candidate."),
+            )]),
+        ),
+    ]);
+    let driver = json_object(&[
+        ("name", json_str("mikino")),
+        ("version", json_str(clap::crate_version!())),
+        ("informationUri", json_str(env!("CARGO_PKG_HOMEPAGE"))),
+        ("rules", json_array(&[rule])),
+    ]);
+    let run = json_object(&[
+        ("tool", json_object(&[("driver", driver)])),
+        (
+            "automationDetails",
+            json_object(&[("id", json_str(&format!("mikino/{}", meta.run_id)))]),
+        ),
+        (
+            "invocations",
+            json_array(&[json_object(&[
+                ("commandLine", json_str(&meta.command_line.join(" "))),
+                ("startTimeUtc", json_str(&meta.date)),
+                ("executionSuccessful", "true".into()),
+            ])]),
+        ),
+        (
+            "artifacts",
+            json_array(&[json_object(&[
+                ("location", json_object(&[("uri", json_str(&meta.input))])),
+                (
+                    "hashes",
+                    json_object(&[("sha-256", json_str(&meta.input_sha256))]),
+                ),
+            ])]),
+        ),
+        ("properties", json_object(&[("metadata", meta.to_json())])),
+        ("results", json_array(&results)),
+    ]);
+    let mut sarif = json_object(&[
+        (
+            "$schema",
+            json_str("https://json.schemastore.org/sarif-2.1.0.json"),
+        ),
+        ("version", json_str("2.1.0")),
+        ("runs", json_array(&[run])),
+    ]);
+    sarif.push('\n');
+    sarif
+}
+
+/// Style of the HTML report.
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h1, h2 { font-weight: normal; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { padding: .3em .8em; text-align: left; border-bottom: 1px solid #ddd; }
+.badge { display: inline-block; padding: .1em .6em; border-radius: .8em; font-size: .85em; \
+color: white; }
+.proved, .passed { background: #2a8a3e; }
+.falsified, .failed { background: #c0392b; }
+.unknown, .skipped { background: #888; }
+details { margin: .5em 0; }
+summary { cursor: pointer; }
+pre { background: #f4f4f4; padding: .8em; overflow-x: auto; }
+.bar { background: #4a78b5; height: 1em; }
+.chart td { border: none; padding: .1em .8em; }
+";
+
+/// HTML badge for a status.
+fn html_badge(status: &str) -> String {
+    format!("<span class=\"badge {0}\">{0}</span>", status)
+}
+
+/// HTML table of a trace, one column per step.
+fn html_trace(trace: &Trace) -> String {
+    let mut html = "<table>\n<tr><th>step</th>".to_string();
+    for (step, _) in trace {
+        html.push_str(&format!("<th>{}</th>", step))
+    }
+    html.push_str("</tr>\n");
+    let vars = trace.first().map(|(_, values)| values.len()).unwrap_or(0);
+    for idx in 0..vars {
+        let var = &trace[0].1[idx].0;
+        html.push_str(&format!("<tr><td><code>{}</code></td>", xml_escape(var)));
+        for (_, values) in trace {
+            let val = values.get(idx).map(|(_, val)| val.as_str()).unwrap_or("");
+            html.push_str(&format!("<td><code>{}</code></td>", xml_escape(val)))
+        }
+        html.push_str("</tr>\n")
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// Self-contained HTML report.
+pub fn html(meta: &Metadata, suites: &[Suite]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>mikino run {0}</title>\n<style>\n{1}</style>\n</head>\n<body>\n\
+        <h1>mikino run <code>{0}</code></h1>\n",
+        xml_escape(&meta.run_id),
+        HTML_STYLE
+    );
+    html.push_str("<table>\n");
+    for (name, value) in meta.fields() {
+        html.push_str(&format!(
+            "<tr><th>{}</th><td><code>{}</code></td></tr>\n",
+            name,
+            xml_escape(&value)
+        ))
+    }
+    html.push_str("</table>\n");
+    for suite in suites {
+        html.push_str(&format!(
+            "<h2>System <code>{}</code></h2>\n",
+            xml_escape(&suite.name)
+        ));
+
+        let phases: Vec<&str> = suite.cases.iter().fold(Vec::new(), |mut phases, case| {
+            if !phases.contains(&case.phase) {
+                phases.push(case.phase)
+            }
+            phases
+        });
+        html.push_str("<table>\n<tr><th>candidate</th><th>status</th>");
+        for phase in &phases {
+            html.push_str(&format!("<th>{}</th>", phase))
+        }
+        html.push_str("</tr>\n");
+        for (candidate, status) in &suite.statuses {
+            html.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td>",
+                xml_escape(candidate),
+                html_badge(status)
+            ));
+            for phase in &phases {
+                let case = suite
+                    .cases
+                    .iter()
+                    .find(|case| case.phase == *phase && &case.candidate == candidate);
+                let badge = match case.map(|case| &case.status) {
+                    Some(Status::Passed) => html_badge("passed"),
+                    Some(Status::Failed(_)) => html_badge("failed"),
+                    Some(Status::Skipped(_)) => html_badge("skipped"),
+                    None => String::new(),
+                };
+                html.push_str(&format!("<td>{}</td>", badge))
+            }
+            html.push_str("</tr>\n")
+        }
+        html.push_str("</table>\n");
+
+        for case in &suite.cases {
+            if let (Status::Failed(msg), Some(trace)) = (&case.status, case.trace.as_ref()) {
+                html.push_str(&format!(
+                    "<details>\n<summary><code>{}</code>, {}: {}</summary>\n{}</details>\n",
+                    xml_escape(&case.candidate),
+                    case.phase,
+                    xml_escape(msg),
+                    html_trace(trace)
+                ))
+            }
+        }
+
+        let slowest = suite
+            .timings
+            .iter()
+            .map(|(_, time)| time.as_secs_f64())
+            .fold(0.0, f64::max);
+        html.push_str(&format!(
+            "<h3>Timings, {:.3}s total</h3>\n<table class=\"chart\">\n",
+            suite.time.as_secs_f64()
+        ));
+        for (phase, time) in &suite.timings {
+            let width = if slowest > 0.0 {
+                100.0 * time.as_secs_f64() / slowest
+            } else {
+                0.0
+            };
+            html.push_str(&format!(
+                "<tr><td>{}</td><td style=\"width: 30em\"><div class=\"bar\" \
+                style=\"width: {:.1}%\"></div></td><td>{:.3}s</td></tr>\n",
+                xml_escape(phase),
+                width,
+                time.as_secs_f64()
+            ))
+        }
+        html.push_str("</table>\n");
+
+        html.push_str(&format!(
+            "<details>\n<summary>System, from <code>{}</code></summary>\n<pre>{}</pre>\n\
+            </details>\n",
+            xml_escape(&suite.file),
+            xml_escape(&suite.system)
+        ))
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Escapes a string for Markdown table cells and inline code.
+fn md_escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('`', "'")
+}
+
+/// Markdown report.
+pub fn markdown(meta: &Metadata, verdict: &Verdict) -> String {
+    let mut md = format!("## mikino run `{}`\n\n", md_escape(&meta.run_id));
+    md.push_str("<details><summary>Run metadata</summary>\n\n| | |\n|---|---|\n");
+    for (name, value) in meta.fields() {
+        md.push_str(&format!("| {} | `{}` |\n", name, md_escape(&value)))
+    }
+    md.push_str("\n</details>\n\n");
+    let icon = if !verdict.falsified.is_empty() {
+        "❌"
+    } else if !verdict.unknown.is_empty() {
+        "⚠️"
+    } else {
+        "✅"
+    };
+    md.push_str(&format!(
+        "{} **{}** proved, **{}** falsified, **{}** unknown\n",
+        icon,
+        verdict.proved.len(),
+        verdict.falsified.len(),
+        verdict.unknown.len()
+    ));
+    for suite in &verdict.suites {
+        md.push_str(&format!(
+            "\n### `{}`\n\n| candidate | status | detail |\n|---|---|---|\n",
+            md_escape(&suite.name)
+        ));
+        for (candidate, status) in &suite.statuses {
+            let icon = match *status {
+                "proved" => "✅",
+                "falsified" => "❌",
+                _ => "⚠️",
+            };
+            let detail: Vec<String> = suite
+                .cases
+                .iter()
+                .filter(|case| &case.candidate == candidate)
+                .filter_map(|case| match &case.status {
+                    Status::Failed(msg) => Some(format!("{}: {}", case.phase, msg)),
+                    _ => None,
+                })
+                .collect();
+            md.push_str(&format!(
+                "| `{}` | {} {} | {} |\n",
+                md_escape(candidate),
+                icon,
+                status,
+                md_escape(&detail.join(", "))
+            ))
+        }
+        for case in &suite.cases {
+            if let (Status::Failed(msg), Some(trace)) = (&case.status, case.trace.as_ref()) {
+                md.push_str(&format!(
+                    "\n<details><summary><code>{}</code>, {}: {}{}</summary>\n\n```\n{}```\n\n\
+                    </details>\n",
+                    xml_escape(&case.candidate),
+                    case.phase,
+                    xml_escape(msg),
+                    cex_id_suffix(case),
+                    trace_text(trace)
+                ))
+            }
+        }
+    }
+    md
+}
+
+/// Plain text report.
+pub fn text(meta: &Metadata, verdict: &Verdict) -> String {
+    let mut txt = format!("|===| mikino run {}\n", meta.run_id);
+    for (name, value) in meta.fields().into_iter().skip(1) {
+        txt.push_str(&format!("| {}: {}\n", name, value))
+    }
+    txt.push_str("|\n");
+    txt.push_str(&format!(
+        "| - {} proved, {} falsified, {} unknown\n",
+        verdict.proved.len(),
+        verdict.falsified.len(),
+        verdict.unknown.len()
+    ));
+    let system = if !verdict.falsified.is_empty() {
+        "is unsafe"
+    } else if !verdict.unknown.is_empty() {
+        "might be unsafe"
+    } else {
+        "is safe"
+    };
+    txt.push_str(&format!("| - system {}\n|===|\n", system));
+    for suite in &verdict.suites {
+        txt.push_str(&format!("\n|===| System `{}`\n", suite.name));
+        for (status, desc) in &[
+            ("proved", "proved"),
+            ("falsified", "falsified"),
+            ("unknown", "neither proved nor falsified"),
+        ] {
+            let candidates: Vec<&String> = suite
+                .statuses
+                .iter()
+                .filter(|(_, s)| *s == status)
+                .map(|(candidate, _)| candidate)
+                .collect();
+            if candidates.is_empty() {
+                continue;
+            }
+            txt.push_str(&format!("| - {}\n", desc));
+            for candidate in candidates {
+                let detail: Vec<String> = suite
+                    .cases
+                    .iter()
+                    .filter(|case| &case.candidate == candidate)
+                    .filter_map(|case| match &case.status {
+                        Status::Failed(msg) => Some(format!("{}: {}", case.phase, msg)),
+                        _ => None,
+                    })
+                    .collect();
+                if detail.is_empty() {
+                    txt.push_str(&format!("|   `{}`\n", candidate))
+                } else {
+                    txt.push_str(&format!("|   `{}` ({})\n", candidate, detail.join(", ")))
+                }
+            }
+        }
+        txt.push_str("|===|\n");
+        for case in &suite.cases {
+            if let (Status::Failed(msg), Some(trace)) = (&case.status, case.trace.as_ref()) {
+                txt.push_str(&format!(
+                    "\n- `{}`, {}: {}{}\n",
+                    case.candidate,
+                    case.phase,
+                    msg,
+                    cex_id_suffix(case)
+                ));
+                for line in trace_text(trace).lines() {
+                    txt.push_str(&format!("  {}\n", line))
+                }
+            }
+        }
+    }
+    txt
+}
+
+impl<'env> Check<'env> {
+    /// Records a case for the reports, if any.
+    pub fn record_case(
+        &self,
+        phase: &'static str,
+        candidate: &str,
+        status: Status,
+        trace: Option<Trace>,
+    ) -> Res<()> {
+        if !self.env.records_cases() {
+            return Ok(());
+        }
+        let cex_id = match trace.as_ref() {
+            Some(trace) if phase != "step" => Some(self.cex_id(candidate, trace)),
+            _ => None,
+        };
+        self.cases
+            .lock()
+            .map_err(|_| "failed to record report case")?
+            .push(Case {
+                phase,
+                candidate: candidate.into(),
+                status,
+                trace,
+                cex_id,
+            });
+        Ok(())
+    }
+
+    /// Records the cases of the base and step phases.
+    pub fn record_induction_cases(&self, base: &BaseRes, step: &StepRes) -> Res<()> {
+        for candidate in self.sys.po_s().keys() {
+            let (status, trace) = match base.cexs.get(candidate.as_str()) {
+                Some(cex) => (
+                    Status::Failed("falsified in the initial state(s)".into()),
+                    Some(self.cex_trace(cex, true)?),
+                ),
+                None => (Status::Passed, None),
+            };
+            self.record_case("base", candidate, status, trace)?;
+            let (status, trace) = match step.cexs.get(candidate.as_str()) {
+                Some(cex) => (
+                    Status::Failed("not inductive, counterexample to induction".into()),
+                    Some(self.cex_trace(cex, false)?),
+                ),
+                None => (Status::Passed, None),
+            };
+            self.record_case("step", candidate, status, trace)?
+        }
+        Ok(())
+    }
+
+    /// Cases recorded for a system, `name` describes the system.
+    ///
+    /// `file` is the file the system comes from, and `lines` the line of each candidate in it.
+    pub fn suite(&self, name: &str, file: &str, lines: &Map<String, usize>) -> Res<Suite> {
+        let cases = self
+            .cases
+            .lock()
+            .map_err(|_| "failed to access report cases")?
+            .iter()
+            .map(|case| Case {
+                phase: case.phase,
+                candidate: self.sanitized_text(&case.candidate),
+                status: case.status.clone(),
+                trace: case.trace.as_ref().map(|trace| self.sanitized_trace(trace)),
+                cex_id: case.cex_id.clone(),
+            })
+            .collect();
+        let timings = self
+            .timings
+            .lock()
+            .map_err(|_| "failed to access phase timings")?
+            .clone();
+        let time = timings.iter().map(|(_, time)| *time).sum();
+        let lines = lines
+            .iter()
+            .map(|(candidate, line)| (self.sanitized_text(candidate), *line))
+            .collect();
+        let settled = self
+            .settled
+            .lock()
+            .map_err(|_| "failed to access candidate statuses")?;
+        let statuses = self
+            .sys
+            .po_s()
+            .keys()
+            .map(|candidate| {
+                let status = match settled.get(candidate) {
+                    Some(true) => "proved",
+                    Some(false) => "falsified",
+                    None => "unknown",
+                };
+                (self.sanitized_text(candidate), status)
+            })
+            .collect();
+        Ok(Suite {
+            name: name.into(),
+            file: file.into(),
+            lines,
+            cases,
+            statuses,
+            timings,
+            time,
+            system: self.sanitized_text(&self.sys.to_ml_string()),
+        })
+    }
+}
+
+impl Run {
+    /// True if the cases of the phases are recorded, for reports or annotations.
+    pub fn records_cases(&self) -> bool {
+        !self.reports.is_empty() || self.github_annotations
+    }
+
+    /// Writes the reports of a check run, if any.
+    pub fn write_reports(&self, meta: &Metadata, verdict: &Verdict) -> Res<()> {
+        for report in &self.reports {
+            let content = self.renderer(&report.format)?.render(meta, verdict)?;
+            let mut file = File::create(&report.path)
+                .chain_err(|| format!("while creating report file `{}`", report.path))?;
+            file.write_all(content.as_bytes())
+                .chain_err(|| format!("while writing report to `{}`", report.path))?;
+            if self.verb > 0 {
+                self.progress(|s| format!("report written to `{}`", s.bold.paint(&report.path)))
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+//! Comparison of two revisions of a system.
+
+mikino_api::prelude!();
+
+use crate::{smt, Check, Phase, Run};
+
+/// Status of a candidate in a revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Inductive.
+    Proved,
+    /// Falsified, at some depth if known.
+    Falsified(Option<usize>),
+    /// Neither.
+    Unknown,
+}
+impl Status {
+    /// Description of the status.
+    pub fn desc(self) -> String {
+        match self {
+            Self::Proved => "proved".into(),
+            Self::Falsified(Some(depth)) => format!("falsified at depth {}", depth),
+            Self::Falsified(None) => "falsified".into(),
+            Self::Unknown => "unknown".into(),
+        }
+    }
+}
+
+/// Change of status of a candidate between two revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    /// Newly falsified, regression.
+    NewlyFalsified,
+    /// No longer proved but not falsified, regression.
+    NoLongerProved,
+    /// Newly proved.
+    NewlyProved,
+    /// No longer falsified but not proved.
+    NoLongerFalsified,
+    /// Only in the new revision.
+    Added,
+    /// Only in the old revision.
+    Removed,
+    /// Same status.
+    Unchanged,
+}
+impl Change {
+    /// Change from the old status of a candidate to its new one.
+    pub fn new(old: Option<Status>, new: Option<Status>) -> Self {
+        use Status::*;
+        match (old, new) {
+            (None, _) => Self::Added,
+            (_, None) => Self::Removed,
+            (Some(Falsified(_)), Some(Falsified(_))) => Self::Unchanged,
+            (Some(_), Some(Falsified(_))) => Self::NewlyFalsified,
+            (Some(Proved), Some(Unknown)) => Self::NoLongerProved,
+            (Some(Proved), Some(Proved)) | (Some(Unknown), Some(Unknown)) => Self::Unchanged,
+            (Some(_), Some(Proved)) => Self::NewlyProved,
+            (Some(Falsified(_)), Some(Unknown)) => Self::NoLongerFalsified,
+        }
+    }
+
+    /// Description of the change.
+    pub fn desc(self) -> &'static str {
+        match self {
+            Self::NewlyFalsified => "newly falsified",
+            Self::NoLongerProved => "no longer proved",
+            Self::NewlyProved => "newly proved",
+            Self::NoLongerFalsified => "no longer falsified",
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Unchanged => "unchanged",
+        }
+    }
+
+    /// True if the change is a regression.
+    pub fn is_regression(self) -> bool {
+        matches!(self, Self::NewlyFalsified | Self::NoLongerProved)
+    }
+}
+
+/// Changes of status between the candidates of two revisions, regressions first.
+pub fn changes<'a>(
+    old: &'a Map<String, Status>,
+    new: &'a Map<String, Status>,
+) -> Vec<(Change, &'a String)> {
+    let mut changes: Vec<(Change, &String)> = old
+        .keys()
+        .chain(new.keys().filter(|c| !old.contains_key(*c)))
+        .map(|candidate| {
+            let change = Change::new(old.get(candidate).cloned(), new.get(candidate).cloned());
+            (change, candidate)
+        })
+        .collect();
+    changes.sort();
+    changes
+}
+
+impl<'env> Check<'env> {
+    /// Status of each candidate, BMC runs up to depth `bmc_max` on the non-inductive ones.
+    pub fn statuses(&self, bmc_max: usize) -> Res<Map<String, Status>> {
+        let sys = &self.sys;
+        let base = self.timed("base", || self.base_check())?;
+        let step = self.timed("step", || self.step_check())?;
+        let mut res = Map::new();
+        let mut left = Vec::new();
+        for (candidate, def) in sys.po_s().iter() {
+            if base.cexs.contains_key(candidate.as_str()) {
+                res.insert(candidate.clone(), Status::Falsified(Some(0)));
+            } else if step.cexs.contains_key(candidate.as_str()) {
+                left.push((candidate, def))
+            } else {
+                res.insert(candidate.clone(), Status::Proved);
+            }
+        }
+        if left.is_empty() {
+            return Ok(res);
+        }
+
+        let mut solver = self.phase_solver(Phase::Bmc, "compare_bmc.smt2")?;
+        solver.intent("compare BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        // The base check covers depth 0.
+        for depth in 1..=bmc_max {
+            if left.is_empty() {
+                break;
+            }
+            solver.declare_state(sys, depth)?;
+            solver.assert_trans(sys, depth - 1)?;
+            let mut idx = 0;
+            while idx < left.len() {
+                let (candidate, def) = left[idx];
+                solver.intent(&format!(
+                    "compare: is `{}` falsified at depth {}?",
+                    candidate, depth
+                ))?;
+                if !solver.unsat(&[format!("(not {})", smt::Unroll::new(def, depth))])? {
+                    res.insert(candidate.clone(), Status::Falsified(Some(depth)));
+                    left.remove(idx);
+                } else {
+                    idx += 1
+                }
+            }
+        }
+        for (candidate, _) in left {
+            res.insert(candidate.clone(), Status::Unknown);
+        }
+        Ok(res)
+    }
+}
+
+impl Run {
+    /// Checks two revisions of a system and reports the candidates whose status changed.
+    pub fn compare(
+        &self,
+        old: &str,
+        new: &str,
+        smt_log: &Option<String>,
+        bmc_max: usize,
+    ) -> Res<()> {
+        let mut statuses = Vec::with_capacity(2);
+        for (revision, input) in [("old", old), ("new", new)] {
+            self.progress(|s| {
+                format!(
+                    "checking {} revision `{}`...",
+                    revision,
+                    s.bold.paint(input)
+                )
+            });
+            let smt_log = smt_log
+                .as_ref()
+                .map(|dir| -> Res<String> {
+                    let dir = format!("{}/{}", dir, revision);
+                    std::fs::create_dir_all(&dir)
+                        .chain_err(|| format!("while creating SMT log directory `{}`", dir))?;
+                    Ok(dir)
+                })
+                .transpose()?;
+            let check = Check::new(self, input, &smt_log)
+                .chain_err(|| format!("while loading {} revision `{}`", revision, input))?;
+            statuses.push(check.statuses(bmc_max)?)
+        }
+        let (new_statuses, old_statuses) = (
+            statuses.pop().expect("two revisions"),
+            statuses.pop().expect("two revisions"),
+        );
+
+        println!();
+        self.present_changes("Compare", &old_statuses, &new_statuses);
+        Ok(())
+    }
+
+    /// Presents the changes of status between two revisions, yields the number of regressions.
+    ///
+    /// Unchanged candidates are only listed in verbose mode.
+    pub fn present_changes(
+        &self,
+        title: &str,
+        old_statuses: &Map<String, Status>,
+        new_statuses: &Map<String, Status>,
+    ) -> usize {
+        let changes = changes(old_statuses, new_statuses);
+        let regressions = changes.iter().filter(|(c, _)| c.is_regression()).count();
+        let unchanged = changes
+            .iter()
+            .filter(|(c, _)| *c == Change::Unchanged)
+            .count();
+
+        println!("|===| {} result", self.bold.paint(title));
+        println!(
+            "| - {} candidate(s), {} changed, {} unchanged",
+            changes.len(),
+            changes.len() - unchanged,
+            unchanged
+        );
+        let mut prev = None;
+        for (change, candidate) in &changes {
+            if *change == Change::Unchanged && self.verb == 0 {
+                continue;
+            }
+            if prev != Some(*change) {
+                println!("|");
+                let desc = if change.is_regression() {
+                    self.red.paint(change.desc())
+                } else if *change == Change::NewlyProved {
+                    self.green.paint(change.desc())
+                } else {
+                    self.bold.paint(change.desc())
+                };
+                println!("| - {}", desc);
+                prev = Some(*change)
+            }
+            let desc = |statuses: &Map<String, Status>| {
+                statuses
+                    .get(*candidate)
+                    .map(|status| status.desc())
+                    .unwrap_or_else(|| "-".into())
+            };
+            println!(
+                "|   `{}`: {} -> {}",
+                self.bold.paint(*candidate),
+                desc(old_statuses),
+                desc(new_statuses)
+            )
+        }
+        println!("|");
+        if regressions > 0 {
+            println!("| - {} {}", regressions, self.red.paint("regression(s)"))
+        } else {
+            println!("| - {} regression", self.green.paint("no"))
+        }
+        println!("|===|");
+        regressions
+    }
+}
@@ -0,0 +1,154 @@
+//! Counterexample sanitization, renames identifiers and rescales values in traces.
+
+mikino_api::prelude!();
+
+use toml::Value;
+
+use crate::{conf::Conf, Check, Trace};
+
+/// Renaming and rescaling mapping.
+#[derive(Debug, Clone, Default)]
+pub struct Sanitizer {
+    /// New names of identifiers.
+    pub rename: Map<String, String>,
+    /// Scaling factors of the numeric values of variables.
+    pub scale: Map<String, f64>,
+}
+
+impl Sanitizer {
+    /// Loads a mapping file.
+    pub fn load(file: &str) -> Res<Self> {
+        let conf = Conf::load(file)?;
+        let mut res = Self::default();
+        for (key, table) in &conf.table {
+            let table = match (key.as_str(), table) {
+                ("rename" | "scale", Value::Table(table)) => table,
+                ("rename" | "scale", _) => {
+                    bail!("`{}` in mapping file `{}` is not a table", key, file)
+                }
+                _ => bail!(
+                    "unexpected key `{}` in mapping file `{}`, expected `rename` or `scale`",
+                    key,
+                    file
+                ),
+            };
+            for (id, val) in table {
+                match (key.as_str(), val) {
+                    ("rename", Value::String(name)) => {
+                        res.rename.insert(id.clone(), name.clone());
+                    }
+                    ("scale", Value::Float(factor)) => {
+                        res.scale.insert(id.clone(), *factor);
+                    }
+                    ("scale", Value::Integer(factor)) => {
+                        res.scale.insert(id.clone(), *factor as f64);
+                    }
+                    _ => bail!(
+                        "illegal value for `{}` in `[{}]` of mapping file `{}`, expected a {}",
+                        id,
+                        key,
+                        file,
+                        if key == "rename" { "string" } else { "number" }
+                    ),
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Sanitized name of an identifier.
+    pub fn ident<'a>(&'a self, id: &'a str) -> &'a str {
+        self.rename.get(id).map(String::as_str).unwrap_or(id)
+    }
+
+    /// Renames the identifiers of some text, typically an expression.
+    pub fn text(&self, txt: &str) -> String {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        let mut res = String::with_capacity(txt.len());
+        let mut token = String::new();
+        for c in txt.chars() {
+            if is_ident(c) {
+                token.push(c)
+            } else {
+                res.push_str(self.ident(&token));
+                token.clear();
+                res.push(c)
+            }
+        }
+        res.push_str(self.ident(&token));
+        res
+    }
+
+    /// Sanitized value of a variable.
+    pub fn value(&self, var: &str, val: &str) -> String {
+        let factor = match self.scale.get(var) {
+            Some(factor) => *factor,
+            None => return val.into(),
+        };
+        let (raw, suffix) = match val.strip_suffix(" (completed)") {
+            Some(raw) => (raw, " (completed)"),
+            None => (val, ""),
+        };
+        let num = match raw.split_once('/') {
+            Some((num, den)) => num
+                .trim()
+                .parse::<f64>()
+                .and_then(|num| den.trim().parse::<f64>().map(|den| num / den)),
+            None => raw.parse::<f64>(),
+        };
+        match num {
+            Ok(num) => {
+                let scaled = num * factor;
+                if scaled.fract() == 0.0 && scaled.abs() < 1e15 {
+                    format!("{}{}", scaled as i64, suffix)
+                } else {
+                    format!("{}{}", scaled, suffix)
+                }
+            }
+            // Booleans are not rescaled.
+            Err(_) => val.into(),
+        }
+    }
+
+    /// Sanitized values of a state.
+    pub fn values(&self, values: &[(String, String)]) -> Vec<(String, String)> {
+        values
+            .iter()
+            .map(|(var, val)| (self.ident(var).to_string(), self.value(var, val)))
+            .collect()
+    }
+
+    /// Sanitized trace.
+    pub fn trace(&self, trace: &Trace) -> Trace {
+        trace
+            .iter()
+            .map(|(step, values)| (*step, self.values(values)))
+            .collect()
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Trace as it should be output, sanitized if `--sanitize_cex` is active.
+    pub fn sanitized_trace(&self, trace: &Trace) -> Trace {
+        match self.sanitizer.as_ref() {
+            Some(sanitizer) => sanitizer.trace(trace),
+            None => trace.clone(),
+        }
+    }
+
+    /// State values as they should be output, see [`Self::sanitized_trace`].
+    pub fn sanitized_values(&self, values: &[(String, String)]) -> Vec<(String, String)> {
+        match self.sanitizer.as_ref() {
+            Some(sanitizer) => sanitizer.values(values),
+            None => values.to_vec(),
+        }
+    }
+
+    /// Text as it should be output, see [`Self::sanitized_trace`].
+    pub fn sanitized_text(&self, txt: &str) -> String {
+        match self.sanitizer.as_ref() {
+            Some(sanitizer) => sanitizer.text(txt),
+            None => txt.into(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Sanity checks on the system, no initial states or no successors, before the actual analyses.
+
+mikino_api::prelude!();
+
+use crate::{Check, Phase};
+
+impl<'env> Check<'env> {
+    /// Checks that the initial predicate, and the initial predicate and the transition relation,
+    /// are satisfiable.
+    ///
+    /// Only reports something if one of them is not. Yields `ok`, `contradictory_init` or
+    /// `contradictory_trans`, for the JSON output.
+    pub fn sanity(&self) -> Res<&'static str> {
+        let sys = &self.sys;
+        if self.env.verb > 0 {
+            self.env
+                .progress(|s| format!("running {} checks...", s.bold.paint("sanity")))
+        }
+        let mut solver = self.phase_solver(Phase::Base, "sanity.smt2")?;
+        solver.intent("sanity: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+        solver.intent("sanity: is the initial predicate satisfiable?")?;
+        let init = solver.check_sat()?;
+        let trans = if init {
+            solver.declare_state(sys, 1)?;
+            solver.assert_trans(sys, 0)?;
+            solver.intent("sanity: does some initial state have a successor?")?;
+            solver.check_sat()?
+        } else {
+            false
+        };
+        let status = if !init {
+            "contradictory_init"
+        } else if !trans {
+            "contradictory_trans"
+        } else {
+            "ok"
+        };
+        if status == "ok" || self.env.json {
+            return Ok(status);
+        }
+
+        println!("|===| {} check result", self.bold.paint("Sanity"));
+        if !init {
+            println!(
+                "| - your initial predicate is {} (unsatisfiable), the system has no states",
+                self.red.paint("contradictory")
+            );
+            println!(
+                "|   all candidates {}, whatever the results below say",
+                self.red.paint("hold vacuously")
+            );
+        } else {
+            println!(
+                "| - {} initial state has a successor, the initial predicate and the transition \
+                relation are {}",
+                self.red.paint("no"),
+                self.red.paint("contradictory")
+            );
+            println!(
+                "|   the only reachable states are the initial ones, candidates {} beyond them",
+                self.red.paint("hold vacuously")
+            );
+        }
+        println!("|===|");
+        println!();
+
+        Ok(status)
+    }
+}
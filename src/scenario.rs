@@ -0,0 +1,87 @@
+//! Regression scenarios from counterexamples.
+
+mikino_api::prelude!();
+
+use crate::{cex_store::StoredCex, Check, Run};
+
+/// Name of the step counter.
+const STEP_VAR: &str = "scenario_step";
+
+impl Run {
+    /// Prints the scenario of a stored counterexample.
+    pub fn scenario(&self, id: &str, steps: Option<usize>, vars: &Option<Vec<String>>) -> Res<()> {
+        let dir = match self.cex_store.as_ref() {
+            Some(dir) => dir,
+            None => bail!("no counterexample store, see `--cex_store`"),
+        };
+        let cex = StoredCex::load(dir, id)?;
+        let check = Check::from_txt(self, &cex.system, &None)
+            .chain_err(|| format!("while parsing the system of counterexample `{}`", cex.id))?;
+        let decls: Vec<&str> = check.sys.decls().iter().map(|decl| decl.id()).collect();
+        if let Some(vars) = vars {
+            for var in vars {
+                if !decls.contains(&var.as_str()) {
+                    bail!(
+                        "unknown variable `{}` in `--vars`, counterexample `{}` has {}",
+                        var,
+                        cex.id,
+                        decls.join(", ")
+                    )
+                }
+            }
+        }
+        let fixed = |var: &str| {
+            vars.as_ref()
+                .map(|vars| vars.iter().any(|v| v == var))
+                .unwrap_or(true)
+        };
+        let mut counter = STEP_VAR.to_string();
+        while decls.contains(&counter.as_str()) {
+            counter.push('_')
+        }
+
+        let len = steps
+            .map(|steps| steps.min(cex.trace.len()))
+            .unwrap_or(cex.trace.len());
+        // Constraints of each step, over the next state for all steps but the first.
+        let constraints: Vec<String> = cex.trace[..len]
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, values))| {
+                let prime = if idx == 0 { "" } else { "'" };
+                let eqs: Vec<String> = values
+                    .iter()
+                    .filter(|(var, val)| fixed(var) && !val.ends_with(" (completed)"))
+                    .map(|(var, val)| format!("{}{} = {}", prime, var, val))
+                    .collect();
+                if eqs.is_empty() {
+                    "true".into()
+                } else {
+                    eqs.join(" ⋀ ")
+                }
+            })
+            .collect();
+
+        println!(
+            "// Scenario from counterexample {} (falsifies `{}`), {} step(s).",
+            cex.id, cex.candidate, len
+        );
+        println!("// Add the following to the `svars`, `init` and `trans` blocks of the system.");
+        println!("// svars");
+        println!("    {}: int,", counter);
+        println!("// init");
+        println!("    {} = 0,", counter);
+        if let Some(first) = constraints.first() {
+            println!("    {},", first)
+        }
+        println!("// trans");
+        println!("    '{} = {} + 1,", counter, counter);
+        for (idx, constraint) in constraints.iter().enumerate().skip(1) {
+            println!(
+                "    if '{} = {} {{ {} }} else {{ true }},",
+                counter, idx, constraint
+            )
+        }
+        Ok(())
+    }
+}
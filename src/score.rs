@@ -0,0 +1,174 @@
+//! Weighted safety score.
+//!
+//! `#[weight(<N>)]` annotations give candidates a weight, `1` by default.
+
+mikino_api::prelude!();
+
+use crate::{budget::Verdict, text, Check, Run};
+
+/// Annotation prefix.
+const ANNOTATION: &str = "#[weight";
+
+/// Weight of a candidate.
+pub struct Weight {
+    /// Name of the candidate.
+    pub name: String,
+    /// Weight of the candidate.
+    pub weight: u64,
+    /// Line of the annotation.
+    pub line: usize,
+}
+
+/// Length of the annotation starting `elm` if any, `#[...]` with no `]` in its arguments.
+fn annotation_len(elm: &str) -> Option<usize> {
+    if elm.starts_with("#[") {
+        elm.find(']').map(|idx| idx + 1)
+    } else {
+        None
+    }
+}
+
+/// Removes the `#[weight(...)]` annotations of a system.
+///
+/// The annotation can appear anywhere among the annotations of a candidate, see
+/// [`crate::ignore`]. Yields the new system and the weights.
+pub fn strip(txt: &str) -> Res<(String, Vec<Weight>)> {
+    if !txt.contains(ANNOTATION) {
+        return Ok((txt.into(), Vec::new()));
+    }
+    let mut res = txt.to_string();
+    let mut weights = Vec::new();
+    for (start, end) in text::elements(txt, "candidates")?.into_iter().rev() {
+        let mut idx = start;
+        let mut annotation = None;
+        while let Some(len) = annotation_len(&txt[idx..end]) {
+            if txt[idx..].starts_with(ANNOTATION) {
+                annotation = Some((idx, len))
+            }
+            idx += len;
+            idx = end - txt[idx..end].trim_start().len();
+        }
+        let (ann_start, len) = match annotation {
+            Some(annotation) => annotation,
+            None => continue,
+        };
+        let line = txt[..ann_start].matches('\n').count() + 1;
+        let illegal = || {
+            format!(
+                "illegal annotation at line {}, expected `#[weight(<INT>)]`",
+                line
+            )
+        };
+        let weight = txt[ann_start + ANNOTATION.len()..ann_start + len - 1]
+            .trim()
+            .strip_prefix('(')
+            .and_then(|arg| arg.strip_suffix(')'))
+            .and_then(|arg| arg.trim().parse::<u64>().ok())
+            .ok_or_else(illegal)?;
+        let name = txt[idx..end]
+            .strip_prefix('"')
+            .and_then(|rest| rest.find('"').map(|end| rest[..end].to_string()))
+            .ok_or_else(|| {
+                format!(
+                    "expected a candidate name after the annotation at line {}",
+                    line
+                )
+            })?;
+        res.replace_range(ann_start..ann_start + len, "");
+        weights.push(Weight { name, weight, line })
+    }
+    weights.reverse();
+    Ok((res, weights))
+}
+
+impl<'env> Check<'env> {
+    /// Sets the weights of the candidates, fails if a candidate has several weights.
+    ///
+    /// Weights of candidates the system does not have, such as ignored ones, are dropped.
+    pub fn set_weights(&mut self, weights: Vec<Weight>) -> Res<()> {
+        for Weight { name, weight, line } in weights {
+            if !self.sys.po_s().contains_key(&name) {
+                continue;
+            }
+            if self.weights.insert(name.clone(), weight).is_some() {
+                bail!(
+                    "candidate `{}` has more than one weight (line {})",
+                    name,
+                    line
+                )
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Verdict {
+    /// Weight of a candidate of the verdict.
+    pub fn weight(&self, candidate: &str) -> u64 {
+        self.weights.get(candidate).cloned().unwrap_or(1)
+    }
+
+    /// Safety score of the verdict, as a percentage.
+    ///
+    /// The score of a verdict without candidates, or with a total weight of zero, is `100`.
+    pub fn score(&self) -> f64 {
+        let sum = |candidates: &[String]| -> u64 {
+            candidates
+                .iter()
+                .map(|candidate| self.weight(candidate))
+                .sum()
+        };
+        let proved = sum(&self.proved);
+        let total = proved + sum(&self.falsified) + sum(&self.unknown);
+        if total == 0 {
+            100.0
+        } else {
+            proved as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+impl Run {
+    /// Reports the safety score, yields `true` if the run passes.
+    pub fn safety_score(&self, verdict: &Verdict, threshold: f64) -> bool {
+        let score = verdict.score();
+        let pass = score >= threshold;
+        if self.json {
+            return pass;
+        }
+        println!();
+        println!("|===| {} result", self.bold.paint("Safety score"));
+        println!("| - score of {:.2}%, threshold of {}%", score, threshold);
+        let lost: Vec<&String> = verdict
+            .falsified
+            .iter()
+            .chain(verdict.unknown.iter())
+            .filter(|candidate| verdict.weight(candidate) > 0)
+            .collect();
+        if !lost.is_empty() {
+            println!("|");
+            println!(
+                "| - the following candidate(s) are {} and do not score",
+                self.red.paint("falsified or unknown")
+            );
+            for candidate in lost {
+                println!(
+                    "|   `{}` (weight {})",
+                    self.red.paint(candidate),
+                    verdict.weight(candidate)
+                )
+            }
+        }
+        println!("|");
+        if pass {
+            println!("| - run {}", self.green.paint("passes"))
+        } else {
+            println!(
+                "| - run {}, the score is below the threshold",
+                self.red.paint("fails")
+            )
+        }
+        println!("|===|");
+        pass
+    }
+}
@@ -0,0 +1,179 @@
+//! Failing query shrinking, by delta debugging over the assertions in fresh solvers.
+
+mikino_api::prelude!();
+
+use std::{
+    collections::BTreeSet as Set,
+    fs::File,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::smt::Backend;
+
+/// True if a command is a declaration or an option, which shrinking does not drop blindly.
+fn is_decl(cmd: &str) -> bool {
+    cmd.starts_with("(declare") || cmd.starts_with("(define") || cmd.starts_with("(set-")
+}
+
+/// Builds the script for some commands.
+fn script<'a>(cmds: impl IntoIterator<Item = &'a String>) -> String {
+    let mut script = "(set-option :produce-models true)\n".to_string();
+    for cmd in cmds {
+        script.push_str(cmd);
+        script.push('\n')
+    }
+    script.push_str("(check-sat)\n(exit)\n");
+    script
+}
+
+/// True if the solver fails on a script.
+fn fails(cmd: &str, backend: Backend, script: &str) -> Res<bool> {
+    let mut args = cmd.split_whitespace();
+    let bin = args
+        .next()
+        .ok_or_else(|| format!("illegal empty solver command `{}`", cmd))?;
+    let mut kid = Command::new(bin)
+        .args(args)
+        .args(backend.args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .chain_err(|| format!("while spawning {} with command `{}`", backend.name(), cmd))?;
+    // The solver may crash before reading everything, which is a failure, not an error.
+    let _ = kid
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(script.as_bytes());
+    let output = kid
+        .wait_with_output()
+        .chain_err(|| format!("while waiting for {} to exit", backend.name()))?;
+    if !output.status.success() {
+        return Ok(true);
+    }
+    let out = String::from_utf8_lossy(&output.stdout);
+    let mut answers = out.lines().map(str::trim).filter(|line| !line.is_empty());
+    if answers.clone().any(|line| line.starts_with("(error")) {
+        return Ok(true);
+    }
+    Ok(!matches!(
+        answers.next_back(),
+        Some("sat" | "unsat" | "unknown")
+    ))
+}
+
+/// Delta debugging, yields a 1-minimal subset of `items` on which `fails` holds.
+///
+/// Assumes `fails` holds on `items`.
+pub fn ddmin<T: Clone>(mut items: Vec<T>, mut fails: impl FnMut(&[T]) -> Res<bool>) -> Res<Vec<T>> {
+    if fails(&[])? {
+        return Ok(Vec::new());
+    }
+    let mut n = 2;
+    while items.len() >= 2 {
+        let chunk = items.len().div_ceil(n);
+        let chunks: Vec<(usize, usize)> = (0..items.len())
+            .step_by(chunk)
+            .map(|start| (start, (start + chunk).min(items.len())))
+            .collect();
+
+        let mut reduced = None;
+        for (start, end) in chunks.iter().cloned() {
+            if fails(&items[start..end])? {
+                reduced = Some((items[start..end].to_vec(), 2));
+                break;
+            }
+        }
+        if reduced.is_none() {
+            for (start, end) in chunks.iter().cloned() {
+                let complement: Vec<T> = items[..start]
+                    .iter()
+                    .chain(items[end..].iter())
+                    .cloned()
+                    .collect();
+                if fails(&complement)? {
+                    reduced = Some((complement, (n - 1).max(2)));
+                    break;
+                }
+            }
+        }
+
+        match reduced {
+            Some((subset, next_n)) => {
+                items = subset;
+                n = next_n
+            }
+            None if n >= items.len() => break,
+            None => n = (n * 2).min(items.len()),
+        }
+    }
+    Ok(items)
+}
+
+/// Shrinks a failing query and saves it to `path`.
+///
+/// Returns `false` if the failure does not reproduce in a fresh session, in which case the full
+/// query is saved.
+pub fn shrink(cmd: &str, backend: Backend, query: &[String], path: &Path) -> Res<bool> {
+    let (decls, assertions): (Vec<String>, Vec<String>) =
+        query.iter().cloned().partition(|cmd| is_decl(cmd));
+
+    let reproduces = fails(cmd, backend, &script(decls.iter().chain(&assertions)))?;
+    let (decls, shrunk) = if reproduces {
+        let shrunk = ddmin(assertions.clone(), |subset| {
+            fails(cmd, backend, &script(decls.iter().chain(subset)))
+        })?;
+        // Drop the constants no remaining command mentions.
+        let tokens: Set<&str> = shrunk
+            .iter()
+            .chain(
+                decls
+                    .iter()
+                    .filter(|decl| !decl.starts_with("(declare-const")),
+            )
+            .flat_map(|cmd| cmd.split(|c: char| c.is_whitespace() || c == '(' || c == ')'))
+            .collect();
+        let pruned: Vec<String> = decls
+            .iter()
+            .filter(|decl| match decl.strip_prefix("(declare-const ") {
+                Some(rest) => rest
+                    .split_whitespace()
+                    .next()
+                    .map(|name| tokens.contains(name))
+                    .unwrap_or(true),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        if fails(cmd, backend, &script(pruned.iter().chain(&shrunk)))? {
+            (pruned, shrunk)
+        } else {
+            (decls, shrunk)
+        }
+    } else {
+        (decls, assertions.clone())
+    };
+
+    let mut file = File::create(path)
+        .chain_err(|| format!("while creating shrunk query file `{}`", path.display()))?;
+    let header = if reproduces {
+        format!(
+            "; Failing query for `{}`, shrunk by mikino from {} to {} assertion(s).",
+            cmd,
+            assertions.len(),
+            shrunk.len()
+        )
+    } else {
+        format!(
+            "; Query `{}` failed on, the failure does not reproduce in a fresh session.",
+            cmd
+        )
+    };
+    writeln!(file, "{}\n{}", header, script(decls.iter().chain(&shrunk)))
+        .chain_err(|| format!("while writing shrunk query to `{}`", path.display()))?;
+
+    Ok(reproduces)
+}
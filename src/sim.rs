@@ -0,0 +1,302 @@
+//! Concrete random simulation, a cheap smoke test that proves nothing.
+
+mikino_api::prelude!();
+
+use expr::{Expr, Typ};
+
+use crate::{smt, Check, Trace};
+
+/// Values random integers and rationals are drawn from, `[-RANGE, RANGE]`.
+const RANGE: u64 = 100;
+
+/// Xorshift pseudo-random number generator, good enough for sampling.
+pub struct Rng {
+    state: u64,
+}
+impl Rng {
+    /// Constructor, from the current time if `seed` is `None`.
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(0)
+        });
+        Self { state: seed.max(1) }
+    }
+
+    /// Next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random number in `[0, bound)`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Random SMT-LIB value of some type.
+    pub fn value(&mut self, typ: Typ) -> String {
+        let suffix = match typ {
+            Typ::Bool => return (self.below(2) == 0).to_string(),
+            Typ::Int => "",
+            Typ::Rat => ".0",
+        };
+        let n = self.below(2 * RANGE + 1);
+        if n > RANGE {
+            format!("(- {}{})", n - RANGE, suffix)
+        } else {
+            format!("{}{}", n, suffix)
+        }
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Random model of the state at `step`, `None` if there is none.
+    ///
+    /// The constraints on the state must be asserted in `solver`.
+    pub fn random_state(
+        &self,
+        solver: &mut smt::Solver,
+        rng: &mut Rng,
+        step: usize,
+    ) -> Res<Option<smt::State>> {
+        let mut vars: Vec<_> = self.sys.decls().iter().collect();
+        // Fisher-Yates.
+        for idx in (1..vars.len()).rev() {
+            vars.swap(idx, rng.below(idx as u64 + 1) as usize)
+        }
+        let mut scopes = 0;
+        for var in vars {
+            solver.push()?;
+            solver.assert(format!(
+                "(= {} {})",
+                smt::svar(var.id(), step),
+                self.sample(rng, var.id(), var.typ())
+            ))?;
+            if solver.check_sat()? {
+                scopes += 1
+            } else {
+                solver.pop()?
+            }
+        }
+        let state = if scopes > 0 || solver.check_sat()? {
+            Some(solver.get_state(&self.sys, step)?)
+        } else {
+            None
+        };
+        for _ in 0..scopes {
+            solver.pop()?
+        }
+        Ok(state)
+    }
+
+    /// Random initial state, fails if there is none.
+    pub fn random_init(&self, solver: &mut smt::Solver, rng: &mut Rng) -> Res<smt::State> {
+        solver.push()?;
+        solver.assert_init(&self.sys, 0)?;
+        let init = self.random_state(solver, rng, 0)?;
+        solver.pop()?;
+        match init {
+            Some(state) => Ok(state),
+            None => bail!("the initial predicate is unsatisfiable, cannot simulate"),
+        }
+    }
+
+    /// Random successor of a state, `None` if there is none.
+    ///
+    /// If `fuzz` is not `None`, the successor is biased toward the falsification of these
+    /// candidates, see [`Self::fuzz_state`].
+    pub fn random_successor(
+        &self,
+        solver: &mut smt::Solver,
+        rng: &mut Rng,
+        state: &smt::State,
+        fuzz: Option<&[&Expr]>,
+    ) -> Res<Option<smt::State>> {
+        solver.push()?;
+        solver.assert(smt::state_at(state, 0))?;
+        solver.assert_trans(&self.sys, 0)?;
+        let next = match fuzz {
+            Some(targets) => self.fuzz_state(solver, rng, targets),
+            None => self.random_state(solver, rng, 1),
+        };
+        solver.pop()?;
+        next
+    }
+
+    /// Candidates a state falsifies.
+    fn falsified_by(&self, solver: &mut smt::Solver, state: &smt::State) -> Res<Vec<String>> {
+        let mut res = Vec::new();
+        for (candidate, def) in self.sys.po_s().iter() {
+            solver.intent(&format!("sim: does the state falsify `{}`?", candidate))?;
+            let holds = solver.unsat(&[
+                smt::state_at(state, 0),
+                format!("(not {})", smt::Unroll::new(def, 0)),
+            ])?;
+            if !holds {
+                res.push(candidate.clone())
+            }
+        }
+        Ok(res)
+    }
+
+    /// Runs `runs` random simulations of `steps` transitions.
+    ///
+    /// If `fuzz`, successors are biased toward falsifications, see [`crate::fuzz`]. If `coverage`,
+    /// reports the branches of the transition relation the runs exercise.
+    pub fn simulate(
+        &self,
+        steps: usize,
+        runs: usize,
+        seed: Option<u64>,
+        fuzz: bool,
+        coverage: bool,
+    ) -> Res<()> {
+        let sys = &self.sys;
+        self.env.progress(|s| {
+            format!(
+                "{} {} run(s) of {} step(s)...",
+                s.bold.paint(if fuzz { "fuzzing" } else { "simulating" }),
+                runs,
+                steps
+            )
+        });
+        if self.env.verb > 0 {
+            for (var, dist) in &self.dists {
+                self.env
+                    .progress(|s| format!("sampling `{}` from {}", s.bold.paint(var), dist))
+            }
+        }
+        let mut rng = self.rng(seed);
+        let mut solver = self.solver("sim.smt2")?;
+        solver.intent("simulation setup")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+
+        // First violation of each candidate, with its trace.
+        let mut violations: Map<String, (usize, Trace)> = Map::new();
+        // All the runs, only populated if `coverage`.
+        let mut traces: Vec<Trace> = Vec::new();
+        let mut deadlocks = 0;
+        let mut total_steps = 0;
+        for run in 0..runs {
+            if self.env.verb > 0 {
+                self.env
+                    .progress(|s| format!("run {}", s.under.paint((run + 1).to_string())))
+            }
+            solver.intent(&format!("sim: run {}, initial state", run + 1))?;
+            let mut state = self.random_init(&mut solver, &mut rng)?;
+
+            let mut trace: Trace = Vec::with_capacity(steps + 1);
+            for step in 0..=steps {
+                trace.push((step, smt::pretty_state(&state)));
+                for candidate in self.falsified_by(&mut solver, &state)? {
+                    violations
+                        .entry(candidate)
+                        .or_insert_with(|| (run + 1, trace.clone()));
+                }
+                if step == steps {
+                    break;
+                }
+
+                solver.intent(&format!("sim: run {}, step {}", run + 1, step + 1))?;
+                let targets: Vec<_> = sys
+                    .po_s()
+                    .iter()
+                    .filter(|(candidate, _)| !violations.contains_key(*candidate))
+                    .map(|(_, def)| def)
+                    .collect();
+                let targets = fuzz.then_some(targets.as_slice());
+                match self.random_successor(&mut solver, &mut rng, &state, targets)? {
+                    Some(next) => {
+                        total_steps += 1;
+                        state = next
+                    }
+                    None => {
+                        deadlocks += 1;
+                        break;
+                    }
+                }
+            }
+            if coverage {
+                traces.push(trace)
+            }
+        }
+
+        for (candidate, (run, trace)) in &violations {
+            println!(
+                "found a {} in run {} at depth {}:",
+                self.red.paint("falsification"),
+                run,
+                self.bold.paint((trace.len() - 1).to_string())
+            );
+            self.present_trace(sys, candidate, trace, &[], true)?;
+        }
+        if !violations.is_empty() {
+            println!()
+        }
+
+        println!(
+            "|===| {} result",
+            self.bold.paint(if fuzz { "Fuzzing" } else { "Simulation" })
+        );
+        println!(
+            "| - {} run(s), {} transition(s) in total",
+            runs, total_steps
+        );
+        if deadlocks > 0 {
+            println!(
+                "| - {} run(s) reached a state with {} successor",
+                deadlocks,
+                self.red.paint("no")
+            )
+        }
+        println!("|");
+        let unseen: Vec<&String> = sys
+            .po_s()
+            .keys()
+            .filter(|candidate| !violations.contains_key(*candidate))
+            .collect();
+        if !unseen.is_empty() {
+            println!(
+                "| - no violation {} for the following candidate(s)",
+                self.bold.paint("observed")
+            );
+            for candidate in &unseen {
+                println!("|   `{}`", self.bold.paint(*candidate))
+            }
+        }
+        if !unseen.is_empty() && !violations.is_empty() {
+            println!("|")
+        }
+        if !violations.is_empty() {
+            println!(
+                "| - the following candidate(s) are {}",
+                self.red.paint("falsifiable")
+            );
+            for candidate in violations.keys() {
+                println!("|   `{}`", self.red.paint(candidate))
+            }
+            println!("|");
+            println!("| - system is {}", self.red.paint("unsafe"))
+        } else {
+            println!(
+                "| - {} is not a proof, the system {}",
+                if fuzz { "fuzzing" } else { "simulation" },
+                self.red.paint("might be unsafe")
+            )
+        }
+        println!("|===|");
+
+        if coverage {
+            println!();
+            self.coverage(&traces.iter().collect::<Vec<_>>())?
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1083 @@
+//! Direct SMT interaction, for the analyses `mikino_api` does not provide.
+//!
+//! The system's variables are unrolled as `<id>@<step>` constants.
+
+mikino_api::prelude!();
+
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use expr::{Cst, Expr, Op, Typ};
+use trans::Sys;
+
+use crate::{
+    cancel,
+    json::json_str,
+    logging::{Level, Logger},
+};
+
+/// Name of the SMT constant for variable `id` at some step.
+pub fn svar(id: &str, step: usize) -> String {
+    format!("{}@{}", id, step)
+}
+
+/// SMT-LIB sort of a mikino type.
+pub fn sort(typ: Typ) -> &'static str {
+    match typ {
+        Typ::Bool => "Bool",
+        Typ::Int => "Int",
+        Typ::Rat => "Real",
+    }
+}
+
+/// SMT-LIB representation of a constant.
+pub fn cst(cst: &Cst) -> String {
+    fn int(i: String) -> String {
+        match i.strip_prefix('-') {
+            Some(abs) => format!("(- {})", abs),
+            None => i,
+        }
+    }
+    match cst {
+        Cst::B(b) => b.to_string(),
+        Cst::I(i) => int(i.to_string()),
+        Cst::R(r) => format!("(/ {} {})", int(r.numer().to_string()), r.denom()),
+    }
+}
+
+/// SMT-LIB representation of an operator.
+fn op(op: Op) -> &'static str {
+    match op {
+        Op::Ite => "ite",
+        Op::Implies => "=>",
+        Op::Add => "+",
+        Op::Sub => "-",
+        Op::Mul => "*",
+        Op::Div => "/",
+        Op::IDiv => "div",
+        Op::Mod => "mod",
+        Op::Ge => ">=",
+        Op::Le => "<=",
+        Op::Gt => ">",
+        Op::Lt => "<",
+        Op::Eq => "=",
+        Op::Not => "not",
+        Op::And => "and",
+        Op::Or => "or",
+    }
+}
+
+/// Displays an expression unrolled at some step.
+///
+/// Current variables live at `curr`, next variables at `next`.
+pub struct Unroll<'e> {
+    expr: &'e Expr,
+    curr: usize,
+    next: usize,
+}
+impl<'e> Unroll<'e> {
+    /// Constructor, next variables live at `step + 1`.
+    pub fn new(expr: &'e Expr, step: usize) -> Self {
+        Self {
+            expr,
+            curr: step,
+            next: step + 1,
+        }
+    }
+    /// Backward unrolling: current variables live at `step + 1`, next variables at `step`.
+    pub fn backward(expr: &'e Expr, step: usize) -> Self {
+        Self {
+            expr,
+            curr: step + 1,
+            next: step,
+        }
+    }
+}
+impl<'e> fmt::Display for Unroll<'e> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.expr {
+            Expr::Cst(c) => cst(c).fmt(fmt),
+            Expr::Var(var) => {
+                let step = if var.is_next() { self.next } else { self.curr };
+                svar(var.id(), step).fmt(fmt)
+            }
+            Expr::App { op: Op::And, args } if args.is_empty() => "true".fmt(fmt),
+            Expr::App { op: Op::Or, args } if args.is_empty() => "false".fmt(fmt),
+            Expr::App { op: o, args } => {
+                write!(fmt, "({}", op(*o))?;
+                for arg in args {
+                    let arg = Unroll {
+                        expr: arg,
+                        curr: self.curr,
+                        next: self.next,
+                    };
+                    write!(fmt, " {}", arg)?
+                }
+                ")".fmt(fmt)
+            }
+        }
+    }
+}
+
+/// S-expression, used to parse solver answers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SExpr {
+    /// Atom.
+    Atom(String),
+    /// List.
+    List(Vec<SExpr>),
+}
+impl SExpr {
+    /// Parses a single s-expression.
+    pub fn parse(txt: &str) -> Res<Self> {
+        let mut stack: Vec<Vec<SExpr>> = vec![vec![]];
+        let mut chars = txt.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '(' => stack.push(vec![]),
+                ')' => {
+                    let list = stack
+                        .pop()
+                        .filter(|_| !stack.is_empty())
+                        .ok_or_else(|| format!("unbalanced solver answer `{}`", txt))?;
+                    stack
+                        .last_mut()
+                        .expect("stack cannot be empty")
+                        .push(SExpr::List(list))
+                }
+                '|' | '"' => {
+                    let mut atom = c.to_string();
+                    for next in chars.by_ref() {
+                        atom.push(next);
+                        if next == c {
+                            break;
+                        }
+                    }
+                    stack
+                        .last_mut()
+                        .expect("stack cannot be empty")
+                        .push(SExpr::Atom(atom))
+                }
+                c if c.is_whitespace() => (),
+                c => {
+                    let mut atom = c.to_string();
+                    while let Some(next) = chars.peek() {
+                        if next.is_whitespace() || *next == '(' || *next == ')' {
+                            break;
+                        }
+                        atom.push(*next);
+                        chars.next();
+                    }
+                    stack
+                        .last_mut()
+                        .expect("stack cannot be empty")
+                        .push(SExpr::Atom(atom))
+                }
+            }
+        }
+        match (stack.pop(), stack.is_empty()) {
+            (Some(mut top), true) if top.len() == 1 => Ok(top.pop().expect("length is 1")),
+            _ => bail!("illegal solver answer `{}`", txt),
+        }
+    }
+
+    /// Replaces the step suffix of the variables of an SMT-LIB formula.
+    ///
+    /// For instance, `expr.rename("@1", "@0")` turns `x@1` into `x@0`.
+    pub fn rename(&self, from: &str, to: &str) -> Self {
+        match self {
+            SExpr::Atom(atom) => match atom.strip_suffix(from) {
+                Some(id) => SExpr::Atom(format!("{}{}", id, to)),
+                None => self.clone(),
+            },
+            SExpr::List(elems) => {
+                SExpr::List(elems.iter().map(|elem| elem.rename(from, to)).collect())
+            }
+        }
+    }
+
+    /// Pretty, mikino-style representation of a value.
+    ///
+    /// Turns `(- 7)` into `-7` and `(/ 1.0 2.0)` into `1/2`.
+    pub fn to_value(&self) -> String {
+        fn num(s: &str) -> &str {
+            s.strip_suffix(".0").unwrap_or(s)
+        }
+        match self {
+            SExpr::Atom(a) => num(a).into(),
+            SExpr::List(elems) => match elems.as_slice() {
+                [SExpr::Atom(op), arg] if op == "-" => format!("-{}", arg.to_value()),
+                [SExpr::Atom(op), n, d] if op == "/" => {
+                    format!("{}/{}", n.to_value(), d.to_value())
+                }
+                _ => self.to_string(),
+            },
+        }
+    }
+}
+impl fmt::Display for SExpr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SExpr::Atom(a) => a.fmt(fmt),
+            SExpr::List(elems) => {
+                "(".fmt(fmt)?;
+                for (idx, elem) in elems.iter().enumerate() {
+                    if idx > 0 {
+                        " ".fmt(fmt)?
+                    }
+                    elem.fmt(fmt)?
+                }
+                ")".fmt(fmt)
+            }
+        }
+    }
+}
+
+/// Expression stating that the states at `lft` and `rgt` are different.
+pub fn distinct_states(sys: &Sys, lft: usize, rgt: usize) -> String {
+    let mut res = "(or".to_string();
+    for var in sys.decls().iter() {
+        res.push_str(&format!(
+            " (not (= {} {}))",
+            svar(var.id(), lft),
+            svar(var.id(), rgt)
+        ))
+    }
+    if res == "(or" {
+        "false".into()
+    } else {
+        res.push(')');
+        res
+    }
+}
+
+/// Solver backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Z3.
+    Z3,
+    /// CVC5, or CVC4.
+    Cvc5,
+    /// Yices 2, SMT-LIB 2 frontend.
+    Yices,
+}
+impl Backend {
+    /// Backend of a solver command, from the name of its binary; defaults to Z3.
+    pub fn of_cmd(cmd: &str) -> Self {
+        let bin = cmd.split_whitespace().next().unwrap_or("");
+        let name = std::path::Path::new(bin)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.contains("cvc") {
+            Self::Cvc5
+        } else if name.contains("yices") {
+            Self::Yices
+        } else {
+            Self::Z3
+        }
+    }
+
+    /// Name of the backend.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Z3 => "Z3",
+            Self::Cvc5 => "CVC5",
+            Self::Yices => "Yices",
+        }
+    }
+
+    /// Arguments to run the backend in incremental SMT-LIB 2 mode on its standard input.
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::Z3 => &["-in", "-smt2"],
+            Self::Cvc5 => &["--lang=smt2", "--incremental"],
+            Self::Yices => &["--incremental"],
+        }
+    }
+}
+
+/// Policy for the variables the solver omits in its models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Completion {
+    /// Fill in the default value of the variable's type.
+    Default,
+    /// Fail.
+    Error,
+    /// Ask the solver for a value.
+    Solver,
+}
+impl Completion {
+    /// Policy names, as accepted on the command line.
+    pub const NAMES: &'static [&'static str] = &["default", "error", "solver"];
+
+    /// Policy from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::Default),
+            "error" => Some(Self::Error),
+            "solver" => Some(Self::Solver),
+            _ => None,
+        }
+    }
+}
+
+/// Default value of a type, used for model completion.
+pub fn default_value(typ: Typ) -> SExpr {
+    SExpr::Atom(
+        match typ {
+            Typ::Bool => "false",
+            Typ::Int => "0",
+            Typ::Rat => "0.0",
+        }
+        .into(),
+    )
+}
+
+/// Value of a variable in a state.
+///
+/// Comparison ignores whether the value was completed.
+#[derive(Debug, Clone)]
+pub struct Value {
+    /// Actual value.
+    pub val: SExpr,
+    /// True if the solver's model did not contain this value.
+    pub completed: bool,
+}
+impl Value {
+    /// Value from the solver's model.
+    pub fn new(val: SExpr) -> Self {
+        Self {
+            val,
+            completed: false,
+        }
+    }
+    /// Completed value.
+    pub fn completed(val: SExpr) -> Self {
+        Self {
+            val,
+            completed: true,
+        }
+    }
+}
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.val == other.val
+    }
+}
+impl Eq for Value {}
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.val.cmp(&other.val)
+    }
+}
+
+/// A state: values for each variable of the system, in declaration order.
+pub type State = Vec<(String, Value)>;
+
+/// Expression stating that the variables at `step` have the values of `state`.
+pub fn state_at(state: &State, step: usize) -> String {
+    let mut res = "(and".to_string();
+    for (var, val) in state {
+        res.push_str(&format!(" (= {} {})", svar(var, step), val.val))
+    }
+    res.push(')');
+    res
+}
+
+/// Pretty version of a state, see [`SExpr::to_value`].
+///
+/// Completed values are marked as such.
+pub fn pretty_state(state: &State) -> Vec<(String, String)> {
+    state
+        .iter()
+        .map(|(var, val)| {
+            let mut pretty = val.val.to_value();
+            if val.completed {
+                pretty.push_str(" (completed)")
+            }
+            (var.clone(), pretty)
+        })
+        .collect()
+}
+
+/// Human-readable transcript of the solver interactions of a run.
+///
+/// Interleaves the intent of each query with the query itself and the solver's answers. Cloning
+/// yields a handle to the same transcript.
+#[derive(Clone)]
+pub struct Transcript {
+    /// Transcript file.
+    file: Arc<Mutex<File>>,
+}
+impl Transcript {
+    /// Creates the transcript file.
+    pub fn new(path: &str) -> Res<Self> {
+        let file = File::create(path)
+            .chain_err(|| format!("while creating SMT transcript file `{}`", path))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Writes some text, each line prefixed with `pref`.
+    fn write(&self, pref: &str, txt: &str) -> Res<()> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| "SMT transcript is unavailable, a thread panicked while writing it")?;
+        for line in txt.lines() {
+            writeln!(file, "{}{}", pref, line).chain_err(|| "while writing SMT transcript")?
+        }
+        Ok(())
+    }
+
+    /// States the intent of the queries that follow.
+    pub fn intent(&self, txt: &str) -> Res<()> {
+        self.write("", "")?;
+        self.write("|=== ", txt)
+    }
+    /// Adds a note, typically a result.
+    pub fn note(&self, txt: &str) -> Res<()> {
+        self.write("| ", txt)
+    }
+    /// Logs a query.
+    pub fn query(&self, txt: &str) -> Res<()> {
+        self.write("> ", txt)
+    }
+    /// Logs an answer.
+    pub fn answer(&self, txt: &str) -> Res<()> {
+        self.write("< ", txt)
+    }
+}
+
+/// Counts of the interactions with the solvers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counts {
+    /// Number of solvers.
+    pub solvers: usize,
+    /// Number of `check-sat` commands.
+    pub check_sats: usize,
+    /// Number of assertions.
+    pub assertions: usize,
+    /// Number of models and values retrieved.
+    pub models: usize,
+    /// Cumulative time spent waiting for the solvers' answers.
+    pub time: Duration,
+}
+
+/// Telemetry of the solver interactions of a run.
+///
+/// Solvers add their counts when they are dropped. Cloning yields a handle to the same counts.
+#[derive(Clone, Default)]
+pub struct Telemetry {
+    /// Counts of the solvers dropped so far.
+    counts: Arc<Mutex<Counts>>,
+}
+impl Telemetry {
+    /// Adds the counts of a solver.
+    fn add(&self, solver: &Counts) {
+        if let Ok(mut counts) = self.counts.lock() {
+            counts.solvers += 1;
+            counts.check_sats += solver.check_sats;
+            counts.assertions += solver.assertions;
+            counts.models += solver.models;
+            counts.time += solver.time;
+        }
+    }
+
+    /// Counts of the solvers dropped so far.
+    pub fn counts(&self) -> Res<Counts> {
+        self.counts
+            .lock()
+            .map(|counts| *counts)
+            .map_err(|_| "failed to access solver telemetry".into())
+    }
+}
+
+/// Declarations of the variables of a system at some step.
+pub fn state_decls(sys: &Sys, step: usize) -> Vec<String> {
+    sys.decls()
+        .iter()
+        .map(|var| {
+            format!(
+                "(declare-const {} {})",
+                svar(var.id(), step),
+                sort(var.typ())
+            )
+        })
+        .collect()
+}
+
+/// A solver process.
+pub struct Solver {
+    /// Solver command.
+    cmd: String,
+    /// Backend.
+    backend: Backend,
+    /// Solver process, shared with the cancellation tokens watching it.
+    kid: Arc<Mutex<Child>>,
+    /// Solver's input.
+    stdin: ChildStdin,
+    /// Solver's output.
+    stdout: BufReader<ChildStdout>,
+    /// Optional file to log all commands to.
+    tee: Option<File>,
+    /// Optional transcript.
+    transcript: Option<Transcript>,
+    /// Model completion policy.
+    completion: Completion,
+    /// File to save the shrunk query to on solver failures, and the commands of each scope.
+    shrink: Option<(PathBuf, Vec<Vec<String>>)>,
+    /// Candidates whose queries are logged and the other candidates, see [`crate::log_filter`].
+    log_filter: Option<(Set<String>, Set<String>)>,
+    /// True if the queries since the last intent are left out of the log.
+    log_muted: bool,
+    /// Counts of the interactions with this solver.
+    counts: Counts,
+    /// Telemetry the counts are added to when the solver is dropped, if any.
+    telemetry: Option<Telemetry>,
+    /// Log file the commands and answers are written to, if any.
+    logger: Option<Logger>,
+    /// Time limit of each `check-sat` in milliseconds, if any, see [`Self::set_query_timeout`].
+    query_timeout: Option<u64>,
+}
+impl Solver {
+    /// Spawns a solver.
+    ///
+    /// The `solver_cmd` can contain arguments, separated by spaces, the backend is inferred from
+    /// the binary, see [`Backend::of_cmd`]. If `tee` is not `None`, all commands are logged to
+    /// that file.
+    pub fn new(solver_cmd: &str, tee: Option<PathBuf>) -> Res<Self> {
+        let backend = Backend::of_cmd(solver_cmd);
+        let mut cmd = solver_cmd.split_whitespace();
+        let bin = cmd
+            .next()
+            .ok_or_else(|| format!("illegal empty solver command `{}`", solver_cmd))?;
+        let mut kid = Command::new(bin)
+            .args(cmd)
+            .args(backend.args())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .chain_err(|| {
+                format!(
+                    "while spawning {} with command `{}`",
+                    backend.name(),
+                    solver_cmd
+                )
+            })?;
+        let stdin = kid.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(kid.stdout.take().expect("piped stdout"));
+        let tee = match tee {
+            Some(path) => Some(
+                File::create(&path)
+                    .chain_err(|| format!("while creating SMT log file `{}`", path.display()))?,
+            ),
+            None => None,
+        };
+        let mut slf = Self {
+            cmd: solver_cmd.into(),
+            backend,
+            kid: Arc::new(Mutex::new(kid)),
+            stdin,
+            stdout,
+            tee,
+            transcript: None,
+            completion: Completion::Solver,
+            shrink: None,
+            log_filter: None,
+            log_muted: false,
+            counts: Counts::default(),
+            telemetry: None,
+            logger: None,
+            query_timeout: None,
+        };
+        slf.command("(set-option :print-success true)")?;
+        slf.command("(set-option :produce-models true)")?;
+        Ok(slf)
+    }
+
+    /// Kills the solver process when `token` is raised, see [`cancel::Token::watch`].
+    pub fn kill_on(&self, token: &cancel::Token) {
+        token.watch(&self.kid)
+    }
+
+    /// Sets the model completion policy.
+    pub fn set_completion(&mut self, completion: Completion) {
+        self.completion = completion
+    }
+
+    /// Shrinks the query and saves it to `path` when the solver fails on a `check-sat`.
+    ///
+    /// Only the commands issued from now on are part of the query.
+    pub fn set_shrink(&mut self, path: PathBuf) {
+        self.shrink = Some((path, vec![Vec::new()]))
+    }
+
+    /// Only logs the queries about the `selected` candidates among `selected` and `others`, see
+    /// [`crate::log_filter`].
+    pub fn set_log_filter(&mut self, selected: Set<String>, others: Set<String>) {
+        self.log_filter = Some((selected, others))
+    }
+
+    /// Sets the telemetry the counts of this solver are added to, see [`Telemetry`].
+    pub fn set_telemetry(&mut self, telemetry: Telemetry) {
+        self.telemetry = Some(telemetry)
+    }
+
+    /// Writes all subsequent commands and answers to a log file, see [`crate::logging`].
+    pub fn set_logger(&mut self, logger: Logger) {
+        self.logger = Some(logger)
+    }
+
+    /// Bounds each subsequent `check-sat` to `ms` milliseconds.
+    ///
+    /// An `unknown` answer (timeout or incomplete) yields `None` in
+    /// [`Self::check_sat_or_unknown`], and an error in [`Self::check_sat`]. Not supported by Yices.
+    pub fn set_query_timeout(&mut self, ms: u64) -> Res<()> {
+        match self.backend {
+            Backend::Z3 => self.command(format!("(set-option :timeout {})", ms))?,
+            Backend::Cvc5 => self.command(format!("(set-option :tlimit-per {})", ms))?,
+            Backend::Yices => bail!("per-query timeouts are not supported with Yices"),
+        }
+        self.query_timeout = Some(ms);
+        Ok(())
+    }
+
+    /// Sets the transcript for all subsequent interactions.
+    pub fn set_transcript(&mut self, transcript: Transcript) {
+        self.transcript = Some(transcript)
+    }
+
+    /// Writes something to the solver, and to the log file and transcript if any.
+    fn write(&mut self, txt: impl fmt::Display) -> Res<()> {
+        let txt = txt.to_string();
+        let muted = self.log_muted;
+        if let Some(tee) = self.tee.as_mut().filter(|_| !muted) {
+            writeln!(tee, "{}", txt).chain_err(|| "while writing to SMT log file")?
+        }
+        if let Some(transcript) = self.transcript.as_ref() {
+            transcript.query(&txt)?
+        }
+        if let Some(logger) = self.logger.as_ref() {
+            logger.log(Level::Debug, "smt_query", || {
+                vec![("solver", json_str(&self.cmd)), ("query", json_str(&txt))]
+            })?
+        }
+        writeln!(self.stdin, "{}", txt)
+            .and_then(|()| self.stdin.flush())
+            .chain_err(|| format!("while writing to {}'s input", self.backend.name()))
+    }
+
+    /// Reads one (balanced) answer from the solver.
+    fn read(&mut self) -> Res<String> {
+        let start = Instant::now();
+        let res = self.read_answer();
+        self.counts.time += start.elapsed();
+        res
+    }
+
+    /// Reads one (balanced) answer from the solver, see [`Self::read`].
+    fn read_answer(&mut self) -> Res<String> {
+        let mut answer = String::new();
+        let mut depth = 0isize;
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .chain_err(|| format!("while reading {}'s output", self.backend.name()))?;
+            if read == 0 {
+                bail!("{} closed its output unexpectedly", self.backend.name())
+            }
+            for c in line.chars() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => (),
+                }
+            }
+            answer.push_str(&line);
+            if depth <= 0 && !answer.trim().is_empty() {
+                break;
+            }
+        }
+        let answer = answer.trim().to_string();
+        if let Some(transcript) = self.transcript.as_ref() {
+            if answer != "success" {
+                transcript.answer(&answer)?
+            }
+        }
+        if let Some(logger) = self.logger.as_ref() {
+            logger.log(Level::Debug, "smt_answer", || {
+                vec![
+                    ("solver", json_str(&self.cmd)),
+                    ("answer", json_str(&answer)),
+                ]
+            })?
+        }
+        if answer.starts_with("(error") {
+            bail!("{} error: {}", self.backend.name(), answer)
+        }
+        Ok(answer)
+    }
+
+    /// Sends a command for which the solver only answers `success`.
+    pub fn command(&mut self, cmd: impl fmt::Display) -> Res<()> {
+        let cmd = cmd.to_string();
+        self.send(&cmd)?;
+        if let Some((_, scopes)) = self.shrink.as_mut() {
+            scopes
+                .last_mut()
+                .expect("[fatal] solver has no scope")
+                .push(cmd)
+        }
+        Ok(())
+    }
+
+    /// Sends a command for which the solver only answers `success`, without recording it.
+    fn send(&mut self, cmd: &str) -> Res<()> {
+        self.write(cmd)?;
+        let answer = self.read()?;
+        if answer != "success" {
+            bail!(
+                "expected `success` from {}, got `{}`",
+                self.backend.name(),
+                answer
+            )
+        }
+        Ok(())
+    }
+
+    /// States the intent of the next queries, as a comment in the SMT log file and in the
+    /// transcript, if any.
+    ///
+    /// With a log filter, the next queries are left out of the log if the intent mentions some
+    /// candidates, as `` `name` ``, but none of the selected ones.
+    pub fn intent(&mut self, txt: &str) -> Res<()> {
+        if let Some((selected, others)) = self.log_filter.as_ref() {
+            let mentions = |candidate: &&String| txt.contains(&format!("`{}`", candidate));
+            self.log_muted =
+                !selected.iter().any(|c| mentions(&c)) && others.iter().any(|c| mentions(&c))
+        }
+        let muted = self.log_muted;
+        if let Some(tee) = self.tee.as_mut().filter(|_| !muted) {
+            for line in txt.lines() {
+                writeln!(tee, "; {}", line).chain_err(|| "while writing to SMT log file")?
+            }
+        }
+        if let Some(transcript) = self.transcript.as_ref() {
+            transcript.intent(txt)?
+        }
+        Ok(())
+    }
+
+    /// Declares the variables of a system at some step.
+    pub fn declare_state(&mut self, sys: &Sys, step: usize) -> Res<()> {
+        for decl in state_decls(sys, step) {
+            self.command(decl)?
+        }
+        Ok(())
+    }
+
+    /// Asserts a boolean expression.
+    pub fn assert(&mut self, expr: impl fmt::Display) -> Res<()> {
+        self.counts.assertions += 1;
+        self.command(format!("(assert {})", expr))
+    }
+
+    /// Asserts the initial predicate at some step.
+    pub fn assert_init(&mut self, sys: &Sys, step: usize) -> Res<()> {
+        self.assert(Unroll::new(sys.init(), step))
+    }
+
+    /// Asserts the transition relation from `step` to `step + 1`.
+    pub fn assert_trans(&mut self, sys: &Sys, step: usize) -> Res<()> {
+        self.assert(Unroll::new(sys.trans(), step))
+    }
+
+    /// Pushes a scope.
+    pub fn push(&mut self) -> Res<()> {
+        self.send("(push 1)")?;
+        if let Some((_, scopes)) = self.shrink.as_mut() {
+            scopes.push(Vec::new())
+        }
+        Ok(())
+    }
+
+    /// Pops a scope.
+    pub fn pop(&mut self) -> Res<()> {
+        self.send("(pop 1)")?;
+        if let Some((_, scopes)) = self.shrink.as_mut() {
+            if scopes.len() > 1 {
+                scopes.pop();
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks for satisfiability, fails if the solver answers `unknown`.
+    ///
+    /// See [`Self::check_sat_or_unknown`].
+    pub fn check_sat(&mut self) -> Res<bool> {
+        match self.check_sat_or_unknown()? {
+            Some(sat) => Ok(sat),
+            None => bail!(
+                "{} answered `unknown` (timeout or incomplete) under the time limit of {}ms",
+                self.backend.name(),
+                self.query_timeout.unwrap_or(0)
+            ),
+        }
+    }
+
+    /// Checks for satisfiability, `None` if the solver answers `unknown` (timeout or incomplete)
+    /// under a time limit.
+    ///
+    /// Without a time limit, see [`Self::set_query_timeout`], `unknown` answers are errors. If
+    /// shrinking is active, crashes and malformed answers trigger query shrinking, see
+    /// [`Self::set_shrink`].
+    pub fn check_sat_or_unknown(&mut self) -> Res<Option<bool>> {
+        self.counts.check_sats += 1;
+        let failure: Error = match self.write("(check-sat)").and_then(|()| self.read()) {
+            Ok(answer) => match answer.as_str() {
+                "sat" => return Ok(Some(true)),
+                "unsat" => return Ok(Some(false)),
+                "unknown" if self.query_timeout.is_some() => return Ok(None),
+                "unknown" => bail!(
+                    "unexpected check-sat answer `unknown` from {}",
+                    self.backend.name()
+                ),
+                _ => format!(
+                    "unexpected check-sat answer `{}` from {}",
+                    answer,
+                    self.backend.name()
+                )
+                .into(),
+            },
+            Err(e) => e,
+        };
+        if let Some((path, scopes)) = self.shrink.as_ref() {
+            let query: Vec<String> = scopes.iter().flatten().cloned().collect();
+            let shrunk = crate::shrink::shrink(&self.cmd, self.backend, &query, path);
+            return Err(failure).chain_err(|| match shrunk {
+                Ok(true) => format!("shrunk failing query saved to `{}`", path.display()),
+                Ok(false) => format!(
+                    "failure does not reproduce in a fresh {} session, \
+                    full query saved to `{}`",
+                    self.backend.name(),
+                    path.display()
+                ),
+                Err(e) => format!("failed to shrink failing query: {}", e),
+            });
+        }
+        Err(failure)
+    }
+
+    /// True if the conjunction of some assertions is unsatisfiable, in a scope of its own.
+    pub fn unsat(&mut self, assertions: &[String]) -> Res<bool> {
+        self.push()?;
+        for assertion in assertions {
+            self.assert(assertion)?
+        }
+        let sat = self.check_sat()?;
+        self.pop()?;
+        Ok(!sat)
+    }
+
+    /// Asserts a soft constraint, Z3 only.
+    ///
+    /// The next `check-sat` maximizes the number of soft constraints of group `id` that hold.
+    pub fn assert_soft(&mut self, expr: impl fmt::Display, id: &str) -> Res<()> {
+        if self.backend != Backend::Z3 {
+            bail!(
+                "soft constraints are only supported with Z3, not {}",
+                self.backend.name()
+            )
+        }
+        self.counts.assertions += 1;
+        self.command(format!("(assert-soft {} :id {})", expr, id))
+    }
+
+    /// Adds an arithmetic objective to maximize, Z3 only.
+    ///
+    /// The next `check-sat` yields a model maximizing `expr`, objectives are scoped.
+    pub fn maximize(&mut self, expr: impl fmt::Display) -> Res<()> {
+        if self.backend != Backend::Z3 {
+            bail!(
+                "optimization objectives are only supported with Z3, not {}",
+                self.backend.name()
+            )
+        }
+        self.command(format!("(maximize {})", expr))
+    }
+
+    /// Eliminates the quantifiers of a formula with Z3's `qe` tactic, Z3 only.
+    ///
+    /// Yields an equivalent quantifier-free formula.
+    pub fn qe(&mut self, formula: impl fmt::Display) -> Res<String> {
+        if self.backend != Backend::Z3 {
+            bail!(
+                "quantifier elimination is only supported with Z3, not {}",
+                self.backend.name()
+            )
+        }
+        self.push()?;
+        self.assert(formula)?;
+        self.write("(apply (then qe simplify))")?;
+        let answer = self.read();
+        self.pop()?;
+        let answer = answer?;
+        // `(goals (goal <formula>* :precision <p> :depth <n>)*)`
+        let goals = match SExpr::parse(&answer)? {
+            SExpr::List(elems) if elems.first() == Some(&SExpr::Atom("goals".into())) => elems,
+            _ => bail!("unexpected answer to quantifier elimination `{}`", answer),
+        };
+        let mut disj = "(or false".to_string();
+        for goal in &goals[1..] {
+            let conjs = match goal {
+                SExpr::List(elems) if elems.first() == Some(&SExpr::Atom("goal".into())) => {
+                    &elems[1..]
+                }
+                _ => bail!(
+                    "unexpected goal `{}` in quantifier elimination answer",
+                    goal
+                ),
+            };
+            disj.push_str(" (and true");
+            for conj in conjs {
+                match conj {
+                    SExpr::Atom(a) if a.starts_with(':') => break,
+                    conj => disj.push_str(&format!(" {}", conj)),
+                }
+            }
+            disj.push(')')
+        }
+        disj.push(')');
+        Ok(disj)
+    }
+
+    /// Retrieves the model after a `sat` result, as a map from constant names to values.
+    pub fn get_model(&mut self) -> Res<Map<String, SExpr>> {
+        self.counts.models += 1;
+        self.write("(get-model)")?;
+        let answer = self.read()?;
+        let mut items = match SExpr::parse(&answer)? {
+            SExpr::List(items) => items,
+            _ => bail!(
+                "unexpected get-model answer `{}` from {}",
+                answer,
+                self.backend.name()
+            ),
+        };
+        if items.first() == Some(&SExpr::Atom("model".into())) {
+            items.remove(0);
+        }
+        let mut model = Map::new();
+        for item in items {
+            match item {
+                SExpr::List(mut def)
+                    if def.len() == 5 && def[0] == SExpr::Atom("define-fun".into()) =>
+                {
+                    let val = def.pop().expect("length is 5");
+                    if let SExpr::Atom(name) = &def[1] {
+                        model.insert(name.clone(), val);
+                    }
+                }
+                // Function definitions and such, irrelevant.
+                _ => (),
+            }
+        }
+        Ok(model)
+    }
+
+    /// Retrieves the value of a single constant after a `sat` result.
+    pub fn get_value(&mut self, name: &str) -> Res<SExpr> {
+        self.counts.models += 1;
+        self.write(format!("(get-value ({}))", name))?;
+        let answer = self.read()?;
+        match SExpr::parse(&answer)? {
+            SExpr::List(mut pairs) if pairs.len() == 1 => match pairs.pop() {
+                Some(SExpr::List(mut pair)) if pair.len() == 2 => {
+                    Ok(pair.pop().expect("length is 2"))
+                }
+                _ => bail!(
+                    "unexpected get-value answer `{}` from {}",
+                    answer,
+                    self.backend.name()
+                ),
+            },
+            _ => bail!(
+                "unexpected get-value answer `{}` from {}",
+                answer,
+                self.backend.name()
+            ),
+        }
+    }
+
+    /// Retrieves the value of the variables of a system at some step, after a `sat` result.
+    ///
+    /// Values missing from the model are handled according to the model completion policy.
+    pub fn get_state(&mut self, sys: &Sys, step: usize) -> Res<State> {
+        let model = self.get_model()?;
+        self.state_of_model(&model, sys, step)
+    }
+
+    /// Retrieves the trace from step `0` to `depth`, after a `sat` result.
+    pub fn get_trace(&mut self, sys: &Sys, depth: usize) -> Res<crate::Trace> {
+        let model = self.get_model()?;
+        let mut trace = crate::Trace::with_capacity(depth + 1);
+        for step in 0..=depth {
+            let state = self.state_of_model(&model, sys, step)?;
+            trace.push((step, pretty_state(&state)))
+        }
+        Ok(trace)
+    }
+
+    /// Extracts the state at some step from a model, see [`Self::get_state`].
+    fn state_of_model(&mut self, model: &Map<String, SExpr>, sys: &Sys, step: usize) -> Res<State> {
+        let mut state = State::new();
+        for var in sys.decls().iter() {
+            let name = svar(var.id(), step);
+            let val = match model.get(&name) {
+                Some(val) => Value::new(val.clone()),
+                None => match self.completion {
+                    Completion::Default => Value::completed(default_value(var.typ())),
+                    Completion::Error => bail!(
+                        "the solver's model has no value for variable `{}` at step {} \
+                        (see `--model_completion`)",
+                        var.id(),
+                        step
+                    ),
+                    Completion::Solver => Value::completed(self.get_value(&name)?),
+                },
+            };
+            state.push((var.id().to_string(), val))
+        }
+        Ok(state)
+    }
+}
+impl Drop for Solver {
+    fn drop(&mut self) {
+        let _ = self.write("(exit)");
+        if let Ok(mut kid) = self.kid.lock() {
+            let _ = kid.wait();
+        }
+        if let Some(telemetry) = self.telemetry.as_ref() {
+            telemetry.add(&self.counts)
+        }
+    }
+}
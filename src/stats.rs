@@ -0,0 +1,223 @@
+//! Timing statistics.
+//!
+//! Checks record where time goes as they run, `--stats` prints it once a system is checked.
+
+mikino_api::prelude!();
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    json::{json_array, json_object, json_str},
+    Check,
+};
+
+/// Timing statistics of a check.
+#[derive(Debug, Clone)]
+pub struct Stats {
+    /// Start of the check.
+    pub start: Instant,
+    /// Time of each BMC depth, in the order they were checked.
+    pub depths: Vec<(usize, Duration)>,
+    /// Phase settling each candidate, and time from the start of the check.
+    pub settled: Map<String, (String, Duration)>,
+    /// Number of base, step and BMC checks `mikino_api` handled, and their cumulative time.
+    pub api: (usize, Duration),
+}
+
+impl Stats {
+    /// Constructor, the check starts now.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            depths: Vec::new(),
+            settled: Map::new(),
+            api: (0, Duration::ZERO),
+        }
+    }
+}
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats a duration in seconds.
+fn secs(time: Duration) -> String {
+    format!("{:.3}s", time.as_secs_f64())
+}
+
+impl<'env> Check<'env> {
+    /// Records the time spent checking some BMC depth.
+    pub fn record_depth(&self, depth: usize, time: Duration) -> Res<()> {
+        self.stats
+            .lock()
+            .map_err(|_| "failed to record BMC depth timing")?
+            .depths
+            .push((depth, time));
+        Ok(())
+    }
+
+    /// Records a base, step or BMC check `mikino_api` handled.
+    pub fn record_api_call(&self, time: Duration) -> Res<()> {
+        let mut stats = self
+            .stats
+            .lock()
+            .map_err(|_| "failed to record check timing")?;
+        stats.api.0 += 1;
+        stats.api.1 += time;
+        Ok(())
+    }
+
+    /// Records that `phase` settled a candidate, ignored if it is already settled.
+    pub fn record_settled(&self, phase: &str, candidate: &str) -> Res<()> {
+        let mut stats = self
+            .stats
+            .lock()
+            .map_err(|_| "failed to record candidate timing")?;
+        let time = stats.start.elapsed();
+        stats
+            .settled
+            .entry(candidate.to_string())
+            .or_insert_with(|| (phase.to_string(), time));
+        Ok(())
+    }
+
+    /// Prints the timing statistics.
+    pub fn print_stats(&self) -> Res<()> {
+        let stats = self
+            .stats
+            .lock()
+            .map_err(|_| "failed to access timing statistics")?;
+        let timings = self
+            .timings
+            .lock()
+            .map_err(|_| "failed to access phase timings")?;
+        let width = timings
+            .iter()
+            .map(|(phase, _)| phase.len())
+            .chain(self.sys.po_s().keys().map(String::len))
+            .chain(Some("total".len()))
+            .max()
+            .unwrap_or(0);
+
+        println!("|===| {}", self.bold.paint("Stats"));
+        println!("| - phases");
+        for (phase, time) in timings.iter() {
+            println!("|   {: <width$} {: >9}", phase, secs(*time), width = width)
+        }
+        println!(
+            "|   {: <width$} {}",
+            "total",
+            self.bold
+                .paint(format!("{: >9}", secs(stats.start.elapsed()))),
+            width = width
+        );
+        if !stats.depths.is_empty() {
+            println!("|");
+            println!("| - bmc depths");
+            for (depth, time) in &stats.depths {
+                println!("|   depth {: >3} {: >9}", depth, secs(*time))
+            }
+            if let Some((depth, time)) = stats.depths.iter().max_by_key(|(_, time)| *time) {
+                println!(
+                    "|   slowest depth is {} ({})",
+                    self.bold.paint(depth.to_string()),
+                    secs(*time)
+                )
+            }
+        }
+        println!("|");
+        println!("| - candidates, time to settle");
+        for candidate in self.sys.po_s().keys() {
+            match stats.settled.get(candidate) {
+                Some((phase, time)) => println!(
+                    "|   {: <width$} {: >9} ({})",
+                    candidate,
+                    secs(*time),
+                    phase,
+                    width = width
+                ),
+                None => println!(
+                    "|   {: <width$} {: >9} (unsettled)",
+                    candidate,
+                    "-",
+                    width = width
+                ),
+            }
+        }
+        let (calls, time) = stats.api;
+        if calls > 0 {
+            println!("|");
+            println!("| - base, step and BMC checks");
+            println!(
+                "|   {} check(s), {}",
+                self.bold.paint(calls.to_string()),
+                secs(time)
+            );
+        }
+        let counts = self.telemetry.counts()?;
+        if counts.solvers > 0 {
+            println!("|");
+            println!("| - solver interactions of the other analyses");
+            println!(
+                "|   {} check-sat(s) over {} solver(s), {} assertion(s), {} model(s)",
+                self.bold.paint(counts.check_sats.to_string()),
+                counts.solvers,
+                counts.assertions,
+                counts.models
+            );
+            println!("|   {} waiting for the solvers", secs(counts.time));
+        }
+        println!("|===|");
+        Ok(())
+    }
+
+    /// Timing statistics as a JSON object.
+    pub fn json_stats(&self) -> Res<String> {
+        let stats = self
+            .stats
+            .lock()
+            .map_err(|_| "failed to access timing statistics")?;
+        let seconds = |time: &Duration| format!("{:.3}", time.as_secs_f64());
+        let depths: Vec<String> = stats
+            .depths
+            .iter()
+            .map(|(depth, time)| {
+                json_object(&[("depth", depth.to_string()), ("time", seconds(time))])
+            })
+            .collect();
+        let candidates: Vec<String> = self
+            .sys
+            .po_s()
+            .keys()
+            .map(|candidate| {
+                let (phase, time) = match stats.settled.get(candidate) {
+                    Some((phase, time)) => (json_str(phase), seconds(time)),
+                    None => ("null".into(), "null".into()),
+                };
+                json_object(&[
+                    ("name", json_str(&self.sanitized_text(candidate))),
+                    ("phase", phase),
+                    ("time", time),
+                ])
+            })
+            .collect();
+        let counts = self.telemetry.counts()?;
+        let solver = json_object(&[
+            ("solvers", counts.solvers.to_string()),
+            ("check_sats", counts.check_sats.to_string()),
+            ("assertions", counts.assertions.to_string()),
+            ("models", counts.models.to_string()),
+            ("time", seconds(&counts.time)),
+        ]);
+        let (calls, time) = stats.api;
+        let api = json_object(&[("calls", calls.to_string()), ("time", seconds(&time))]);
+        Ok(json_object(&[
+            ("total", seconds(&stats.start.elapsed())),
+            ("bmc_depths", json_array(&depths)),
+            ("candidates", json_array(&candidates)),
+            ("solver", solver),
+            ("api", api),
+        ]))
+    }
+}
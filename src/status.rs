@@ -0,0 +1,99 @@
+//! Live status line for long analyses, only rendered in terminals.
+
+mikino_api::prelude!();
+
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use crate::Run;
+
+/// Minimum time between two redraws.
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// A status line, erased when dropped.
+pub struct StatusLine {
+    /// Stream the line is rendered on, if any.
+    stream: Option<atty::Stream>,
+    /// Creation time, for the elapsed time.
+    start: Instant,
+    /// Time of the last redraw, if the line is currently shown.
+    shown: Option<Instant>,
+}
+
+impl StatusLine {
+    /// Constructor, see [`Run::live_status`].
+    pub fn new(env: &Run) -> Self {
+        Self {
+            stream: env.live_status(),
+            start: Instant::now(),
+            shown: None,
+        }
+    }
+
+    /// True if the line is rendered.
+    pub fn is_active(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Writes some text on the stream of the line.
+    fn write(&self, txt: &str) {
+        let _ = match self.stream {
+            Some(atty::Stream::Stderr) => {
+                let mut stderr = std::io::stderr();
+                write!(stderr, "{}", txt).and_then(|()| stderr.flush())
+            }
+            _ => {
+                let mut stdout = std::io::stdout();
+                write!(stdout, "{}", txt).and_then(|()| stdout.flush())
+            }
+        };
+    }
+
+    /// Redraws the line with `msg` and the elapsed time, at most every 100ms.
+    pub fn update(&mut self, msg: impl FnOnce() -> String) {
+        if self.stream.is_none() {
+            return;
+        }
+        if let Some(last) = self.shown {
+            if last.elapsed() < REFRESH {
+                return;
+            }
+        }
+        self.write(&format!(
+            "\r{} ({:.1}s)\x1b[K",
+            msg(),
+            self.start.elapsed().as_secs_f64()
+        ));
+        self.shown = Some(Instant::now())
+    }
+
+    /// Erases the line, if shown.
+    pub fn clear(&mut self) {
+        if self.shown.take().is_some() {
+            self.write("\r\x1b[K");
+        }
+    }
+}
+impl Drop for StatusLine {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+impl Run {
+    /// Stream live status lines are rendered on, if any.
+    ///
+    /// Stdout when it is a terminal, stderr when stdout goes through the pager so that the line
+    /// does not end up in the paged output. None with `--json` or `--no_progress`.
+    pub fn live_status(&self) -> Option<atty::Stream> {
+        if self.no_progress || self.json || !self.stdout_tty {
+            None
+        } else if self.pager {
+            Some(atty::Stream::Stderr).filter(|stream| atty::is(*stream))
+        } else {
+            Some(atty::Stream::Stdout)
+        }
+    }
+}
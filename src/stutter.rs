@@ -0,0 +1,154 @@
+//! Stuttering analysis, steps leaving all variables unchanged.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+
+use crate::{smt, Check, Phase};
+
+/// Whether the transition relation allows stuttering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stuttering {
+    /// In all states.
+    Always,
+    /// In some states only.
+    Sometimes,
+    /// In no state.
+    Never,
+}
+
+impl<'env> Check<'env> {
+    /// Stuttering of the transition relation, and variables that cannot stutter.
+    fn stuttering_analysis(&self, invariants: &[&str]) -> Res<(Stuttering, Vec<&str>)> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Step, "stuttering.smt2")?;
+        solver.intent("stuttering: invariants")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+        for invariant in invariants {
+            solver.assert(smt::Unroll::new(&sys.po_s()[*invariant], 0))?
+        }
+        let stutters = |var: &str| format!("(= {} {})", smt::svar(var, 1), smt::svar(var, 0));
+        let mut frame = "(and true".to_string();
+        for var in sys.decls().iter() {
+            frame.push(' ');
+            frame.push_str(&stutters(var.id()))
+        }
+        frame.push(')');
+        let trans = smt::Unroll::new(sys.trans(), 0).to_string();
+
+        solver.intent("stuttering: is there a state that cannot stutter?")?;
+        let always = solver.unsat(&[frame.clone(), format!("(not {})", trans)])?;
+        let stuttering = if always {
+            Stuttering::Always
+        } else {
+            solver.intent("stuttering: is there a state that can stutter?")?;
+            if solver.unsat(&[frame, trans.clone()])? {
+                Stuttering::Never
+            } else {
+                Stuttering::Sometimes
+            }
+        };
+
+        let mut clocks = Vec::new();
+        for var in sys.decls().iter() {
+            solver.intent(&format!(
+                "stuttering: can `{}` keep its value in some step?",
+                var.id()
+            ))?;
+            if solver.unsat(&[trans.clone(), stutters(var.id())])? {
+                clocks.push(var.id())
+            }
+        }
+        Ok((stuttering, clocks))
+    }
+
+    /// Runs the stuttering analysis, `eventually` are the `eventually` objectives of the run.
+    pub fn stuttering(&self, base: &BaseRes, step: &StepRes, eventually: &[String]) -> Res<()> {
+        let invariants: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        println!();
+        self.env
+            .progress(|s| format!("analyzing {}...", s.bold.paint("stuttering")));
+        let (stuttering, clocks) = self
+            .stuttering_analysis(&invariants)
+            .chain_err(|| "during stuttering analysis")?;
+
+        // Candidates mentioning variables that cannot stutter.
+        let mut sensitive = Vec::new();
+        for (candidate, def) in self.sys.po_s().iter() {
+            let def = def.to_string();
+            let tokens: Vec<&str> = def
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .collect();
+            let mentioned: Vec<&str> = clocks
+                .iter()
+                .filter(|clock| tokens.contains(clock))
+                .cloned()
+                .collect();
+            if !mentioned.is_empty() {
+                sensitive.push((candidate, mentioned))
+            }
+        }
+
+        println!("|===| {} analysis result", self.bold.paint("Stuttering"));
+        let (desc, style) = match stuttering {
+            Stuttering::Always => ("in all states", self.green),
+            Stuttering::Sometimes => ("in some states only", self.bold),
+            Stuttering::Never => ("in no state", self.red),
+        };
+        print!(
+            "| - the transition relation allows stuttering {}",
+            style.paint(desc)
+        );
+        if invariants.is_empty() {
+            println!()
+        } else {
+            println!(" verifying the {} proved candidate(s)", invariants.len())
+        }
+        if !clocks.is_empty() {
+            println!(
+                "| - the following variable(s) {} keep their value in a step",
+                self.red.paint("never")
+            );
+            for clock in &clocks {
+                println!("|   `{}`", self.bold.paint(*clock))
+            }
+        }
+        for (candidate, mentioned) in &sensitive {
+            println!("|");
+            println!(
+                "| - {}: `{}` mentions {}, which change(s) at every step",
+                self.red.paint("warning"),
+                self.bold.paint(*candidate),
+                mentioned
+                    .iter()
+                    .map(|var| format!("`{}`", var))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            println!(
+                "|   it implicitly assumes the system never stutters, and may not hold if it is \
+                composed with or refined by stuttering steps"
+            );
+        }
+        if stuttering == Stuttering::Always && !eventually.is_empty() {
+            println!("|");
+            println!(
+                "| - {}: the system can stutter forever, so the following `eventually` \
+                objective(s) are falsified by a lasso that does nothing unless they hold initially",
+                self.red.paint("warning")
+            );
+            for objective in eventually {
+                println!("|   `{}`", self.bold.paint(objective))
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
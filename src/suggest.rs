@@ -0,0 +1,279 @@
+//! Strengthening suggestions for non-inductive candidates.
+//!
+//! Simple bounds and relations excluding the pre-state of a CTI, inductive with the candidate.
+
+mikino_api::prelude!();
+
+use check::{BaseRes, StepRes};
+use expr::{Expr, Typ};
+
+use crate::{metrics, smt, Check, Phase};
+
+/// Maximum number of suggestions per candidate.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A strengthening of a candidate.
+enum Strengthening {
+    /// Bound on a variable, `v ≥ c` if `lower`, `v ≤ c` otherwise.
+    Bound {
+        var: String,
+        lower: bool,
+        cst: String,
+        desc: String,
+    },
+    /// Value of a boolean variable.
+    Bool { var: String, val: bool },
+    /// Relation between two variables, `op` is `<=`, `>=` or `=`.
+    Rel {
+        lft: String,
+        op: &'static str,
+        rgt: String,
+    },
+}
+impl Strengthening {
+    /// SMT-LIB representation, at some step.
+    fn smt(&self, step: usize) -> String {
+        match self {
+            Self::Bound {
+                var, lower, cst, ..
+            } => format!(
+                "({} {} {})",
+                if *lower { ">=" } else { "<=" },
+                smt::svar(var, step),
+                cst
+            ),
+            Self::Bool { var, val: true } => smt::svar(var, step),
+            Self::Bool { var, val: false } => format!("(not {})", smt::svar(var, step)),
+            Self::Rel { lft, op, rgt } => {
+                format!("({} {} {})", op, smt::svar(lft, step), smt::svar(rgt, step))
+            }
+        }
+    }
+
+    /// Mikino representation.
+    fn desc(&self) -> String {
+        match self {
+            Self::Bound {
+                var, lower, desc, ..
+            } => format!("{} {} {}", var, if *lower { "≥" } else { "≤" }, desc),
+            Self::Bool { var, val: true } => var.clone(),
+            Self::Bool { var, val: false } => format!("¬{}", var),
+            Self::Rel { lft, op, rgt } => {
+                let op = match *op {
+                    "<=" => "≤",
+                    ">=" => "≥",
+                    op => op,
+                };
+                format!("{} {} {}", lft, op, rgt)
+            }
+        }
+    }
+}
+
+/// SMT-LIB constant of some type for a numeric constant, `None` if it does not have this type.
+fn smt_cst(cst: f64, typ: Typ) -> Option<String> {
+    let abs = cst.abs();
+    let abs = match typ {
+        Typ::Int if cst.fract() == 0.0 => format!("{}", abs as i64),
+        Typ::Rat if cst.fract() == 0.0 => format!("{}.0", abs as i64),
+        Typ::Rat => format!("{}", abs),
+        _ => return None,
+    };
+    Some(if cst < 0.0 {
+        format!("(- {})", abs)
+    } else {
+        abs
+    })
+}
+
+impl<'env> Check<'env> {
+    /// Library of strengthenings for the system.
+    fn strengthenings(&self) -> Vec<Strengthening> {
+        let mut constants = metrics::constants(&self.sys);
+        if !constants.contains(&0.0) {
+            constants.push(0.0)
+        }
+        let vars: Vec<(&str, Typ)> = self
+            .sys
+            .decls()
+            .iter()
+            .map(|var| (var.id(), var.typ()))
+            .collect();
+
+        let mut res = Vec::new();
+        for (var, typ) in vars.iter().cloned() {
+            if typ == Typ::Bool {
+                for val in [true, false] {
+                    res.push(Strengthening::Bool {
+                        var: var.into(),
+                        val,
+                    })
+                }
+                continue;
+            }
+            for cst in constants.iter().cloned() {
+                if let Some(smt_cst) = smt_cst(cst, typ) {
+                    for lower in [true, false] {
+                        res.push(Strengthening::Bound {
+                            var: var.into(),
+                            lower,
+                            cst: smt_cst.clone(),
+                            desc: cst.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+        for (idx, (lft, lft_typ)) in vars.iter().enumerate() {
+            for (rgt, rgt_typ) in &vars[idx + 1..] {
+                if lft_typ != rgt_typ || *lft_typ == Typ::Bool {
+                    continue;
+                }
+                for op in ["<=", ">=", "="] {
+                    res.push(Strengthening::Rel {
+                        lft: lft.to_string(),
+                        op,
+                        rgt: rgt.to_string(),
+                    })
+                }
+            }
+        }
+        res
+    }
+
+    /// Strengthenings that make a candidate inductive.
+    fn candidate_suggestions(
+        &self,
+        solver: &mut smt::Solver,
+        candidate: &str,
+        def: &Expr,
+        library: &[Strengthening],
+    ) -> Res<Vec<String>> {
+        let sys = &self.sys;
+        solver.intent(&format!("suggest: CTI for candidate `{}`", candidate))?;
+        solver.push()?;
+        solver.assert_trans(sys, 0)?;
+        solver.assert(smt::Unroll::new(def, 0))?;
+        solver.assert(format!("(not {})", smt::Unroll::new(def, 1)))?;
+        let cti = if solver.check_sat()? {
+            Some(solver.get_state(sys, 0)?)
+        } else {
+            None
+        };
+        solver.pop()?;
+        let Some(cti) = cti else {
+            return Ok(Vec::new());
+        };
+
+        let mut res = Vec::new();
+        for strengthening in library {
+            if res.len() >= MAX_SUGGESTIONS {
+                break;
+            }
+            let desc = strengthening.desc();
+            solver.intent(&format!(
+                "suggest: does `{}` exclude the CTI of `{}`?",
+                desc, candidate
+            ))?;
+            if !solver.unsat(&[smt::state_at(&cti, 0), strengthening.smt(0)])? {
+                continue;
+            }
+            solver.intent(&format!("suggest: does `{}` hold initially?", desc))?;
+            solver.push()?;
+            solver.assert_init(sys, 0)?;
+            let init = solver.unsat(&[format!("(not {})", strengthening.smt(0))])?;
+            solver.pop()?;
+            if !init {
+                continue;
+            }
+            solver.intent(&format!(
+                "suggest: is `{}` inductive together with `{}`?",
+                desc, candidate
+            ))?;
+            solver.push()?;
+            solver.assert_trans(sys, 0)?;
+            let inductive = solver.unsat(&[
+                strengthening.smt(0),
+                smt::Unroll::new(def, 0).to_string(),
+                format!(
+                    "(or (not {}) (not {}))",
+                    strengthening.smt(1),
+                    smt::Unroll::new(def, 1)
+                ),
+            ])?;
+            solver.pop()?;
+            if inductive {
+                res.push(desc)
+            }
+        }
+        Ok(res)
+    }
+
+    /// Suggests strengthenings for the non-inductive candidates that hold in the initial states.
+    pub fn suggest(&self, base: &BaseRes, step: &StepRes) -> Res<()> {
+        let candidates: Vec<&str> = base
+            .okay
+            .iter()
+            .filter(|candidate| !step.okay.contains(*candidate))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        let library = self.strengthenings();
+        println!();
+        self.env.progress(|s| {
+            format!(
+                "trying {} {} on {} non-inductive candidate(s)...",
+                library.len(),
+                s.bold.paint("strengthening(s)"),
+                candidates.len()
+            )
+        });
+
+        let mut solver = self.phase_solver(Phase::Step, "suggest.smt2")?;
+        solver.intent("suggest setup")?;
+        solver.declare_state(&self.sys, 0)?;
+        solver.declare_state(&self.sys, 1)?;
+        let mut suggestions = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            let def = self.sys.po_s().get(candidate).ok_or_else(|| {
+                format!(
+                    "failed to retrieve definition for candidate `{}`",
+                    candidate,
+                )
+            })?;
+            let found = self
+                .candidate_suggestions(&mut solver, candidate, def, &library)
+                .chain_err(|| format!("while looking for suggestions for `{}`", candidate))?;
+            suggestions.push((candidate, found))
+        }
+
+        println!("|===| {} result", self.bold.paint("Suggestion"));
+        for (idx, (candidate, found)) in suggestions.iter().enumerate() {
+            if idx > 0 {
+                println!("|")
+            }
+            if found.is_empty() {
+                println!(
+                    "| - `{}`: {} simple strengthening makes it inductive",
+                    self.bold.paint(*candidate),
+                    self.red.paint("no")
+                )
+            } else {
+                println!(
+                    "| - `{}` is {} with any of the following candidate(s)",
+                    self.bold.paint(*candidate),
+                    self.green.paint("inductive together")
+                );
+                for desc in found {
+                    println!("|   `{}`", self.green.paint(desc))
+                }
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
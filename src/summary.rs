@@ -0,0 +1,131 @@
+//! Consolidated per-candidate summary of a check.
+
+mikino_api::prelude!();
+
+use crate::{budget::Outcome, Check};
+
+/// Strongest statement about a candidate from its outcomes, in the order they were reported.
+fn statement(outcomes: &[&Outcome]) -> String {
+    let depth = |outcome: &Outcome| outcome.depth.unwrap_or(0);
+    if let Some(cex) = outcomes
+        .iter()
+        .find(|outcome| outcome.status == "falsified")
+    {
+        return format!("cex at depth {}", depth(cex));
+    }
+    if let Some(proof) = outcomes.iter().find(|outcome| outcome.status == "proved") {
+        return if proof.phase == "induction" {
+            "proved".into()
+        } else {
+            format!("proved ({})", proof.phase)
+        };
+    }
+    let safe_up_to = outcomes
+        .iter()
+        .filter(|outcome| outcome.phase == "bmc" && outcome.status == "unfalsified")
+        .filter_map(|outcome| outcome.depth)
+        .max()
+        .map(|depth| format!("safe up to {} step(s)", depth));
+    let timeout = outcomes.iter().any(|outcome| outcome.status == "timeout");
+    match (safe_up_to, timeout) {
+        (Some(safe_up_to), true) => format!("timeout, {}", safe_up_to),
+        (Some(safe_up_to), false) => safe_up_to,
+        (None, true) => "timeout".into(),
+        (None, false) => "unknown".into(),
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Prints the consolidated summary of the outcomes of the candidates.
+    pub fn print_summary(&self) -> Res<()> {
+        let history = self
+            .history
+            .lock()
+            .map_err(|_| "failed to access candidate outcomes")?;
+        let mut rows: Vec<[String; 5]> = Vec::with_capacity(self.sys.po_s().len());
+        for candidate in self.sys.po_s().keys() {
+            let outcomes: Vec<&Outcome> = history
+                .iter()
+                .filter(|(name, _)| name == candidate)
+                .map(|(_, outcome)| outcome)
+                .collect();
+            let (base, step) = match outcomes
+                .iter()
+                .find(|outcome| outcome.phase == "induction")
+                .map(|outcome| outcome.status.as_str())
+            {
+                Some("falsified") => ("falsified", "-"),
+                Some("not_inductive") => ("ok", "not inductive"),
+                Some(_) => ("ok", "ok"),
+                None => ("-", "-"),
+            };
+            let bmc = outcomes
+                .iter()
+                .rev()
+                .find(|outcome| outcome.phase == "bmc")
+                .map(|outcome| match (outcome.status.as_str(), outcome.depth) {
+                    ("falsified", Some(depth)) => format!("cex at depth {}", depth),
+                    (_, Some(depth)) => format!("{} step(s)", depth),
+                    (status, None) => status.into(),
+                })
+                .unwrap_or_else(|| "-".into());
+            rows.push([
+                candidate.clone(),
+                base.into(),
+                step.into(),
+                bmc,
+                statement(&outcomes),
+            ])
+        }
+
+        let header = ["candidate", "base", "step", "bmc", "verdict"];
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len())
+            }
+        }
+        // Cells are padded before painting, escape sequences would break the alignment.
+        let line = |cells: Vec<String>| {
+            let cells: Vec<String> = cells
+                .into_iter()
+                .map(|cell| format!(" {} ", cell))
+                .collect();
+            println!("|{}", cells.join("|"))
+        };
+        let pad = |cell: &str, width: usize| format!("{: <width$}", cell, width = width);
+
+        println!("|===| {}", self.bold.paint("Summary"));
+        line(
+            header
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| self.bold.paint(pad(cell, *width)).to_string())
+                .collect(),
+        );
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        println!("|{}", rule.join("+"));
+        for row in &rows {
+            let cells = row
+                .iter()
+                .zip(widths.iter())
+                .enumerate()
+                .map(|(idx, (cell, width))| {
+                    let cell = pad(cell, *width);
+                    let style = match idx {
+                        1 if row[1] == "falsified" => self.red,
+                        3 if row[3].starts_with("cex") => self.red,
+                        4 if row[4].starts_with("cex") => self.red,
+                        4 if row[4].starts_with("proved") => self.green,
+                        4 => self.bold,
+                        _ => return cell,
+                    };
+                    style.paint(cell).to_string()
+                })
+                .collect();
+            line(cells)
+        }
+        println!("|===|");
+        Ok(())
+    }
+}
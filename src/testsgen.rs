@@ -0,0 +1,240 @@
+//! Test vector generation, from BMC falsifications and random simulation runs.
+
+mikino_api::prelude!();
+
+use std::{fs, io::Write, path::Path};
+
+use crate::{
+    json::{json_str, json_value},
+    mode::TestsFormat,
+    smt, Check, Phase, Trace,
+};
+
+/// File name component for a candidate, only keeps alphanumeric characters.
+fn file_name(candidate: &str) -> String {
+    candidate
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl<'env> Check<'env> {
+    /// Values of the variables at each step of a trace, in declaration order.
+    ///
+    /// Values are sanitized if `--sanitize_cex` is active, see [`crate::sanitize`].
+    fn vector(&self, trace: &Trace) -> Vec<(usize, Vec<String>)> {
+        self.sanitized_trace(trace)
+            .iter()
+            .map(|(step, values)| {
+                let row = self
+                    .sys
+                    .decls()
+                    .iter()
+                    .map(|var| {
+                        values
+                            .iter()
+                            .find(|(id, _)| id == self.sanitized_text(var.id()).as_str())
+                            .map(|(_, val)| val.trim_end_matches(" (completed)").to_string())
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                (*step, row)
+            })
+            .collect()
+    }
+
+    /// Renders a trace in some format.
+    ///
+    /// `origin` describes where the trace comes from, only JSON vectors mention it.
+    fn render_vector(&self, trace: &Trace, origin: &str, format: TestsFormat) -> String {
+        let vars: Vec<String> = self
+            .sys
+            .decls()
+            .iter()
+            .map(|var| self.sanitized_text(var.id()))
+            .collect();
+        let rows = self.vector(trace);
+        match format {
+            TestsFormat::Csv => {
+                let mut csv = format!("step,{}\n", vars.join(","));
+                for (step, row) in rows {
+                    csv.push_str(&format!("{},{}\n", step, row.join(",")))
+                }
+                csv
+            }
+            TestsFormat::Json => {
+                let steps: Vec<String> = rows
+                    .iter()
+                    .map(|(step, row)| {
+                        let values: Vec<String> = vars
+                            .iter()
+                            .zip(row.iter())
+                            .filter(|(_, val)| !val.is_empty())
+                            .map(|(var, val)| format!("{}: {}", json_str(var), json_value(val)))
+                            .collect();
+                        format!(
+                            "    {{ \"step\": {}, \"state\": {{ {} }} }}",
+                            step,
+                            values.join(", ")
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\n  \"origin\": {},\n  \"steps\": [\n{}\n  ]\n}}\n",
+                    json_str(origin),
+                    steps.join(",\n")
+                )
+            }
+        }
+    }
+
+    /// Shortest falsification of each candidate, up to depth `max`.
+    fn bmc_traces(&self, max: usize) -> Res<Vec<(String, Trace)>> {
+        let sys = &self.sys;
+        let mut solver = self.phase_solver(Phase::Bmc, "testsgen_bmc.smt2")?;
+        solver.intent("testsgen BMC setup: initial state(s)")?;
+        solver.declare_state(sys, 0)?;
+        solver.assert_init(sys, 0)?;
+
+        let mut left: Vec<&String> = sys.po_s().keys().collect();
+        let mut res = Vec::new();
+        for depth in 0..=max {
+            if left.is_empty() {
+                break;
+            }
+            if depth > 0 {
+                solver.declare_state(sys, depth)?;
+                solver.assert_trans(sys, depth - 1)?;
+            }
+            let mut idx = 0;
+            while idx < left.len() {
+                let candidate = left[idx];
+                let def = &sys.po_s()[candidate];
+                solver.intent(&format!(
+                    "testsgen: falsification for candidate `{}` at depth {}?",
+                    candidate, depth
+                ))?;
+                solver.push()?;
+                solver.assert(format!("(not {})", smt::Unroll::new(def, depth)))?;
+                if solver.check_sat()? {
+                    res.push((candidate.clone(), solver.get_trace(sys, depth)?));
+                    left.remove(idx);
+                } else {
+                    idx += 1
+                }
+                solver.pop()?;
+            }
+        }
+        Ok(res)
+    }
+
+    /// Random simulation runs, stopped early when a state has no successor.
+    fn sim_traces(&self, steps: usize, runs: usize, seed: Option<u64>) -> Res<Vec<Trace>> {
+        let sys = &self.sys;
+        let mut rng = self.rng(seed);
+        let mut solver = self.solver("testsgen_sim.smt2")?;
+        solver.intent("testsgen simulation setup")?;
+        solver.declare_state(sys, 0)?;
+        solver.declare_state(sys, 1)?;
+
+        let mut res = Vec::with_capacity(runs);
+        for run in 0..runs {
+            solver.intent(&format!("testsgen: run {}, initial state", run + 1))?;
+            let mut state = self.random_init(&mut solver, &mut rng)?;
+            let mut trace: Trace = vec![(0, smt::pretty_state(&state))];
+            for step in 1..=steps {
+                solver.intent(&format!("testsgen: run {}, step {}", run + 1, step))?;
+                match self.random_successor(&mut solver, &mut rng, &state, None)? {
+                    Some(next) => {
+                        trace.push((step, smt::pretty_state(&next)));
+                        state = next
+                    }
+                    None => break,
+                }
+            }
+            res.push(trace)
+        }
+        Ok(res)
+    }
+
+    /// Writes test vectors to the `output` directory.
+    ///
+    /// See [`crate::mode::Mode::TestsGen`] for the parameters.
+    pub fn testsgen(
+        &self,
+        output: &str,
+        format: TestsFormat,
+        bmc_max: usize,
+        steps: usize,
+        runs: usize,
+        seed: Option<u64>,
+    ) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "{} test vectors, BMC up to depth {} and {} simulation run(s)...",
+                s.bold.paint("generating"),
+                bmc_max,
+                runs
+            )
+        });
+        let falsifications = self.bmc_traces(bmc_max)?;
+        let runs = self.sim_traces(steps, runs, seed)?;
+
+        let dir = Path::new(output);
+        fs::create_dir_all(dir)
+            .chain_err(|| format!("while creating test vector directory `{}`", output))?;
+        let write = |name: String, content: String| -> Res<()> {
+            let path = dir.join(format!("{}.{}", name, format.extension()));
+            let mut file = fs::File::create(&path)
+                .chain_err(|| format!("while creating test vector file `{}`", path.display()))?;
+            file.write_all(content.as_bytes())
+                .chain_err(|| format!("while writing test vector to `{}`", path.display()))
+        };
+        for (idx, (candidate, trace)) in falsifications.iter().enumerate() {
+            write(
+                format!("bmc_{}_{}", idx, file_name(&self.sanitized_text(candidate))),
+                self.render_vector(
+                    trace,
+                    &format!(
+                        "BMC falsification of candidate `{}`",
+                        self.sanitized_text(candidate)
+                    ),
+                    format,
+                ),
+            )?
+        }
+        for (idx, trace) in runs.iter().enumerate() {
+            write(
+                format!("sim_{}", idx),
+                self.render_vector(trace, &format!("simulation run {}", idx + 1), format),
+            )?
+        }
+
+        println!("|===| {} result", self.bold.paint("Test generation"));
+        println!(
+            "| - wrote {} falsification(s) and {} simulation run(s) to `{}`",
+            falsifications.len(),
+            runs.len(),
+            self.bold.paint(output)
+        );
+        let unfalsified: Vec<&String> = self
+            .sys
+            .po_s()
+            .keys()
+            .filter(|candidate| falsifications.iter().all(|(c, _)| c != *candidate))
+            .collect();
+        if !unfalsified.is_empty() {
+            println!("|");
+            println!(
+                "| - no falsification up to depth {} for the following candidate(s)",
+                bmc_max
+            );
+            for candidate in unfalsified {
+                println!("|   `{}`", self.bold.paint(candidate))
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,549 @@
+//! Text-level edits of the top-level blocks of system files, before `mikino_api` parses them.
+
+mikino_api::prelude!();
+
+/// Replaces the candidates of a system, given as name/expression pairs.
+///
+/// Works on the text of the system, fails if it has no `candidates { ... }` block.
+pub fn with_candidates(txt: &str, candidates: &[(String, String)]) -> Res<String> {
+    let (start, end) = block(txt, "candidates")?;
+    let mut res = txt[..start].to_string();
+    res.push('\n');
+    for (name, expr) in candidates {
+        res.push_str(&format!("    \"{}\": {},\n", name.replace('"', "'"), expr))
+    }
+    res.push_str(&txt[end..]);
+    Ok(res)
+}
+
+/// Conjoins assumptions to the initial predicate and the transition relation.
+///
+/// Assumptions are added to the comma-separated conjunctions of the `init` and `trans` blocks. In
+/// the transition relation they constrain both the current and the next state, the latter by
+/// priming the variables of the system.
+pub fn with_assumptions(txt: &str, assumptions: &[String]) -> Res<String> {
+    let sys = parse::trans(txt).chain_err(|| "while parsing system to add assumptions")?;
+    let vars: Set<String> = sys.decls().iter().map(|var| var.id().to_string()).collect();
+
+    let mut res = txt.to_string();
+    for (keyword, next) in [("init", false), ("trans", true)] {
+        let (start, _) = block(&res, keyword)?;
+        let mut conj = String::new();
+        for assumption in assumptions {
+            conj.push_str(&format!("\n    ({}),", assumption));
+            if next {
+                conj.push_str(&format!("\n    ({}),", prime(assumption, &vars)));
+            }
+        }
+        res.insert_str(start, &conj)
+    }
+    Ok(res)
+}
+
+/// Primes the variables of an expression, `cnt + 1` becomes `'cnt + 1`.
+///
+/// Variables that are already primed are left untouched.
+pub fn prime(expr: &str, vars: &Set<String>) -> String {
+    let mut res = String::with_capacity(expr.len());
+    let mut chars = expr.chars().peekable();
+    let mut prev = None;
+    while let Some(char) = chars.next() {
+        if char.is_alphabetic() || char == '_' {
+            let mut ident = char.to_string();
+            while let Some(next) = chars.peek().cloned() {
+                if !(next.is_alphanumeric() || next == '_') {
+                    break;
+                }
+                ident.push(next);
+                chars.next();
+            }
+            if prev != Some('\'') && vars.contains(&ident) {
+                res.push('\'')
+            }
+            res.push_str(&ident);
+            prev = ident.chars().last()
+        } else {
+            res.push(char);
+            prev = Some(char)
+        }
+    }
+    res
+}
+
+/// Replaces the content of a top-level block such as `trans { ... }`.
+pub fn with_block(txt: &str, keyword: &str, content: &str) -> Res<String> {
+    let (start, end) = block(txt, keyword)?;
+    Ok(format!(
+        "{}\n    {}\n{}",
+        &txt[..start],
+        content,
+        &txt[end..]
+    ))
+}
+
+/// Byte ranges of the comma-separated elements of a top-level block, *e.g.* the conjuncts of
+/// `trans { ... }`.
+///
+/// Ranges are trimmed and do not start with comments, empty elements such as the one after a
+/// trailing comma are ignored.
+pub fn elements(txt: &str, keyword: &str) -> Res<Vec<(usize, usize)>> {
+    let (start, end) = block(txt, keyword)?;
+    let bytes = txt.as_bytes();
+    let mut res = Vec::new();
+    let mut push = |mut lft: usize, rgt: usize| {
+        loop {
+            while lft < rgt && bytes[lft].is_ascii_whitespace() {
+                lft += 1
+            }
+            match skip_comment_or_string(txt, lft) {
+                Some(next) if lft < rgt && bytes[lft] == b'/' => lft = next.min(rgt),
+                _ => break,
+            }
+        }
+        let rgt = lft + txt[lft..rgt].trim_end().len();
+        if lft < rgt {
+            res.push((lft, rgt))
+        }
+    };
+    let (mut idx, mut elm_start, mut depth) = (start, start, 0);
+    while idx < end {
+        if let Some(next) = skip_comment_or_string(txt, idx) {
+            idx = next;
+            continue;
+        }
+        match bytes[idx] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => {
+                push(elm_start, idx);
+                elm_start = idx + 1
+            }
+            _ => (),
+        }
+        idx += 1
+    }
+    push(elm_start, end);
+    Ok(res)
+}
+
+/// Byte range of the content of a top-level block such as `init { ... }`, between the braces.
+///
+/// Skips comments and string literals.
+pub fn block(txt: &str, keyword: &str) -> Res<(usize, usize)> {
+    match find_block(txt, keyword) {
+        Some((_, start, end)) => Ok((start, end)),
+        None => bail!(
+            "could not find the `{} {{ ... }}` block of the system",
+            keyword
+        ),
+    }
+}
+
+/// Removes the top-level `<keyword> { ... }` block of a system, if any.
+///
+/// Yields the new system, where the block is replaced by its newlines so that lines do not
+/// change, and the content of the block.
+pub fn take_block(txt: &str, keyword: &str) -> (String, Option<String>) {
+    match find_block(txt, keyword) {
+        Some((kw_start, start, end)) => {
+            let mut res = txt[..kw_start].to_string();
+            res.push_str(&"\n".repeat(txt[kw_start..=end].matches('\n').count()));
+            res.push_str(&txt[end + 1..]);
+            (res, Some(txt[start..end].to_string()))
+        }
+        None => (txt.to_string(), None),
+    }
+}
+
+/// Start of the keyword and byte range of the content of a top-level block, see [`block`].
+///
+/// The keyword only matches outside of braces.
+fn find_block(txt: &str, keyword: &str) -> Option<(usize, usize, usize)> {
+    let bytes = txt.as_bytes();
+    let (mut idx, mut depth) = (0, 0usize);
+    while idx < bytes.len() {
+        if let Some(next) = skip_comment_or_string(txt, idx) {
+            idx = next;
+            continue;
+        }
+        match bytes[idx] {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && keyword_at(txt, idx, keyword) => {
+                let kw_start = idx;
+                idx += keyword.len();
+                while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                    idx += 1
+                }
+                if bytes.get(idx) == Some(&b'{') {
+                    let start = idx + 1;
+                    return closing_brace(txt, start).map(|end| (kw_start, start, end));
+                }
+                continue;
+            }
+            _ => (),
+        }
+        idx += 1
+    }
+    None
+}
+
+/// Named systems of a file, as `system <NAME> { ... }` top-level blocks, with their content and
+/// the line the content starts at.
+///
+/// Yields nothing if the file declares a single anonymous system. Fails on anything but comments
+/// between named systems.
+pub fn systems(txt: &str) -> Res<Vec<(String, String, usize)>> {
+    const KEYWORD: &str = "system";
+    let bytes = txt.as_bytes();
+    let line = |idx: usize| txt[..idx].matches('\n').count() + 1;
+    let mut res: Vec<(String, String, usize)> = Vec::new();
+    let mut idx = 0;
+
+    loop {
+        loop {
+            while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                idx += 1
+            }
+            match skip_comment_or_string(txt, idx) {
+                Some(next) if bytes[idx] == b'/' => idx = next,
+                _ => break,
+            }
+        }
+        if idx >= bytes.len() {
+            break;
+        }
+
+        if !txt[idx..].starts_with(KEYWORD)
+            || is_ident_byte(bytes.get(idx + KEYWORD.len()).cloned())
+        {
+            if res.is_empty() {
+                return Ok(Vec::new());
+            }
+            bail!(
+                "expected `{} <NAME> {{ ... }}` at line {}, only named systems can follow one",
+                KEYWORD,
+                line(idx)
+            )
+        }
+        idx += KEYWORD.len();
+        while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+            idx += 1
+        }
+        let name_start = idx;
+        while is_ident_byte(bytes.get(idx).cloned()) {
+            idx += 1
+        }
+        let name = &txt[name_start..idx];
+        if name.is_empty() {
+            bail!(
+                "expected system name after `{}` at line {}",
+                KEYWORD,
+                line(idx)
+            )
+        }
+        while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+            idx += 1
+        }
+        if bytes.get(idx) != Some(&b'{') {
+            bail!(
+                "expected `{{` after system name `{}` at line {}",
+                name,
+                line(idx)
+            )
+        }
+        idx += 1;
+
+        let start = idx;
+        idx = match closing_brace(txt, start) {
+            Some(end) => end + 1,
+            None => bail!(
+                "unclosed system `{}` starting at line {}",
+                name,
+                line(start)
+            ),
+        };
+        if res.iter().any(|(other, _, _)| other == name) {
+            bail!("system `{}` is declared twice", name)
+        }
+        res.push((
+            name.to_string(),
+            txt[start..idx - 1].to_string(),
+            line(start),
+        ))
+    }
+
+    Ok(res)
+}
+
+/// Line of the name of each candidate of a system, the first line being `first_line`.
+pub fn candidate_lines(txt: &str, first_line: usize) -> Res<Map<String, usize>> {
+    let mut res = Map::new();
+    for (start, end) in elements(txt, "candidates")? {
+        if let Some((name_start, name_end)) = candidate_name(txt, start, end) {
+            let line = txt[..name_start].matches('\n').count() + first_line;
+            res.insert(txt[name_start..name_end].to_string(), line);
+        }
+    }
+    Ok(res)
+}
+
+/// Name and expression of each candidate of a system.
+pub fn candidates(txt: &str) -> Res<Vec<(String, String)>> {
+    let mut res = Vec::new();
+    for (start, end) in elements(txt, "candidates")? {
+        let (name_start, name_end) = candidate_name(txt, start, end)
+            .ok_or_else(|| format!("illegal candidate `{}`", &txt[start..end]))?;
+        match txt[name_end + 1..end].trim_start().strip_prefix(':') {
+            Some(expr) => res.push((
+                txt[name_start..name_end].to_string(),
+                expr.trim().to_string(),
+            )),
+            None => bail!(
+                "expected `:` after candidate name `{}`",
+                &txt[name_start..name_end]
+            ),
+        }
+    }
+    Ok(res)
+}
+
+/// Byte range of the name of the candidate in some range, without the quotes.
+///
+/// Skips annotations such as `#[ignore(...)]`.
+fn candidate_name(txt: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let elm = &txt[start..end];
+    let mut skip = 0;
+    while elm[skip..].starts_with("#[") {
+        match elm[skip..].find(")]") {
+            Some(idx) => skip += idx + 2,
+            None => break,
+        }
+        skip = elm.len() - elm[skip..].trim_start().len();
+    }
+    let name_start = start + skip + elm[skip..].find('"')? + 1;
+    let len = txt[name_start..end].find('"')?;
+    Some((name_start, name_start + len))
+}
+
+/// A named block: its name, its content, and the line it starts at.
+pub type NamedBlock = (String, String, usize);
+
+/// Removes the top-level `<keyword> <NAME> { ... }` blocks of a system.
+///
+/// Yields the new system, where each block is replaced by its newlines so that lines do not
+/// change, and the name, content and line of each block.
+pub fn take_named_blocks(txt: &str, keyword: &str) -> Res<(String, Vec<NamedBlock>)> {
+    let bytes = txt.as_bytes();
+    let line = |idx: usize| txt[..idx].matches('\n').count() + 1;
+    let mut res = String::with_capacity(txt.len());
+    let mut blocks = Vec::new();
+    let (mut idx, mut copied, mut depth) = (0, 0, 0);
+
+    while idx < bytes.len() {
+        if let Some(next) = skip_comment_or_string(txt, idx) {
+            idx = next;
+            continue;
+        }
+        match bytes[idx] {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ if depth == 0 && keyword_at(txt, idx, keyword) => {
+                let block_start = idx;
+                idx += keyword.len();
+                while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                    idx += 1
+                }
+                let name_start = idx;
+                while is_ident_byte(bytes.get(idx).cloned()) {
+                    idx += 1
+                }
+                let name = &txt[name_start..idx];
+                while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+                    idx += 1
+                }
+                if name.is_empty() || bytes.get(idx) != Some(&b'{') {
+                    bail!(
+                        "expected `{} <NAME> {{ ... }}` at line {}",
+                        keyword,
+                        line(block_start)
+                    )
+                }
+                let start = idx + 1;
+                match closing_brace(txt, start) {
+                    Some(end) => idx = end + 1,
+                    None => bail!(
+                        "unclosed `{} {}` starting at line {}",
+                        keyword,
+                        name,
+                        line(block_start)
+                    ),
+                }
+                blocks.push((
+                    name.to_string(),
+                    txt[start..idx - 1].to_string(),
+                    line(block_start),
+                ));
+                res.push_str(&txt[copied..block_start]);
+                res.push_str(&"\n".repeat(txt[block_start..idx].matches('\n').count()));
+                copied = idx;
+                continue;
+            }
+            _ => (),
+        }
+        idx += 1
+    }
+    res.push_str(&txt[copied..]);
+    Ok((res, blocks))
+}
+
+/// Index of the brace closing the one right before `start`, skipping comments and string
+/// literals.
+fn closing_brace(txt: &str, start: usize) -> Option<usize> {
+    let bytes = txt.as_bytes();
+    let (mut idx, mut depth) = (start, 1);
+    while idx < bytes.len() {
+        if let Some(next) = skip_comment_or_string(txt, idx) {
+            idx = next;
+            continue;
+        }
+        match bytes[idx] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => (),
+        }
+        idx += 1
+    }
+    None
+}
+
+/// True if `keyword` appears at `idx` as a whole identifier.
+fn keyword_at(txt: &str, idx: usize, keyword: &str) -> bool {
+    let bytes = txt.as_bytes();
+    bytes[idx..].starts_with(keyword.as_bytes())
+        && !is_ident_byte(idx.checked_sub(1).map(|prev| bytes[prev]))
+        && !is_ident_byte(bytes.get(idx + keyword.len()).cloned())
+}
+
+/// Index right after the comment or string literal starting at `idx`, if any.
+fn skip_comment_or_string(txt: &str, mut idx: usize) -> Option<usize> {
+    let bytes = txt.as_bytes();
+    match bytes.get(idx)? {
+        b'/' if bytes.get(idx + 1) == Some(&b'/') => {
+            while idx < bytes.len() && bytes[idx] != b'\n' {
+                idx += 1
+            }
+        }
+        b'/' if bytes.get(idx + 1) == Some(&b'*') => {
+            idx += 2;
+            while idx < bytes.len() && !bytes[idx..].starts_with(b"*/") {
+                idx += 1
+            }
+            idx += 2
+        }
+        b'"' => {
+            idx += 1;
+            while idx < bytes.len() && bytes[idx] != b'"' {
+                if bytes[idx] == b'\\' {
+                    idx += 1
+                }
+                idx += 1
+            }
+            idx += 1
+        }
+        _ => return None,
+    }
+    Some(idx)
+}
+
+/// True if a byte can appear in an identifier.
+fn is_ident_byte(byte: Option<u8>) -> bool {
+    byte.map(|b| b.is_ascii_alphanumeric() || b == b'_')
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SYS: &str = "\
+svars { cnt: int, inc: bool }
+// init { commented out }
+init { cnt = 0, if inc { cnt ≥ 0 } else { true } }
+trans {
+    'cnt = if inc { cnt + 1 } else { cnt }, /* } */
+}
+candidates {
+    \"cnt } pos\": cnt ≥ 0,
+}
+";
+
+    #[test]
+    fn block_skips_nested_braces_and_comments() {
+        let (start, end) = block(SYS, "init").unwrap();
+        assert_eq!(
+            &SYS[start..end],
+            " cnt = 0, if inc { cnt ≥ 0 } else { true } "
+        );
+        let (start, end) = block(SYS, "trans").unwrap();
+        assert!(SYS[start..end].trim().ends_with("/* } */"));
+        assert!(block(SYS, "assume").is_err());
+    }
+
+    #[test]
+    fn block_only_matches_top_level_keywords() {
+        let txt = "trans { x { init { false } } }\ninit { true }";
+        let (start, end) = block(txt, "init").unwrap();
+        assert_eq!(&txt[start..end], " true ");
+    }
+
+    #[test]
+    fn elements_split_top_level_commas() {
+        let elms: Vec<&str> = elements(SYS, "init")
+            .unwrap()
+            .into_iter()
+            .map(|(start, end)| &SYS[start..end])
+            .collect();
+        assert_eq!(elms, ["cnt = 0", "if inc { cnt ≥ 0 } else { true }"]);
+        let elms = elements(SYS, "trans").unwrap();
+        assert_eq!(elms.len(), 1);
+        assert_eq!(
+            candidates(SYS).unwrap(),
+            [("cnt } pos".to_string(), "cnt ≥ 0".to_string())]
+        );
+    }
+
+    #[test]
+    fn named_systems() {
+        let txt =
+            "// two systems\nsystem a { init { true } }\n\nsystem b {\n  trans { x { } }\n}\n";
+        let named = systems(txt).unwrap();
+        let names: Vec<&str> = named.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(named[0].1, " init { true } ");
+        assert_eq!(named[1].1, "\n  trans { x { } }\n");
+        assert_eq!(named[1].2, 4);
+
+        assert!(systems(SYS).unwrap().is_empty());
+        assert!(systems("system a { init { true }").is_err());
+        assert!(systems("system a { }\nsystem a { }").is_err());
+        assert!(systems("system a { }\ninit { true }").is_err());
+    }
+
+    #[test]
+    fn named_blocks_with_nested_braces() {
+        let txt = "view v { if b { x } else { y } }\ninit { true }\n";
+        let (rest, blocks) = take_named_blocks(txt, "view").unwrap();
+        assert_eq!(rest, "\ninit { true }\n");
+        assert_eq!(
+            blocks,
+            [("v".to_string(), " if b { x } else { y } ".to_string(), 1)]
+        );
+        assert!(take_named_blocks("view v { x", "view").is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Global wall-clock timeout, `--timeout <DURATION>`.
+//!
+//! Expiry cancels the run, see [`crate::cancel`]; a run still busy after a grace period reports its
+//! partial results and stops.
+
+mikino_api::prelude!();
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    budget::{exit, Verdict},
+    mute, pager, report, Check, Run,
+};
+
+/// Time a run has to stop after its `--timeout` expires, before the watchdog stops it.
+const GRACE: Duration = Duration::from_secs(2);
+
+/// Time between two checks of the analyses running on a worker thread, see [`Check::watched`].
+const POLL: Duration = Duration::from_millis(50);
+
+/// True once the run reports its results after its `--timeout` expired.
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+/// True once the run did not stop within the grace period after its `--timeout` expired.
+static STALLED: AtomicBool = AtomicBool::new(false);
+
+/// Parses a duration: an integer with a unit, `ms`, `s`, `m` or `h`, seconds if none.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (val, unit) = s.split_at(split);
+    let val: u64 = val
+        .parse()
+        .map_err(|_| format!("expected a duration such as `30s` or `5m`, got `{}`", s))?;
+    let secs = |factor: u64| {
+        val.checked_mul(factor)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration `{}` is too large", s))
+    };
+    let duration = match unit.trim() {
+        "ms" => Duration::from_millis(val),
+        "" | "s" => Duration::from_secs(val),
+        "m" => secs(60)?,
+        "h" => secs(3600)?,
+        unit => {
+            return Err(format!(
+                "unknown duration unit `{}`, expected `ms`, `s`, `m` or `h`",
+                unit
+            ))
+        }
+    };
+    if duration.is_zero() {
+        return Err("expected a positive duration".into());
+    }
+    Ok(duration)
+}
+
+/// Bound of an analysis that stopped at depth `max`, if any, for candidates it left unknown.
+///
+/// Without a max depth, only a timeout stops the analysis.
+pub fn unknown_bound(max: Option<usize>, timed_out: bool) -> String {
+    match max {
+        Some(max) if !timed_out => format!("in {} step(s) or less", max),
+        _ => "before a timeout".into(),
+    }
+}
+
+/// Human-readable duration, as `--timeout` accepts it.
+pub fn pretty_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis % 1000 != 0 {
+        format!("{}ms", millis)
+    } else {
+        format!("{}s", duration.as_secs())
+    }
+}
+
+impl Run {
+    /// Cancels the run once the `--timeout` expires, if any.
+    ///
+    /// Queries `mikino_api` handles cannot be interrupted: if the run does not report its results
+    /// within a grace period after expiry, it is stalled and [`Check::watched`] reports the partial
+    /// results. If this does not start within another grace period, the watchdog ends the run with
+    /// the unknown exit code.
+    pub fn start_timeout(&self) {
+        let Some(timeout) = self.timeout else {
+            return;
+        };
+        let token = self.cancel_token();
+        let (json, verdict_only) = (self.json, self.verdict_only);
+        let report = format!(
+            "|===| {} result\n\
+            | - reached the time limit of {} and the current query did not stop within {}\n\
+            | - candidates not reported above {}\n\
+            |===|",
+            self.bold.paint("Timeout"),
+            self.bold.paint(pretty_duration(timeout)),
+            pretty_duration(GRACE),
+            self.red.paint("timed out"),
+        );
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            token.cancel();
+            std::thread::sleep(GRACE);
+            if REPORTING.load(Ordering::Relaxed) {
+                return;
+            }
+            STALLED.store(true, Ordering::Relaxed);
+            std::thread::sleep(GRACE);
+            if REPORTING.load(Ordering::Relaxed) {
+                return;
+            }
+            if verdict_only {
+                mute::stop();
+                println!("{}", exit::token(exit::UNKNOWN))
+            } else if json {
+                eprintln!(
+                    "reached the time limit of {}, stopping",
+                    pretty_duration(timeout)
+                )
+            } else {
+                println!();
+                println!("{}", report)
+            }
+            pager::wait();
+            std::process::exit(exit::UNKNOWN)
+        });
+    }
+
+    /// True if the `--timeout` expired.
+    pub fn timed_out(&self) -> bool {
+        self.timeout.is_some() && self.cancel.is_cancelled()
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Runs the `analyses` of a system, then yields its verdict with `finish`.
+    ///
+    /// With a `--timeout`, the analyses run on a worker thread. If they are stalled, see
+    /// [`Run::start_timeout`], the partial results of the whole run are reported and the run ends:
+    /// `done` is the verdict of the systems checked before.
+    pub fn watched(
+        &self,
+        done: &Verdict,
+        analyses: impl FnOnce() -> Res<()> + Send,
+        finish: impl Fn() -> Res<Verdict>,
+    ) -> Res<Verdict> {
+        if self.env.timeout.is_none() {
+            analyses()?;
+            return finish();
+        }
+        std::thread::scope(|scope| {
+            let worker = scope.spawn(analyses);
+            while !worker.is_finished() {
+                if STALLED.load(Ordering::Relaxed) {
+                    self.env.progress(|s| {
+                        format!(
+                            "the current query did not stop within {} after the time limit, \
+                            reporting partial results",
+                            s.bold.paint(pretty_duration(GRACE))
+                        )
+                    });
+                    let res = finish().and_then(|verdict| {
+                        let mut all = done.clone();
+                        all.extend(verdict);
+                        self.env.conclude(&all)
+                    });
+                    // The worker never stops, the scope must not be left.
+                    self.env.end(res)
+                }
+                std::thread::sleep(POLL)
+            }
+            worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })?;
+        finish()
+    }
+
+    /// Candidates no analysis settled, and not reported as `timeout` yet.
+    fn unsettled(&self) -> Res<Vec<String>> {
+        let settled = self
+            .settled
+            .lock()
+            .map_err(|_| "failed to access candidate statuses")?;
+        let outcomes = self
+            .outcomes
+            .lock()
+            .map_err(|_| "failed to access candidate outcomes")?;
+        Ok(self
+            .sys
+            .po_s()
+            .keys()
+            .filter(|candidate| !settled.contains_key(*candidate))
+            .filter(|candidate| {
+                outcomes
+                    .get(*candidate)
+                    .map(|outcome| outcome.status != "timeout")
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Reports the candidates no analysis settled as `timeout` if the `--timeout` expired.
+    ///
+    /// Candidates already reported as `timeout` are left as is.
+    pub fn settle_timeouts(&self) -> Res<()> {
+        if !self.env.timed_out() {
+            return Ok(());
+        }
+        REPORTING.store(true, Ordering::Relaxed);
+        let left = self.unsettled()?;
+        if left.is_empty() {
+            return Ok(());
+        }
+        for candidate in &left {
+            self.settle("timeout", candidate, "timeout", None)?;
+            self.record_case(
+                "timeout",
+                candidate,
+                report::Status::Skipped("timeout".into()),
+                None,
+            )?
+        }
+
+        if self.env.json {
+            return Ok(());
+        }
+        println!();
+        println!("|===| {} result", self.bold.paint("Timeout"));
+        println!(
+            "| - reached the time limit of {}, the following candidate(s) {}",
+            self.bold.paint(pretty_duration(
+                self.env.timeout.expect("timed out without a timeout")
+            )),
+            self.red.paint("timed out")
+        );
+        for candidate in &left {
+            println!("|   `{}`", self.bold.paint(self.sanitized_text(candidate)))
+        }
+        println!("|===|");
+        Ok(())
+    }
+
+    /// Reports the candidates no analysis settled as `timeout` after a query of a phase
+    /// `mikino_api` handles exceeded the `--query_timeout`, see [`Self::api_phase`].
+    pub fn settle_query_timeout(&self, phase: &'static str) -> Res<()> {
+        let left = self.unsettled()?;
+        for candidate in &left {
+            self.settle(phase, candidate, "timeout", None)?;
+            self.record_case(
+                phase,
+                candidate,
+                report::Status::Skipped("query timeout".into()),
+                None,
+            )?
+        }
+        if self.env.json || left.is_empty() {
+            return Ok(());
+        }
+        println!();
+        println!("|===| {} result", self.bold.paint("Query timeout"));
+        println!(
+            "| - a {} query exceeded the time limit of {}ms, Z3 answered `unknown` \
+            (timeout or incomplete)",
+            phase,
+            self.env.query_timeout.unwrap_or(0)
+        );
+        println!(
+            "| - the following candidate(s) are {}",
+            self.red.paint("unknown")
+        );
+        for candidate in &left {
+            println!("|   `{}`", self.bold.paint(self.sanitized_text(candidate)))
+        }
+        println!("|===|");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration(" 5m "), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn illegal_durations() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("-3s").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration(&format!("{}m", u64::MAX)).is_err());
+        assert!(parse_duration(&format!("{}h", u64::MAX / 60)).is_err());
+    }
+
+    #[test]
+    fn unknown_bounds() {
+        assert_eq!(unknown_bound(Some(7), false), "in 7 step(s) or less");
+        assert_eq!(unknown_bound(Some(7), true), "before a timeout");
+        assert_eq!(unknown_bound(None, true), "before a timeout");
+        // `--query_timeout` without `--bmc_max`.
+        assert_eq!(unknown_bound(None, false), "before a timeout");
+    }
+
+    #[test]
+    fn pretty_durations() {
+        assert_eq!(pretty_duration(Duration::from_millis(1500)), "1500ms");
+        assert_eq!(pretty_duration(Duration::from_secs(300)), "300s");
+        assert_eq!(
+            parse_duration(&pretty_duration(Duration::from_millis(1500))),
+            Ok(Duration::from_millis(1500))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Layout of the traces, blocks or tables.
+
+mikino_api::prelude!();
+
+use std::ops::Range;
+
+use crate::{layout, Check, Trace};
+
+/// Layout of the traces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One block per step.
+    Blocks,
+    /// One column per step.
+    Table,
+}
+
+impl Format {
+    /// Names of the formats.
+    pub const NAMES: [&'static str; 2] = ["blocks", "table"];
+
+    /// Format from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blocks" => Some(Self::Blocks),
+            "table" => Some(Self::Table),
+            _ => None,
+        }
+    }
+}
+
+/// Label of a step, relative to `k` for step counterexamples.
+pub fn step_label(step: usize, is_base: bool) -> String {
+    match (is_base, step) {
+        (true, step) => step.to_string(),
+        (false, 0) => "k".into(),
+        (false, step) => format!("k + {}", step),
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Presents a trace as a table, see [`crate::trace_format`].
+    ///
+    /// The trace must be sanitized already, steps in `elided` are collapsed.
+    pub fn present_table(&self, trace: &Trace, elided: &Range<usize>, is_base: bool) {
+        // Variables in order of appearance.
+        let mut vars: Vec<&str> = Vec::new();
+        for (_, values) in trace {
+            for (var, _) in values {
+                if !vars.contains(&var.as_str()) {
+                    vars.push(var)
+                }
+            }
+        }
+
+        // Columns as (header, index of the step) pairs, `None` for the elided steps.
+        let mut columns: Vec<(String, Option<usize>)> = Vec::with_capacity(trace.len());
+        for (idx, (step, _)) in trace.iter().enumerate() {
+            if elided.contains(&idx) {
+                if idx == elided.start {
+                    columns.push(("...".into(), None))
+                }
+                continue;
+            }
+            columns.push((step_label(*step, is_base), Some(idx)))
+        }
+        let value = |idx: usize, var: &str| {
+            trace[idx]
+                .1
+                .iter()
+                .find(|(v, _)| v == var)
+                .map(|(_, val)| val.as_str())
+                .unwrap_or("-")
+        };
+        let var_width = vars
+            .iter()
+            .map(|var| var.len())
+            .fold("variable".len(), usize::max);
+        let mut widths: Vec<usize> = columns
+            .iter()
+            .map(|(header, idx)| {
+                vars.iter()
+                    .map(|var| idx.map(|idx| value(idx, var).chars().count()).unwrap_or(0))
+                    .fold(header.len(), usize::max)
+            })
+            .collect();
+        if let Some(width) = self.env.width {
+            // `  | <var> | <cell> | <cell>`, cells share what the variable column leaves.
+            let total = var_width + 4 + widths.iter().map(|width| width + 3).sum::<usize>();
+            if total > width {
+                let room = width.saturating_sub(var_width + 4) / columns.len().max(1);
+                let max = room.saturating_sub(3).max(layout::MIN_WIDTH / 2);
+                for (width, (header, _)) in widths.iter_mut().zip(columns.iter()) {
+                    *width = (*width).min(max.max(header.len()))
+                }
+            }
+        }
+
+        // Cells are padded before painting, escape sequences would break the alignment.
+        let header: Vec<String> = columns
+            .iter()
+            .zip(widths.iter())
+            .map(|((header, _), width)| {
+                self.under
+                    .paint(format!("{: >1$}", header, width))
+                    .to_string()
+            })
+            .collect();
+        println!(
+            "  | {: >2$} | {}",
+            "variable",
+            header.join(" | "),
+            var_width
+        );
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        println!("  |{}+{}", "-".repeat(var_width + 2), rule.join("+"));
+        for var in &vars {
+            let cells: Vec<String> = columns
+                .iter()
+                .zip(widths.iter())
+                .map(|((_, idx), width)| {
+                    let idx = match idx {
+                        Some(idx) => *idx,
+                        None => return format!("{: >1$}", "...", width),
+                    };
+                    let val = value(idx, var);
+                    let cell = format!("{: >1$}", layout::truncate(val, *width), width);
+                    // No previous step to compare to right after elided steps.
+                    let prev = idx
+                        .checked_sub(1)
+                        .filter(|prev| !elided.contains(prev))
+                        .map(|prev| trace[prev].1.as_slice());
+                    match self.change_style(var, val, prev) {
+                        Some(style) => style.paint(cell).to_string(),
+                        None => cell,
+                    }
+                })
+                .collect();
+            println!(
+                "  | {} | {}",
+                self.bold.paint(format!("{: >1$}", var, var_width)),
+                cells.join(" | ")
+            )
+        }
+    }
+}
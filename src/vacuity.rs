@@ -0,0 +1,131 @@
+//! Vacuity detection for candidates: valid ones, ones implied by the system alone, and implications
+//! with an unreachable antecedent.
+
+mikino_api::prelude!();
+
+use expr::{Expr, Op};
+
+use crate::{smt, Check};
+
+/// Reason why a candidate holds vacuously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vacuity {
+    /// True in every state.
+    Valid,
+    /// Implied by the initial and transition predicates.
+    ByConstruction,
+    /// Antecedent of an implication that is never reachable.
+    Antecedent,
+}
+impl Vacuity {
+    /// Human-readable description.
+    pub fn desc(self) -> &'static str {
+        match self {
+            Self::Valid => "valid, true in every state",
+            Self::ByConstruction => "implied by the initial and transition predicates alone",
+            Self::Antecedent => "antecedent is never reachable",
+        }
+    }
+}
+
+impl<'env> Check<'env> {
+    /// Checks all candidates for vacuity and reports the vacuous ones.
+    pub fn vacuity(&self) -> Res<()> {
+        self.env.progress(|s| {
+            format!(
+                "checking {} candidate(s) for {}...",
+                self.sys.po_s().len(),
+                s.bold.paint("vacuity")
+            )
+        });
+
+        let mut solver = self.solver("vacuity.smt2")?;
+        solver.intent("vacuity setup")?;
+        solver.declare_state(&self.sys, 0)?;
+        solver.declare_state(&self.sys, 1)?;
+        let mut vacuous = Vec::new();
+        for (candidate, def) in self.sys.po_s().iter() {
+            let vacuity = self
+                .candidate_vacuity(&mut solver, candidate, def)
+                .chain_err(|| {
+                    format!(
+                        "during vacuity check for candidate `{}`",
+                        self.bold.paint(candidate)
+                    )
+                })?;
+            if let Some(vacuity) = vacuity {
+                vacuous.push((candidate.as_str(), vacuity))
+            }
+        }
+
+        println!();
+        println!("|===| {} check result", self.bold.paint("Vacuity"));
+        if vacuous.is_empty() {
+            println!(
+                "| - {} of the candidate(s) hold vacuously",
+                self.green.paint("none")
+            )
+        } else {
+            println!(
+                "| - the following candidate(s) hold {}, they say nothing about the system",
+                self.red.paint("vacuously")
+            );
+            for (candidate, vacuity) in &vacuous {
+                println!("|   `{}`: {}", self.red.paint(*candidate), vacuity.desc())
+            }
+        }
+        println!("|===|");
+
+        Ok(())
+    }
+
+    /// Vacuity check for a single candidate.
+    ///
+    /// States `0` and `1` must be declared in `solver`.
+    pub fn candidate_vacuity(
+        &self,
+        solver: &mut smt::Solver,
+        candidate: &str,
+        def: &Expr,
+    ) -> Res<Option<Vacuity>> {
+        let sys = &self.sys;
+        let at = |expr: &Expr, step: usize| smt::Unroll::new(expr, step).to_string();
+        let not_at = |expr: &Expr, step: usize| format!("(not {})", at(expr, step));
+        let init = at(sys.init(), 0);
+        let trans = smt::Unroll::new(sys.trans(), 0).to_string();
+
+        solver.intent(&format!("vacuity: is candidate `{}` valid?", candidate))?;
+        if solver.unsat(&[not_at(def, 0)])? {
+            return Ok(Some(Vacuity::Valid));
+        }
+
+        solver.intent(&format!(
+            "vacuity: is candidate `{}` implied by the initial and transition predicates?",
+            candidate
+        ))?;
+        if solver.unsat(&[init.clone(), not_at(def, 0)])?
+            && solver.unsat(&[trans.clone(), not_at(def, 1)])?
+        {
+            return Ok(Some(Vacuity::ByConstruction));
+        }
+
+        let antecedent = match def {
+            Expr::App {
+                op: Op::Implies,
+                args,
+            } if args.len() == 2 => &args[0],
+            _ => return Ok(None),
+        };
+        solver.intent(&format!(
+            "vacuity: is the antecedent of candidate `{}` reachable?",
+            candidate
+        ))?;
+        if solver.unsat(&[init, at(antecedent, 0)])?
+            && solver.unsat(&[not_at(antecedent, 0), trans, at(antecedent, 1)])?
+        {
+            return Ok(Some(Vacuity::Antecedent));
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,212 @@
+//! Numeric base of the integer values in traces.
+//!
+//! `--value_format` sets the base, `#[format(<BASE>)]` annotations override it per variable.
+
+mikino_api::prelude!();
+
+use expr::Typ;
+
+use crate::{text, Check, Trace};
+
+/// Annotation prefix.
+const ANNOTATION: &str = "#[format";
+
+/// Numeric base of integer values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    /// Decimal.
+    Dec,
+    /// Hexadecimal, `0x` prefix.
+    Hex,
+    /// Binary, `0b` prefix.
+    Bin,
+}
+
+impl Base {
+    /// Names of the bases.
+    pub const NAMES: [&'static str; 3] = ["dec", "hex", "bin"];
+
+    /// Parses a base.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim() {
+            "dec" => Ok(Self::Dec),
+            "hex" => Ok(Self::Hex),
+            "bin" => Ok(Self::Bin),
+            name => Err(format!(
+                "unknown value format `{}`, expected one of {}",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Formats an integer value, left untouched if it is not one.
+    ///
+    /// Handles both `-7` and SMT-LIB's `(- 7)` for negative values, and completed values, see
+    /// [`crate::smt::Completion`].
+    pub fn format(self, val: &str) -> String {
+        if self == Self::Dec {
+            return val.into();
+        }
+        let (raw, suffix) = match val.strip_suffix(" (completed)") {
+            Some(raw) => (raw, " (completed)"),
+            None => (val, ""),
+        };
+        let (neg, abs) = match raw
+            .strip_prefix("(-")
+            .and_then(|abs| abs.strip_suffix(')'))
+            .or_else(|| raw.strip_prefix('-'))
+        {
+            Some(abs) => (true, abs.trim()),
+            None => (false, raw.trim()),
+        };
+        let abs = match abs.parse::<u128>() {
+            Ok(abs) => abs,
+            Err(_) => return val.into(),
+        };
+        let sign = if neg { "-" } else { "" };
+        match self {
+            Self::Dec => unreachable!(),
+            Self::Hex => format!("{}0x{:x}{}", sign, abs, suffix),
+            Self::Bin => format!("{}0b{:b}{}", sign, abs, suffix),
+        }
+    }
+}
+
+/// A base attached to a variable.
+#[derive(Debug, Clone)]
+pub struct VarFormat {
+    /// Variable.
+    pub var: String,
+    /// Base.
+    pub base: Base,
+    /// Line of the annotation.
+    pub line: usize,
+}
+
+/// Removes the `#[format(...)]` annotations of a system, yields the new system and the bases.
+///
+/// Other annotations before the declaration are left untouched.
+pub fn strip(txt: &str) -> Res<(String, Vec<VarFormat>)> {
+    if !txt.contains(ANNOTATION) {
+        return Ok((txt.into(), Vec::new()));
+    }
+    let mut res = txt.to_string();
+    let mut formats = Vec::new();
+    for (start, end) in text::elements(txt, "svars")?.into_iter().rev() {
+        let elm = &txt[start..end];
+        let line = txt[..start].matches('\n').count() + 1;
+        let illegal = |msg: String| format!("illegal annotation at line {}: {}", line, msg);
+        // Annotations of the element, as byte ranges in `elm`.
+        let mut annotations = Vec::new();
+        let mut idx = 0;
+        while elm[idx..].starts_with("#[") {
+            let close = elm[idx..]
+                .find(")]")
+                .ok_or_else(|| illegal("expected `#[<NAME>(...)]`".into()))?;
+            let rgt = idx + close + 2;
+            let next = rgt + (elm[rgt..].len() - elm[rgt..].trim_start().len());
+            annotations.push((idx, rgt, next));
+            idx = next
+        }
+        let decl = &elm[idx..];
+        let names = decl.split_once(':').map(|(names, _)| names).unwrap_or(decl);
+        let names: Vec<&str> = names.split_whitespace().collect();
+        for (lft, rgt, next) in annotations.into_iter().rev() {
+            let annotation = &elm[lft..rgt];
+            if !annotation.starts_with(ANNOTATION) {
+                continue;
+            }
+            let desc = annotation[ANNOTATION.len()..]
+                .trim_start()
+                .strip_prefix('(')
+                .and_then(|desc| desc.strip_suffix(")]"))
+                .ok_or_else(|| illegal("expected `#[format(<BASE>)]`".into()))?;
+            let base = Base::parse(desc).map_err(illegal)?;
+            if names.is_empty() {
+                bail!(
+                    "expected a variable declaration after the annotation at line {}",
+                    line
+                )
+            }
+            for var in names.iter().rev() {
+                formats.push(VarFormat {
+                    var: var.to_string(),
+                    base,
+                    line,
+                })
+            }
+            res.replace_range(start + lft..start + next, "");
+        }
+    }
+    formats.reverse();
+    Ok((res, formats))
+}
+
+impl<'env> Check<'env> {
+    /// Sets the bases of the variables of the system, fails if they mention unknown or
+    /// non-integer variables, or if a variable has several bases.
+    pub fn set_formats(&mut self, formats: Vec<VarFormat>) -> Res<()> {
+        for VarFormat { var, base, line } in formats {
+            let decl = match self.sys.decls().iter().find(|decl| decl.id() == var) {
+                Some(decl) => decl,
+                None => bail!(
+                    "format at line {} mentions unknown variable `{}`",
+                    line,
+                    var
+                ),
+            };
+            if decl.typ() != Typ::Int {
+                bail!(
+                    "illegal format for variable `{}` at line {}: only applies to int variables",
+                    var,
+                    line
+                )
+            }
+            if self.formats.insert(var.clone(), base).is_some() {
+                bail!("variable `{}` has more than one format", var)
+            }
+        }
+        Ok(())
+    }
+
+    /// Trace with its integer values formatted, see [`crate::value_format`].
+    ///
+    /// Variables rescaled by `--sanitize_cex` are not formatted, their values are rescaled
+    /// afterwards, see [`crate::sanitize`].
+    pub fn formatted_trace(&self, trace: &Trace) -> Trace {
+        let default = self.env.value_format;
+        if default == Base::Dec && self.formats.is_empty() {
+            return trace.clone();
+        }
+        let ints: Set<&str> = self
+            .sys
+            .decls()
+            .iter()
+            .filter(|decl| decl.typ() == Typ::Int)
+            .filter(|decl| {
+                !self
+                    .sanitizer
+                    .as_ref()
+                    .is_some_and(|sanitizer| sanitizer.scale.contains_key(decl.id()))
+            })
+            .map(|decl| decl.id())
+            .collect();
+        trace
+            .iter()
+            .map(|(step, values)| {
+                let values = values
+                    .iter()
+                    .map(|(var, val)| {
+                        if !ints.contains(var.as_str()) {
+                            return (var.clone(), val.clone());
+                        }
+                        let base = self.formats.get(var).cloned().unwrap_or(default);
+                        (var.clone(), base.format(val))
+                    })
+                    .collect();
+                (*step, values)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Named views on the variables of a system, for trace presentation.
+//!
+//! Top-level `view <NAME> { <VAR>, ... }` blocks name groups of variables.
+
+mikino_api::prelude!();
+
+use crate::{text, Check};
+
+/// Keyword of view blocks.
+const KEYWORD: &str = "view";
+
+/// A named group of variables.
+#[derive(Debug, Clone)]
+pub struct View {
+    /// Name of the view.
+    pub name: String,
+    /// Variables of the view.
+    pub vars: Vec<String>,
+    /// Line of the view in the system file.
+    pub line: usize,
+}
+
+/// Removes the views of a system, yields the new system and its views.
+pub fn strip(txt: &str) -> Res<(String, Vec<View>)> {
+    if !txt.contains(KEYWORD) {
+        return Ok((txt.into(), Vec::new()));
+    }
+    let (txt, blocks) = text::take_named_blocks(txt, KEYWORD)?;
+    let mut views: Vec<View> = Vec::with_capacity(blocks.len());
+    for (name, content, line) in blocks {
+        if views.iter().any(|view| view.name == name) {
+            bail!("view `{}` at line {} is declared twice", name, line)
+        }
+        let vars = content
+            .split(',')
+            .map(str::trim)
+            .filter(|var| !var.is_empty())
+            .map(String::from)
+            .collect();
+        views.push(View { name, vars, line })
+    }
+    Ok((txt, views))
+}
+
+impl<'env> Check<'env> {
+    /// Sets the views of the system, fails if they mention unknown variables or if `--views`
+    /// mentions unknown views.
+    pub fn set_views(&mut self, views: Vec<View>) -> Res<()> {
+        for view in &views {
+            for var in &view.vars {
+                if !self.sys.decls().iter().any(|decl| decl.id() == var) {
+                    bail!(
+                        "view `{}` at line {} mentions unknown variable `{}`",
+                        view.name,
+                        view.line,
+                        var
+                    )
+                }
+            }
+        }
+        for name in self.env.views.iter().flatten() {
+            if name != "none" && !views.iter().any(|view| &view.name == name) {
+                bail!("`--views` mentions unknown view `{}`", name)
+            }
+        }
+        self.views = views;
+        Ok(())
+    }
+
+    /// True if a view is expanded in traces.
+    fn is_expanded(&self, view: &View) -> bool {
+        self.env
+            .views
+            .as_ref()
+            .map(|names| names.contains(&view.name))
+            .unwrap_or(true)
+    }
+
+    /// Presents the values of a step of a sanitized trace view by view, `prev` are the values of
+    /// the previous step if any.
+    ///
+    /// See [`Self::present_steps`].
+    pub fn present_views(
+        &self,
+        values: &[(String, String)],
+        prev: Option<&[(String, String)]>,
+        max_id_len: usize,
+    ) {
+        let mut shown = Set::new();
+        let present = |var: &str, val: &str| {
+            let var_str = format!("{: >1$}", var, max_id_len);
+            println!(
+                "  | {} = {}",
+                self.bold.paint(var_str),
+                self.highlighted(var, val, prev)
+            )
+        };
+        for view in &self.views {
+            let vars: Vec<String> = view
+                .vars
+                .iter()
+                .map(|var| self.sanitized_text(var))
+                .collect();
+            shown.extend(vars.iter().cloned());
+            if self.is_expanded(view) {
+                println!("  | [{}]", self.gray.paint(&view.name));
+                for (var, val) in values.iter().filter(|(var, _)| vars.contains(var)) {
+                    present(var, val)
+                }
+                continue;
+            }
+            let changed: Vec<&str> = match prev {
+                Some(prev) => values
+                    .iter()
+                    .filter(|(var, val)| {
+                        vars.contains(var) && prev.iter().any(|(v, p)| v == var && p != val)
+                    })
+                    .map(|(var, _)| var.as_str())
+                    .collect(),
+                None => Vec::new(),
+            };
+            let desc = if prev.is_none() {
+                "collapsed".to_string()
+            } else if changed.is_empty() {
+                "unchanged".to_string()
+            } else {
+                format!("changed: {}", changed.join(", "))
+            };
+            println!(
+                "  | [{}] {} variable(s), {}",
+                self.gray.paint(&view.name),
+                vars.len(),
+                desc
+            )
+        }
+        let others: Vec<&(String, String)> = values
+            .iter()
+            .filter(|(var, _)| !shown.contains(var))
+            .collect();
+        if !others.is_empty() {
+            println!("  | [{}]", self.gray.paint("other"));
+            for (var, val) in others {
+                present(var, val)
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Changes since the last version the user saw.
+//!
+//! The last version seen is stored in the user's configuration directory.
+
+mikino_api::prelude!();
+
+use std::path::PathBuf;
+
+use crate::{mode::Mode, Run};
+
+/// The changelog.
+const CHANGELOG: &str = include_str!("../changes.md");
+
+/// Version the changes of the `Unreleased` section of the changelog belong to.
+const VERSION: &str = clap::crate_version!();
+
+/// A section of the changelog, its version and its entries.
+struct Section {
+    /// Version of the section.
+    version: String,
+    /// Entries, with their continuation lines.
+    entries: Vec<String>,
+}
+
+/// Sections of the changelog, most recent first.
+fn sections() -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for line in CHANGELOG.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            let version = match title.trim() {
+                "Unreleased" => VERSION,
+                title => title.trim_start_matches('v'),
+            };
+            sections.push(Section {
+                version: version.into(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        let section = match sections.last_mut() {
+            Some(section) => section,
+            None => continue,
+        };
+        if line.starts_with("- ") {
+            section.entries.push(line.into())
+        } else if let Some(entry) = section
+            .entries
+            .last_mut()
+            .filter(|_| !line.trim().is_empty())
+        {
+            entry.push('\n');
+            entry.push_str(line)
+        }
+    }
+    sections
+}
+
+/// Numbers of a version such as `0.9.1`, non-numeric parts are ignored.
+fn numbers(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|n| n.parse().unwrap_or(0))
+        .collect()
+}
+
+/// File the last version seen is stored in, `None` if there is no configuration directory.
+fn last_seen_file() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|val| !val.is_empty());
+    let mut path = if cfg!(windows) {
+        PathBuf::from(var("APPDATA")?)
+    } else if let Some(dir) = var("XDG_CONFIG_HOME") {
+        PathBuf::from(dir)
+    } else {
+        let mut path = PathBuf::from(var("HOME")?);
+        path.push(".config");
+        path
+    };
+    path.push("mikino");
+    path.push("last_seen");
+    Some(path)
+}
+
+impl Run {
+    /// Highlights the code spans of a line, lines with unbalanced backquotes are left as is.
+    fn with_code_spans(&self, line: &str) -> String {
+        if line.matches('`').count() % 2 != 0 {
+            return line.into();
+        }
+        line.split('`')
+            .enumerate()
+            .map(|(idx, part)| {
+                if idx % 2 == 1 {
+                    self.code.paint(format!("`{}`", part)).to_string()
+                } else {
+                    part.into()
+                }
+            })
+            .collect()
+    }
+
+    /// Prints the changes after `since`, the last version seen if `None`, see [`crate::whats_new`].
+    pub fn whats_new(&self, since: &Option<String>) -> Res<()> {
+        let file = last_seen_file();
+        let last_seen = match (since, file.as_ref()) {
+            (Some(since), _) => Some(since.clone()),
+            (None, Some(file)) if file.is_file() => Some(
+                std::fs::read_to_string(file)
+                    .chain_err(|| format!("while reading `{}`", file.display()))?
+                    .trim()
+                    .to_string(),
+            ),
+            (None, _) => None,
+        };
+        let sections: Vec<Section> = sections()
+            .into_iter()
+            .filter(|section| match last_seen.as_ref() {
+                Some(last_seen) => numbers(&section.version) > numbers(last_seen),
+                None => section.version == VERSION,
+            })
+            .filter(|section| !section.entries.is_empty())
+            .collect();
+
+        let subcommands: Vec<String> = Mode::subcommands()
+            .iter()
+            .map(|cmd| cmd.get_name().to_string())
+            .collect();
+        let (mut new_cmds, mut new_opts) = (Set::new(), Set::new());
+        for entry in sections.iter().flat_map(|section| section.entries.iter()) {
+            // Code spans are the odd parts.
+            let parts: Vec<&str> = entry.split('`').collect();
+            for idx in (1..parts.len()).step_by(2) {
+                let span = parts[idx];
+                for opt in span
+                    .split_whitespace()
+                    .filter(|word| word.starts_with("--"))
+                {
+                    new_opts.insert(opt.to_string());
+                }
+                let after = parts.get(idx + 1).map(|txt| txt.trim_start()).unwrap_or("");
+                if subcommands.iter().any(|cmd| cmd == span)
+                    && (after.starts_with("mode") || after.starts_with("command"))
+                {
+                    new_cmds.insert(span.to_string());
+                }
+            }
+        }
+
+        println!(
+            "|===| {} in mikino {}{}",
+            self.bold.paint("What's new"),
+            VERSION,
+            last_seen
+                .as_ref()
+                .map(|version| format!(" since {}", version))
+                .unwrap_or_default()
+        );
+        if sections.is_empty() {
+            println!("| - nothing new")
+        }
+        if !new_cmds.is_empty() {
+            let cmds: Vec<String> = new_cmds
+                .iter()
+                .map(|cmd| self.code.paint(cmd).to_string())
+                .collect();
+            println!(
+                "| - {}: {}",
+                self.bold.paint("subcommands"),
+                cmds.join(", ")
+            )
+        }
+        if !new_opts.is_empty() {
+            let opts: Vec<String> = new_opts
+                .iter()
+                .map(|opt| self.code.paint(opt).to_string())
+                .collect();
+            println!("| - {}: {}", self.bold.paint("options"), opts.join(", "))
+        }
+        for section in &sections {
+            println!("|");
+            println!("| {}", self.under.paint(format!("v{}", section.version)));
+            for entry in &section.entries {
+                for line in entry.lines() {
+                    println!("| {}", self.with_code_spans(line))
+                }
+            }
+        }
+        println!("|===|");
+
+        if since.is_none() {
+            if let Some(file) = file {
+                if let Some(dir) = file.parent() {
+                    std::fs::create_dir_all(dir)
+                        .chain_err(|| format!("while creating directory `{}`", dir.display()))?
+                }
+                std::fs::write(&file, VERSION)
+                    .chain_err(|| format!("while writing `{}`", file.display()))?
+            }
+        }
+        Ok(())
+    }
+}